@@ -0,0 +1,428 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{parser_options, slugify, split_fragment, LineIndex};
+use crate::utils::{collect_md_files, validate_directory};
+use crate::wiki::NoteIndex;
+
+/// A ` ^block-id` marker at the end of a line.
+static BLOCK_ID_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)(?:^|[ \t]+)\^([A-Za-z0-9][A-Za-z0-9-]*)[ \t]*$")
+        .expect("invalid block id regex")
+});
+
+const ID_LENGTH: usize = 6;
+
+#[derive(Serialize, Clone)]
+pub struct BlockEntry {
+    pub file_path: String,
+    pub file_name: String,
+    pub id: String,
+    /// 1-based line where the block starts.
+    pub line: usize,
+    /// First line of the block, without the marker.
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+pub struct BlockIdOptions {
+    pub content: String,
+    /// 1-based line inside the block.
+    pub line: usize,
+}
+
+#[derive(Serialize)]
+pub struct BlockIdResult {
+    pub id: String,
+    /// `content` with the marker added; unchanged when the block already had one.
+    pub content: String,
+    pub created: bool,
+}
+
+#[derive(Deserialize)]
+pub struct WikiTargetOptions {
+    pub folder: String,
+    /// Link target as written, e.g. `note`, `note#Heading` or `note#^id`.
+    pub target: String,
+    /// Note containing the link, used for `[[#Heading]]` links to itself.
+    pub from: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct WikiTargetLocation {
+    pub file_path: String,
+    /// 1-based line of the heading or block, `1` for the note itself.
+    pub line: usize,
+}
+
+/// A block ID found in a note.
+pub struct BlockMarker {
+    pub id: String,
+    /// Byte range of the marker, including the whitespace before it.
+    pub range: Range<usize>,
+}
+
+/// Every `^block-id` marker in `body` outside code and HTML.
+pub fn block_markers(body: &str) -> Vec<BlockMarker> {
+    if !body.contains('^') {
+        return Vec::new();
+    }
+    let code: Vec<Range<usize>> = Parser::new_ext(body, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::CodeBlock(_))
+            | Event::Start(Tag::HtmlBlock)
+            | Event::Code(_)
+            | Event::InlineHtml(_) => Some(range),
+            _ => None,
+        })
+        .collect();
+    BLOCK_ID_RE
+        .captures_iter(body)
+        .filter_map(|cap| {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            let id = cap.get(1).expect("capture group 1 always exists");
+            (!code.iter().any(|r| r.contains(&id.start()))).then(|| BlockMarker {
+                id: id.as_str().to_string(),
+                range: whole.range(),
+            })
+        })
+        .collect()
+}
+
+/// Remove every block marker from `text`.
+pub fn strip_block_markers(text: &str) -> String {
+    BLOCK_ID_RE.replace_all(text, "").into_owned()
+}
+
+/// Byte range of the block a marker belongs to: the innermost list item
+/// (from the start of its line) or paragraph containing it. A marker on a
+/// line of its own refers to the block right before it, e.g. a table.
+pub fn block_range(body: &str, marker: &Range<usize>) -> Option<Range<usize>> {
+    let at = marker.end.saturating_sub(1);
+    let mut item: Option<Range<usize>> = None;
+    let mut paragraph: Option<Range<usize>> = None;
+    let mut top_level: Vec<Range<usize>> = Vec::new();
+    let mut depth = 0usize;
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(tag) => {
+                if depth == 0 {
+                    top_level.push(range.clone());
+                }
+                depth += 1;
+                if range.contains(&at) {
+                    match tag {
+                        Tag::Item => item = Some(range),
+                        Tag::Paragraph => paragraph = Some(range),
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ if depth == 0 => top_level.push(range),
+            _ => {}
+        }
+    }
+
+    if let Some(para) = &paragraph {
+        let standalone = body[para.clone()].trim() == body[marker.clone()].trim();
+        if item.is_none() && standalone {
+            return top_level.iter().rfind(|r| r.end <= para.start).cloned();
+        }
+    }
+    match (item, paragraph) {
+        (Some(item), _) => {
+            let line_start = body[..item.start].rfind('\n').map_or(0, |i| i + 1);
+            Some(line_start..item.end)
+        }
+        (None, Some(para)) => Some(para),
+        (None, None) => None,
+    }
+}
+
+/// Byte offset where the block marked `id` starts.
+pub fn find_block(body: &str, id: &str) -> Option<usize> {
+    let marker = block_markers(body).into_iter().find(|m| m.id == id)?;
+    Some(block_range(body, &marker.range).map_or(marker.range.start, |r| r.start))
+}
+
+/// List the block IDs of every note under `folder`.
+#[tauri::command]
+pub fn list_blocks(folder: String) -> Result<Vec<BlockEntry>, AppError> {
+    let root = validate_directory(&folder)?;
+    let mut files = collect_md_files(&root);
+    files.sort();
+
+    let mut entries = Vec::new();
+    for path in files {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let (_, body) = split_frontmatter(&content);
+        let offset = content.len() - body.len();
+        let index = LineIndex::new(&content);
+        for marker in block_markers(body) {
+            let range = block_range(body, &marker.range).unwrap_or(marker.range.clone());
+            let text = strip_block_markers(&body[range.clone()]);
+            let first_line = text
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())
+                .unwrap_or_default();
+            entries.push(BlockEntry {
+                file_path: path.to_string_lossy().into_owned(),
+                file_name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                id: marker.id,
+                line: index.position(range.start + offset).0,
+                text: first_line.chars().take(120).collect(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Return the block ID of the paragraph, list item or other block at
+/// `line`, adding a new marker when it has none. New IDs are random-looking
+/// and unique within the note.
+#[tauri::command]
+pub fn create_block_id(options: BlockIdOptions) -> Result<BlockIdResult, AppError> {
+    let content = options.content;
+    let (_, body) = split_frontmatter(&content);
+    let offset = content.len() - body.len();
+    let no_block = || AppError::Other(format!("No block at line {}", options.line));
+
+    let line_start = content
+        .split_inclusive('\n')
+        .take(options.line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    if options.line == 0 || line_start < offset || line_start >= content.len() {
+        return Err(no_block());
+    }
+    let line_start = line_start - offset;
+    let line_end = body[line_start..]
+        .find('\n')
+        .map_or(body.len(), |i| line_start + i);
+    let line = &body[line_start..line_end];
+    if line.trim().is_empty() {
+        return Err(no_block());
+    }
+    // Nested list items start at their marker, not at the line start.
+    let at = line_start + (line.len() - line.trim_start().len());
+
+    let markers = block_markers(body);
+    let target = insertion_point(body, at).ok_or_else(no_block)?;
+
+    // Reuse the marker the block already has.
+    for marker in &markers {
+        if block_range(body, &marker.range).is_some_and(|r| r.start == target.block.start) {
+            return Ok(BlockIdResult {
+                id: marker.id.clone(),
+                content,
+                created: false,
+            });
+        }
+    }
+
+    let taken: HashSet<&str> = markers.iter().map(|m| m.id.as_str()).collect();
+    let id = generate_id(&body[target.block.clone()], &taken);
+    let insert = if target.standalone {
+        let next_blank = body[target.at..]
+            .split('\n')
+            .nth(1)
+            .is_none_or(|l| l.trim().is_empty());
+        if next_blank {
+            format!("\n\n^{}", id)
+        } else {
+            format!("\n\n^{}\n", id)
+        }
+    } else {
+        format!(" ^{}", id)
+    };
+
+    let mut updated = content.clone();
+    updated.insert_str(offset + target.at, &insert);
+    Ok(BlockIdResult {
+        id,
+        content: updated,
+        created: true,
+    })
+}
+
+/// Resolve a wiki link target to the file and line it points at.
+#[tauri::command]
+pub fn resolve_wiki_link(options: WikiTargetOptions) -> Result<WikiTargetLocation, AppError> {
+    let root = validate_directory(&options.folder)?;
+    let (name, fragment) = split_fragment(options.target.trim());
+    let file = if name.trim().is_empty() {
+        options.from.as_ref().map(PathBuf::from)
+    } else {
+        NoteIndex::build(&root).resolve(name).cloned()
+    };
+    let file = file
+        .filter(|f| f.is_file())
+        .ok_or_else(|| AppError::NotFound(name.to_string()))?;
+
+    let mut line = 1;
+    if let Some(fragment) = fragment.map(str::trim).filter(|f| !f.is_empty()) {
+        let content = fs::read_to_string(&file)?;
+        let (_, body) = split_frontmatter(&content);
+        let offset = content.len() - body.len();
+        let found = match fragment.strip_prefix('^') {
+            Some(id) => find_block(body, id),
+            None => find_heading(body, fragment),
+        };
+        let at = found.ok_or_else(|| AppError::NotFound(options.target.clone()))?;
+        line = LineIndex::new(&content).position(at + offset).0;
+    }
+
+    Ok(WikiTargetLocation {
+        file_path: file.to_string_lossy().into_owned(),
+        line,
+    })
+}
+
+/// Byte offset of the heading whose slug matches `heading`.
+fn find_heading(body: &str, heading: &str) -> Option<usize> {
+    let wanted = slugify(heading);
+    let mut current: Option<(usize, String)> = None;
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { .. }) => current = Some((range.start, String::new())),
+            Event::Text(t) | Event::Code(t) => {
+                if let Some((_, text)) = current.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((start, text)) = current.take() {
+                    if slugify(&text) == wanted {
+                        return Some(start);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+struct InsertionPoint {
+    /// The block the new marker belongs to, as [`block_range`] reports it.
+    block: Range<usize>,
+    /// Where the marker goes.
+    at: usize,
+    /// Put the marker on a line of its own after the block.
+    standalone: bool,
+}
+
+/// Find where a marker for the block containing byte `at` goes: the end of
+/// a paragraph or of a list item's own text, or a new line after any other
+/// top-level block.
+fn insertion_point(body: &str, at: usize) -> Option<InsertionPoint> {
+    let mut item: Option<Range<usize>> = None;
+    let mut paragraph: Option<Range<usize>> = None;
+    let mut top_level: Option<(Range<usize>, bool)> = None;
+    let mut first_child: Option<usize> = None;
+    let mut depth = 0usize;
+    let mut item_depth = 0usize;
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(tag) => {
+                let contains = range.contains(&at);
+                if depth == 0 && contains {
+                    top_level = Some((range.clone(), matches!(tag, Tag::Heading { .. })));
+                }
+                depth += 1;
+                match tag {
+                    Tag::Item if contains => {
+                        item = Some(range);
+                        item_depth = depth;
+                        paragraph = None;
+                        first_child = None;
+                    }
+                    Tag::Paragraph if contains => paragraph = Some(range),
+                    Tag::List(_) | Tag::CodeBlock(_) | Tag::BlockQuote(_) | Tag::Table(_)
+                        if depth == item_depth + 1
+                            && first_child.is_none()
+                            && item.as_ref().is_some_and(|i| i.contains(&range.start)) =>
+                    {
+                        first_child = Some(range.start);
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    let (block, is_heading) = top_level?;
+    if is_heading {
+        return None;
+    }
+    // Markers inside a list item always mark the whole item.
+    let item_block = item.as_ref().map(|item| {
+        let line_start = body[..item.start].rfind('\n').map_or(0, |i| i + 1);
+        line_start..item.end
+    });
+    match (item, paragraph) {
+        (_, Some(para)) => Some(InsertionPoint {
+            block: item_block.unwrap_or_else(|| para.clone()),
+            at: trim_end(body, para.end),
+            standalone: false,
+        }),
+        (Some(item), None) => Some(InsertionPoint {
+            block: item_block.unwrap_or_else(|| item.clone()),
+            at: trim_end(body, first_child.unwrap_or(item.end)),
+            standalone: false,
+        }),
+        (None, None) => Some(InsertionPoint {
+            at: trim_end(body, block.end),
+            block,
+            standalone: true,
+        }),
+    }
+}
+
+/// Move `end` back over trailing whitespace and line breaks.
+fn trim_end(body: &str, end: usize) -> usize {
+    body[..end].trim_end().len()
+}
+
+fn generate_id(seed: &str, taken: &HashSet<&str>) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    for attempt in 0u64.. {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        attempt.hash(&mut hasher);
+        let mut value = hasher.finish();
+        let id: String = (0..ID_LENGTH)
+            .map(|_| {
+                let c = ALPHABET[(value % ALPHABET.len() as u64) as usize];
+                value /= ALPHABET.len() as u64;
+                c as char
+            })
+            .collect();
+        if !taken.contains(id.as_str()) {
+            return id;
+        }
+    }
+    unreachable!("block id space exhausted")
+}
@@ -0,0 +1,1468 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::error::AppError;
+use crate::frontmatter::{split_frontmatter, Frontmatter};
+use crate::transclude::code_ranges;
+use crate::utils::validate_directory;
+
+/// Bibliographies picked up from the workspace root when a note names none.
+const DEFAULT_BIBLIOGRAPHIES: &[&str] = &[
+    "references.bib",
+    "references.json",
+    "bibliography.bib",
+    "bibliography.json",
+];
+const DEFAULT_SECTION_TITLE: &str = "References";
+
+const KEY: &str = r"\w+(?:[:.#$%&+?<>~/-]\w+)*";
+
+/// `[see @smith2020, p. 4; -@jones2019]`, but not links or images.
+static BRACKET_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[([^\[\]\n]*@[^\[\]\n]*)\](\(|\[)?").expect("invalid citation regex")
+});
+static ITEM_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"^(?s)(?P<prefix>(?:.*?\s)?)(?P<suppress>-)?@(?P<key>{})(?P<suffix>.*)$",
+        KEY
+    ))
+    .expect("invalid citation item regex")
+});
+/// `@smith2020` or `@smith2020 [p. 4]` in running text.
+static NARRATIVE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?P<lead>^|[^\w@\[\\])@(?P<key>{})(?: \[(?P<locator>[^\[\]\n]*)\])?",
+        KEY
+    ))
+    .expect("invalid narrative citation regex")
+});
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(?:<!--\s*bibliography\s*-->|::: *\{#refs\}[ \t]*\n:::)[ \t]*$")
+        .expect("invalid bibliography placeholder regex")
+});
+static LATEX_ACCENT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{?\\(["'`^~=.]|[cvuH](?:\s+|\{))\s*\{?([A-Za-z])\}?\}?"#)
+        .expect("invalid LaTeX accent regex")
+});
+static LATEX_SYMBOL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\\(ss|ae|AE|oe|OE|aa|AA|o|O|l|L|i)\b(?:\{\})?")
+        .expect("invalid LaTeX symbol regex")
+});
+static LATEX_COMMAND_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\[A-Za-z]+\*?\s*").expect("invalid LaTeX command regex"));
+
+type CachedBibliography = (SystemTime, Arc<Vec<Reference>>);
+
+/// Parsed bibliography files by path, reloaded when the file changes.
+static CACHE: Lazy<Mutex<HashMap<PathBuf, CachedBibliography>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Citation style. `csl: ieee.csl` or `citation-style: chicago` in the
+/// frontmatter selects one; the default is APA.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    #[default]
+    Apa,
+    Chicago,
+    Ieee,
+}
+
+impl CitationStyle {
+    fn from_frontmatter(frontmatter: Option<&Frontmatter>) -> Self {
+        let Some(name) =
+            frontmatter.and_then(|fm| fm.get("citation-style").or_else(|| fm.get("csl")))
+        else {
+            return Self::default();
+        };
+        let name = name.to_lowercase();
+        if name.contains("ieee") {
+            Self::Ieee
+        } else if name.contains("chicago") {
+            Self::Chicago
+        } else {
+            Self::Apa
+        }
+    }
+
+    fn numeric(self) -> bool {
+        self == Self::Ieee
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CitationOptions {
+    pub content: String,
+    /// Note path, used to resolve a relative `bibliography` in frontmatter.
+    pub file_path: Option<String>,
+    pub folder: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CitationResult {
+    /// The note with citations formatted and the bibliography appended.
+    pub content: String,
+    pub style: CitationStyle,
+    /// Cited keys that are not in the bibliography.
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CitationKey {
+    pub key: String,
+    pub title: String,
+    /// Short author list, e.g. `Smith & Jones`.
+    pub authors: String,
+    pub year: String,
+}
+
+/// Format `[@key]` citations in a note and append its bibliography.
+///
+/// References come from the note's `bibliography` frontmatter key or from
+/// `references.bib`/`references.json` in the workspace root. The list is
+/// inserted at `<!-- bibliography -->` (or pandoc's `::: {#refs}` div) if
+/// present, otherwise under a "References" heading at the end.
+#[tauri::command]
+pub fn render_citations(options: CitationOptions) -> Result<CitationResult, AppError> {
+    let (frontmatter, body) = split_frontmatter(&options.content);
+    let head = &options.content[..options.content.len() - body.len()];
+    let source = options.file_path.as_deref().map(Path::new);
+    let root = options
+        .folder
+        .as_deref()
+        .map(validate_directory)
+        .transpose()?;
+    let style = CitationStyle::from_frontmatter(frontmatter.as_ref());
+    let references = load_references(frontmatter.as_ref(), source, root.as_deref())?;
+    let (rendered, missing) = match references {
+        Some(references) => format_citations(body, frontmatter.as_ref(), &references, style),
+        None => (body.to_string(), Vec::new()),
+    };
+    Ok(CitationResult {
+        content: format!("{}{}", head, rendered),
+        style,
+        missing,
+    })
+}
+
+/// Citation keys available to a note, for autocompletion.
+#[tauri::command]
+pub fn list_citation_keys(options: CitationOptions) -> Result<Vec<CitationKey>, AppError> {
+    let (frontmatter, _) = split_frontmatter(&options.content);
+    let source = options.file_path.as_deref().map(Path::new);
+    let root = options
+        .folder
+        .as_deref()
+        .map(validate_directory)
+        .transpose()?;
+    let Some(references) = load_references(frontmatter.as_ref(), source, root.as_deref())? else {
+        return Ok(Vec::new());
+    };
+    let mut keys: Vec<CitationKey> = references
+        .iter()
+        .map(|r| CitationKey {
+            key: r.key.clone(),
+            title: r.title.clone(),
+            authors: short_authors(r, CitationStyle::Apa, false),
+            year: r.year.clone().unwrap_or_default(),
+        })
+        .collect();
+    keys.sort_by_key(|k| k.key.to_lowercase());
+    Ok(keys)
+}
+
+/// Format the citations in `body`, the markdown of `source` without its
+/// frontmatter. Used by the exporters; returns `body` unchanged when the
+/// note has no bibliography.
+pub fn apply_citations(
+    body: &str,
+    frontmatter: Option<&Frontmatter>,
+    source: &Path,
+    root: &Path,
+) -> String {
+    if !body.contains('@') {
+        return body.to_string();
+    }
+    match load_references(frontmatter, Some(source), Some(root)) {
+        Ok(Some(references)) => {
+            let style = CitationStyle::from_frontmatter(frontmatter);
+            format_citations(body, frontmatter, &references, style).0
+        }
+        _ => body.to_string(),
+    }
+}
+
+fn load_references(
+    frontmatter: Option<&Frontmatter>,
+    source: Option<&Path>,
+    root: Option<&Path>,
+) -> Result<Option<Vec<Reference>>, AppError> {
+    let named = frontmatter
+        .map(|fm| fm.get_list("bibliography"))
+        .unwrap_or_default();
+    let mut files = Vec::new();
+    for name in &named {
+        let candidates = [source.and_then(Path::parent), root];
+        let found = candidates
+            .iter()
+            .flatten()
+            .map(|dir| dir.join(name))
+            .find(|p| p.is_file())
+            .or_else(|| Some(PathBuf::from(name)).filter(|p| p.is_absolute() && p.is_file()));
+        files.push(found.ok_or_else(|| AppError::NotFound(name.clone()))?);
+    }
+    if named.is_empty() {
+        if let Some(root) = root {
+            files.extend(
+                DEFAULT_BIBLIOGRAPHIES
+                    .iter()
+                    .map(|name| root.join(name))
+                    .find(|p| p.is_file()),
+            );
+        }
+    }
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let mut references = Vec::new();
+    let mut seen = HashSet::new();
+    for file in files {
+        for reference in read_bibliography(&file)?.iter() {
+            if seen.insert(reference.key.clone()) {
+                references.push(reference.clone());
+            }
+        }
+    }
+    Ok(Some(references))
+}
+
+fn read_bibliography(path: &Path) -> Result<Arc<Vec<Reference>>, AppError> {
+    let modified = fs::metadata(path)?.modified()?;
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((stamp, references)) = cache.get(path) {
+        if *stamp == modified {
+            return Ok(references.clone());
+        }
+    }
+    let text = fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let references = Arc::new(if is_json {
+        parse_csl_json(&text)?
+    } else {
+        parse_bibtex(&text)
+    });
+    cache.insert(path.to_path_buf(), (modified, references.clone()));
+    Ok(references)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Article,
+    Book,
+    Chapter,
+    Paper,
+    Thesis,
+    Report,
+    Web,
+    Other,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Name {
+    family: String,
+    given: String,
+}
+
+impl Name {
+    /// `J. A.` for `John Adam`, `J.-P.` for `Jean-Pierre`.
+    fn initials(&self) -> String {
+        self.given
+            .split_whitespace()
+            .map(|part| {
+                part.split('-')
+                    .filter_map(|p| p.chars().next())
+                    .map(|c| format!("{}.", c))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn inverted(&self, initials: bool) -> String {
+        let given = if initials {
+            self.initials()
+        } else {
+            self.given.clone()
+        };
+        if given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{}, {}", self.family, given)
+        }
+    }
+
+    fn natural(&self, initials: bool) -> String {
+        let given = if initials {
+            self.initials()
+        } else {
+            self.given.clone()
+        };
+        if given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{} {}", given, self.family)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Reference {
+    key: String,
+    kind: Kind,
+    authors: Vec<Name>,
+    editors: Vec<Name>,
+    title: String,
+    container: String,
+    year: Option<String>,
+    volume: String,
+    issue: String,
+    pages: String,
+    publisher: String,
+    place: String,
+    edition: String,
+    doi: String,
+    url: String,
+}
+
+impl Reference {
+    fn new(key: &str, kind: Kind) -> Self {
+        Self {
+            key: key.to_string(),
+            kind,
+            authors: Vec::new(),
+            editors: Vec::new(),
+            title: String::new(),
+            container: String::new(),
+            year: None,
+            volume: String::new(),
+            issue: String::new(),
+            pages: String::new(),
+            publisher: String::new(),
+            place: String::new(),
+            edition: String::new(),
+            doi: String::new(),
+            url: String::new(),
+        }
+    }
+
+    /// The DOI as a link, else the URL.
+    fn link(&self) -> Option<String> {
+        if !self.doi.is_empty() {
+            let doi = self
+                .doi
+                .trim_start_matches("https://doi.org/")
+                .trim_start_matches("http://dx.doi.org/")
+                .trim_start_matches("doi:");
+            return Some(format!("<https://doi.org/{}>", doi));
+        }
+        (!self.url.is_empty()).then(|| format!("<{}>", self.url))
+    }
+}
+
+// --- BibTeX ---
+
+struct BibParser {
+    chars: Vec<char>,
+    pos: usize,
+    macros: HashMap<String, String>,
+}
+
+impl BibParser {
+    fn new(text: &str) -> Self {
+        let months = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ];
+        let macros = months
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.to_string(), (i + 1).to_string()))
+            .collect();
+        Self {
+            chars: text.chars().collect(),
+            pos: 0,
+            macros,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !"{}(),=#\"".contains(c))
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Text up to the matching closing delimiter, braces balanced.
+    fn delimited(&mut self, close: char) -> String {
+        let mut depth = 0usize;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                c if c == close && depth == 0 => return out,
+                _ => {}
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// A field value: `{...}`, `"..."`, a number or a macro, joined by `#`.
+    fn value(&mut self) -> String {
+        let mut out = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    out.push_str(&self.delimited('}'));
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    out.push_str(&self.delimited('"'));
+                }
+                Some(_) => {
+                    let name = self.identifier();
+                    let expanded = self.macros.get(&name.to_lowercase()).cloned();
+                    out.push_str(&expanded.unwrap_or(name));
+                }
+                None => break,
+            }
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        out
+    }
+
+    /// `name = value` pairs up to the entry's closing delimiter.
+    fn fields(&mut self, close: char) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(',') => self.pos += 1,
+                Some(_) => {
+                    let name = self.identifier().to_lowercase();
+                    self.skip_whitespace();
+                    if self.peek() != Some('=') {
+                        // Malformed field: skip to the next comma.
+                        while self.peek().is_some_and(|c| c != ',' && c != close) {
+                            self.pos += 1;
+                        }
+                        continue;
+                    }
+                    self.pos += 1;
+                    let value = self.value();
+                    fields.push((name, value));
+                }
+            }
+        }
+        fields
+    }
+
+    fn entries(&mut self) -> Vec<Reference> {
+        let mut references = Vec::new();
+        while self.pos < self.chars.len() {
+            if self.peek() != Some('@') {
+                self.pos += 1;
+                continue;
+            }
+            self.pos += 1;
+            let entry_type = self.identifier().to_lowercase();
+            self.skip_whitespace();
+            let close = match self.peek() {
+                Some('{') => '}',
+                Some('(') => ')',
+                _ => continue,
+            };
+            self.pos += 1;
+            match entry_type.as_str() {
+                "comment" | "preamble" => {
+                    self.delimited(close);
+                }
+                "string" => {
+                    for (name, value) in self.fields(close) {
+                        self.macros.insert(name, value);
+                    }
+                }
+                _ => {
+                    self.skip_whitespace();
+                    let key = self.identifier();
+                    let fields = self.fields(close);
+                    if !key.is_empty() {
+                        references.push(bibtex_reference(&key, &entry_type, &fields));
+                    }
+                }
+            }
+        }
+        references
+    }
+}
+
+fn parse_bibtex(text: &str) -> Vec<Reference> {
+    BibParser::new(text).entries()
+}
+
+fn bibtex_reference(key: &str, entry_type: &str, fields: &[(String, String)]) -> Reference {
+    let get = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| fields.iter().find(|(name, _)| name == n))
+            .map(|(_, v)| v.as_str())
+    };
+    let text = |names: &[&str]| get(names).map(latex_to_text).unwrap_or_default();
+    let kind = match entry_type {
+        "article" => Kind::Article,
+        "book" | "mvbook" | "booklet" => Kind::Book,
+        "incollection" | "inbook" => Kind::Chapter,
+        "inproceedings" | "conference" => Kind::Paper,
+        "phdthesis" | "mastersthesis" | "thesis" => Kind::Thesis,
+        "techreport" | "report" => Kind::Report,
+        "online" | "electronic" | "www" => Kind::Web,
+        _ if get(&["url"]).is_some() => Kind::Web,
+        _ => Kind::Other,
+    };
+    let mut reference = Reference::new(key, kind);
+    reference.authors = get(&["author"]).map(bibtex_names).unwrap_or_default();
+    reference.editors = get(&["editor"]).map(bibtex_names).unwrap_or_default();
+    reference.title = text(&["title"]);
+    reference.container = text(&["journal", "journaltitle", "booktitle"]);
+    reference.year = get(&["year", "date"]).and_then(first_year);
+    reference.volume = text(&["volume"]);
+    reference.issue = text(&["number", "issue"]);
+    reference.pages = text(&["pages"]);
+    reference.publisher = text(&["publisher", "institution", "school", "organization"]);
+    reference.place = text(&["address", "location"]);
+    reference.edition = text(&["edition"]);
+    reference.doi = text(&["doi"]);
+    reference.url = get(&["url", "howpublished"])
+        .map(|u| {
+            u.trim_start_matches("\\url{")
+                .trim_end_matches('}')
+                .to_string()
+        })
+        .filter(|u| u.contains("://"))
+        .unwrap_or_default();
+    reference
+}
+
+/// Split a BibTeX name list on top-level `and`.
+fn bibtex_names(value: &str) -> Vec<Name> {
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    let words: Vec<&str> = value.split_whitespace().collect();
+    for word in words {
+        if depth == 0 && word == "and" {
+            names.push(std::mem::take(&mut current));
+            continue;
+        }
+        depth += word.matches('{').count();
+        depth = depth.saturating_sub(word.matches('}').count());
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    names.push(current);
+    names
+        .iter()
+        .filter(|n| !n.is_empty() && n.as_str() != "others")
+        .map(|n| bibtex_name(n))
+        .collect()
+}
+
+/// `Last, First`, `Last, Jr, First`, `First von Last` or `{Corporate Name}`.
+fn bibtex_name(raw: &str) -> Name {
+    let raw = raw.trim();
+    if raw.starts_with('{') && raw.ends_with('}') && !raw[1..raw.len() - 1].contains('}') {
+        return Name {
+            family: latex_to_text(raw),
+            given: String::new(),
+        };
+    }
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+    for c in raw.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    match parts.as_slice() {
+        [last, first] | [last, _, first] => Name {
+            family: latex_to_text(last),
+            given: latex_to_text(first),
+        },
+        _ => {
+            let words: Vec<&str> = raw.split_whitespace().collect();
+            // Lowercase particles such as "van" belong to the family name.
+            let split = words
+                .iter()
+                .position(|w| w.chars().next().is_some_and(char::is_lowercase))
+                .filter(|&i| i > 0)
+                .unwrap_or(words.len().saturating_sub(1));
+            Name {
+                family: latex_to_text(&words[split..].join(" ")),
+                given: latex_to_text(&words[..split].join(" ")),
+            }
+        }
+    }
+}
+
+fn compose_accent(accent: &str, letter: char) -> String {
+    let table: &[(&str, &str, &str)] = &[
+        ("\"", "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+        ("'", "aeiouyAEIOUYcnszCNSZ", "áéíóúýÁÉÍÓÚÝćńśźĆŃŚŹ"),
+        ("`", "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        ("^", "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ("~", "anoANO", "ãñõÃÑÕ"),
+        ("c", "csCS", "çşÇŞ"),
+        ("v", "cszrenCSZREN", "čšžřěňČŠŽŘĚŇ"),
+        ("u", "agAG", "ăğĂĞ"),
+        ("H", "ouOU", "őűŐŰ"),
+    ];
+    let accent = accent.trim_end_matches(|c: char| c == '{' || c.is_whitespace());
+    table
+        .iter()
+        .find(|(name, _, _)| *name == accent)
+        .and_then(|(_, base, composed)| {
+            let i = base.chars().position(|c| c == letter)?;
+            composed.chars().nth(i)
+        })
+        .map(String::from)
+        .unwrap_or_else(|| letter.to_string())
+}
+
+/// Turn BibTeX field markup into plain text.
+fn latex_to_text(value: &str) -> String {
+    let text = LATEX_ACCENT_RE.replace_all(value, |caps: &Captures| {
+        compose_accent(&caps[1], caps[2].chars().next().unwrap_or_default())
+    });
+    let text = LATEX_SYMBOL_RE.replace_all(&text, |caps: &Captures| {
+        match &caps[1] {
+            "ss" => "ß",
+            "ae" => "æ",
+            "AE" => "Æ",
+            "oe" => "œ",
+            "OE" => "Œ",
+            "aa" => "å",
+            "AA" => "Å",
+            "o" => "ø",
+            "O" => "Ø",
+            "l" => "ł",
+            "L" => "Ł",
+            _ => "ı",
+        }
+        .to_string()
+    });
+    let text = text
+        .replace("\\&", "&")
+        .replace("\\%", "%")
+        .replace("\\$", "$")
+        .replace("\\_", "_")
+        .replace("\\#", "#")
+        .replace("---", "—")
+        .replace("--", "–")
+        .replace("``", "“")
+        .replace("''", "”")
+        .replace('~', " ");
+    let text = LATEX_COMMAND_RE.replace_all(&text, "");
+    text.replace(['{', '}'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn first_year(value: &str) -> Option<String> {
+    let digits: String = value
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    (digits.len() == 4).then_some(digits)
+}
+
+// --- CSL-JSON ---
+
+fn parse_csl_json(text: &str) -> Result<Vec<Reference>, AppError> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| AppError::Other(format!("Invalid CSL-JSON: {}", e)))?;
+    let items = match &value {
+        Value::Array(items) => items.as_slice(),
+        Value::Object(map) => map
+            .get("items")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default(),
+        _ => &[],
+    };
+    Ok(items.iter().filter_map(csl_reference).collect())
+}
+
+fn csl_reference(item: &Value) -> Option<Reference> {
+    let text = |key: &str| match item.get(key) {
+        Some(Value::String(s)) => s.trim().to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    };
+    let key = text("id");
+    if key.is_empty() {
+        return None;
+    }
+    let kind = match text("type").as_str() {
+        "article" | "article-journal" | "article-magazine" | "article-newspaper" => Kind::Article,
+        "book" => Kind::Book,
+        "chapter" | "entry-encyclopedia" | "entry-dictionary" => Kind::Chapter,
+        "paper-conference" => Kind::Paper,
+        "thesis" => Kind::Thesis,
+        "report" => Kind::Report,
+        "webpage" | "post" | "post-weblog" => Kind::Web,
+        _ => Kind::Other,
+    };
+    let names = |key: &str| -> Vec<Name> {
+        item.get(key)
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|n| {
+                        let field = |k: &str| n.get(k).and_then(Value::as_str).unwrap_or_default();
+                        if !field("literal").is_empty() {
+                            Name {
+                                family: field("literal").to_string(),
+                                given: String::new(),
+                            }
+                        } else {
+                            let particle = field("non-dropping-particle");
+                            Name {
+                                family: format!("{} {}", particle, field("family"))
+                                    .trim()
+                                    .to_string(),
+                                given: field("given").to_string(),
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let issued = item.get("issued");
+    let year = issued
+        .and_then(|d| d.get("date-parts"))
+        .and_then(|parts| parts.get(0)?.get(0).cloned())
+        .map(|y| match y {
+            Value::String(s) => s,
+            other => other.to_string(),
+        })
+        .or_else(|| {
+            issued
+                .and_then(|d| d.get("raw").or_else(|| d.get("literal")))
+                .and_then(Value::as_str)
+                .and_then(first_year)
+        });
+
+    let mut reference = Reference::new(&key, kind);
+    reference.authors = names("author");
+    reference.editors = names("editor");
+    reference.title = text("title");
+    reference.container = text("container-title");
+    reference.year = year;
+    reference.volume = text("volume");
+    reference.issue = text("issue");
+    reference.pages = text("page").replace('-', "–");
+    reference.publisher = text("publisher");
+    reference.place = text("publisher-place");
+    reference.edition = text("edition");
+    reference.doi = text("DOI");
+    reference.url = text("URL");
+    Some(reference)
+}
+
+// --- Formatting ---
+
+struct CiteItem {
+    prefix: String,
+    key: String,
+    locator: String,
+    suppress_author: bool,
+}
+
+/// Per-reference labels: the IEEE number or the author-date year with its
+/// `a`/`b` suffix.
+struct Labels {
+    numbers: HashMap<String, usize>,
+    years: HashMap<String, String>,
+}
+
+fn format_citations(
+    body: &str,
+    frontmatter: Option<&Frontmatter>,
+    references: &[Reference],
+    style: CitationStyle,
+) -> (String, Vec<String>) {
+    let by_key: HashMap<&str, &Reference> =
+        references.iter().map(|r| (r.key.as_str(), r)).collect();
+    let code = code_ranges(body);
+    let in_code = |range: &Range<usize>| {
+        code.iter()
+            .any(|c| c.start < range.end && range.start < c.end)
+    };
+
+    // Find every citation first so numbering follows the text.
+    let mut citations: Vec<(Range<usize>, Vec<CiteItem>, bool)> = Vec::new();
+    for caps in BRACKET_RE.captures_iter(body) {
+        let whole = caps.get(0).map(|m| m.range()).unwrap_or_default();
+        if !caps[1].is_empty() || caps.get(3).is_some() || in_code(&whole) {
+            continue;
+        }
+        let items: Option<Vec<CiteItem>> = caps[2].split(';').map(parse_item).collect();
+        if let Some(items) = items {
+            citations.push((whole, items, false));
+        }
+    }
+    for caps in NARRATIVE_RE.captures_iter(body) {
+        let lead = caps.name("lead").map_or(0, |m| m.len());
+        let whole = caps
+            .get(0)
+            .map(|m| m.start() + lead..m.end())
+            .unwrap_or_default();
+        let key = caps["key"].to_string();
+        let overlaps = citations
+            .iter()
+            .any(|(r, ..)| r.start < whole.end && whole.start < r.end);
+        // Only known keys, so that @mentions stay as they are.
+        if overlaps || in_code(&whole) || !by_key.contains_key(key.as_str()) {
+            continue;
+        }
+        let item = CiteItem {
+            prefix: String::new(),
+            key,
+            locator: caps
+                .name("locator")
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default(),
+            suppress_author: false,
+        };
+        citations.push((whole, vec![item], true));
+    }
+    citations.sort_by_key(|(range, ..)| range.start);
+
+    let mut cited: Vec<&str> = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+    for (_, items, _) in &citations {
+        for item in items {
+            if by_key.contains_key(item.key.as_str()) {
+                if !cited.contains(&item.key.as_str()) {
+                    cited.push(by_key[item.key.as_str()].key.as_str());
+                }
+            } else if !missing.contains(&item.key) {
+                missing.push(item.key.clone());
+            }
+        }
+    }
+    for entry in frontmatter
+        .map(|fm| fm.get_list("nocite"))
+        .unwrap_or_default()
+    {
+        for key in entry
+            .split([',', ';', ' '])
+            .map(|k| k.trim().trim_start_matches('@'))
+        {
+            if key == "*" {
+                for reference in references {
+                    if !cited.contains(&reference.key.as_str()) {
+                        cited.push(&reference.key);
+                    }
+                }
+            } else if let Some(reference) = by_key.get(key) {
+                if !cited.contains(&reference.key.as_str()) {
+                    cited.push(&reference.key);
+                }
+            }
+        }
+    }
+    if citations.is_empty() && cited.is_empty() {
+        return (body.to_string(), missing);
+    }
+
+    let mut entries: Vec<&Reference> = cited.iter().map(|k| by_key[k]).collect();
+    if !style.numeric() {
+        entries.sort_by_cached_key(|r| {
+            (
+                sort_name(r).to_lowercase(),
+                r.year.clone().unwrap_or_default(),
+                r.title.to_lowercase(),
+            )
+        });
+    }
+    let labels = assign_labels(&entries, style);
+
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for (range, items, narrative) in &citations {
+        out.push_str(&body[last..range.start]);
+        out.push_str(&format_citation(items, *narrative, &by_key, &labels, style));
+        last = range.end;
+    }
+    out.push_str(&body[last..]);
+
+    let suppress = frontmatter
+        .and_then(|fm| fm.get("suppress-bibliography"))
+        .is_some_and(|v| v == "true");
+    if suppress || entries.is_empty() {
+        return (out, missing);
+    }
+    let list = entries
+        .iter()
+        .map(|r| format_entry(r, &labels, style))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let rendered = match PLACEHOLDER_RE.find(&out) {
+        Some(m) => format!("{}{}{}", &out[..m.start()], list, &out[m.end()..]),
+        None => {
+            let title = frontmatter
+                .and_then(|fm| fm.get("reference-section-title"))
+                .unwrap_or(DEFAULT_SECTION_TITLE);
+            format!("{}\n\n## {}\n\n{}\n", out.trim_end(), title, list)
+        }
+    };
+    (rendered, missing)
+}
+
+fn parse_item(text: &str) -> Option<CiteItem> {
+    let caps = ITEM_RE.captures(text.trim())?;
+    let suffix = caps["suffix"].trim();
+    Some(CiteItem {
+        prefix: caps["prefix"].trim().to_string(),
+        key: caps["key"].to_string(),
+        locator: suffix
+            .strip_prefix(',')
+            .unwrap_or(suffix)
+            .trim()
+            .to_string(),
+        suppress_author: caps.name("suppress").is_some(),
+    })
+}
+
+fn assign_labels(entries: &[&Reference], style: CitationStyle) -> Labels {
+    let numbers = entries
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.key.clone(), i + 1))
+        .collect();
+    // Same authors and year: 2020a, 2020b in bibliography order.
+    let mut groups: HashMap<(String, String), Vec<&str>> = HashMap::new();
+    for r in entries {
+        let year = r.year.clone().unwrap_or_else(|| "n.d.".to_string());
+        groups
+            .entry((short_authors(r, style, false), year))
+            .or_default()
+            .push(&r.key);
+    }
+    let mut years = HashMap::new();
+    for ((_, year), keys) in groups {
+        for (i, key) in keys.iter().enumerate() {
+            let label = if keys.len() > 1 {
+                let letter = (b'a' + (i % 26) as u8) as char;
+                let separator = if year == "n.d." { "-" } else { "" };
+                format!("{}{}{}", year, separator, letter)
+            } else {
+                year.clone()
+            };
+            years.insert(key.to_string(), label);
+        }
+    }
+    Labels { numbers, years }
+}
+
+fn sort_name(reference: &Reference) -> String {
+    reference
+        .authors
+        .first()
+        .or(reference.editors.first())
+        .map(|n| n.family.clone())
+        .unwrap_or_else(|| reference.title.clone())
+}
+
+/// Author names for an in-text citation.
+fn short_authors(reference: &Reference, style: CitationStyle, narrative: bool) -> String {
+    let names = if reference.authors.is_empty() {
+        &reference.editors
+    } else {
+        &reference.authors
+    };
+    let families: Vec<&str> = names.iter().map(|n| n.family.as_str()).collect();
+    let and = if style == CitationStyle::Apa && !narrative {
+        "&"
+    } else {
+        "and"
+    };
+    match families.as_slice() {
+        [] => format!("“{}”", reference.title),
+        [one] => one.to_string(),
+        [a, b] => format!("{} {} {}", a, and, b),
+        [a, b, c] if style == CitationStyle::Chicago => format!("{}, {}, and {}", a, b, c),
+        [first, ..] => format!("{} et al.", first),
+    }
+}
+
+fn format_citation(
+    items: &[CiteItem],
+    narrative: bool,
+    by_key: &HashMap<&str, &Reference>,
+    labels: &Labels,
+    style: CitationStyle,
+) -> String {
+    let parts: Vec<String> = items
+        .iter()
+        .map(|item| {
+            let Some(reference) = by_key.get(item.key.as_str()) else {
+                return format!("**{}?**", item.key);
+            };
+            let locator = if style == CitationStyle::Chicago {
+                strip_page_label(&item.locator)
+            } else {
+                item.locator.clone()
+            };
+            let body = match style {
+                CitationStyle::Ieee => {
+                    let number = labels.numbers.get(&reference.key).copied().unwrap_or(0);
+                    if locator.is_empty() {
+                        format!("[{}]", number)
+                    } else {
+                        format!("[{}, {}]", number, locator)
+                    }
+                }
+                _ => {
+                    let year = labels
+                        .years
+                        .get(&reference.key)
+                        .cloned()
+                        .unwrap_or_default();
+                    let separator = if style == CitationStyle::Apa {
+                        ", "
+                    } else {
+                        " "
+                    };
+                    let date = if locator.is_empty() {
+                        year
+                    } else {
+                        format!("{}, {}", year, locator)
+                    };
+                    if narrative {
+                        format!("{} ({})", short_authors(reference, style, true), date)
+                    } else if item.suppress_author {
+                        date
+                    } else {
+                        format!(
+                            "{}{}{}",
+                            short_authors(reference, style, false),
+                            separator,
+                            date
+                        )
+                    }
+                }
+            };
+            if narrative && style.numeric() {
+                format!("{} {}", short_authors(reference, style, true), body)
+            } else if item.prefix.is_empty() {
+                body
+            } else {
+                format!("{} {}", item.prefix, body)
+            }
+        })
+        .collect();
+    if narrative || style.numeric() {
+        parts.join(", ")
+    } else {
+        format!("({})", parts.join("; "))
+    }
+}
+
+/// Chicago gives bare page numbers: `p. 4` becomes `4`.
+fn strip_page_label(locator: &str) -> String {
+    for label in ["pp. ", "p. ", "pp.", "p."] {
+        if let Some(rest) = locator.strip_prefix(label) {
+            return rest.trim().to_string();
+        }
+    }
+    locator.to_string()
+}
+
+/// Escape characters that markdown would treat as markup.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '_' | '[' | ']' | '<' | '>' | '`' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Append `punct` unless `text` already ends with sentence punctuation.
+fn close(text: &str, punct: char) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}{}", text, punct)
+    }
+}
+
+/// Join names with a final conjunction. `serial` puts a comma before it,
+/// which inverted names (`Smith, J.`) need even when there are only two.
+fn join_names(names: &[String], conjunction: &str, serial: bool) -> String {
+    let comma = if serial { "," } else { "" };
+    match names {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{}{} {} {}", rest.join(", "), comma, conjunction, last),
+    }
+}
+
+fn format_entry(reference: &Reference, labels: &Labels, style: CitationStyle) -> String {
+    match style {
+        CitationStyle::Apa => format_apa(reference, labels),
+        CitationStyle::Chicago => format_chicago(reference, labels),
+        CitationStyle::Ieee => format_ieee(reference, labels),
+    }
+}
+
+fn format_apa(r: &Reference, labels: &Labels) -> String {
+    let year = labels.years.get(&r.key).cloned().unwrap_or_default();
+    let title = escape(&r.title);
+    let container = escape(&r.container);
+    let standalone = matches!(
+        r.kind,
+        Kind::Book | Kind::Thesis | Kind::Report | Kind::Web | Kind::Other
+    );
+    let title_markup = if standalone && !title.is_empty() {
+        format!("*{}*", title)
+    } else {
+        title.clone()
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    let authors: Vec<String> = r
+        .authors
+        .iter()
+        .map(|n| escape(&n.inverted(true)))
+        .collect();
+    let has_authors = !authors.is_empty();
+    if has_authors {
+        parts.push(format!(
+            "{} ({}).",
+            close(&join_names(&authors, "&", true), '.'),
+            year
+        ));
+    } else {
+        parts.push(close(&title_markup, '.'));
+        parts.push(format!("({}).", year));
+    }
+    if has_authors && !title.is_empty() {
+        let mut t = title_markup.clone();
+        if r.kind == Kind::Book && !r.edition.is_empty() {
+            t = format!("{} ({} ed.)", t, r.edition);
+        }
+        if r.kind == Kind::Thesis && !r.publisher.is_empty() {
+            t = format!("{} [Thesis, {}]", t, escape(&r.publisher));
+        }
+        parts.push(close(&t, '.'));
+    }
+    match r.kind {
+        Kind::Article => {
+            let mut source = format!("*{}*", container);
+            if !r.volume.is_empty() {
+                source.push_str(&format!(", *{}*", r.volume));
+            }
+            if !r.issue.is_empty() {
+                source.push_str(&format!("({})", r.issue));
+            }
+            if !r.pages.is_empty() {
+                source.push_str(&format!(", {}", r.pages));
+            }
+            if !container.is_empty() {
+                parts.push(close(&source, '.'));
+            }
+        }
+        Kind::Chapter | Kind::Paper => {
+            let mut source = String::from("In ");
+            if !r.editors.is_empty() {
+                let editors: Vec<String> =
+                    r.editors.iter().map(|n| escape(&n.natural(true))).collect();
+                let label = if editors.len() > 1 { "Eds." } else { "Ed." };
+                source.push_str(&format!(
+                    "{} ({}), ",
+                    join_names(&editors, "&", editors.len() > 2),
+                    label
+                ));
+            }
+            source.push_str(&format!("*{}*", container));
+            if !r.pages.is_empty() {
+                source.push_str(&format!(" (pp. {})", r.pages));
+            }
+            parts.push(close(&source, '.'));
+            if !r.publisher.is_empty() {
+                parts.push(close(&escape(&r.publisher), '.'));
+            }
+        }
+        Kind::Book | Kind::Report => {
+            if !r.publisher.is_empty() {
+                parts.push(close(&escape(&r.publisher), '.'));
+            }
+        }
+        Kind::Web | Kind::Other => {
+            if !container.is_empty() {
+                parts.push(close(&container, '.'));
+            }
+        }
+        Kind::Thesis => {}
+    }
+    parts.extend(r.link());
+    parts.join(" ")
+}
+
+fn format_chicago(r: &Reference, labels: &Labels) -> String {
+    let year = labels.years.get(&r.key).cloned().unwrap_or_default();
+    let title = escape(&r.title);
+    let container = escape(&r.container);
+    let quoted = matches!(
+        r.kind,
+        Kind::Article | Kind::Chapter | Kind::Paper | Kind::Thesis
+    );
+
+    let mut parts: Vec<String> = Vec::new();
+    let authors: Vec<String> = r
+        .authors
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            escape(&if i == 0 {
+                n.inverted(false)
+            } else {
+                n.natural(false)
+            })
+        })
+        .collect();
+    let title_markup = if quoted {
+        format!("“{}.”", title.trim_end_matches('.'))
+    } else {
+        close(&format!("*{}*", title), '.')
+    };
+    if authors.is_empty() {
+        parts.push(title_markup.clone());
+        parts.push(close(&year, '.'));
+    } else {
+        parts.push(close(&join_names(&authors, "and", true), '.'));
+        parts.push(close(&year, '.'));
+        parts.push(title_markup);
+    }
+    match r.kind {
+        Kind::Article => {
+            let mut source = format!("*{}*", container);
+            if !r.volume.is_empty() {
+                source.push_str(&format!(" {}", r.volume));
+            }
+            if !r.issue.is_empty() {
+                source.push_str(&format!(" ({})", r.issue));
+            }
+            if !r.pages.is_empty() {
+                source.push_str(&format!(": {}", r.pages));
+            }
+            parts.push(close(&source, '.'));
+        }
+        Kind::Chapter | Kind::Paper => {
+            let mut source = format!("In *{}*", container);
+            if !r.editors.is_empty() {
+                let editors: Vec<String> = r
+                    .editors
+                    .iter()
+                    .map(|n| escape(&n.natural(false)))
+                    .collect();
+                source.push_str(&format!(
+                    ", edited by {}",
+                    join_names(&editors, "and", editors.len() > 2)
+                ));
+            }
+            if !r.pages.is_empty() {
+                source.push_str(&format!(", {}", r.pages));
+            }
+            parts.push(close(&source, '.'));
+            parts.extend(publisher_with_place(r));
+        }
+        Kind::Thesis => {
+            parts.push(close(&format!("Thesis, {}", escape(&r.publisher)), '.'));
+        }
+        Kind::Book | Kind::Report => parts.extend(publisher_with_place(r)),
+        Kind::Web | Kind::Other => {
+            if !container.is_empty() {
+                parts.push(close(&container, '.'));
+            }
+        }
+    }
+    if let Some(link) = r.link() {
+        parts.push(format!("{}.", link));
+    }
+    parts.join(" ")
+}
+
+/// `Place: Publisher.`
+fn publisher_with_place(r: &Reference) -> Option<String> {
+    match (r.place.is_empty(), r.publisher.is_empty()) {
+        (_, true) => None,
+        (true, false) => Some(close(&escape(&r.publisher), '.')),
+        (false, false) => Some(close(
+            &format!("{}: {}", escape(&r.place), escape(&r.publisher)),
+            '.',
+        )),
+    }
+}
+
+fn format_ieee(r: &Reference, labels: &Labels) -> String {
+    let number = labels.numbers.get(&r.key).copied().unwrap_or(0);
+    let title = escape(&r.title);
+    let container = escape(&r.container);
+    let mut names: Vec<String> = r.authors.iter().map(|n| escape(&n.natural(true))).collect();
+    if names.len() > 6 {
+        names = vec![format!("{} et al.", names[0])];
+    }
+    let authors = join_names(&names, "and", names.len() > 2);
+    let year = r.year.clone().unwrap_or_default();
+
+    let mut fields: Vec<String> = Vec::new();
+    match r.kind {
+        Kind::Article => {
+            fields.push(format!("*{}*", container));
+            if !r.volume.is_empty() {
+                fields.push(format!("vol. {}", r.volume));
+            }
+            if !r.issue.is_empty() {
+                fields.push(format!("no. {}", r.issue));
+            }
+            if !r.pages.is_empty() {
+                fields.push(format!("pp. {}", r.pages));
+            }
+            fields.push(year);
+        }
+        Kind::Chapter | Kind::Paper => {
+            let mut source = format!("in *{}*", container);
+            if !r.editors.is_empty() {
+                let editors: Vec<String> =
+                    r.editors.iter().map(|n| escape(&n.natural(true))).collect();
+                let label = if editors.len() > 1 { "Eds." } else { "Ed." };
+                source.push_str(&format!(
+                    ", {}, {}",
+                    join_names(&editors, "and", editors.len() > 2),
+                    label
+                ));
+            }
+            fields.push(source);
+            fields.extend(publisher_with_place(r).map(|p| p.trim_end_matches('.').to_string()));
+            fields.push(year);
+            if !r.pages.is_empty() {
+                fields.push(format!("pp. {}", r.pages));
+            }
+        }
+        Kind::Book => {
+            if !r.edition.is_empty() {
+                fields.push(format!("{} ed.", r.edition));
+            }
+            fields.extend(publisher_with_place(r).map(|p| p.trim_end_matches('.').to_string()));
+            fields.push(year);
+        }
+        Kind::Thesis | Kind::Report => {
+            fields.push(if r.kind == Kind::Thesis {
+                "Thesis".to_string()
+            } else {
+                "Tech. Rep.".to_string()
+            });
+            if !r.publisher.is_empty() {
+                fields.push(escape(&r.publisher));
+            }
+            fields.push(year);
+        }
+        Kind::Web | Kind::Other => {
+            if !container.is_empty() {
+                fields.push(container);
+            }
+            fields.push(year);
+        }
+    }
+    fields.retain(|f| !f.is_empty());
+
+    let mut entry = format!("\\[{}\\] ", number);
+    if !authors.is_empty() {
+        entry.push_str(&format!("{}, ", authors));
+    }
+    match (r.kind == Kind::Book, fields.is_empty()) {
+        (true, true) => entry.push_str(&format!("*{}*.", title)),
+        (true, false) => entry.push_str(&format!("*{}*, {}.", title, fields.join(", "))),
+        (false, true) => entry.push_str(&format!("“{}.”", title.trim_end_matches('.'))),
+        (false, false) => entry.push_str(&format!("“{},” {}.", title, fields.join(", "))),
+    }
+    if !r.doi.is_empty() {
+        entry.push_str(&format!(
+            " doi: {}.",
+            r.doi.trim_start_matches("https://doi.org/")
+        ));
+    } else if !r.url.is_empty() {
+        entry.push_str(&format!(" [Online]. Available: <{}>", r.url));
+    }
+    entry
+}
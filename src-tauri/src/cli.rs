@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use crate::docx;
+use crate::utils::{collect_md_files, is_markdown_file};
+
+const DOCX_USAGE: &str = "\
+Usage: markview docx [--reference REF.docx] [--out-dir DIR] <FILE_OR_FOLDER>...
+
+Convert markdown files to Word documents. Folders are converted recursively.
+Each output is written next to its source unless --out-dir is given.";
+
+/// Handle command-line subcommands. Returns the process exit code when `args`
+/// (without the program name) name a subcommand, or `None` to start the app.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let command: fn(&[String]) -> i32 = match args.first().map(String::as_str) {
+        Some("docx") => run_docx,
+        _ => return None,
+    };
+    attach_console();
+    Some(command(&args[1..]))
+}
+
+/// Release builds on Windows use the GUI subsystem and start without a
+/// console, so output and errors would be lost; write them to the console
+/// of the shell the command was run from instead.
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails harmlessly when there is no parent console or one is attached
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn run_docx(args: &[String]) -> i32 {
+    let mut reference: Option<PathBuf> = None;
+    let mut out_dir: Option<PathBuf> = None;
+    let mut inputs: Vec<PathBuf> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", DOCX_USAGE);
+                return 0;
+            }
+            "-r" | "--reference" | "-o" | "--out-dir" => {
+                let Some(value) = iter.next() else {
+                    eprintln!("Missing value for {}\n\n{}", arg, DOCX_USAGE);
+                    return 2;
+                };
+                if arg.starts_with("-r") || arg == "--reference" {
+                    reference = Some(PathBuf::from(value));
+                } else {
+                    out_dir = Some(PathBuf::from(value));
+                }
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option {}\n\n{}", arg, DOCX_USAGE);
+                return 2;
+            }
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+
+    if inputs.is_empty() {
+        eprintln!("{}", DOCX_USAGE);
+        return 2;
+    }
+
+    let mut jobs: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for input in &inputs {
+        if input.is_dir() {
+            let mut files = collect_md_files(input);
+            files.sort();
+            for file in files {
+                // Keep the folder layout below the input folder in --out-dir.
+                let relative = file.strip_prefix(input).unwrap_or(&file).to_path_buf();
+                jobs.push((
+                    file.clone(),
+                    output_path(&file, &relative, out_dir.as_deref()),
+                ));
+            }
+        } else if input.is_file() && is_markdown_file(input) {
+            let name = PathBuf::from(input.file_name().unwrap_or_default());
+            jobs.push((input.clone(), output_path(input, &name, out_dir.as_deref())));
+        } else {
+            eprintln!(
+                "Skipping {}: not a markdown file or folder",
+                input.display()
+            );
+        }
+    }
+
+    let mut failures = 0;
+    for (source, target) in &jobs {
+        match docx::convert_file(source, target, reference.as_deref()) {
+            Ok(()) => println!("{} -> {}", source.display(), target.display()),
+            Err(e) => {
+                eprintln!("{}: {}", source.display(), e);
+                failures += 1;
+            }
+        }
+    }
+
+    println!(
+        "Converted {} of {} file(s)",
+        jobs.len() - failures,
+        jobs.len()
+    );
+    if failures > 0 || jobs.is_empty() {
+        1
+    } else {
+        0
+    }
+}
+
+fn output_path(source: &Path, relative: &Path, out_dir: Option<&Path>) -> PathBuf {
+    match out_dir {
+        Some(dir) => dir.join(relative).with_extension("docx"),
+        None => source.with_extension("docx"),
+    }
+}
@@ -1,9 +1,13 @@
 use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
 use crate::error::AppError;
-use crate::utils::{collect_md_files, is_markdown_file, sanitize_filename, validate_directory};
+use crate::saved_search;
+use crate::utils::{
+    collect_workspace_files, is_markdown_file, is_table_file, sanitize_filename, validate_directory,
+};
 
 #[derive(Serialize, Clone)]
 pub struct FileEntry {
@@ -11,18 +15,24 @@ pub struct FileEntry {
     pub path: String,
     pub is_directory: bool,
     pub children: Option<Vec<FileEntry>>,
+    /// Id of the saved search behind a virtual smart folder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smart_folder: Option<String>,
 }
 
-/// Recursively reads a directory and returns a tree of `.md` files and
-/// directories that (transitively) contain `.md` files.
-/// Directories come first, then files, both sorted alphabetically.
+/// Recursively reads a directory and returns a tree of `.md` and `.csv`/`.tsv`
+/// files and directories that (transitively) contain such files.
+/// Directories come first, then files, both sorted alphabetically. Smart
+/// folders (saved searches shown as folders) lead the tree.
 #[tauri::command]
-pub fn read_directory(path: String) -> Result<Vec<FileEntry>, AppError> {
+pub fn read_directory(app: AppHandle, path: String) -> Result<Vec<FileEntry>, AppError> {
     let root = validate_directory(&path)?;
 
-    let md_files = collect_md_files(&root);
+    let mut tree = saved_search::smart_folder_entries(&app, &root);
+
+    let md_files = collect_workspace_files(&root);
     if md_files.is_empty() {
-        return Ok(Vec::new());
+        return Ok(tree);
     }
 
     // Build a set of all directories that should be kept because they sit
@@ -40,10 +50,7 @@ pub fn read_directory(path: String) -> Result<Vec<FileEntry>, AppError> {
         }
     }
 
-    fn build_tree(
-        dir: &Path,
-        keep_dirs: &std::collections::HashSet<PathBuf>,
-    ) -> Vec<FileEntry> {
+    fn build_tree(dir: &Path, keep_dirs: &std::collections::HashSet<PathBuf>) -> Vec<FileEntry> {
         let mut children: Vec<fs::DirEntry> = match fs::read_dir(dir) {
             Ok(rd) => rd.filter_map(|e| e.ok()).collect(),
             Err(_) => return Vec::new(),
@@ -71,14 +78,18 @@ pub fn read_directory(path: String) -> Result<Vec<FileEntry>, AppError> {
                         path: child_path.to_string_lossy().into_owned(),
                         is_directory: true,
                         children: Some(sub),
+                        smart_folder: None,
                     });
                 }
-            } else if child_path.is_file() && is_markdown_file(&child_path) {
+            } else if child_path.is_file()
+                && (is_markdown_file(&child_path) || is_table_file(&child_path))
+            {
                 files.push(FileEntry {
                     name,
                     path: child_path.to_string_lossy().into_owned(),
                     is_directory: false,
                     children: None,
+                    smart_folder: None,
                 });
             }
         }
@@ -88,7 +99,8 @@ pub fn read_directory(path: String) -> Result<Vec<FileEntry>, AppError> {
         dirs
     }
 
-    Ok(build_tree(&root, &keep_dirs))
+    tree.extend(build_tree(&root, &keep_dirs));
+    Ok(tree)
 }
 
 /// Reads a file's content as a UTF-8 string.
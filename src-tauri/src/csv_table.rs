@@ -0,0 +1,305 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Alignment, Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::AppError;
+use crate::format::render_table;
+use crate::markdown::{parser_options, LineIndex};
+
+/// Numbers as they appear in spreadsheets: signs, thousands separators,
+/// decimals, percentages and common currency symbols.
+static NUMBER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[-+(]?[$€£¥]?\s?\d{1,3}(?:[,' ]?\d{3})*(?:[.,]\d+)?\)?\s?[%$€£¥]?$|^[-+]?\d*\.\d+(?:[eE][-+]?\d+)?$")
+        .expect("invalid number regex")
+});
+static DATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2})?)?$|^\d{1,2}[/.]\d{1,2}[/.]\d{2,4}$",
+    )
+    .expect("invalid date regex")
+});
+
+#[derive(Deserialize)]
+pub struct CsvImportOptions {
+    /// CSV or TSV file to read.
+    pub path: Option<String>,
+    /// Delimited text to convert instead of a file, e.g. from the clipboard.
+    pub text: Option<String>,
+    /// Field delimiter; detected from the file extension or content when omitted.
+    pub delimiter: Option<String>,
+    /// Treat the first row as the table header. Otherwise generic
+    /// `Column N` headers are added.
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CsvExportOptions {
+    /// Markdown containing the table.
+    pub content: String,
+    /// 1-based line inside the table to export; the first table when omitted.
+    pub line: Option<usize>,
+    pub output_path: String,
+    pub delimiter: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Convert CSV/TSV data into a GFM table.
+///
+/// Columns whose values are all numbers or dates are right-aligned. Pipes
+/// are escaped and line breaks inside quoted fields become `<br>`.
+#[tauri::command]
+pub fn csv_to_table(options: CsvImportOptions) -> Result<String, AppError> {
+    let (text, path_delimiter) = match (&options.text, &options.path) {
+        (Some(text), _) => (text.clone(), None),
+        (None, Some(path)) => {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                return Err(AppError::NotFound(path.to_string_lossy().into_owned()));
+            }
+            let delimiter = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+                .then_some('\t');
+            (fs::read_to_string(&path)?, delimiter)
+        }
+        (None, None) => return Err(AppError::Other("No CSV data given".to_string())),
+    };
+
+    let delimiter = parse_delimiter(options.delimiter.as_deref())
+        .or(path_delimiter)
+        .unwrap_or_else(|| detect_delimiter(&text));
+    let mut rows = parse_delimited(&text, delimiter);
+    if rows.is_empty() {
+        return Err(AppError::Other("No rows found".to_string()));
+    }
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if !options.has_header {
+        rows.insert(0, (1..=columns).map(|i| format!("Column {}", i)).collect());
+    }
+    let aligns = detect_alignment(&rows[1..], columns);
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| escape_cell(cell)).collect())
+        .collect();
+    Ok(render_table(&cells, &aligns))
+}
+
+/// Write a markdown table from `content` to a CSV/TSV file. Returns the
+/// number of rows written, including the header.
+#[tauri::command]
+pub fn export_table_csv(options: CsvExportOptions) -> Result<usize, AppError> {
+    let output = PathBuf::from(&options.output_path);
+    let extension_delimiter = output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+        .then_some('\t');
+    let delimiter = parse_delimiter(options.delimiter.as_deref())
+        .or(extension_delimiter)
+        .unwrap_or(',');
+
+    let rows = find_table(&options.content, options.line).ok_or_else(|| {
+        AppError::Other(match options.line {
+            Some(line) => format!("No table found at line {}", line),
+            None => "No table found".to_string(),
+        })
+    })?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&output, write_delimited(&rows, delimiter))?;
+    Ok(rows.len())
+}
+
+fn parse_delimiter(value: Option<&str>) -> Option<char> {
+    match value? {
+        "\\t" | "tab" => Some('\t'),
+        other => other.chars().next(),
+    }
+}
+
+/// Pick the delimiter that splits the first lines most consistently.
+fn detect_delimiter(text: &str) -> char {
+    let sample: Vec<&str> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(10)
+        .collect();
+    let mut best = (',', 0);
+    for candidate in ['\t', ',', ';', '|'] {
+        let counts: Vec<usize> = sample
+            .iter()
+            .map(|line| parse_delimited(line, candidate).first().map_or(0, Vec::len))
+            .collect();
+        let Some(&first) = counts.first() else {
+            continue;
+        };
+        if first > 1 && counts.iter().all(|&c| c == first) && first > best.1 {
+            best = (candidate, first);
+        }
+    }
+    best.0
+}
+
+/// Parse RFC 4180 style delimited text. Quoted fields may contain the
+/// delimiter, doubled quotes and line breaks. Blank lines are skipped.
+fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut field_started = false;
+    let mut chars = text.chars().peekable();
+
+    let end_row = |row: &mut Vec<String>, rows: &mut Vec<Vec<String>>| {
+        let row = std::mem::take(row);
+        if !(row.len() == 1 && row[0].is_empty()) {
+            rows.push(row);
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+            continue;
+        }
+        match c {
+            '"' if !field_started => {
+                in_quotes = true;
+                field_started = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                end_row(&mut row, &mut rows);
+                field_started = false;
+            }
+            c if c == delimiter => {
+                row.push(std::mem::take(&mut field));
+                field_started = false;
+            }
+            c => {
+                field.push(c);
+                field_started = true;
+            }
+        }
+    }
+    if field_started || !row.is_empty() {
+        row.push(field);
+        end_row(&mut row, &mut rows);
+    }
+    rows
+}
+
+fn write_delimited(rows: &[Vec<String>], delimiter: char) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|field| {
+                if field.contains([delimiter, '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect();
+        out.push_str(&fields.join(&delimiter.to_string()));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Right-align columns where every non-empty value is a number or date.
+fn detect_alignment(body: &[Vec<String>], columns: usize) -> Vec<Alignment> {
+    (0..columns)
+        .map(|i| {
+            let mut values = body
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .peekable();
+            let numeric = values.peek().is_some()
+                && values.all(|v| NUMBER_RE.is_match(v) || DATE_RE.is_match(v));
+            if numeric {
+                Alignment::Right
+            } else {
+                Alignment::None
+            }
+        })
+        .collect()
+}
+
+fn escape_cell(cell: &str) -> String {
+    cell.trim()
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Plain-text cells of the table containing `line`, or of the first table.
+fn find_table(content: &str, line: Option<usize>) -> Option<Vec<Vec<String>>> {
+    let index = LineIndex::new(content);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell: Option<String> = None;
+    let mut in_table = false;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Table(_)) => {
+                let (start, _) = index.position(range.start);
+                let (end, _) = index.position(range.end.saturating_sub(1).max(range.start));
+                in_table = line.is_none_or(|l| (start..=end).contains(&l));
+                rows.clear();
+            }
+            Event::End(TagEnd::Table) if in_table => return Some(rows),
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) if in_table => {
+                rows.push(Vec::new());
+            }
+            Event::Start(Tag::TableCell) if in_table => cell = Some(String::new()),
+            Event::End(TagEnd::TableCell) => {
+                if let (Some(text), Some(row)) = (cell.take(), rows.last_mut()) {
+                    row.push(text.trim().to_string());
+                }
+            }
+            Event::Text(text) | Event::Code(text) | Event::InlineMath(text) => {
+                if let Some(cell) = cell.as_mut() {
+                    cell.push_str(&text);
+                }
+            }
+            Event::InlineHtml(html) => {
+                if let Some(cell) = cell.as_mut() {
+                    if html.trim().eq_ignore_ascii_case("<br>")
+                        || html.trim().eq_ignore_ascii_case("<br/>")
+                        || html.trim().eq_ignore_ascii_case("<br />")
+                    {
+                        cell.push('\n');
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
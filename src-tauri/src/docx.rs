@@ -0,0 +1,1078 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::citations::apply_citations;
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::highlight::{highlight_lines, TokenKind};
+use crate::markdown::{escape_xml, is_external_url, parser_options, percent_decode, slugify};
+use crate::transclude::expand_embeds;
+use crate::wiki::wiki_links_to_markdown;
+
+/// Widest an embedded image may be: 6 inches in EMU (English Metric Units).
+const MAX_IMAGE_WIDTH_EMU: u64 = 6 * 914_400;
+
+/// EMU per pixel at 96 DPI.
+const EMU_PER_PIXEL: u64 = 9_525;
+
+/// Word limits bookmark names to 40 characters.
+const MAX_BOOKMARK_LEN: usize = 40;
+
+static SECT_PR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<w:sectPr[ >].*?</w:sectPr>").expect("invalid sectPr regex"));
+static HEADER_FOOTER_REF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<w:(headerReference|footerReference)[^>]*/>").expect("invalid header ref regex")
+});
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const REL_HYPERLINK: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink";
+const REL_IMAGE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image";
+
+#[derive(Deserialize)]
+pub struct DocxOptions {
+    /// Markdown file to convert; relative image paths resolve against its folder.
+    pub input_path: String,
+    pub output_path: String,
+    /// Editor content to convert instead of the file on disk (unsaved changes).
+    pub content: Option<String>,
+    /// `.docx` whose styles, theme and page setup are reused.
+    pub reference_doc: Option<String>,
+    /// Workspace root used to find `![[embedded]]` notes by name.
+    pub folder: Option<String>,
+}
+
+/// Convert markdown to a Word document.
+///
+/// Headings, lists, tables, footnotes, code, quotes and images are mapped to
+/// Word styles (`Heading1`…, `ListParagraph`, `TableGrid`, `SourceCode`,
+/// `Quote`, `FootnoteText`…). When a reference document is given its styles,
+/// theme and page setup are used, with any missing styles filled in from the
+/// built-in defaults. Returns the path of the written file.
+#[tauri::command]
+pub fn export_docx(options: DocxOptions) -> Result<String, AppError> {
+    let input = PathBuf::from(&options.input_path);
+    let content = match options.content {
+        Some(content) => content,
+        None => fs::read_to_string(&input)?,
+    };
+    let reference = options.reference_doc.as_ref().map(PathBuf::from);
+    let root = options.folder.as_ref().map(PathBuf::from);
+    write_docx(
+        &content,
+        &input,
+        root.as_deref(),
+        Path::new(&options.output_path),
+        reference.as_deref(),
+    )?;
+    Ok(options.output_path)
+}
+
+/// Convert a markdown file on disk. Used by the CLI batch converter.
+pub fn convert_file(input: &Path, output: &Path, reference: Option<&Path>) -> Result<(), AppError> {
+    let content = fs::read_to_string(input)?;
+    write_docx(&content, input, None, output, reference)
+}
+
+fn write_docx(
+    content: &str,
+    source: &Path,
+    root: Option<&Path>,
+    output: &Path,
+    reference: Option<&Path>,
+) -> Result<(), AppError> {
+    let (frontmatter, body) = split_frontmatter(content);
+    let base_dir = source.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let root = root.unwrap_or(&base_dir);
+    let body = apply_citations(
+        &expand_embeds(body, source, root),
+        frontmatter.as_ref(),
+        source,
+        root,
+    );
+    let body = wiki_links_to_markdown(&body);
+
+    let mut builder = DocxBuilder::new(&base_dir);
+    for event in Parser::new_ext(&body, parser_options()) {
+        builder.handle(event);
+    }
+    builder.close_paragraph();
+
+    let title = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("title").map(str::to_string))
+        .unwrap_or_else(|| {
+            source
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+    let author = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("author").map(str::to_string))
+        .unwrap_or_default();
+
+    let reference = match reference {
+        Some(path) => Some(ReferenceParts::load(path)?),
+        None => None,
+    };
+
+    builder.package(output, &title, &author, reference.as_ref())
+}
+
+/// Parts reused from a user-supplied reference document.
+struct ReferenceParts {
+    styles: Option<String>,
+    theme: Option<Vec<u8>>,
+    font_table: Option<String>,
+    sect_pr: Option<String>,
+}
+
+impl ReferenceParts {
+    fn load(path: &Path) -> Result<Self, AppError> {
+        let file = File::open(path)
+            .map_err(|_| AppError::NotFound(path.to_string_lossy().into_owned()))?;
+        let mut archive = ZipArchive::new(file)
+            .map_err(|e| AppError::Export(format!("Invalid reference document: {}", e)))?;
+
+        let mut read_entry = |name: &str| -> Option<Vec<u8>> {
+            let mut entry = archive.by_name(name).ok()?;
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf).ok()?;
+            Some(buf)
+        };
+
+        let styles =
+            read_entry("word/styles.xml").map(|b| String::from_utf8_lossy(&b).into_owned());
+        let theme = read_entry("word/theme/theme1.xml");
+        let font_table =
+            read_entry("word/fontTable.xml").map(|b| String::from_utf8_lossy(&b).into_owned());
+        // Page size and margins come from the reference's final section. Header
+        // and footer references point at parts we do not copy, so drop them.
+        let sect_pr = read_entry("word/document.xml").and_then(|b| {
+            let xml = String::from_utf8_lossy(&b).into_owned();
+            SECT_PR_RE.find_iter(&xml).last().map(|m| {
+                HEADER_FOOTER_REF_RE
+                    .replace_all(m.as_str(), "")
+                    .into_owned()
+            })
+        });
+
+        Ok(Self {
+            styles,
+            theme,
+            font_table,
+            sect_pr,
+        })
+    }
+}
+
+struct Relationship {
+    id: String,
+    kind: &'static str,
+    target: String,
+    external: bool,
+}
+
+struct ListLevel {
+    num_id: usize,
+    /// Whether the next paragraph is the first of the current item (gets the marker).
+    first_paragraph: bool,
+}
+
+struct TableState {
+    alignments: Vec<Alignment>,
+    rows: Vec<(bool, Vec<String>)>,
+    current: Vec<String>,
+    cell_index: usize,
+    in_head: bool,
+}
+
+struct DocxBuilder<'a> {
+    base_dir: &'a Path,
+    /// Output buffers; the last one receives XML. Footnotes and table cells
+    /// push their own buffer.
+    out: Vec<String>,
+    paragraph_open: bool,
+    hyperlink_open: bool,
+
+    bold_depth: usize,
+    italic_depth: usize,
+    strike: bool,
+    link: bool,
+    heading: Option<(u8, String)>,
+    code_block: Option<(String, String)>,
+    image: Option<(String, String)>,
+    quote_depth: usize,
+    list_stack: Vec<ListLevel>,
+    /// `(is_ordered, start)` of every numbering instance, indexed by `num_id - 1`.
+    numbering: Vec<(bool, u64)>,
+    task_marker: Option<bool>,
+    table: Option<TableState>,
+
+    footnote_ids: HashMap<String, usize>,
+    footnotes: HashMap<usize, String>,
+    footnote_stack: Vec<usize>,
+    footnote_first_paragraph: bool,
+
+    relationships: Vec<Relationship>,
+    media: Vec<(String, PathBuf)>,
+    next_bookmark: usize,
+    next_drawing: usize,
+}
+
+impl<'a> DocxBuilder<'a> {
+    fn new(base_dir: &'a Path) -> Self {
+        Self {
+            base_dir,
+            out: vec![String::new()],
+            paragraph_open: false,
+            hyperlink_open: false,
+            bold_depth: 0,
+            italic_depth: 0,
+            strike: false,
+            link: false,
+            heading: None,
+            code_block: None,
+            image: None,
+            quote_depth: 0,
+            list_stack: Vec::new(),
+            numbering: Vec::new(),
+            task_marker: None,
+            table: None,
+            footnote_ids: HashMap::new(),
+            footnotes: HashMap::new(),
+            footnote_stack: Vec::new(),
+            footnote_first_paragraph: false,
+            relationships: Vec::new(),
+            media: Vec::new(),
+            next_bookmark: 0,
+            next_drawing: 1,
+        }
+    }
+
+    fn buf(&mut self) -> &mut String {
+        self.out
+            .last_mut()
+            .expect("builder always has an output buffer")
+    }
+
+    fn add_relationship(&mut self, kind: &'static str, target: String, external: bool) -> String {
+        let id = format!("rIdX{}", self.relationships.len() + 1);
+        self.relationships.push(Relationship {
+            id: id.clone(),
+            kind,
+            target,
+            external,
+        });
+        id
+    }
+
+    fn footnote_id(&mut self, label: &str) -> usize {
+        let next = self.footnote_ids.len() + 1;
+        *self.footnote_ids.entry(label.to_string()).or_insert(next)
+    }
+
+    fn handle(&mut self, event: Event) {
+        if let Some((_, code)) = self.code_block.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let (lang, code) = self.code_block.take().expect("checked above");
+                    self.code_block(&lang, &code);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some((_, alt)) = self.image.as_mut() {
+            match event {
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                Event::End(TagEnd::Image) => {
+                    let (dest, alt) = self.image.take().expect("checked above");
+                    self.image(&dest, &alt);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.text(&text, false),
+            Event::Code(text) => self.text(&text, true),
+            Event::InlineMath(text) => self.text(&format!("${}$", text), true),
+            Event::DisplayMath(text) => {
+                self.close_paragraph();
+                self.code_block("", &text);
+            }
+            Event::SoftBreak => self.text(" ", false),
+            Event::HardBreak => {
+                self.ensure_paragraph();
+                self.buf().push_str("<w:r><w:br/></w:r>");
+            }
+            Event::Rule => {
+                self.close_paragraph();
+                self.buf().push_str(
+                    "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>",
+                );
+            }
+            Event::FootnoteReference(label) => {
+                let id = self.footnote_id(&label);
+                self.ensure_paragraph();
+                self.buf().push_str(&format!(
+                    "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteReference w:id=\"{}\"/></w:r>",
+                    id
+                ));
+            }
+            Event::TaskListMarker(checked) => self.task_marker = Some(checked),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let lower = html.to_lowercase();
+                if lower.contains("pagebreak") || lower.contains("page-break") {
+                    self.close_paragraph();
+                    self.buf()
+                        .push_str("<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>");
+                } else if lower.starts_with("<br") {
+                    self.ensure_paragraph();
+                    self.buf().push_str("<w:r><w:br/></w:r>");
+                }
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.close_paragraph(),
+            Tag::Heading { level, .. } => {
+                self.close_paragraph();
+                self.heading = Some((level as u8, String::new()));
+                self.out.push(String::new());
+            }
+            Tag::BlockQuote(_) => {
+                self.close_paragraph();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.close_paragraph();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code_block = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.close_paragraph();
+                self.numbering.push((start.is_some(), start.unwrap_or(1)));
+                let num_id = self.numbering.len();
+                self.list_stack.push(ListLevel {
+                    num_id,
+                    first_paragraph: false,
+                });
+            }
+            Tag::Item => {
+                self.close_paragraph();
+                if let Some(level) = self.list_stack.last_mut() {
+                    level.first_paragraph = true;
+                }
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.close_paragraph();
+                let id = self.footnote_id(&label);
+                self.footnote_stack.push(id);
+                self.footnote_first_paragraph = true;
+                self.out.push(String::new());
+            }
+            Tag::Table(alignments) => {
+                self.close_paragraph();
+                self.table = Some(TableState {
+                    alignments,
+                    rows: Vec::new(),
+                    current: Vec::new(),
+                    cell_index: 0,
+                    in_head: false,
+                });
+            }
+            Tag::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.in_head = true;
+                    table.cell_index = 0;
+                }
+            }
+            Tag::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    table.cell_index = 0;
+                }
+            }
+            Tag::TableCell => {
+                let (in_head, alignment) = match self.table.as_ref() {
+                    Some(t) => (t.in_head, t.alignments.get(t.cell_index).copied()),
+                    None => (false, None),
+                };
+                let jc = match alignment {
+                    Some(Alignment::Center) => "<w:jc w:val=\"center\"/>",
+                    Some(Alignment::Right) => "<w:jc w:val=\"right\"/>",
+                    _ => "",
+                };
+                self.out.push(format!("<w:p><w:pPr>{}</w:pPr>", jc));
+                self.paragraph_open = true;
+                if in_head {
+                    self.bold_depth += 1;
+                }
+            }
+            Tag::Emphasis => self.italic_depth += 1,
+            Tag::Strong => self.bold_depth += 1,
+            Tag::Strikethrough => self.strike = true,
+            Tag::Link { dest_url, .. } => {
+                self.ensure_paragraph();
+                let open = if let Some(anchor) = dest_url.strip_prefix('#') {
+                    format!(
+                        "<w:hyperlink w:anchor=\"{}\">",
+                        escape_xml(&bookmark_name(anchor))
+                    )
+                } else {
+                    let id = self.add_relationship(REL_HYPERLINK, dest_url.to_string(), true);
+                    format!("<w:hyperlink r:id=\"{}\">", id)
+                };
+                self.buf().push_str(&open);
+                self.hyperlink_open = true;
+                self.link = true;
+            }
+            Tag::Image { dest_url, .. } => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.close_paragraph(),
+            TagEnd::Heading(_) => {
+                let runs = self.out.pop().unwrap_or_default();
+                let (level, text) = self.heading.take().unwrap_or((1, String::new()));
+                let id = self.next_bookmark;
+                self.next_bookmark += 1;
+                let name = bookmark_name(&slugify(&text));
+                self.buf().push_str(&format!(
+                    "<w:p><w:pPr><w:pStyle w:val=\"Heading{level}\"/></w:pPr><w:bookmarkStart w:id=\"{id}\" w:name=\"{name}\"/>{runs}<w:bookmarkEnd w:id=\"{id}\"/></w:p>",
+                    level = level,
+                    id = id,
+                    name = escape_xml(&name),
+                    runs = runs,
+                ));
+            }
+            TagEnd::BlockQuote(_) => {
+                self.close_paragraph();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::List(_) => {
+                self.close_paragraph();
+                self.list_stack.pop();
+            }
+            TagEnd::Item => self.close_paragraph(),
+            TagEnd::FootnoteDefinition => {
+                self.close_paragraph();
+                let xml = self.out.pop().unwrap_or_default();
+                if let Some(id) = self.footnote_stack.pop() {
+                    self.footnotes.insert(id, xml);
+                }
+            }
+            TagEnd::TableHead => {
+                self.finish_row(true);
+                if let Some(table) = self.table.as_mut() {
+                    table.in_head = false;
+                }
+            }
+            TagEnd::TableRow => self.finish_row(false),
+            TagEnd::TableCell => {
+                if self.hyperlink_open {
+                    self.buf().push_str("</w:hyperlink>");
+                    self.hyperlink_open = false;
+                }
+                let mut cell = self.out.pop().unwrap_or_default();
+                cell.push_str("</w:p>");
+                self.paragraph_open = false;
+                if let Some(table) = self.table.as_mut() {
+                    if table.in_head {
+                        self.bold_depth = self.bold_depth.saturating_sub(1);
+                    }
+                    table.current.push(cell);
+                    table.cell_index += 1;
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.table(table);
+                }
+            }
+            TagEnd::Emphasis => self.italic_depth = self.italic_depth.saturating_sub(1),
+            TagEnd::Strong => self.bold_depth = self.bold_depth.saturating_sub(1),
+            TagEnd::Strikethrough => self.strike = false,
+            TagEnd::Link => {
+                if self.hyperlink_open {
+                    self.buf().push_str("</w:hyperlink>");
+                    self.hyperlink_open = false;
+                }
+                self.link = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_row(&mut self, header: bool) {
+        if let Some(table) = self.table.as_mut() {
+            let cells = std::mem::take(&mut table.current);
+            table.rows.push((header, cells));
+        }
+    }
+
+    /// Open a paragraph whose style depends on the surrounding block context.
+    fn ensure_paragraph(&mut self) {
+        if self.paragraph_open || self.heading.is_some() {
+            return;
+        }
+        let mut ppr = String::new();
+        let mut prefix = String::new();
+
+        if !self.footnote_stack.is_empty() {
+            ppr.push_str("<w:pStyle w:val=\"FootnoteText\"/>");
+            if self.footnote_first_paragraph {
+                self.footnote_first_paragraph = false;
+                prefix.push_str(
+                    "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteRef/></w:r><w:r><w:t xml:space=\"preserve\"> </w:t></w:r>",
+                );
+            }
+        } else if !self.list_stack.is_empty() {
+            let depth = self.list_stack.len() - 1;
+            let current = self.list_stack.last_mut().expect("checked above");
+            ppr.push_str("<w:pStyle w:val=\"ListParagraph\"/>");
+            if current.first_paragraph {
+                current.first_paragraph = false;
+                ppr.push_str(&format!(
+                    "<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{}\"/></w:numPr>",
+                    depth.min(8),
+                    current.num_id
+                ));
+            } else {
+                // Continuation paragraphs line up with the item text.
+                ppr.push_str(&format!("<w:ind w:left=\"{}\"/>", 720 * (depth + 1)));
+            }
+        } else if self.quote_depth > 0 {
+            ppr.push_str("<w:pStyle w:val=\"Quote\"/>");
+        }
+
+        if let Some(checked) = self.task_marker.take() {
+            prefix.push_str(&format!(
+                "<w:r><w:t xml:space=\"preserve\">{} </w:t></w:r>",
+                if checked { "\u{2612}" } else { "\u{2610}" }
+            ));
+        }
+
+        let open = if ppr.is_empty() {
+            format!("<w:p>{}", prefix)
+        } else {
+            format!("<w:p><w:pPr>{}</w:pPr>{}", ppr, prefix)
+        };
+        self.buf().push_str(&open);
+        self.paragraph_open = true;
+    }
+
+    fn close_paragraph(&mut self) {
+        if !self.paragraph_open {
+            return;
+        }
+        if self.hyperlink_open {
+            self.buf().push_str("</w:hyperlink>");
+            self.hyperlink_open = false;
+        }
+        self.buf().push_str("</w:p>");
+        self.paragraph_open = false;
+    }
+
+    fn run_properties(&self, code: bool) -> String {
+        let mut rpr = String::new();
+        if code {
+            rpr.push_str("<w:rStyle w:val=\"VerbatimChar\"/>");
+        } else if self.link {
+            rpr.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        }
+        if self.bold_depth > 0 {
+            rpr.push_str("<w:b/>");
+        }
+        if self.italic_depth > 0 {
+            rpr.push_str("<w:i/>");
+        }
+        if self.strike {
+            rpr.push_str("<w:strike/>");
+        }
+        rpr
+    }
+
+    fn text(&mut self, text: &str, code: bool) {
+        if let Some((_, heading_text)) = self.heading.as_mut() {
+            heading_text.push_str(text);
+        } else {
+            self.ensure_paragraph();
+        }
+        let rpr = self.run_properties(code);
+        let run = run_xml(&rpr, text);
+        self.buf().push_str(&run);
+    }
+
+    fn code_block(&mut self, lang: &str, code: &str) {
+        let lines = if lang.is_empty() {
+            code.lines()
+                .map(|l| vec![(TokenKind::Plain, l.to_string())])
+                .collect()
+        } else {
+            highlight_lines(code, lang)
+        };
+
+        let mut xml = String::from("<w:p><w:pPr><w:pStyle w:val=\"SourceCode\"/></w:pPr>");
+        for (i, tokens) in lines.iter().enumerate() {
+            if i > 0 {
+                xml.push_str("<w:r><w:br/></w:r>");
+            }
+            for (kind, text) in tokens {
+                let rpr = match kind {
+                    TokenKind::Plain => "",
+                    TokenKind::Keyword => "<w:b/><w:color w:val=\"8B1A8B\"/>",
+                    TokenKind::String => "<w:color w:val=\"1A801A\"/>",
+                    TokenKind::Comment => "<w:i/><w:color w:val=\"808080\"/>",
+                    TokenKind::Number => "<w:color w:val=\"1A59B3\"/>",
+                };
+                xml.push_str(&run_xml(rpr, text));
+            }
+        }
+        xml.push_str("</w:p>");
+        self.buf().push_str(&xml);
+    }
+
+    fn image(&mut self, dest: &str, alt: &str) {
+        let path = if is_external_url(dest) {
+            None
+        } else {
+            Some(self.base_dir.join(percent_decode(dest))).filter(|p| p.is_file())
+        };
+        let extension = path
+            .as_ref()
+            .and_then(|p| p.extension())
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let dimensions = path
+            .as_ref()
+            .filter(|_| matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "gif"))
+            .and_then(|p| image::image_dimensions(p).ok());
+
+        let (Some(path), Some((px_w, px_h))) = (path, dimensions) else {
+            // Remote or unsupported image: keep the alt text in italics.
+            self.italic_depth += 1;
+            let label = if alt.is_empty() { dest } else { alt };
+            self.text(&format!("[image: {}]", label), false);
+            self.italic_depth -= 1;
+            return;
+        };
+
+        let mut cx = u64::from(px_w) * EMU_PER_PIXEL;
+        let mut cy = u64::from(px_h) * EMU_PER_PIXEL;
+        if cx > MAX_IMAGE_WIDTH_EMU {
+            cy = cy * MAX_IMAGE_WIDTH_EMU / cx;
+            cx = MAX_IMAGE_WIDTH_EMU;
+        }
+
+        let media_name = format!("image{}.{}", self.media.len() + 1, extension);
+        self.media.push((media_name.clone(), path));
+        let rel_id = self.add_relationship(REL_IMAGE, format!("media/{}", media_name), false);
+        let drawing_id = self.next_drawing;
+        self.next_drawing += 1;
+
+        self.ensure_paragraph();
+        let xml = format!(
+            concat!(
+                "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\">",
+                "<wp:extent cx=\"{cx}\" cy=\"{cy}\"/>",
+                "<wp:docPr id=\"{id}\" name=\"Picture {id}\" descr=\"{alt}\"/>",
+                "<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">",
+                "<a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">",
+                "<pic:pic xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">",
+                "<pic:nvPicPr><pic:cNvPr id=\"{id}\" name=\"{name}\"/><pic:cNvPicPr/></pic:nvPicPr>",
+                "<pic:blipFill><a:blip r:embed=\"{rel}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>",
+                "<pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>",
+                "<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>",
+                "</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"
+            ),
+            cx = cx,
+            cy = cy,
+            id = drawing_id,
+            alt = escape_xml(alt),
+            name = escape_xml(&media_name),
+            rel = rel_id,
+        );
+        self.buf().push_str(&xml);
+    }
+
+    fn table(&mut self, table: TableState) {
+        let columns = table.rows.iter().map(|(_, c)| c.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let mut xml = String::from(
+            "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"5000\" w:type=\"pct\"/></w:tblPr><w:tblGrid>",
+        );
+        for _ in 0..columns {
+            xml.push_str("<w:gridCol/>");
+        }
+        xml.push_str("</w:tblGrid>");
+        for (header, cells) in &table.rows {
+            xml.push_str("<w:tr>");
+            if *header {
+                xml.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+            }
+            for i in 0..columns {
+                xml.push_str("<w:tc>");
+                match cells.get(i) {
+                    Some(cell) => xml.push_str(cell),
+                    None => xml.push_str("<w:p/>"),
+                }
+                xml.push_str("</w:tc>");
+            }
+            xml.push_str("</w:tr>");
+        }
+        xml.push_str("</w:tbl><w:p/>");
+        self.buf().push_str(&xml);
+    }
+
+    fn package(
+        self,
+        output: &Path,
+        title: &str,
+        author: &str,
+        reference: Option<&ReferenceParts>,
+    ) -> Result<(), AppError> {
+        let body = self.out.into_iter().next().unwrap_or_default();
+        let sect_pr = reference
+            .and_then(|r| r.sect_pr.clone())
+            .unwrap_or_else(|| DEFAULT_SECT_PR.to_string());
+
+        let document = format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+                "<w:document xmlns:w=\"{w}\" xmlns:r=\"{r}\" ",
+                "xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\">",
+                "<w:body>{body}{sect}</w:body></w:document>"
+            ),
+            w = NS_W,
+            r = NS_R,
+            body = body,
+            sect = sect_pr,
+        );
+
+        let mut footnotes_xml = format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+                "<w:footnotes xmlns:w=\"{w}\" xmlns:r=\"{r}\">",
+                "<w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>",
+                "<w:footnote w:type=\"continuationSeparator\" w:id=\"0\"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>"
+            ),
+            w = NS_W,
+            r = NS_R,
+        );
+        let mut ids: Vec<usize> = self.footnote_ids.values().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            let content = self
+                .footnotes
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| "<w:p/>".to_string());
+            footnotes_xml.push_str(&format!(
+                "<w:footnote w:id=\"{}\">{}</w:footnote>",
+                id, content
+            ));
+        }
+        footnotes_xml.push_str("</w:footnotes>");
+
+        let styles = match reference.and_then(|r| r.styles.as_ref()) {
+            Some(reference_styles) => merge_styles(reference_styles),
+            None => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:styles xmlns:w=\"{}\">{}{}</w:styles>",
+                NS_W,
+                DEFAULT_DOC_DEFAULTS,
+                DEFAULT_STYLES.iter().map(|(_, xml)| *xml).collect::<String>()
+            ),
+        };
+        let theme = reference.and_then(|r| r.theme.as_ref());
+        let font_table = reference.and_then(|r| r.font_table.as_ref());
+
+        let mut rels = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        );
+        rels.push_str("<Relationship Id=\"rIdStyles\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>");
+        rels.push_str("<Relationship Id=\"rIdNumbering\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering\" Target=\"numbering.xml\"/>");
+        rels.push_str("<Relationship Id=\"rIdFootnotes\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/footnotes\" Target=\"footnotes.xml\"/>");
+        if theme.is_some() {
+            rels.push_str("<Relationship Id=\"rIdTheme\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme\" Target=\"theme/theme1.xml\"/>");
+        }
+        if font_table.is_some() {
+            rels.push_str("<Relationship Id=\"rIdFontTable\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/fontTable\" Target=\"fontTable.xml\"/>");
+        }
+        for rel in &self.relationships {
+            rels.push_str(&format!(
+                "<Relationship Id=\"{}\" Type=\"{}\" Target=\"{}\"{}/>",
+                rel.id,
+                rel.kind,
+                escape_xml(&rel.target),
+                if rel.external {
+                    " TargetMode=\"External\""
+                } else {
+                    ""
+                }
+            ));
+        }
+        rels.push_str("</Relationships>");
+
+        let mut content_types = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+            "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+            "<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+            "<Default Extension=\"png\" ContentType=\"image/png\"/>",
+            "<Default Extension=\"jpg\" ContentType=\"image/jpeg\"/>",
+            "<Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/>",
+            "<Default Extension=\"gif\" ContentType=\"image/gif\"/>",
+            "<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>",
+            "<Override PartName=\"/word/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml\"/>",
+            "<Override PartName=\"/word/numbering.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml\"/>",
+            "<Override PartName=\"/word/footnotes.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml\"/>",
+            "<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>"
+        ));
+        if theme.is_some() {
+            content_types.push_str("<Override PartName=\"/word/theme/theme1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.theme+xml\"/>");
+        }
+        if font_table.is_some() {
+            content_types.push_str("<Override PartName=\"/word/fontTable.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.fontTable+xml\"/>");
+        }
+        content_types.push_str("</Types>");
+
+        let core = format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+                "<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" ",
+                "xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" ",
+                "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">",
+                "<dc:title>{title}</dc:title><dc:creator>{author}</dc:creator>",
+                "<dcterms:created xsi:type=\"dcterms:W3CDTF\">{now}</dcterms:created>",
+                "</cp:coreProperties>"
+            ),
+            title = escape_xml(title),
+            author = escape_xml(author),
+            now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        );
+
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(output)?;
+        let mut zip = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let zip_err =
+            |e: zip::result::ZipError| AppError::Export(format!("Failed to write DOCX: {}", e));
+
+        let mut entries: Vec<(String, Vec<u8>)> = vec![
+            (
+                "[Content_Types].xml".to_string(),
+                content_types.into_bytes(),
+            ),
+            ("_rels/.rels".to_string(), PACKAGE_RELS.as_bytes().to_vec()),
+            ("docProps/core.xml".to_string(), core.into_bytes()),
+            ("word/document.xml".to_string(), document.into_bytes()),
+            (
+                "word/_rels/document.xml.rels".to_string(),
+                rels.into_bytes(),
+            ),
+            ("word/styles.xml".to_string(), styles.into_bytes()),
+            (
+                "word/numbering.xml".to_string(),
+                numbering_xml(&self.numbering).into_bytes(),
+            ),
+            ("word/footnotes.xml".to_string(), footnotes_xml.into_bytes()),
+        ];
+        if let Some(theme) = theme {
+            entries.push(("word/theme/theme1.xml".to_string(), theme.clone()));
+        }
+        if let Some(font_table) = font_table {
+            entries.push((
+                "word/fontTable.xml".to_string(),
+                font_table.clone().into_bytes(),
+            ));
+        }
+        for (name, path) in &self.media {
+            entries.push((format!("word/media/{}", name), fs::read(path)?));
+        }
+
+        for (name, data) in entries {
+            zip.start_file(name, options).map_err(zip_err)?;
+            zip.write_all(&data)?;
+        }
+        zip.finish().map_err(zip_err)?;
+        Ok(())
+    }
+}
+
+fn run_xml(rpr: &str, text: &str) -> String {
+    if rpr.is_empty() {
+        format!(
+            "<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+            escape_xml(text)
+        )
+    } else {
+        format!(
+            "<w:r><w:rPr>{}</w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+            rpr,
+            escape_xml(text)
+        )
+    }
+}
+
+/// Turn a heading slug into a valid Word bookmark name.
+fn bookmark_name(slug: &str) -> String {
+    let mut name: String = slug
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name
+        .chars()
+        .next()
+        .map(|c| c.is_alphabetic())
+        .unwrap_or(false)
+    {
+        name.insert(0, 'h');
+    }
+    name.chars().take(MAX_BOOKMARK_LEN).collect()
+}
+
+/// Keep the reference styles and append any style we reference that it lacks.
+fn merge_styles(reference: &str) -> String {
+    let missing: String = DEFAULT_STYLES
+        .iter()
+        .filter(|(id, _)| !reference.contains(&format!("w:styleId=\"{}\"", id)))
+        .map(|(_, xml)| *xml)
+        .collect();
+    match reference.rfind("</w:styles>") {
+        Some(pos) => format!("{}{}{}", &reference[..pos], missing, &reference[pos..]),
+        None => reference.to_string(),
+    }
+}
+
+fn numbering_xml(instances: &[(bool, u64)]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{}\">",
+        NS_W
+    );
+
+    for (abstract_id, ordered) in [(0, false), (1, true)] {
+        xml.push_str(&format!(
+            "<w:abstractNum w:abstractNumId=\"{}\"><w:multiLevelType w:val=\"hybridMultilevel\"/>",
+            abstract_id
+        ));
+        for level in 0..9 {
+            let (format, text) = if ordered {
+                let format = ["decimal", "lowerLetter", "lowerRoman"][level % 3];
+                (format, format!("%{}.", level + 1))
+            } else {
+                (
+                    "bullet",
+                    ["\u{2022}", "\u{25E6}", "\u{25AA}"][level % 3].to_string(),
+                )
+            };
+            xml.push_str(&format!(
+                concat!(
+                    "<w:lvl w:ilvl=\"{lvl}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{fmt}\"/>",
+                    "<w:lvlText w:val=\"{text}\"/><w:lvlJc w:val=\"left\"/>",
+                    "<w:pPr><w:ind w:left=\"{left}\" w:hanging=\"360\"/></w:pPr></w:lvl>"
+                ),
+                lvl = level,
+                fmt = format,
+                text = text,
+                left = 720 * (level + 1),
+            ));
+        }
+        xml.push_str("</w:abstractNum>");
+    }
+
+    // One numbering instance per list so ordered lists restart at their own start.
+    for (i, (ordered, start)) in instances.iter().enumerate() {
+        let abstract_id = if *ordered { 1 } else { 0 };
+        xml.push_str(&format!(
+            "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/>",
+            i + 1,
+            abstract_id
+        ));
+        if *ordered {
+            xml.push_str(&format!(
+                "<w:lvlOverride w:ilvl=\"0\"><w:startOverride w:val=\"{}\"/></w:lvlOverride>",
+                start
+            ));
+        }
+        xml.push_str("</w:num>");
+    }
+
+    xml.push_str("</w:numbering>");
+    xml
+}
+
+const PACKAGE_RELS: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>",
+    "<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>",
+    "</Relationships>"
+);
+
+/// A4 with 2.5 cm margins.
+const DEFAULT_SECT_PR: &str = "<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/><w:pgMar w:top=\"1417\" w:right=\"1417\" w:bottom=\"1417\" w:left=\"1417\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/></w:sectPr>";
+
+const DEFAULT_DOC_DEFAULTS: &str = concat!(
+    "<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii=\"Calibri\" w:hAnsi=\"Calibri\" w:eastAsia=\"Calibri\" w:cs=\"Calibri\"/>",
+    "<w:sz w:val=\"22\"/><w:szCs w:val=\"22\"/><w:lang w:val=\"en-US\"/></w:rPr></w:rPrDefault>",
+    "<w:pPrDefault><w:pPr><w:spacing w:after=\"160\" w:line=\"264\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault></w:docDefaults>"
+);
+
+/// Built-in style definitions keyed by style id. Used as-is without a
+/// reference document, and to fill gaps in one.
+const DEFAULT_STYLES: &[(&str, &str)] = &[
+    ("Normal", "<w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/></w:style>"),
+    ("Heading1", "<w:style w:type=\"paragraph\" w:styleId=\"Heading1\"><w:name w:val=\"heading 1\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before=\"360\" w:after=\"120\"/><w:outlineLvl w:val=\"0\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"36\"/></w:rPr></w:style>"),
+    ("Heading2", "<w:style w:type=\"paragraph\" w:styleId=\"Heading2\"><w:name w:val=\"heading 2\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before=\"280\" w:after=\"100\"/><w:outlineLvl w:val=\"1\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"30\"/></w:rPr></w:style>"),
+    ("Heading3", "<w:style w:type=\"paragraph\" w:styleId=\"Heading3\"><w:name w:val=\"heading 3\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before=\"240\" w:after=\"80\"/><w:outlineLvl w:val=\"2\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"26\"/></w:rPr></w:style>"),
+    ("Heading4", "<w:style w:type=\"paragraph\" w:styleId=\"Heading4\"><w:name w:val=\"heading 4\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before=\"200\" w:after=\"60\"/><w:outlineLvl w:val=\"3\"/></w:pPr><w:rPr><w:b/><w:i/><w:sz w:val=\"24\"/></w:rPr></w:style>"),
+    ("Heading5", "<w:style w:type=\"paragraph\" w:styleId=\"Heading5\"><w:name w:val=\"heading 5\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val=\"4\"/></w:pPr><w:rPr><w:b/></w:rPr></w:style>"),
+    ("Heading6", "<w:style w:type=\"paragraph\" w:styleId=\"Heading6\"><w:name w:val=\"heading 6\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val=\"5\"/></w:pPr><w:rPr><w:i/></w:rPr></w:style>"),
+    ("ListParagraph", "<w:style w:type=\"paragraph\" w:styleId=\"ListParagraph\"><w:name w:val=\"List Paragraph\"/><w:basedOn w:val=\"Normal\"/><w:qFormat/><w:pPr><w:spacing w:after=\"60\"/><w:contextualSpacing/></w:pPr></w:style>"),
+    ("Quote", "<w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/><w:qFormat/><w:pPr><w:pBdr><w:left w:val=\"single\" w:sz=\"18\" w:space=\"8\" w:color=\"BFBFBF\"/></w:pBdr><w:ind w:left=\"720\"/></w:pPr><w:rPr><w:i/><w:color w:val=\"595959\"/></w:rPr></w:style>"),
+    ("SourceCode", "<w:style w:type=\"paragraph\" w:customStyle=\"1\" w:styleId=\"SourceCode\"><w:name w:val=\"Source Code\"/><w:basedOn w:val=\"Normal\"/><w:pPr><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F2F2F2\"/><w:spacing w:after=\"160\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"19\"/></w:rPr></w:style>"),
+    ("VerbatimChar", "<w:style w:type=\"character\" w:customStyle=\"1\" w:styleId=\"VerbatimChar\"><w:name w:val=\"Verbatim Char\"/><w:rPr><w:rFonts w:ascii=\"Consolas\" w:hAnsi=\"Consolas\" w:cs=\"Consolas\"/><w:sz w:val=\"20\"/><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"EDEDED\"/></w:rPr></w:style>"),
+    ("Hyperlink", "<w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/><w:rPr><w:color w:val=\"0563C1\"/><w:u w:val=\"single\"/></w:rPr></w:style>"),
+    ("FootnoteText", "<w:style w:type=\"paragraph\" w:styleId=\"FootnoteText\"><w:name w:val=\"footnote text\"/><w:basedOn w:val=\"Normal\"/><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:sz w:val=\"18\"/></w:rPr></w:style>"),
+    ("FootnoteReference", "<w:style w:type=\"character\" w:styleId=\"FootnoteReference\"><w:name w:val=\"footnote reference\"/><w:rPr><w:vertAlign w:val=\"superscript\"/></w:rPr></w:style>"),
+    ("TableGrid", "<w:style w:type=\"table\" w:styleId=\"TableGrid\"><w:name w:val=\"Table Grid\"/><w:tblPr><w:tblBorders><w:top w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:left w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:bottom w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:right w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:insideH w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:insideV w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/></w:tblBorders><w:tblCellMar><w:left w:w=\"108\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar></w:tblPr></w:style>"),
+];
@@ -0,0 +1,756 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::citations::apply_citations;
+use crate::error::AppError;
+use crate::frontmatter::{split_frontmatter, Frontmatter};
+use crate::markdown::{
+    escape_xml, is_external_url, parser_options, percent_decode, render_html_with, split_fragment,
+    Heading, LinkKind,
+};
+use crate::transclude::expand_embeds;
+use crate::utils::{collect_md_files, fnv1a, validate_directory};
+use crate::wiki::wiki_links_to_markdown;
+
+/// Name of the optional file that defines chapter order (mdBook style).
+const SUMMARY_FILE: &str = "SUMMARY.md";
+
+/// Filenames probed (in the folder and its `assets/` subfolder) when no cover is configured.
+const COVER_CANDIDATES: &[&str] = &["cover.jpg", "cover.jpeg", "cover.png", "cover.webp"];
+
+const STYLESHEET: &str = r#"body { font-family: serif; line-height: 1.5; margin: 0 1em; }
+h1, h2, h3, h4, h5, h6 { font-family: sans-serif; line-height: 1.2; }
+pre { white-space: pre-wrap; font-size: 0.85em; background: #f4f4f4; padding: 0.5em; }
+code { font-family: monospace; }
+blockquote { margin-left: 1em; padding-left: 1em; border-left: 3px solid #ccc; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #999; padding: 0.25em 0.5em; }
+img { max-width: 100%; }
+.cover { text-align: center; margin: 0; padding: 0; }
+.cover img { max-height: 100%; }
+"#;
+
+static VOID_ELEMENT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<(br|hr|img|input|col|source|wbr)(\s[^>]*?)?\s*(/?)>")
+        .expect("invalid void element regex")
+});
+
+#[derive(Deserialize)]
+pub struct EpubOptions {
+    /// Workspace folder the book is built from.
+    pub folder: String,
+    /// Destination `.epub` path.
+    pub output_path: String,
+    /// Explicit ordered list of notes. When omitted, `SUMMARY.md` or
+    /// filename order is used.
+    pub files: Option<Vec<String>>,
+    /// Cover image path. Overrides the `cover` frontmatter field.
+    pub cover_image: Option<String>,
+}
+
+struct ChapterSource {
+    path: PathBuf,
+    /// Title from the `SUMMARY.md` entry, if any.
+    summary_title: Option<String>,
+}
+
+struct Chapter {
+    file_name: String,
+    title: String,
+    xhtml: String,
+    headings: Vec<Heading>,
+}
+
+struct Resource {
+    href: String,
+    media_type: &'static str,
+    source: PathBuf,
+}
+
+struct BookMetadata {
+    title: String,
+    authors: Vec<String>,
+    language: String,
+    identifier: String,
+    description: Option<String>,
+    publisher: Option<String>,
+    date: Option<String>,
+}
+
+/// Build an EPUB 3 book from a folder of notes.
+///
+/// Chapters are ordered by the explicit `files` list, the links in
+/// `SUMMARY.md`, or by path. Book metadata comes from the frontmatter of
+/// `SUMMARY.md` (or the first chapter). Local images are embedded and
+/// cross-note links are rewritten to point at the matching chapter.
+/// Returns the path of the written file.
+#[tauri::command]
+pub fn export_epub(options: EpubOptions) -> Result<String, AppError> {
+    let root = validate_directory(&options.folder)?;
+    let sources = chapter_sources(&root, options.files.as_deref())?;
+    if sources.is_empty() {
+        return Err(AppError::Export("No markdown files to export".to_string()));
+    }
+
+    // Metadata lives in SUMMARY.md if present, otherwise in the first chapter.
+    let summary_path = root.join(SUMMARY_FILE);
+    let meta_path = if summary_path.is_file() {
+        summary_path
+    } else {
+        sources[0].path.clone()
+    };
+    let meta_content = fs::read_to_string(&meta_path).unwrap_or_default();
+    let (frontmatter, _) = split_frontmatter(&meta_content);
+    let frontmatter = frontmatter.unwrap_or_default();
+    let metadata = book_metadata(&root, &frontmatter);
+
+    // Map every chapter source (canonical path and bare stem) to its XHTML file
+    // so links between notes can be rewritten before rendering.
+    let mut chapter_files: HashMap<PathBuf, String> = HashMap::new();
+    let mut chapter_stems: HashMap<String, String> = HashMap::new();
+    for (i, source) in sources.iter().enumerate() {
+        let file_name = format!("chapter-{:03}.xhtml", i + 1);
+        if let Ok(canonical) = source.path.canonicalize() {
+            chapter_files.insert(canonical, file_name.clone());
+        }
+        if let Some(stem) = source.path.file_stem() {
+            chapter_stems
+                .entry(stem.to_string_lossy().to_lowercase())
+                .or_insert_with(|| file_name.clone());
+        }
+    }
+
+    let mut resources: Vec<Resource> = Vec::new();
+    let mut resource_index: HashMap<PathBuf, String> = HashMap::new();
+    let mut chapters: Vec<Chapter> = Vec::with_capacity(sources.len());
+
+    for (i, source) in sources.iter().enumerate() {
+        let content = fs::read_to_string(&source.path)?;
+        let (chapter_fm, body) = split_frontmatter(&content);
+        let body = expand_embeds(body, &source.path, &root);
+        let body = apply_citations(&body, chapter_fm.as_ref(), &source.path, &root);
+        let body = wiki_links_to_markdown(&body);
+        let note_dir = source.path.parent().unwrap_or(&root).to_path_buf();
+
+        let mut rendered = render_html_with(&body, |kind, dest| {
+            if is_external_url(dest) || dest.starts_with('#') {
+                return None;
+            }
+            let (path_part, fragment) = split_fragment(dest);
+            let decoded = percent_decode(path_part);
+            match kind {
+                LinkKind::Image => {
+                    let resolved = resolve_local(&root, &note_dir, &decoded)?;
+                    let href = embed_resource(&resolved, &mut resources, &mut resource_index)?;
+                    Some(format!("../{}", href))
+                }
+                LinkKind::Link => {
+                    let target = resolve_local(&root, &note_dir, &decoded)
+                        .and_then(|p| chapter_files.get(&p).cloned())
+                        .or_else(|| {
+                            // By name only for notes, so `report.pdf` does not
+                            // become the `report.md` chapter
+                            let path = Path::new(&decoded);
+                            if extension_of(path) != "md" {
+                                return None;
+                            }
+                            let stem = path.file_stem()?;
+                            chapter_stems
+                                .get(&stem.to_string_lossy().to_lowercase())
+                                .cloned()
+                        })?;
+                    Some(match fragment {
+                        Some(frag) => format!("{}#{}", target, frag),
+                        None => target,
+                    })
+                }
+            }
+        });
+
+        unique_heading_ids(&mut rendered.html, &mut rendered.headings);
+
+        let title = chapter_fm
+            .as_ref()
+            .and_then(|fm| fm.get("title").map(str::to_string))
+            .or_else(|| rendered.headings.first().map(|h| h.text.clone()))
+            .or_else(|| source.summary_title.clone())
+            .unwrap_or_else(|| file_stem_string(&source.path));
+
+        let file_name = format!("chapter-{:03}.xhtml", i + 1);
+        let xhtml = xhtml_document(
+            &title,
+            &metadata.language,
+            &format!(
+                "<section epub:type=\"chapter\">\n{}</section>",
+                to_xhtml(&rendered.html)
+            ),
+        );
+        chapters.push(Chapter {
+            file_name,
+            title,
+            xhtml,
+            headings: rendered.headings,
+        });
+    }
+
+    // Cover: explicit option, then frontmatter, then conventional filenames.
+    let meta_dir = meta_path.parent().unwrap_or(&root).to_path_buf();
+    let cover_path = options
+        .cover_image
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|p| p.is_file())
+        .or_else(|| {
+            frontmatter
+                .get("cover")
+                .and_then(|c| resolve_local(&root, &meta_dir, c))
+        })
+        .or_else(|| find_cover(&root));
+    let cover = cover_path.and_then(|path| {
+        media_type_for(&path).map(|media_type| Resource {
+            href: format!("images/cover.{}", extension_of(&path)),
+            media_type,
+            source: path,
+        })
+    });
+
+    write_epub(
+        Path::new(&options.output_path),
+        &metadata,
+        &chapters,
+        &resources,
+        cover.as_ref(),
+    )?;
+
+    Ok(options.output_path)
+}
+
+/// Determine the ordered list of chapter files.
+fn chapter_sources(root: &Path, files: Option<&[String]>) -> Result<Vec<ChapterSource>, AppError> {
+    if let Some(files) = files {
+        return Ok(files
+            .iter()
+            .map(|f| ChapterSource {
+                path: PathBuf::from(f),
+                summary_title: None,
+            })
+            .filter(|c| c.path.is_file())
+            .collect());
+    }
+
+    let summary_path = root.join(SUMMARY_FILE);
+    if summary_path.is_file() {
+        let content = fs::read_to_string(&summary_path)?;
+        let (_, body) = split_frontmatter(&content);
+        let sources = summary_links(root, body);
+        if !sources.is_empty() {
+            return Ok(sources);
+        }
+    }
+
+    let mut md_files: Vec<PathBuf> = collect_md_files(root)
+        .into_iter()
+        .filter(|p| p.file_name().map(|n| n != SUMMARY_FILE).unwrap_or(true))
+        .collect();
+    md_files.sort_by_key(|p| p.to_string_lossy().to_lowercase());
+
+    Ok(md_files
+        .into_iter()
+        .map(|path| ChapterSource {
+            path,
+            summary_title: None,
+        })
+        .collect())
+}
+
+/// Collect the `.md` links of a `SUMMARY.md` in document order.
+fn summary_links(root: &Path, body: &str) -> Vec<ChapterSource> {
+    let mut sources: Vec<ChapterSource> = Vec::new();
+    let mut current: Option<(PathBuf, String)> = None;
+
+    for event in Parser::new_ext(body, parser_options()) {
+        match event {
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let (path_part, _) = split_fragment(&dest_url);
+                let decoded = percent_decode(path_part);
+                if !is_external_url(&dest_url) && decoded.to_lowercase().ends_with(".md") {
+                    current = Some((root.join(decoded), String::new()));
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, title)) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some((path, title)) = current.take() {
+                    let duplicate = sources.iter().any(|s| s.path == path);
+                    if path.is_file() && !duplicate {
+                        sources.push(ChapterSource {
+                            path,
+                            summary_title: Some(title.trim().to_string()).filter(|t| !t.is_empty()),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    sources
+}
+
+fn book_metadata(root: &Path, fm: &Frontmatter) -> BookMetadata {
+    let title = fm
+        .get("title")
+        .map(str::to_string)
+        .unwrap_or_else(|| file_stem_string(root));
+
+    let mut authors = fm.get_list("author");
+    if authors.is_empty() {
+        authors = fm.get_list("authors");
+    }
+
+    let language = fm
+        .get("lang")
+        .or_else(|| fm.get("language"))
+        .unwrap_or("en")
+        .to_string();
+
+    let identifier = fm
+        .get("identifier")
+        .or_else(|| fm.get("isbn"))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            // Stable across builds and Rust versions, so readers keep treating
+            // re-exports as the same book
+            let key = format!("{}\0{}", root.to_string_lossy(), title);
+            format!("urn:markview:{:016x}", fnv1a(key.as_bytes()))
+        });
+
+    BookMetadata {
+        title,
+        authors,
+        language,
+        identifier,
+        description: fm.get("description").map(str::to_string),
+        publisher: fm.get("publisher").map(str::to_string),
+        date: fm.get("date").map(str::to_string),
+    }
+}
+
+/// Resolve a relative destination against the note's folder, falling back
+/// to the workspace root. Returns a canonical path to an existing file.
+pub fn resolve_local(root: &Path, note_dir: &Path, dest: &str) -> Option<PathBuf> {
+    if dest.is_empty() {
+        return None;
+    }
+    let candidate = Path::new(dest);
+    let tries = if candidate.is_absolute() {
+        vec![candidate.to_path_buf()]
+    } else {
+        vec![note_dir.join(candidate), root.join(candidate)]
+    };
+    tries
+        .into_iter()
+        .find(|p| p.is_file())
+        .and_then(|p| p.canonicalize().ok())
+}
+
+/// Register an image as a book resource, returning its href inside `OEBPS/`.
+fn embed_resource(
+    path: &Path,
+    resources: &mut Vec<Resource>,
+    index: &mut HashMap<PathBuf, String>,
+) -> Option<String> {
+    if let Some(href) = index.get(path) {
+        return Some(href.clone());
+    }
+    let media_type = media_type_for(path)?;
+    let href = format!(
+        "images/image-{:03}.{}",
+        resources.len() + 1,
+        extension_of(path)
+    );
+    resources.push(Resource {
+        href: href.clone(),
+        media_type,
+        source: path.to_path_buf(),
+    });
+    index.insert(path.to_path_buf(), href.clone());
+    Some(href)
+}
+
+fn find_cover(root: &Path) -> Option<PathBuf> {
+    [root.to_path_buf(), root.join("assets")]
+        .iter()
+        .flat_map(|dir| COVER_CANDIDATES.iter().map(move |name| dir.join(name)))
+        .find(|p| p.is_file())
+}
+
+pub fn media_type_for(path: &Path) -> Option<&'static str> {
+    match extension_of(path).as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "svg" => Some("image/svg+xml"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn extension_of(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn file_stem_string(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Give repeated heading ids within a chapter `-1`, `-2`, ... suffixes, so
+/// every id in the document is unique and the navigation points at the
+/// right section. `headings` are in document order, as is their markup.
+fn unique_heading_ids(html: &mut String, headings: &mut [Heading]) {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cursor = 0;
+    for heading in headings.iter_mut() {
+        let open = format!("<h{} id=\"", heading.level);
+        let Some(start) = html[cursor..].find(&open).map(|p| cursor + p + open.len()) else {
+            return;
+        };
+        let Some(end) = html[start..].find('"').map(|p| start + p) else {
+            return;
+        };
+        cursor = end;
+        if seen.insert(heading.slug.clone()) {
+            continue;
+        }
+        let mut n = 1;
+        let mut slug = format!("{}-{}", heading.slug, n);
+        while seen.contains(&slug) {
+            n += 1;
+            slug = format!("{}-{}", heading.slug, n);
+        }
+        seen.insert(slug.clone());
+        let escaped = escape_xml(&slug);
+        html.replace_range(start..end, &escaped);
+        cursor = start + escaped.len();
+        heading.slug = slug;
+    }
+}
+
+/// Make rendered HTML well-formed XHTML: close void elements and replace
+/// the one HTML entity markdown commonly produces that XML does not define.
+fn to_xhtml(html: &str) -> String {
+    let closed = VOID_ELEMENT_RE.replace_all(html, |cap: &regex::Captures| {
+        let attrs = cap.get(2).map(|m| m.as_str()).unwrap_or("");
+        format!("<{}{} />", &cap[1], attrs.trim_end())
+    });
+    closed.replace("&nbsp;", "&#160;")
+}
+
+fn xhtml_document(title: &str, language: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="utf-8" />
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="../style.css" />
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        lang = escape_xml(language),
+        title = escape_xml(title),
+        body = body,
+    )
+}
+
+fn write_epub(
+    output: &Path,
+    metadata: &BookMetadata,
+    chapters: &[Chapter],
+    resources: &[Resource],
+    cover: Option<&Resource>,
+) -> Result<(), AppError> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(output)?;
+    let mut zip = ZipWriter::new(file);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let zip_err =
+        |e: zip::result::ZipError| AppError::Export(format!("Failed to write EPUB: {}", e));
+
+    // The mimetype entry must come first and be stored uncompressed.
+    zip.start_file("mimetype", stored).map_err(zip_err)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)
+        .map_err(zip_err)?;
+    zip.write_all(CONTAINER_XML.as_bytes())?;
+
+    zip.start_file("OEBPS/content.opf", deflated)
+        .map_err(zip_err)?;
+    zip.write_all(package_document(metadata, chapters, resources, cover).as_bytes())?;
+
+    zip.start_file("OEBPS/nav.xhtml", deflated)
+        .map_err(zip_err)?;
+    zip.write_all(nav_document(metadata, chapters).as_bytes())?;
+
+    zip.start_file("OEBPS/toc.ncx", deflated).map_err(zip_err)?;
+    zip.write_all(ncx_document(metadata, chapters).as_bytes())?;
+
+    zip.start_file("OEBPS/style.css", deflated)
+        .map_err(zip_err)?;
+    zip.write_all(STYLESHEET.as_bytes())?;
+
+    if let Some(cover) = cover {
+        zip.start_file(format!("OEBPS/{}", cover.href), stored)
+            .map_err(zip_err)?;
+        zip.write_all(&fs::read(&cover.source)?)?;
+
+        let page = xhtml_document(
+            &metadata.title,
+            &metadata.language,
+            &format!(
+                "<section class=\"cover\" epub:type=\"cover\"><img src=\"../{}\" alt=\"{}\" /></section>",
+                cover.href,
+                escape_xml(&metadata.title)
+            ),
+        );
+        zip.start_file("OEBPS/text/cover.xhtml", deflated)
+            .map_err(zip_err)?;
+        zip.write_all(page.as_bytes())?;
+    }
+
+    for chapter in chapters {
+        zip.start_file(format!("OEBPS/text/{}", chapter.file_name), deflated)
+            .map_err(zip_err)?;
+        zip.write_all(chapter.xhtml.as_bytes())?;
+    }
+
+    for resource in resources {
+        // Images are already compressed; storing them avoids wasted work.
+        zip.start_file(format!("OEBPS/{}", resource.href), stored)
+            .map_err(zip_err)?;
+        zip.write_all(&fs::read(&resource.source)?)?;
+    }
+
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn package_document(
+    metadata: &BookMetadata,
+    chapters: &[Chapter],
+    resources: &[Resource],
+    cover: Option<&Resource>,
+) -> String {
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut meta = String::new();
+    meta.push_str(&format!(
+        "    <dc:identifier id=\"book-id\">{}</dc:identifier>\n",
+        escape_xml(&metadata.identifier)
+    ));
+    meta.push_str(&format!(
+        "    <dc:title>{}</dc:title>\n",
+        escape_xml(&metadata.title)
+    ));
+    meta.push_str(&format!(
+        "    <dc:language>{}</dc:language>\n",
+        escape_xml(&metadata.language)
+    ));
+    for author in &metadata.authors {
+        meta.push_str(&format!(
+            "    <dc:creator>{}</dc:creator>\n",
+            escape_xml(author)
+        ));
+    }
+    if let Some(description) = &metadata.description {
+        meta.push_str(&format!(
+            "    <dc:description>{}</dc:description>\n",
+            escape_xml(description)
+        ));
+    }
+    if let Some(publisher) = &metadata.publisher {
+        meta.push_str(&format!(
+            "    <dc:publisher>{}</dc:publisher>\n",
+            escape_xml(publisher)
+        ));
+    }
+    if let Some(date) = &metadata.date {
+        meta.push_str(&format!("    <dc:date>{}</dc:date>\n", escape_xml(date)));
+    }
+    meta.push_str(&format!(
+        "    <meta property=\"dcterms:modified\">{}</meta>\n",
+        modified
+    ));
+    if cover.is_some() {
+        // EPUB 2 readers look for this to find the cover.
+        meta.push_str("    <meta name=\"cover\" content=\"cover-image\"/>\n");
+    }
+
+    let mut manifest = String::new();
+    manifest.push_str(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+    );
+    manifest.push_str(
+        "    <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n",
+    );
+    manifest.push_str("    <item id=\"css\" href=\"style.css\" media-type=\"text/css\"/>\n");
+    if let Some(cover) = cover {
+        manifest.push_str(&format!(
+            "    <item id=\"cover-image\" href=\"{}\" media-type=\"{}\" properties=\"cover-image\"/>\n",
+            cover.href, cover.media_type
+        ));
+        manifest.push_str(
+            "    <item id=\"cover\" href=\"text/cover.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+        );
+    }
+    for (i, chapter) in chapters.iter().enumerate() {
+        manifest.push_str(&format!(
+            "    <item id=\"chapter-{}\" href=\"text/{}\" media-type=\"application/xhtml+xml\"/>\n",
+            i + 1,
+            chapter.file_name
+        ));
+    }
+    for (i, resource) in resources.iter().enumerate() {
+        manifest.push_str(&format!(
+            "    <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            i + 1,
+            resource.href,
+            resource.media_type
+        ));
+    }
+
+    let mut spine = String::new();
+    if cover.is_some() {
+        spine.push_str("    <itemref idref=\"cover\" linear=\"no\"/>\n");
+    }
+    for i in 0..chapters.len() {
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", i + 1));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{meta}  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine toc="ncx">
+{spine}  </spine>
+</package>
+"#,
+        lang = escape_xml(&metadata.language),
+        meta = meta,
+        manifest = manifest,
+        spine = spine,
+    )
+}
+
+/// EPUB 3 navigation document: one entry per chapter with its second-level
+/// headings nested underneath.
+fn nav_document(metadata: &BookMetadata, chapters: &[Chapter]) -> String {
+    let mut items = String::new();
+    for chapter in chapters {
+        items.push_str(&format!(
+            "      <li><a href=\"text/{}\">{}</a>",
+            chapter.file_name,
+            escape_xml(&chapter.title)
+        ));
+        let sections: Vec<&Heading> = chapter.headings.iter().filter(|h| h.level == 2).collect();
+        if !sections.is_empty() {
+            items.push_str("\n        <ol>\n");
+            for heading in sections {
+                items.push_str(&format!(
+                    "          <li><a href=\"text/{}#{}\">{}</a></li>\n",
+                    chapter.file_name,
+                    escape_xml(&heading.slug),
+                    escape_xml(&heading.text)
+                ));
+            }
+            items.push_str("        </ol>\n      ");
+        }
+        items.push_str("</li>\n");
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="utf-8" />
+<title>{title}</title>
+</head>
+<body>
+  <nav epub:type="toc" id="toc">
+    <h1>{title}</h1>
+    <ol>
+{items}    </ol>
+  </nav>
+</body>
+</html>
+"#,
+        lang = escape_xml(&metadata.language),
+        title = escape_xml(&metadata.title),
+        items = items,
+    )
+}
+
+/// Legacy NCX table of contents for EPUB 2 reading systems.
+fn ncx_document(metadata: &BookMetadata, chapters: &[Chapter]) -> String {
+    let mut points = String::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        points.push_str(&format!(
+            "    <navPoint id=\"nav-{n}\" playOrder=\"{n}\">\n      <navLabel><text>{title}</text></navLabel>\n      <content src=\"text/{file}\"/>\n    </navPoint>\n",
+            n = i + 1,
+            title = escape_xml(&chapter.title),
+            file = chapter.file_name,
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head>
+    <meta name="dtb:uid" content="{id}"/>
+  </head>
+  <docTitle><text>{title}</text></docTitle>
+  <navMap>
+{points}  </navMap>
+</ncx>
+"#,
+        id = escape_xml(&metadata.identifier),
+        title = escape_xml(&metadata.title),
+        points = points,
+    )
+}
@@ -24,13 +24,44 @@ pub enum AppError {
     #[error("Git error: {0}")]
     Git(String),
 
+    #[error("Not a git repository: {0}")]
+    NotARepo(String),
+
+    #[error("Nothing to commit")]
+    NothingToCommit,
+
+    #[error("Unresolved merge conflicts: {0}")]
+    Conflicts(String),
+
+    #[error("Git authentication failed: {0}")]
+    GitAuth(String),
+
     #[error("Network error: {0}")]
     Network(String),
 
+    #[error("Export error: {0}")]
+    Export(String),
+
+    #[error("Invalid query: {0}")]
+    Query(String),
+
     #[error("{0}")]
     Other(String),
 }
 
+impl From<git2::Error> for AppError {
+    fn from(e: git2::Error) -> Self {
+        let message = e.message().to_string();
+        match e.code() {
+            git2::ErrorCode::Auth | git2::ErrorCode::Certificate => AppError::GitAuth(message),
+            git2::ErrorCode::Conflict
+            | git2::ErrorCode::MergeConflict
+            | git2::ErrorCode::Unmerged => AppError::Conflicts(message),
+            _ => AppError::Git(message),
+        }
+    }
+}
+
 // Tauri 2.x requires the error type to implement `Serialize` so it can be
 // sent across the IPC boundary.  We serialize every variant as a plain string
 // which keeps the frontend backward-compatible (it already expects strings).
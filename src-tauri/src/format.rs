@@ -0,0 +1,1093 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Alignment, Event, LinkType, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::parser_options;
+use crate::utils::{collect_md_files, validate_directory, write_atomic};
+
+static REF_DEF_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^( {0,3})\[((?:[^\]\\^]|\\.)(?:[^\]\\]|\\.)*)\]:[ \t]*(<[^>\n]*>|\S+)(?:[ \t]+("[^"\n]*"|'[^'\n]*'|\([^)\n]*\)))?[ \t]*$"#,
+    )
+    .expect("invalid reference definition regex")
+});
+static WIKI_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"!?\[\[[^\]\n]+\]\]").expect("invalid wiki link regex"));
+static THEMATIC_BREAK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^ {0,3}(?:(?:-[ \t]*){3,}|(?:\*[ \t]*){3,}|(?:_[ \t]*){3,})$")
+        .expect("invalid thematic break regex")
+});
+/// Words that would start a new block if wrapped to the beginning of a line.
+static BLOCK_START_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:[-+*]|\d{1,9}[.)]|#{1,6}|=+|-+|\*+|_+|>.*|\|.*|`{3,}.*|~{3,}.*|<.*)$")
+        .expect("invalid block start regex")
+});
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BulletStyle {
+    #[default]
+    Dash,
+    Asterisk,
+    Plus,
+}
+
+impl BulletStyle {
+    fn marker(self) -> u8 {
+        match self {
+            BulletStyle::Dash => b'-',
+            BulletStyle::Asterisk => b'*',
+            BulletStyle::Plus => b'+',
+        }
+    }
+
+    /// Marker for a list directly following another list, which would merge
+    /// into it if both used the same marker.
+    fn alternate(self) -> u8 {
+        match self {
+            BulletStyle::Asterisk => b'-',
+            _ => b'*',
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmphasisStyle {
+    /// `*em*` and `**strong**`.
+    #[default]
+    Asterisk,
+    /// `_em_` and `__strong__`.
+    Underscore,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProseWrap {
+    /// Keep existing line breaks in paragraphs.
+    #[default]
+    Preserve,
+    /// Wrap paragraphs at `print_width`.
+    Always,
+    /// Join each paragraph onto a single line.
+    Never,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct FormatOptions {
+    #[serde(default)]
+    pub bullet: BulletStyle,
+    #[serde(default)]
+    pub emphasis: EmphasisStyle,
+    #[serde(default)]
+    pub prose_wrap: ProseWrap,
+    #[serde(default = "default_print_width")]
+    pub print_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            bullet: BulletStyle::default(),
+            emphasis: EmphasisStyle::default(),
+            prose_wrap: ProseWrap::default(),
+            print_width: default_print_width(),
+        }
+    }
+}
+
+fn default_print_width() -> usize {
+    80
+}
+
+/// Format markdown `content` and return the result.
+#[tauri::command]
+pub fn format_document(content: String, options: FormatOptions) -> Result<String, AppError> {
+    Ok(format_markdown(&content, &options))
+}
+
+/// Format every markdown file under `folder` in place. Each file is replaced
+/// atomically. Returns the paths of the files that changed.
+#[tauri::command]
+pub fn format_folder(folder: String, options: FormatOptions) -> Result<Vec<String>, AppError> {
+    let root = validate_directory(&folder)?;
+    let mut files = collect_md_files(&root);
+    files.sort();
+
+    let mut changed = Vec::new();
+    for path in files {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let formatted = format_markdown(&content, &options);
+        if formatted != content {
+            write_atomic(&path, formatted.as_bytes())?;
+            changed.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(changed)
+}
+
+/// Rewrite markdown canonically: list markers and indentation, emphasis
+/// markers, aligned tables, reference definitions, prose wrapping and blank
+/// lines. Frontmatter, code blocks, math and HTML are left untouched.
+///
+/// Each step reparses the output of the previous one, so every step sees
+/// accurate source ranges.
+pub fn format_markdown(content: &str, options: &FormatOptions) -> String {
+    if content.contains("\r\n") {
+        let formatted = format_markdown(&content.replace("\r\n", "\n"), options);
+        return formatted.replace('\n', "\r\n");
+    }
+
+    let (_, body) = split_frontmatter(content);
+    let frontmatter = &content[..content.len() - body.len()];
+
+    let mut body = normalize_markers(body, options);
+    body = normalize_list_indent(&body);
+    body = align_tables(&body);
+    body = clean_reference_definitions(&body);
+    if options.prose_wrap != ProseWrap::Preserve {
+        body = wrap_prose(&body, options);
+    }
+    body = collapse_blank_lines(&body);
+
+    format!("{}{}", frontmatter, body)
+}
+
+/// Apply non-overlapping `(range, replacement)` edits to `text`. Edits that
+/// touch a `$$` math block are dropped.
+fn apply_edits(text: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let math = math_blocks(text);
+    let mut out = text.to_string();
+    let mut limit = usize::MAX;
+    for (range, replacement) in edits {
+        if range.end > limit
+            || math
+                .iter()
+                .any(|m| m.start < range.end && range.start < m.end)
+        {
+            continue;
+        }
+        limit = range.start;
+        out.replace_range(range, &replacement);
+    }
+    out
+}
+
+/// Lines from an opening `$$` to the closing one. The preview parses these
+/// as math before anything else, while the parser here only sees math that
+/// fits in a paragraph; a line such as `- b` inside starts a list for it.
+fn math_blocks(text: &str) -> Vec<Range<usize>> {
+    let code: Vec<Range<usize>> = Parser::new_ext(text, parser_options())
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::Start(Tag::CodeBlock(_))))
+        .map(|(_, range)| range)
+        .collect();
+
+    let mut blocks = Vec::new();
+    let mut open: Option<usize> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        match open {
+            None if trimmed.starts_with("$$") && !code.iter().any(|c| c.contains(&start)) => {
+                if trimmed.len() > 2 && trimmed.ends_with("$$") {
+                    blocks.push(start..offset);
+                } else {
+                    open = Some(start);
+                }
+            }
+            Some(from) if trimmed.ends_with("$$") => {
+                blocks.push(from..offset);
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = open {
+        blocks.push(from..text.len());
+    }
+    blocks
+}
+
+fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn is_container(tag: &Tag) -> bool {
+    matches!(
+        tag,
+        Tag::List(_) | Tag::Item | Tag::BlockQuote(_) | Tag::FootnoteDefinition(_)
+    )
+}
+
+fn is_container_end(tag: &TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::List(_) | TagEnd::Item | TagEnd::BlockQuote(_) | TagEnd::FootnoteDefinition
+    )
+}
+
+/// Bullet list markers and emphasis delimiters. All replacements keep the
+/// same length, so they never interfere with each other.
+fn normalize_markers(body: &str, options: &FormatOptions) -> String {
+    let bytes = body.as_bytes();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let replace = |at: usize, from: u8, to: u8, edits: &mut Vec<(Range<usize>, String)>| {
+        if bytes.get(at) == Some(&from) && from != to {
+            edits.push((at..at + 1, (to as char).to_string()));
+        }
+    };
+
+    // Marker of each open list (None for ordered lists).
+    let mut lists: Vec<Option<u8>> = Vec::new();
+    // End offset and marker of the last list closed at each container depth.
+    let mut previous_list: HashMap<usize, (usize, u8)> = HashMap::new();
+    let mut depth = 0;
+
+    let delimiter = match options.emphasis {
+        EmphasisStyle::Asterisk => b'*',
+        EmphasisStyle::Underscore => b'_',
+    };
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::List(start)) => {
+                let marker = start.is_none().then(|| {
+                    let follows_list = previous_list
+                        .get(&depth)
+                        .is_some_and(|(end, _)| body[*end..range.start].trim().is_empty());
+                    match previous_list.get(&depth) {
+                        Some((_, m)) if follows_list && *m == options.bullet.marker() => {
+                            options.bullet.alternate()
+                        }
+                        _ => options.bullet.marker(),
+                    }
+                });
+                lists.push(marker);
+                depth += 1;
+            }
+            Event::End(TagEnd::List(_)) => {
+                depth -= 1;
+                if let Some(Some(marker)) = lists.pop() {
+                    previous_list.insert(depth, (range.end, marker));
+                } else {
+                    previous_list.remove(&depth);
+                }
+            }
+            Event::Start(Tag::Item) => {
+                depth += 1;
+                let Some(Some(marker)) = lists.last().copied() else {
+                    continue;
+                };
+                let at = range.start + body[range.start..].len()
+                    - body[range.start..].trim_start_matches([' ', '\t']).len();
+                let Some(&current) = bytes.get(at) else {
+                    continue;
+                };
+                if !matches!(current, b'-' | b'*' | b'+') || current == marker {
+                    continue;
+                }
+                // `* --` would become a thematic break as `- --`.
+                let start = line_start(body, at);
+                let end = body[at..].find('\n').map(|i| at + i).unwrap_or(body.len());
+                let mut line = body[start..end].to_string();
+                line.replace_range(at - start..at - start + 1, &(marker as char).to_string());
+                if !THEMATIC_BREAK_RE.is_match(&line) {
+                    replace(at, current, marker, &mut edits);
+                }
+            }
+            Event::Start(Tag::Emphasis) => {
+                let open = range.start;
+                let close = range.end - 1;
+                let current = bytes[open];
+                // `_` does not work inside words.
+                let intraword = body[..open]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphanumeric)
+                    || body[range.end..]
+                        .chars()
+                        .next()
+                        .is_some_and(char::is_alphanumeric);
+                if delimiter == b'_' && intraword {
+                    continue;
+                }
+                if matches!(current, b'*' | b'_') && bytes[close] == current {
+                    replace(open, current, delimiter, &mut edits);
+                    replace(close, current, delimiter, &mut edits);
+                }
+            }
+            Event::Start(Tag::Strong) => {
+                if range.len() < 4 {
+                    continue;
+                }
+                let current = bytes[range.start];
+                let intraword = body[..range.start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphanumeric)
+                    || body[range.end..]
+                        .chars()
+                        .next()
+                        .is_some_and(char::is_alphanumeric);
+                if delimiter == b'_' && intraword {
+                    continue;
+                }
+                let delimiters = [range.start, range.start + 1, range.end - 2, range.end - 1];
+                if matches!(current, b'*' | b'_') && delimiters.iter().all(|&i| bytes[i] == current)
+                {
+                    for i in delimiters {
+                        replace(i, current, delimiter, &mut edits);
+                    }
+                }
+            }
+            Event::Start(tag) if is_container(&tag) => depth += 1,
+            Event::End(tag) if is_container_end(&tag) => depth -= 1,
+            _ => {}
+        }
+    }
+
+    apply_edits(body, edits)
+}
+
+struct ItemFrame {
+    range: Range<usize>,
+    marker_col: usize,
+    content_col: usize,
+    new_marker_col: usize,
+    new_content_col: usize,
+    /// Keep this item (and its children) where it is.
+    locked: bool,
+}
+
+/// Indent nested list items to their parent's content column and use a
+/// single space after list markers. Lists inside block quotes, or whose items
+/// contain code, math or HTML blocks, are left as they are.
+fn normalize_list_indent(body: &str) -> String {
+    let mut frames: Vec<ItemFrame> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut protected: Vec<Range<usize>> = Vec::new();
+    let mut quote_depth = 0;
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+            Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::HtmlBlock) => protected.push(range),
+            Event::DisplayMath(_) | Event::Html(_) => protected.push(range),
+            Event::Start(Tag::Item) => {
+                let parent = open.last().map(|&i| &frames[i]);
+                let start = line_start(body, range.start);
+                let marker_at = range.start + body[range.start..].len()
+                    - body[range.start..].trim_start_matches(' ').len();
+                let prefix = &body[start..marker_at];
+                let marker_len = body[marker_at..]
+                    .bytes()
+                    .take_while(u8::is_ascii_digit)
+                    .count()
+                    + 1;
+                let after = marker_at + marker_len;
+                let rest = &body[after..];
+                let line_rest = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                let gap = line_rest.len() - line_rest.trim_start_matches(' ').len();
+
+                let marker_col = marker_at - start;
+                // An empty first line or 5+ spaces mean the content starts one
+                // space after the marker.
+                let content_col = if line_rest.trim().is_empty() || gap > 4 {
+                    marker_col + marker_len + 1
+                } else {
+                    marker_col + marker_len + gap
+                };
+                let new_marker_col = parent.map(|p| p.new_content_col).unwrap_or(0);
+                let locked = quote_depth > 0
+                    || parent.is_some_and(|p| p.locked)
+                    || !prefix.bytes().all(|b| b == b' ')
+                    || line_rest.contains('\t')
+                    || gap > 4;
+                frames.push(ItemFrame {
+                    range,
+                    marker_col,
+                    content_col,
+                    new_marker_col,
+                    new_content_col: new_marker_col + marker_len + 1,
+                    locked,
+                });
+                open.push(frames.len() - 1);
+            }
+            Event::End(TagEnd::Item) => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+
+    // Items holding verbatim content keep their indentation.
+    for frame in frames.iter_mut() {
+        if protected.iter().any(|p| frame.range.contains(&p.start)) {
+            frame.locked = true;
+        }
+    }
+    // Children of locked items stay put as well.
+    for i in 0..frames.len() {
+        if frames[i].locked {
+            let range = frames[i].range.clone();
+            for frame in frames.iter_mut().skip(i + 1) {
+                if frame.range.start >= range.start && frame.range.end <= range.end {
+                    frame.locked = true;
+                }
+            }
+        }
+    }
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(body.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&i| i < body.len())
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&s| s <= offset) - 1;
+
+    // Column shift per line; inner items overwrite their parents.
+    let mut shifts: Vec<isize> = vec![0; line_starts.len()];
+    for frame in frames.iter().filter(|f| !f.locked) {
+        let first = line_of(line_start(body, frame.range.start));
+        let last = line_of(frame.range.end.saturating_sub(1).max(frame.range.start));
+        shifts[first] = frame.new_marker_col as isize - frame.marker_col as isize;
+        let continuation = frame.new_content_col as isize - frame.content_col as isize;
+        for shift in shifts.iter_mut().take(last + 1).skip(first + 1) {
+            *shift = continuation;
+        }
+    }
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for frame in frames.iter().filter(|f| !f.locked) {
+        // One space between marker and content.
+        let start = line_start(body, frame.range.start);
+        let marker_end = start + frame.content_col.min(body.len() - start);
+        let marker_at = start + frame.marker_col;
+        let gap_start = marker_at + (frame.new_content_col - frame.new_marker_col - 1);
+        if gap_start + 1 < marker_end && body[gap_start..marker_end].bytes().all(|b| b == b' ') {
+            let line_end = body[start..]
+                .find('\n')
+                .map(|i| start + i)
+                .unwrap_or(body.len());
+            if marker_end < line_end {
+                edits.push((gap_start..marker_end, " ".to_string()));
+            }
+        }
+    }
+
+    for (line, &shift) in shifts.iter().enumerate() {
+        if shift == 0 {
+            continue;
+        }
+        let start = line_starts[line];
+        let end = body[start..]
+            .find('\n')
+            .map(|i| start + i)
+            .unwrap_or(body.len());
+        let text = &body[start..end];
+        if text.trim().is_empty() {
+            continue;
+        }
+        let indent = text.len() - text.trim_start_matches(' ').len();
+        if shift > 0 {
+            edits.push((start..start, " ".repeat(shift as usize)));
+        } else {
+            let remove = (-shift as usize).min(indent);
+            if remove > 0 {
+                edits.push((start..start + remove, String::new()));
+            }
+        }
+    }
+
+    // Gap and indent edits on the same line touch disjoint ranges, but the
+    // gap edit starts later, so it is applied first.
+    apply_edits(body, edits)
+}
+
+/// Pad the cells of top-level GFM tables so the columns line up.
+fn align_tables(body: &str) -> String {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut depth = 0;
+    let mut table: Option<(Range<usize>, Vec<Alignment>)> = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Table(aligns))
+                if depth == 0 && line_start(body, range.start) == range.start =>
+            {
+                table = Some((range, aligns));
+                rows.clear();
+            }
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) if table.is_some() => {
+                rows.push(Vec::new());
+            }
+            Event::Start(Tag::TableCell) => {
+                if let (Some(_), Some(row)) = (&table, rows.last_mut()) {
+                    row.push(body[range].trim().to_string());
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                if let Some((range, aligns)) = table.take() {
+                    let end = range.start + body[range.clone()].trim_end().len();
+                    edits.push((range.start..end, render_table(&rows, &aligns)));
+                }
+            }
+            Event::Start(tag) if is_container(&tag) => depth += 1,
+            Event::End(tag) if is_container_end(&tag) => depth -= 1,
+            _ => {}
+        }
+    }
+
+    apply_edits(body, edits)
+}
+
+/// Render a GFM table with padded columns. The first row is the header;
+/// cells are expected to be escaped already.
+pub fn render_table(rows: &[Vec<String>], aligns: &[Alignment]) -> String {
+    let columns = aligns.len();
+    let width = |s: &str| s.chars().count();
+    let mut widths = vec![3; columns];
+    for row in rows {
+        for (i, cell) in row.iter().take(columns).enumerate() {
+            widths[i] = widths[i].max(width(cell));
+        }
+    }
+
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|i| {
+                let cell = row.get(i).map(String::as_str).unwrap_or("");
+                let pad = widths[i] - width(cell);
+                match aligns[i] {
+                    Alignment::Right => format!("{}{}", " ".repeat(pad), cell),
+                    Alignment::Center => format!(
+                        "{}{}{}",
+                        " ".repeat(pad / 2),
+                        cell,
+                        " ".repeat(pad - pad / 2)
+                    ),
+                    _ => format!("{}{}", cell, " ".repeat(pad)),
+                }
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    let delimiter: Vec<String> = aligns
+        .iter()
+        .zip(&widths)
+        .map(|(align, &w)| match align {
+            Alignment::Left => format!(":{}", "-".repeat(w - 1)),
+            Alignment::Right => format!("{}:", "-".repeat(w - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(w - 2)),
+            Alignment::None => "-".repeat(w),
+        })
+        .collect();
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    if let Some(head) = rows.first() {
+        lines.push(render_row(head));
+    }
+    lines.push(format!("| {} |", delimiter.join(" | ")));
+    for row in rows.iter().skip(1) {
+        lines.push(render_row(row));
+    }
+    lines.join("\n")
+}
+
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Drop unused and duplicate link reference definitions and print the rest
+/// as `[label]: destination "title"`.
+fn clean_reference_definitions(body: &str) -> String {
+    let mut used: HashSet<String> = HashSet::new();
+    let mut leaves: Vec<Range<usize>> = Vec::new();
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link { link_type, id, .. })
+            | Event::Start(Tag::Image { link_type, id, .. }) => {
+                if matches!(
+                    link_type,
+                    LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+                ) {
+                    used.insert(normalize_label(&id));
+                }
+            }
+            Event::Start(Tag::Paragraph)
+            | Event::Start(Tag::Heading { .. })
+            | Event::Start(Tag::CodeBlock(_))
+            | Event::Start(Tag::HtmlBlock)
+            | Event::Start(Tag::Table(_)) => leaves.push(range),
+            _ => {}
+        }
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end_matches(['\n', '\r']);
+        if leaves.iter().any(|r| r.contains(&start)) {
+            continue;
+        }
+        let Some(caps) = REF_DEF_RE.captures(text) else {
+            continue;
+        };
+        let label = normalize_label(&caps[2]);
+        if !used.contains(&label) || !seen.insert(label) {
+            edits.push((start..offset, String::new()));
+            continue;
+        }
+        let mut definition = format!("{}[{}]: {}", &caps[1], &caps[2], &caps[3]);
+        if let Some(title) = caps.get(4) {
+            let inner = &title.as_str()[1..title.len() - 1];
+            if inner.contains('"') {
+                definition.push_str(&format!(" {}", title.as_str()));
+            } else {
+                definition.push_str(&format!(" \"{}\"", inner));
+            }
+        }
+        if definition != text {
+            edits.push((start..start + text.len(), definition));
+        }
+    }
+
+    apply_edits(body, edits)
+}
+
+/// Rewrap paragraphs and tight list item text. Paragraphs in block quotes or
+/// containing HTML or display math are skipped, and inline code, math and
+/// autolinks are never split.
+fn wrap_prose(body: &str, options: &FormatOptions) -> String {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut stack: Vec<Holder> = Vec::new();
+    let mut quote_depth = 0;
+    let mut run: Option<InlineRun> = None;
+
+    let finish = |run: &mut Option<InlineRun>, edits: &mut Vec<(Range<usize>, String)>| {
+        if let Some(run) = run.take() {
+            if let Some(edit) = run.rewrap(body, options) {
+                edits.push(edit);
+            }
+        }
+    };
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        let in_item = stack.last() == Some(&Holder::Item) && quote_depth == 0;
+        match event {
+            Event::Start(Tag::Paragraph) => {
+                finish(&mut run, &mut edits);
+                if quote_depth == 0 {
+                    run = Some(InlineRun::new(range.start));
+                }
+                stack.push(Holder::Inline);
+            }
+            Event::End(TagEnd::Paragraph) => {
+                if let Some(r) = run.as_mut() {
+                    r.end = r.end.max(range.end);
+                }
+                finish(&mut run, &mut edits);
+                stack.pop();
+            }
+            Event::Start(Tag::BlockQuote(_)) => {
+                finish(&mut run, &mut edits);
+                quote_depth += 1;
+                stack.push(Holder::Other);
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                quote_depth -= 1;
+                stack.pop();
+            }
+            Event::Start(
+                tag @ (Tag::Emphasis
+                | Tag::Strong
+                | Tag::Strikethrough
+                | Tag::Superscript
+                | Tag::Subscript
+                | Tag::Link { .. }
+                | Tag::Image { .. }),
+            ) => {
+                if in_item && run.is_none() {
+                    run = Some(InlineRun::new(range.start));
+                }
+                if let Some(r) = run.as_mut() {
+                    r.end = r.end.max(range.end);
+                    if let Tag::Link {
+                        link_type: LinkType::Autolink | LinkType::Email,
+                        ..
+                    } = tag
+                    {
+                        r.protected.push(range);
+                    }
+                }
+                stack.push(Holder::Inline);
+            }
+            Event::End(tag) if tag_is_block(&tag) => {
+                finish(&mut run, &mut edits);
+                stack.pop();
+            }
+            Event::End(_) => {
+                stack.pop();
+            }
+            Event::Start(tag) => {
+                finish(&mut run, &mut edits);
+                stack.push(if matches!(tag, Tag::Item) {
+                    Holder::Item
+                } else {
+                    Holder::Other
+                });
+            }
+            Event::Text(_)
+            | Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
+            | Event::InlineHtml(_)
+            | Event::FootnoteReference(_)
+            | Event::SoftBreak
+            | Event::HardBreak
+            | Event::TaskListMarker(_) => {
+                if in_item && run.is_none() {
+                    run = Some(InlineRun::new(range.start));
+                }
+                let Some(r) = run.as_mut() else {
+                    continue;
+                };
+                r.end = r.end.max(range.end);
+                match event {
+                    Event::Code(_) | Event::InlineMath(_) | Event::TaskListMarker(_) => {
+                        r.protected.push(range)
+                    }
+                    Event::DisplayMath(_) | Event::InlineHtml(_) => r.skip = true,
+                    Event::HardBreak => r.breaks.push(range),
+                    _ => {}
+                }
+            }
+            Event::Html(_) | Event::Rule => finish(&mut run, &mut edits),
+        }
+    }
+    finish(&mut run, &mut edits);
+
+    apply_edits(body, edits)
+}
+
+/// What an open tag holds, for finding inline text outside paragraphs.
+#[derive(PartialEq, Eq)]
+enum Holder {
+    Inline,
+    Item,
+    Other,
+}
+
+fn tag_is_block(tag: &TagEnd) -> bool {
+    !matches!(
+        tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+/// A stretch of inline content: a paragraph, or the text of a tight list item.
+struct InlineRun {
+    start: usize,
+    end: usize,
+    /// Spans that must stay on one line.
+    protected: Vec<Range<usize>>,
+    /// Hard line breaks, which end a line in every wrap mode.
+    breaks: Vec<Range<usize>>,
+    skip: bool,
+}
+
+impl InlineRun {
+    fn new(start: usize) -> Self {
+        Self {
+            start,
+            end: start,
+            protected: Vec::new(),
+            breaks: Vec::new(),
+            skip: false,
+        }
+    }
+
+    fn rewrap(&self, body: &str, options: &FormatOptions) -> Option<(Range<usize>, String)> {
+        if self.skip || self.end <= self.start {
+            return None;
+        }
+        let end = self.start + body[self.start..self.end].trim_end().len();
+        let line = line_start(body, self.start);
+        let prefix = &body[line..self.start];
+        if prefix.contains(['>', '\t']) {
+            return None;
+        }
+        let indent = " ".repeat(prefix.chars().count());
+        let mut protected = self.protected.clone();
+        protected.extend(
+            WIKI_LINK_RE
+                .find_iter(&body[self.start..end])
+                .map(|m| self.start + m.start()..self.start + m.end()),
+        );
+
+        // Split into segments at hard breaks, then into words.
+        let mut lines: Vec<String> = Vec::new();
+        let mut segment_start = self.start;
+        let mut segments: Vec<(Range<usize>, &str)> = Vec::new();
+        for hard_break in &self.breaks {
+            let marker = if body[hard_break.clone()].starts_with('\\') {
+                "\\"
+            } else {
+                "  "
+            };
+            segments.push((segment_start..hard_break.start, marker));
+            segment_start = hard_break.end;
+        }
+        segments.push((segment_start..end, ""));
+
+        let width = options.print_width.max(20);
+        for (range, suffix) in segments {
+            let words = words(body, range, &protected);
+            let mut current = String::new();
+            let mut current_width = if lines.is_empty() {
+                prefix.chars().count()
+            } else {
+                indent.len()
+            };
+            for word in words {
+                let word_width = word.chars().count();
+                let fits = current.is_empty()
+                    || options.prose_wrap == ProseWrap::Never
+                    || current_width + 1 + word_width <= width;
+                if fits || BLOCK_START_RE.is_match(&word) {
+                    if !current.is_empty() {
+                        current.push(' ');
+                        current_width += 1;
+                    }
+                } else {
+                    lines.push(std::mem::take(&mut current));
+                    current_width = indent.len();
+                }
+                current.push_str(&word);
+                current_width += word_width;
+            }
+            current.push_str(suffix);
+            lines.push(current);
+        }
+
+        let mut out = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+                out.push_str(&indent);
+            }
+            out.push_str(line);
+        }
+        (out != body[self.start..end]).then_some((self.start..end, out))
+    }
+}
+
+/// Whitespace-separated words of `range`, keeping protected spans whole.
+fn words(body: &str, range: Range<usize>, protected: &[Range<usize>]) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut pending_space = false;
+    for (i, c) in body[range.clone()].char_indices() {
+        let at = range.start + i;
+        let is_protected = protected.iter().any(|p| p.contains(&at));
+        if c.is_whitespace() && !is_protected {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            // Line breaks inside code spans are read as spaces.
+            pending_space = true;
+            continue;
+        }
+        if pending_space {
+            word.push(' ');
+            pending_space = false;
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Collapse runs of blank lines outside code, math and HTML blocks and end
+/// the document with a single newline.
+fn collapse_blank_lines(body: &str) -> String {
+    let mut verbatim: Vec<Range<usize>> = Vec::new();
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::HtmlBlock) => verbatim.push(range),
+            Event::DisplayMath(_) | Event::Html(_) => verbatim.push(range),
+            _ => {}
+        }
+    }
+    verbatim.extend(math_blocks(body));
+
+    let mut out = String::with_capacity(body.len());
+    let mut offset = 0;
+    let mut blank_run = 0;
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let is_blank = line.trim().is_empty();
+        let is_verbatim = verbatim.iter().any(|r| r.contains(&start));
+        if is_blank && !is_verbatim {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+            out.push('\n');
+            continue;
+        }
+        blank_run = 0;
+        out.push_str(line);
+    }
+
+    let trimmed = out.trim_end_matches('\n').len();
+    if trimmed == 0 {
+        return out;
+    }
+    let is_verbatim_tail = verbatim
+        .iter()
+        .any(|r| r.end >= body.len() && r.start < body.len());
+    if !is_verbatim_tail {
+        out.truncate(trimmed);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(content: &str) -> String {
+        format_markdown(content, &FormatOptions::default())
+    }
+
+    fn wrapped(content: &str) -> String {
+        let options = FormatOptions {
+            prose_wrap: ProseWrap::Always,
+            print_width: 20,
+            ..FormatOptions::default()
+        };
+        format_markdown(content, &options)
+    }
+
+    const SAMPLE: &str = "\
+---
+title:   Notes
+tags: [a,b]
+* not a list
+---
+
+# Title
+
+Some __strong__ and _em_ text that is long enough to be wrapped at twenty.
+
+* one
+* two
+    * nested
+
+| a | long header |
+|:-|-:|
+| 1 | 2 |
+
+```rust
+* not a list
+let x = __y__;
+```
+
+$$
+* a \\times __b__
+
+
++ c
+$$
+
+<div>
+* raw   __html__
+</div>
+
+[ref]:   https://example.com
+";
+
+    #[test]
+    fn keeps_frontmatter() {
+        let out = format(SAMPLE);
+        assert!(out.starts_with("---\ntitle:   Notes\ntags: [a,b]\n* not a list\n---\n"));
+    }
+
+    #[test]
+    fn keeps_fenced_code() {
+        let code = "```rust\n* not a list\nlet x = __y__;\n```\n";
+        assert!(format(SAMPLE).contains(code));
+        assert!(wrapped(SAMPLE).contains(code));
+    }
+
+    #[test]
+    fn keeps_math_blocks() {
+        let math = "$$\n* a \\times __b__\n\n\n+ c\n$$\n";
+        assert!(format(SAMPLE).contains(math));
+        assert!(wrapped(SAMPLE).contains(math));
+    }
+
+    #[test]
+    fn keeps_html_blocks() {
+        let html = "<div>\n* raw   __html__\n</div>\n";
+        assert!(format(SAMPLE).contains(html));
+        assert!(wrapped(SAMPLE).contains(html));
+    }
+
+    #[test]
+    fn normalizes_markers_and_list_indentation() {
+        let out = format(SAMPLE);
+        assert!(out.contains("Some **strong** and *em* text"), "{}", out);
+        assert!(out.contains("- one\n- two\n  - nested\n"), "{}", out);
+    }
+
+    #[test]
+    fn is_idempotent() {
+        for out in [format(SAMPLE), wrapped(SAMPLE)] {
+            assert_eq!(format(&out), out);
+            assert_eq!(wrapped(&wrapped(SAMPLE)), wrapped(SAMPLE));
+        }
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let out = format("* a\r\n* b\r\n");
+        assert_eq!(out, "- a\r\n- b\r\n");
+    }
+}
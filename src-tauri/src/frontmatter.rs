@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+/// A single frontmatter value: either a scalar or a list of scalars.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontmatterValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+/// Parsed YAML frontmatter of a markdown document.
+///
+/// Only the flat subset of YAML that notes use in practice is supported:
+/// `key: value` pairs, inline lists (`tags: [a, b]`) and block lists
+/// (`- item` lines below a key). Nested mappings are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Frontmatter {
+    fields: BTreeMap<String, FrontmatterValue>,
+}
+
+impl Frontmatter {
+    /// Return a scalar field, or the first element of a list field.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.fields.get(key)? {
+            FrontmatterValue::Scalar(s) => Some(s.as_str()),
+            FrontmatterValue::List(items) => items.first().map(|s| s.as_str()),
+        }
+    }
+
+    /// `true` when the block defines no keys at all.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Return a field as a list. Scalars become a one-element list.
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        match self.fields.get(key) {
+            Some(FrontmatterValue::Scalar(s)) if !s.is_empty() => vec![s.clone()],
+            Some(FrontmatterValue::List(items)) => items.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Split `content` into its frontmatter block and the remaining body.
+///
+/// Frontmatter must start on the very first line with `---` and end with a
+/// line containing only `---` or `...`. When there is no (terminated)
+/// frontmatter the whole input is returned as the body.
+pub fn split_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
+    let Some(raw) = frontmatter_block(content) else {
+        return (None, content);
+    };
+    (Some(parse_block(raw.yaml)), &content[raw.body_start..])
+}
+
+struct RawBlock<'a> {
+    yaml: &'a str,
+    body_start: usize,
+}
+
+fn frontmatter_block(content: &str) -> Option<RawBlock<'_>> {
+    let first_line_end = content.find('\n')?;
+    if content[..first_line_end].trim_end() != "---" {
+        return None;
+    }
+
+    let mut offset = first_line_end + 1;
+    for line in content[offset..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some(RawBlock {
+                yaml: &content[first_line_end + 1..offset],
+                body_start: offset + line.len(),
+            });
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn parse_block(yaml: &str) -> Frontmatter {
+    let mut fields: BTreeMap<String, FrontmatterValue> = BTreeMap::new();
+    let mut current_key: Option<String> = None;
+
+    for line in yaml.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        let trimmed = line.trim();
+
+        // Block list item belonging to the most recent key.
+        let list_item = if trimmed == "-" {
+            Some("")
+        } else {
+            trimmed.strip_prefix("- ")
+        };
+        if let Some(item) = list_item {
+            if let Some(key) = &current_key {
+                let value = unquote(item.trim());
+                match fields.get_mut(key) {
+                    Some(FrontmatterValue::List(items)) => items.push(value),
+                    _ => {
+                        fields.insert(key.clone(), FrontmatterValue::List(vec![value]));
+                    }
+                }
+            }
+            continue;
+        }
+
+        if indented {
+            // Nested mappings are not supported.
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+
+        let parsed = if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']'))
+        {
+            FrontmatterValue::List(
+                inner
+                    .split(',')
+                    .map(|s| unquote(s.trim()))
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            )
+        } else if value.is_empty() {
+            // Either an empty value or the start of a block list.
+            FrontmatterValue::List(Vec::new())
+        } else {
+            FrontmatterValue::Scalar(unquote(value))
+        };
+
+        fields.insert(key.clone(), parsed);
+        current_key = Some(key);
+    }
+
+    Frontmatter { fields }
+}
+
+fn unquote(value: &str) -> String {
+    let stripped = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')));
+    stripped.unwrap_or(value).to_string()
+}
@@ -1,12 +1,73 @@
-use serde::Serialize;
-use std::process::Command as SysCommand;
+use git2::{
+    ApplyLocation, ApplyOptions, Blob, Commit, Delta, Diff, DiffFindOptions, DiffFormat,
+    DiffOptions, ErrorCode, IndexAddOption, IndexEntry, IndexTime, ObjectType, Patch, Repository,
+    RepositoryState, Signature, Sort, Status, StatusOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
 use crate::error::AppError;
+use crate::utils::{app_data_subdir, prune_oldest, timestamped_name, write_atomic};
+
+/// Copies of discarded files, under the app data directory.
+const BACKUP_DIR: &str = "git-backups";
+/// Number of discards and restores whose backups are kept.
+const MAX_BACKUPS: usize = 20;
+/// Commits listed by `git_file_log` unless a limit is given.
+const DEFAULT_LOG_LIMIT: usize = 100;
 
 #[derive(Serialize, Clone)]
 pub struct GitFileStatus {
     pub path: String,
     pub status: String,
+    /// Status letter of the staged change, if any.
+    pub staged: Option<String>,
+    /// Status letter of the unstaged change, if any (`?` when untracked).
+    pub unstaged: Option<String>,
+}
+
+/// A commit that changed a file, as listed in its history.
+#[derive(Serialize)]
+pub struct GitLogEntry {
+    pub id: String,
+    pub short_id: String,
+    pub author: String,
+    pub email: String,
+    /// Commit time, in seconds since the Unix epoch.
+    pub time: i64,
+    pub summary: String,
+    pub message: String,
+    /// Absolute path the file had in this commit, which differs from the
+    /// current one before a rename.
+    pub path: String,
+}
+
+/// A file compared between two revisions.
+#[derive(Deserialize)]
+pub struct RevisionDiffOptions {
+    pub folder: String,
+    /// The file, at its path in `to`.
+    pub file_path: String,
+    /// Older revision: a commit id, branch, or expression such as `HEAD~2`.
+    pub from: String,
+    /// Path of the file in `from`, if it was renamed since.
+    pub from_path: Option<String>,
+    /// Newer revision; the working tree when absent.
+    pub to: Option<String>,
+}
+
+/// One hunk of a file's staged or unstaged diff.
+#[derive(Deserialize)]
+pub struct HunkOptions {
+    pub folder: String,
+    pub file_path: String,
+    /// Zero-based position of the hunk in the diff.
+    pub hunk: usize,
+    /// The hunk's `@@` header as displayed, so a diff that changed since
+    /// is not patched blindly.
+    pub header: String,
 }
 
 #[derive(Serialize, Clone)]
@@ -17,66 +78,328 @@ pub struct GitInfo {
     pub has_changes: bool,
 }
 
-/// Check whether `folder` is inside a git work tree, return the current branch
-/// and the list of changed / untracked files.
-#[tauri::command]
-pub fn git_status(folder: String) -> Result<GitInfo, AppError> {
-    let is_repo = SysCommand::new("git")
-        .args(["-C", &folder, "rev-parse", "--is-inside-work-tree"])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-
-    if !is_repo {
-        return Ok(GitInfo {
-            is_repo: false,
-            branch: String::new(),
-            files: Vec::new(),
-            has_changes: false,
-        });
+/// Open the repository whose work tree contains `folder`.
+fn open_repo(folder: &str) -> Result<Repository, AppError> {
+    let repo = Repository::discover(folder).map_err(|e| match e.code() {
+        ErrorCode::NotFound => AppError::NotARepo(folder.to_string()),
+        _ => e.into(),
+    })?;
+    if repo.is_bare() {
+        return Err(AppError::NotARepo(folder.to_string()));
     }
+    Ok(repo)
+}
 
-    let branch_output = SysCommand::new("git")
-        .args(["-C", &folder, "branch", "--show-current"])
-        .output()
-        .map_err(|e| AppError::Git(format!("Failed to get branch: {}", e)))?;
+fn workdir(repo: &Repository) -> &Path {
+    // `open_repo` rejects bare repositories
+    repo.workdir().unwrap_or_else(|| repo.path())
+}
 
-    let branch = String::from_utf8_lossy(&branch_output.stdout)
-        .trim()
-        .to_string();
+/// `file` (absolute, or relative to `folder`) as a slash-separated path
+/// relative to the work tree.
+fn repo_path(repo: &Repository, folder: &str, file: &str) -> Result<String, AppError> {
+    let root = workdir(repo);
+    let path = Path::new(folder).join(file);
+    let relative = match path.strip_prefix(root) {
+        Ok(relative) => relative.to_path_buf(),
+        // One side may go through a symlink; the file itself may be deleted
+        Err(_) => {
+            let root = root.canonicalize()?;
+            let parent = path.parent().unwrap_or(&path).canonicalize()?;
+            let resolved: PathBuf = parent.join(path.file_name().unwrap_or_default());
+            resolved
+                .strip_prefix(&root)
+                .map(Path::to_path_buf)
+                .map_err(|_| AppError::Git(format!("{} is outside the repository", file)))?
+        }
+    };
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
 
-    let status_output = SysCommand::new("git")
-        .args(["-C", &folder, "status", "--porcelain"])
-        .output()
-        .map_err(|e| AppError::Git(format!("Failed to get status: {}", e)))?;
+/// The checked-out branch, also before its first commit; empty when HEAD
+/// is detached.
+fn current_branch(repo: &Repository) -> Result<String, AppError> {
+    match repo.head() {
+        Ok(head) if head.is_branch() => Ok(head.shorthand().unwrap_or_default().to_string()),
+        Ok(_) => Ok(String::new()),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD")?;
+            Ok(head
+                .symbolic_target()
+                .and_then(|target| target.strip_prefix("refs/heads/"))
+                .unwrap_or_default()
+                .to_string())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
 
-    let status_text = String::from_utf8_lossy(&status_output.stdout);
+/// One-letter status as shown in the git panel: `?` untracked, `A` added,
+/// `M` modified, `D` deleted, `R` renamed, `U` conflicted.
+fn status_letter(status: Status) -> &'static str {
+    if status.contains(Status::CONFLICTED) {
+        "U"
+    } else if status.contains(Status::WT_NEW) && !status.intersects(Status::INDEX_NEW) {
+        "?"
+    } else if status.contains(Status::INDEX_RENAMED) {
+        "R"
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        "D"
+    } else if status.contains(Status::INDEX_NEW) {
+        "A"
+    } else {
+        "M"
+    }
+}
 
-    let mut files: Vec<GitFileStatus> = Vec::new();
+/// Status letter of the change between HEAD and the index.
+fn staged_letter(status: Status) -> Option<&'static str> {
+    if status.contains(Status::CONFLICTED) {
+        Some("U")
+    } else if status.contains(Status::INDEX_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("D")
+    } else if status.contains(Status::INDEX_NEW) {
+        Some("A")
+    } else if status.intersects(Status::INDEX_MODIFIED | Status::INDEX_TYPECHANGE) {
+        Some("M")
+    } else {
+        None
+    }
+}
 
-    for line in status_text.lines() {
-        if line.len() < 3 {
-            continue;
+/// Status letter of the change between the index and the work tree.
+fn unstaged_letter(status: Status) -> Option<&'static str> {
+    if status.contains(Status::CONFLICTED) {
+        Some("U")
+    } else if status.contains(Status::WT_NEW) {
+        Some("?")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("D")
+    } else if status.intersects(Status::WT_MODIFIED | Status::WT_TYPECHANGE | Status::WT_RENAMED) {
+        Some("M")
+    } else {
+        None
+    }
+}
+
+/// The commit HEAD points to, or `None` before the first commit.
+fn head_commit(repo: &Repository) -> Result<Option<Commit<'_>>, AppError> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, AppError> {
+    repo.signature().map_err(|e| match e.code() {
+        ErrorCode::NotFound => {
+            AppError::Git("Set user.name and user.email in your git config".to_string())
         }
+        _ => e.into(),
+    })
+}
 
-        let xy = &line[..2];
-        let file_path = line[3..].trim().to_string();
+fn file_diff_options(path: &str) -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options.pathspec(path).disable_pathspec_match(true);
+    options
+}
 
-        let status = if xy == "??" {
-            "?".to_string()
-        } else if xy.contains('D') {
-            "D".to_string()
-        } else if xy.starts_with('A') {
-            "A".to_string()
-        } else {
-            "M".to_string()
-        };
+/// Changes to `path` in the work tree that are not staged.
+fn unstaged_diff<'a>(repo: &'a Repository, path: &str) -> Result<Diff<'a>, AppError> {
+    let mut options = file_diff_options(path);
+    Ok(repo.diff_index_to_workdir(None, Some(&mut options))?)
+}
 
-        files.push(GitFileStatus {
-            path: file_path,
-            status,
-        });
+/// Staged changes to `path`; `reverse` gives the diff that undoes them.
+fn staged_diff<'a>(repo: &'a Repository, path: &str, reverse: bool) -> Result<Diff<'a>, AppError> {
+    let tree = head_commit(repo)?.map(|commit| commit.tree()).transpose()?;
+    let mut options = file_diff_options(path);
+    options.reverse(reverse);
+    Ok(repo.diff_tree_to_index(tree.as_ref(), None, Some(&mut options))?)
+}
+
+/// The diff as unified patch text.
+fn patch_text(diff: &Diff) -> Result<String, AppError> {
+    let mut text = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(text)
+}
+
+/// Fail unless hunk `options.hunk` of `diff` still has the expected header.
+fn check_hunk(diff: &Diff, options: &HunkOptions) -> Result<(), AppError> {
+    let mut headers = Vec::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if line.origin() == 'H' {
+            headers.push(
+                String::from_utf8_lossy(line.content())
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+        true
+    })?;
+    if headers.get(options.hunk).map(String::as_str) != Some(options.header.trim_end()) {
+        return Err(AppError::Git(
+            "The diff has changed; refresh it and try again".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Apply only hunk `hunk` of `diff` to the index.
+fn apply_hunk(repo: &Repository, diff: &Diff, hunk: usize) -> Result<(), AppError> {
+    let mut seen = 0;
+    let mut options = ApplyOptions::new();
+    options.hunk_callback(|_| {
+        seen += 1;
+        seen == hunk + 1
+    });
+    repo.apply(diff, ApplyLocation::Index, Some(&mut options))?;
+    Ok(())
+}
+
+/// The file at `path` in the given revision, or `None` if it has no such
+/// file.
+fn blob_at<'a>(
+    repo: &'a Repository,
+    revision: &str,
+    path: &str,
+) -> Result<Option<Blob<'a>>, AppError> {
+    let object = repo.revparse_single(revision).map_err(|e| match e.code() {
+        ErrorCode::NotFound => AppError::NotFound(format!("revision {}", revision)),
+        _ => e.into(),
+    })?;
+    match object.peel_to_tree()?.get_path(Path::new(path)) {
+        Ok(entry) => Ok(Some(entry.to_object(repo)?.peel_to_blob()?)),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The path `path` had in the first parent of `commit`, when the commit
+/// renamed it.
+fn renamed_from(
+    repo: &Repository,
+    commit: &Commit,
+    path: &str,
+) -> Result<Option<String>, AppError> {
+    let Ok(parent) = commit.parent(0) else {
+        return Ok(None);
+    };
+    let mut diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&commit.tree()?), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Renamed)
+        .find(|delta| delta.new_file().path() == Some(Path::new(path)))
+        .and_then(|delta| delta.old_file().path())
+        .map(|old| old.to_string_lossy().replace('\\', "/")))
+}
+
+/// Replace the work tree file `path` with the content of `blob`.
+fn write_blob(repo: &Repository, path: &str, blob: &Blob) -> Result<(), AppError> {
+    write_atomic(&workdir(repo).join(path), blob.content())?;
+    Ok(())
+}
+
+/// Copy the work tree file `path` into `backup`, if it exists.
+fn back_up(repo: &Repository, backup: &Path, path: &str) -> Result<(), AppError> {
+    let full = workdir(repo).join(path);
+    if full.is_file() {
+        let copy = backup.join(path);
+        if let Some(parent) = copy.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&full, &copy)?;
+    }
+    Ok(())
+}
+
+/// A fresh directory for the backups of one discard or restore.
+fn new_backup_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app_data_subdir(app, BACKUP_DIR)?;
+    Ok(dir.join(timestamped_name(&dir, "")))
+}
+
+fn prune_backups(app: &AppHandle) {
+    if let Ok(dir) = app_data_subdir(app, BACKUP_DIR) {
+        prune_oldest(&dir, MAX_BACKUPS);
+    }
+}
+
+/// Paths of the files with unresolved merge conflicts.
+fn conflicted_paths(index: &git2::Index) -> Result<Vec<String>, AppError> {
+    let mut paths = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+        }
     }
+    Ok(paths)
+}
+
+fn has_conflict_markers(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|text| {
+        text.lines()
+            .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+    })
+}
+
+/// Check whether `folder` is inside a git work tree, return the current branch
+/// and the list of changed / untracked files.
+#[tauri::command]
+pub fn git_status(folder: String) -> Result<GitInfo, AppError> {
+    let repo = match open_repo(&folder) {
+        Ok(repo) => repo,
+        Err(AppError::NotARepo(_)) => {
+            return Ok(GitInfo {
+                is_repo: false,
+                branch: String::new(),
+                files: Vec::new(),
+                has_changes: false,
+            });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let branch = current_branch(&repo)?;
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+
+    let files: Vec<GitFileStatus> = statuses
+        .iter()
+        .filter(|entry| !entry.status().contains(Status::IGNORED))
+        .filter_map(|entry| {
+            // Renames are reported under their new path
+            let path = entry
+                .head_to_index()
+                .and_then(|delta| delta.new_file().path().map(Path::to_path_buf))
+                .or_else(|| entry.path().map(PathBuf::from))?;
+            let status = entry.status();
+            Some(GitFileStatus {
+                path: path.to_string_lossy().replace('\\', "/"),
+                status: status_letter(status).to_string(),
+                staged: staged_letter(status).map(str::to_string),
+                unstaged: unstaged_letter(status).map(str::to_string),
+            })
+        })
+        .collect();
 
     let has_changes = !files.is_empty();
 
@@ -88,44 +411,410 @@ pub fn git_status(folder: String) -> Result<GitInfo, AppError> {
     })
 }
 
-/// Return the diff output for a single file inside the given repo folder.
+/// Return the unstaged changes to a single file inside the given repo folder.
 #[tauri::command]
 pub fn git_diff(folder: String, file_path: String) -> Result<String, AppError> {
-    let output = SysCommand::new("git")
-        .args(["-C", &folder, "diff", "--", &file_path])
-        .output()
-        .map_err(|e| AppError::Git(format!("Failed to run git diff: {}", e)))?;
+    let repo = open_repo(&folder)?;
+    let path = repo_path(&repo, &folder, &file_path)?;
+    let diff = unstaged_diff(&repo, &path)?;
+    patch_text(&diff)
+}
+
+/// Return the staged changes to a single file, as they would be committed.
+#[tauri::command]
+pub fn git_diff_staged(folder: String, file_path: String) -> Result<String, AppError> {
+    let repo = open_repo(&folder)?;
+    let path = repo_path(&repo, &folder, &file_path)?;
+    let diff = staged_diff(&repo, &path, false)?;
+    patch_text(&diff)
+}
+
+/// Stage the current content of each file; missing files stage a deletion.
+#[tauri::command]
+pub fn git_stage(folder: String, paths: Vec<String>) -> Result<(), AppError> {
+    let repo = open_repo(&folder)?;
+    let mut index = repo.index()?;
+    for file in &paths {
+        let path = repo_path(&repo, &folder, file)?;
+        if workdir(&repo).join(&path).exists() {
+            index.add_path(Path::new(&path))?;
+        } else {
+            index.remove_path(Path::new(&path))?;
+        }
+    }
+    index.write()?;
+    Ok(())
+}
+
+/// Unstage each file, resetting its index entry to HEAD. The work tree is
+/// left alone.
+#[tauri::command]
+pub fn git_unstage(folder: String, paths: Vec<String>) -> Result<(), AppError> {
+    let repo = open_repo(&folder)?;
+    let mut paths = paths
+        .iter()
+        .map(|file| repo_path(&repo, &folder, file))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A staged rename also staged the removal of its old path
+    let mut options = StatusOptions::new();
+    options.renames_head_to_index(true);
+    let mut renamed_from = Vec::new();
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let Some(delta) = entry.head_to_index() else {
+            continue;
+        };
+        if delta.status() != Delta::Renamed {
+            continue;
+        }
+        let new_path = delta
+            .new_file()
+            .path()
+            .map(|p| p.to_string_lossy().replace('\\', "/"));
+        if new_path.is_some_and(|p| paths.contains(&p)) {
+            if let Some(old_path) = delta.old_file().path() {
+                renamed_from.push(old_path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    paths.extend(renamed_from);
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Git(format!("git diff failed: {}", stderr)));
+    // Entries are reset by exact path; `reset_default` would read the paths
+    // as patterns and could unstage other files
+    let tree = match head_commit(&repo)? {
+        Some(commit) => Some(commit.tree()?),
+        None => None,
+    };
+    let mut index = repo.index()?;
+    for path in &paths {
+        let entry = match tree.as_ref().map(|tree| tree.get_path(Path::new(path))) {
+            Some(Ok(entry)) if entry.kind() == Some(ObjectType::Blob) => Some(entry),
+            Some(Err(e)) if e.code() != ErrorCode::NotFound => return Err(e.into()),
+            _ => None,
+        };
+        match entry {
+            Some(entry) => index.add(&IndexEntry {
+                ctime: IndexTime::new(0, 0),
+                mtime: IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: entry.filemode() as u32,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: entry.id(),
+                flags: path.len().min(0xfff) as u16,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            })?,
+            None => index.remove_path(Path::new(path))?,
+        }
     }
+    index.write()?;
+    Ok(())
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+/// Stage a single hunk of a file's unstaged changes.
+#[tauri::command]
+pub fn git_stage_hunk(options: HunkOptions) -> Result<(), AppError> {
+    let repo = open_repo(&options.folder)?;
+    let path = repo_path(&repo, &options.folder, &options.file_path)?;
+    let diff = unstaged_diff(&repo, &path)?;
+    check_hunk(&diff, &options)?;
+    apply_hunk(&repo, &diff, options.hunk)
+}
+
+/// Unstage a single hunk of a file's staged changes.
+#[tauri::command]
+pub fn git_unstage_hunk(options: HunkOptions) -> Result<(), AppError> {
+    let repo = open_repo(&options.folder)?;
+    let path = repo_path(&repo, &options.folder, &options.file_path)?;
+    let diff = staged_diff(&repo, &path, false)?;
+    check_hunk(&diff, &options)?;
+    let reverse = staged_diff(&repo, &path, true)?;
+    apply_hunk(&repo, &reverse, options.hunk)
+}
+
+/// Throw away the unstaged changes to each file: tracked files go back to
+/// their staged content and untracked files are deleted. Every file is
+/// first copied to a backup directory, whose path is returned.
+#[tauri::command]
+pub fn git_discard(app: AppHandle, folder: String, paths: Vec<String>) -> Result<String, AppError> {
+    let repo = open_repo(&folder)?;
+    let index = repo.index()?;
+    let backup = new_backup_dir(&app)?;
+
+    // Written by exact path rather than through a checkout, whose paths are
+    // matched as patterns and could overwrite other files
+    let mut restore = Vec::new();
+    let mut untracked = Vec::new();
+    for file in &paths {
+        let path = repo_path(&repo, &folder, file)?;
+        back_up(&repo, &backup, &path)?;
+        match index.get_path(Path::new(&path), 0) {
+            Some(entry) => restore.push((path, entry.id)),
+            None => untracked.push(workdir(&repo).join(&path)),
+        }
+    }
+
+    for (path, id) in restore {
+        write_blob(&repo, &path, &repo.find_blob(id)?)?;
+    }
+    for path in untracked {
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+    }
+    prune_backups(&app);
+    Ok(backup.to_string_lossy().into_owned())
 }
 
-/// Stage all changes and create a commit with the given message.
+/// Create a commit with the given message. Every change is staged first,
+/// unless `staged_only` is set, which commits just what is staged. `amend`
+/// replaces the last commit with the staged changes instead, keeping its
+/// message if `message` is empty.
 #[tauri::command]
-pub fn git_commit(folder: String, message: String) -> Result<String, AppError> {
-    let add_output = SysCommand::new("git")
-        .args(["-C", &folder, "add", "-A"])
-        .output()
-        .map_err(|e| AppError::Git(format!("Failed to run git add: {}", e)))?;
+pub fn git_commit(
+    folder: String,
+    message: String,
+    staged_only: Option<bool>,
+    amend: Option<bool>,
+) -> Result<String, AppError> {
+    let amend = amend.unwrap_or(false);
+    if message.trim().is_empty() && !amend {
+        return Err(AppError::Git("Commit message is empty".to_string()));
+    }
+    let repo = open_repo(&folder)?;
 
-    if !add_output.status.success() {
-        let stderr = String::from_utf8_lossy(&add_output.stderr);
-        return Err(AppError::Git(format!("git add failed: {}", stderr)));
+    let mut index = repo.index()?;
+    // An amend only rewords or adds what was staged, never working drafts
+    if staged_only.unwrap_or(false) || amend {
+        if index.has_conflicts() {
+            return Err(AppError::Conflicts(conflicted_paths(&index)?.join(", ")));
+        }
+    } else {
+        // Staging a conflicted file marks it resolved, as `git add` does, but
+        // not while it still has conflict markers
+        let unresolved: Vec<String> = conflicted_paths(&index)?
+            .into_iter()
+            .filter(|path| has_conflict_markers(&workdir(&repo).join(path)))
+            .collect();
+        if !unresolved.is_empty() {
+            return Err(AppError::Conflicts(unresolved.join(", ")));
+        }
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"], None)?;
+        index.write()?;
     }
 
-    let commit_output = SysCommand::new("git")
-        .args(["-C", &folder, "commit", "-m", &message])
-        .output()
-        .map_err(|e| AppError::Git(format!("Failed to run git commit: {}", e)))?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = head_commit(&repo)?;
+    let signature = signature(&repo)?;
+
+    let oid = if amend {
+        let Some(head) = head else {
+            return Err(AppError::Git("There is no commit to amend".to_string()));
+        };
+        let message = (!message.trim().is_empty()).then_some(message.as_str());
+        head.amend(
+            Some("HEAD"),
+            None,
+            Some(&signature),
+            None,
+            message,
+            Some(&tree),
+        )?
+    } else {
+        let merging = repo.state() == RepositoryState::Merge;
+        let unchanged = match &head {
+            Some(head) => head.tree_id() == tree.id(),
+            None => tree.is_empty(),
+        };
+        if unchanged && !merging {
+            return Err(AppError::NothingToCommit);
+        }
+
+        let mut parents: Vec<Commit> = head.into_iter().collect();
+        if merging {
+            // MERGE_HEAD lists the commits being merged in, one per line
+            let merge_heads = fs::read_to_string(repo.path().join("MERGE_HEAD"))?;
+            for line in merge_heads.lines().filter(|l| !l.trim().is_empty()) {
+                let oid = git2::Oid::from_str(line.trim())?;
+                parents.push(repo.find_commit(oid)?);
+            }
+        }
+        let parents: Vec<&Commit> = parents.iter().collect();
+        let oid = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+        if merging {
+            repo.cleanup_state()?;
+        }
+        oid
+    };
+
+    let commit = repo.find_commit(oid)?;
+    let short_id = commit.as_object().short_id()?;
+    Ok(format!(
+        "[{} {}] {}",
+        current_branch(&repo)?,
+        short_id.as_str().unwrap_or_default(),
+        commit.summary().unwrap_or_default()
+    ))
+}
+
+/// The commits that changed a file, newest first, following it back through
+/// renames along the first-parent history.
+#[tauri::command]
+pub fn git_file_log(
+    folder: String,
+    file_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitLogEntry>, AppError> {
+    let repo = open_repo(&folder)?;
+    let mut path = repo_path(&repo, &folder, &file_path)?;
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT);
+    if head_commit(&repo)?.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.simplify_first_parent()?;
+
+    let mut entries = Vec::new();
+    for oid in walk {
+        if entries.len() >= limit {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        let Ok(entry) = commit.tree()?.get_path(Path::new(&path)) else {
+            continue;
+        };
+        let parent_id = match commit.parent(0) {
+            Ok(parent) => parent
+                .tree()?
+                .get_path(Path::new(&path))
+                .ok()
+                .map(|e| e.id()),
+            Err(_) => None,
+        };
+        if parent_id == Some(entry.id()) {
+            continue;
+        }
+
+        let author = commit.author();
+        entries.push(GitLogEntry {
+            id: commit.id().to_string(),
+            short_id: commit
+                .as_object()
+                .short_id()?
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            message: commit.message().unwrap_or_default().to_string(),
+            path: workdir(&repo).join(&path).to_string_lossy().into_owned(),
+        });
+        if parent_id.is_none() {
+            // Added here, either fresh or under another name
+            match renamed_from(&repo, &commit, &path)? {
+                Some(old_path) => path = old_path,
+                None => break,
+            }
+        }
+    }
+    Ok(entries)
+}
 
-    if !commit_output.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_output.stderr);
-        return Err(AppError::Git(format!("git commit failed: {}", stderr)));
+/// The content of a file as it was in the given revision. `file_path` is
+/// the path the file had then.
+#[tauri::command]
+pub fn git_file_at(
+    folder: String,
+    file_path: String,
+    revision: String,
+) -> Result<String, AppError> {
+    let repo = open_repo(&folder)?;
+    let path = repo_path(&repo, &folder, &file_path)?;
+    let blob = blob_at(&repo, &revision, &path)?
+        .ok_or_else(|| AppError::NotFound(format!("{} at {}", path, revision)))?;
+    if blob.is_binary() {
+        return Err(AppError::Other(format!("{} is a binary file", path)));
     }
+    Ok(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// The changes to a file between two revisions, or between a revision and
+/// the working tree.
+#[tauri::command]
+pub fn git_diff_revisions(options: RevisionDiffOptions) -> Result<String, AppError> {
+    let repo = open_repo(&options.folder)?;
+    let new_path = repo_path(&repo, &options.folder, &options.file_path)?;
+    let old_path = match &options.from_path {
+        Some(from_path) => repo_path(&repo, &options.folder, from_path)?,
+        None => new_path.clone(),
+    };
+
+    // A side without the file diffs as empty, showing an add or delete
+    let old = blob_at(&repo, &options.from, &old_path)?
+        .map(|blob| blob.content().to_vec())
+        .unwrap_or_default();
+    let new = match &options.to {
+        Some(to) => blob_at(&repo, to, &new_path)?
+            .map(|blob| blob.content().to_vec())
+            .unwrap_or_default(),
+        None => match fs::read(workdir(&repo).join(&new_path)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        },
+    };
+
+    let mut patch = Patch::from_buffers(
+        &old,
+        Some(Path::new(&old_path)),
+        &new,
+        Some(Path::new(&new_path)),
+        None,
+    )?;
+    let text = patch.to_buf()?;
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+/// Overwrite a file in the working tree with its content from the given
+/// revision, taken from `source_path` if it had another name then. The
+/// current file is backed up first; returns the backup directory.
+#[tauri::command]
+pub fn git_restore_revision(
+    app: AppHandle,
+    folder: String,
+    file_path: String,
+    revision: String,
+    source_path: Option<String>,
+) -> Result<String, AppError> {
+    let repo = open_repo(&folder)?;
+    let path = repo_path(&repo, &folder, &file_path)?;
+    let source = match &source_path {
+        Some(source_path) => repo_path(&repo, &folder, source_path)?,
+        None => path.clone(),
+    };
+    let blob = blob_at(&repo, &revision, &source)?
+        .ok_or_else(|| AppError::NotFound(format!("{} at {}", source, revision)))?;
+
+    let backup = new_backup_dir(&app)?;
+    back_up(&repo, &backup, &path)?;
 
-    Ok(String::from_utf8_lossy(&commit_output.stdout).to_string())
+    write_blob(&repo, &path, &blob)?;
+    prune_backups(&app);
+    Ok(backup.to_string_lossy().into_owned())
 }
@@ -0,0 +1,256 @@
+/// Token classes produced by [`highlight_lines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// A highlighted line: consecutive `(kind, text)` runs that concatenate back
+/// to the original line.
+pub type HighlightedLine = Vec<(TokenKind, String)>;
+
+struct LanguageRules {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    case_insensitive: bool,
+}
+
+const C_LIKE_KEYWORDS: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "fn",
+    "for",
+    "func",
+    "function",
+    "if",
+    "impl",
+    "implements",
+    "import",
+    "in",
+    "interface",
+    "let",
+    "match",
+    "mod",
+    "mut",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "use",
+    "var",
+    "void",
+    "where",
+    "while",
+    "yield",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "self", "True", "try",
+    "while", "with", "yield",
+];
+
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "echo", "elif", "else", "esac", "exit", "export", "fi", "for",
+    "function", "if", "in", "local", "return", "then", "until", "while",
+];
+
+const SQL_KEYWORDS: &[&str] = &[
+    "and", "as", "by", "create", "delete", "from", "group", "having", "insert", "into", "join",
+    "left", "limit", "not", "null", "on", "or", "order", "right", "select", "set", "table",
+    "update", "values", "where",
+];
+
+fn rules_for(lang: &str) -> LanguageRules {
+    match lang.to_ascii_lowercase().as_str() {
+        "python" | "py" => LanguageRules {
+            keywords: PYTHON_KEYWORDS,
+            line_comments: &["#"],
+            block_comment: None,
+            case_insensitive: false,
+        },
+        "bash" | "sh" | "shell" | "zsh" | "toml" | "yaml" | "yml" => LanguageRules {
+            keywords: SHELL_KEYWORDS,
+            line_comments: &["#"],
+            block_comment: None,
+            case_insensitive: false,
+        },
+        "sql" => LanguageRules {
+            keywords: SQL_KEYWORDS,
+            line_comments: &["--"],
+            block_comment: Some(("/*", "*/")),
+            case_insensitive: true,
+        },
+        "html" | "xml" | "svg" => LanguageRules {
+            keywords: &[],
+            line_comments: &[],
+            block_comment: Some(("<!--", "-->")),
+            case_insensitive: false,
+        },
+        _ => LanguageRules {
+            keywords: C_LIKE_KEYWORDS,
+            line_comments: &["//"],
+            block_comment: Some(("/*", "*/")),
+            case_insensitive: false,
+        },
+    }
+}
+
+/// Split `code` into lines of classified tokens using lightweight per-language
+/// rules (keywords, comments, strings, numbers). Unknown languages fall back
+/// to C-like rules, which cover most fenced code in notes reasonably well.
+pub fn highlight_lines(code: &str, lang: &str) -> Vec<HighlightedLine> {
+    let rules = rules_for(lang);
+    let mut in_block_comment = false;
+    let mut lines: Vec<HighlightedLine> = Vec::new();
+
+    for line in code.lines() {
+        let mut tokens: HighlightedLine = Vec::new();
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            if in_block_comment {
+                let (_, end) = rules
+                    .block_comment
+                    .expect("block comment state requires rules");
+                match rest.find(end) {
+                    Some(pos) => {
+                        push_token(&mut tokens, TokenKind::Comment, &rest[..pos + end.len()]);
+                        rest = &rest[pos + end.len()..];
+                        in_block_comment = false;
+                    }
+                    None => {
+                        push_token(&mut tokens, TokenKind::Comment, rest);
+                        rest = "";
+                    }
+                }
+                continue;
+            }
+
+            if let Some((start, _)) = rules.block_comment {
+                if rest.starts_with(start) {
+                    push_token(&mut tokens, TokenKind::Comment, start);
+                    rest = &rest[start.len()..];
+                    in_block_comment = true;
+                    continue;
+                }
+            }
+
+            if rules.line_comments.iter().any(|c| rest.starts_with(c)) {
+                push_token(&mut tokens, TokenKind::Comment, rest);
+                break;
+            }
+
+            let first = rest.chars().next().expect("rest is non-empty");
+
+            if first == '"' || first == '\'' || first == '`' {
+                let len = string_literal_len(rest, first);
+                push_token(&mut tokens, TokenKind::String, &rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+
+            if first.is_ascii_digit() {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                    .unwrap_or(rest.len());
+                push_token(&mut tokens, TokenKind::Number, &rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+
+            if first.is_alphabetic() || first == '_' {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                let is_keyword = if rules.case_insensitive {
+                    rules.keywords.iter().any(|k| k.eq_ignore_ascii_case(word))
+                } else {
+                    rules.keywords.contains(&word)
+                };
+                let kind = if is_keyword {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Plain
+                };
+                push_token(&mut tokens, kind, word);
+                rest = &rest[len..];
+                continue;
+            }
+
+            let len = first.len_utf8();
+            push_token(&mut tokens, TokenKind::Plain, &rest[..len]);
+            rest = &rest[len..];
+        }
+
+        lines.push(tokens);
+    }
+
+    lines
+}
+
+/// Length in bytes of the string literal at the start of `text`, including
+/// both quotes. Unterminated strings extend to the end of the line.
+fn string_literal_len(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return i + c.len_utf8();
+        }
+    }
+    text.len()
+}
+
+/// Append `text` to `tokens`, merging with the previous run of the same kind.
+fn push_token(tokens: &mut HighlightedLine, kind: TokenKind, text: &str) {
+    if let Some((last_kind, last_text)) = tokens.last_mut() {
+        if *last_kind == kind {
+            last_text.push_str(text);
+            return;
+        }
+    }
+    tokens.push((kind, text.to_string()));
+}
@@ -0,0 +1,464 @@
+use pulldown_cmark::Alignment;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{create_file, save_image, write_file};
+use crate::error::AppError;
+use crate::utils::sanitize_filename;
+use crate::{format, import_docx, import_html};
+
+#[derive(Deserialize)]
+pub struct ImportOptions {
+    /// `.docx`, `.html` or `.htm` file to convert.
+    pub source_path: String,
+    /// Folder the markdown note (and its `assets/` images) is created in.
+    pub folder: String,
+    /// Name of the note to create; defaults to the source name with `.md`.
+    pub file_name: Option<String>,
+}
+
+/// Convert a Word or HTML document into a GFM markdown note.
+///
+/// The note is created with the regular file commands, and embedded images
+/// are saved into the folder's `assets/` directory. Returns the path of the
+/// created note.
+#[tauri::command]
+pub fn import_document(options: ImportOptions) -> Result<String, AppError> {
+    let source = PathBuf::from(&options.source_path);
+    if !source.is_file() {
+        return Err(AppError::NotFound(options.source_path));
+    }
+    let extension = source
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "imported".to_string());
+
+    let converter = match extension.as_str() {
+        "docx" => import_docx::convert,
+        "html" | "htm" | "xhtml" => import_html::convert,
+        _ => {
+            return Err(AppError::Other(format!(
+                "Unsupported import format: .{}",
+                extension
+            )))
+        }
+    };
+
+    let name = match options.file_name {
+        Some(name) => sanitize_filename(&name)?,
+        None => sanitize_filename(&format!("{}.md", stem))?,
+    };
+    let name = if name.to_lowercase().ends_with(".md") {
+        name
+    } else {
+        format!("{}.md", name)
+    };
+    let note_path = Path::new(&options.folder)
+        .join(name)
+        .to_string_lossy()
+        .into_owned();
+
+    // Claim the note before writing any image, so a name clash leaves
+    // nothing behind; a failed conversion removes both again
+    create_file(note_path.clone())?;
+    let mut assets = AssetWriter::new(&options.folder, &stem);
+    let written = converter(&source, &mut assets)
+        .and_then(|blocks| write_file(note_path.clone(), render_markdown(&blocks)));
+    if let Err(e) = written {
+        assets.remove_saved();
+        let _ = fs::remove_file(&note_path);
+        return Err(e);
+    }
+    Ok(note_path)
+}
+
+/// Saves extracted images into `{folder}/assets/` with names derived from the
+/// imported document, never overwriting existing files.
+pub struct AssetWriter {
+    folder: String,
+    prefix: String,
+    count: usize,
+    saved: Vec<PathBuf>,
+}
+
+impl AssetWriter {
+    fn new(folder: &str, stem: &str) -> Self {
+        let prefix: String = stem
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        Self {
+            folder: folder.to_string(),
+            prefix,
+            count: 0,
+            saved: Vec::new(),
+        }
+    }
+
+    /// Save `data` and return the markdown link target (`assets/<name>`).
+    pub fn save(&mut self, extension: &str, data: Vec<u8>) -> Result<String, AppError> {
+        let assets_dir = Path::new(&self.folder).join("assets");
+        let name = loop {
+            self.count += 1;
+            let candidate = format!("{}-image-{}.{}", self.prefix, self.count, extension);
+            if !assets_dir.join(&candidate).exists() {
+                break candidate;
+            }
+        };
+        let path = save_image(self.folder.clone(), name.clone(), data)?;
+        self.saved.push(PathBuf::from(path));
+        Ok(format!("assets/{}", name))
+    }
+
+    /// Delete every image saved so far.
+    fn remove_saved(&mut self) {
+        for path in self.saved.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// ── Intermediate document model ─────────────────────────────────────
+// Both importers produce these blocks; `render_markdown` turns them into GFM.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Inline {
+    Text(String, Style),
+    Code(String),
+    Link(String, Vec<Inline>),
+    Image { src: String, alt: String },
+    LineBreak,
+}
+
+#[derive(Debug, Clone)]
+pub enum Block {
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    List {
+        ordered: bool,
+        start: u64,
+        items: Vec<Vec<Block>>,
+    },
+    Quote(Vec<Block>),
+    Code {
+        lang: String,
+        text: String,
+    },
+    Table {
+        aligns: Vec<Alignment>,
+        /// The first row is the header.
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+    Rule,
+}
+
+/// Whether inline content has anything visible in it.
+pub fn has_content(inlines: &[Inline]) -> bool {
+    inlines.iter().any(|i| match i {
+        Inline::Text(text, _) => !text.trim().is_empty(),
+        Inline::Code(_) | Inline::Image { .. } => true,
+        Inline::Link(_, children) => has_content(children),
+        Inline::LineBreak => false,
+    })
+}
+
+pub fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = render_blocks(blocks);
+    out.push('\n');
+    out
+}
+
+fn render_blocks(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(render_block)
+        .filter(|b| !b.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn render_block(block: &Block) -> String {
+    match block {
+        Block::Heading(level, inlines) => {
+            let text = render_inlines(inlines).replace("\\\n", " ");
+            format!(
+                "{} {}",
+                "#".repeat((*level).clamp(1, 6) as usize),
+                text.trim()
+            )
+        }
+        Block::Paragraph(inlines) => escape_block_start(render_inlines(inlines).trim()),
+        Block::List {
+            ordered,
+            start,
+            items,
+        } => {
+            // Items with several blocks make the list loose.
+            let loose = items.iter().any(|item| {
+                item.iter()
+                    .filter(|b| !matches!(b, Block::List { .. }))
+                    .count()
+                    > 1
+            });
+            let rendered: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = if *ordered {
+                        format!("{}. ", start + i as u64)
+                    } else {
+                        "- ".to_string()
+                    };
+                    let body = if loose {
+                        render_blocks(item)
+                    } else {
+                        item.iter()
+                            .map(render_block)
+                            .filter(|b| !b.is_empty())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    };
+                    indent_continuation(&marker, &body)
+                })
+                .collect();
+            rendered.join(if loose { "\n\n" } else { "\n" })
+        }
+        Block::Quote(blocks) => render_blocks(blocks)
+            .lines()
+            .map(|l| {
+                if l.is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {}", l)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Code { lang, text } => {
+            let longest = longest_run(text, '`');
+            let fence = "`".repeat(longest.max(2) + 1);
+            format!(
+                "{}{}\n{}\n{}",
+                fence,
+                lang,
+                text.trim_end_matches('\n'),
+                fence
+            )
+        }
+        Block::Table { aligns, rows } => render_table(aligns, rows),
+        Block::Rule => "---".to_string(),
+    }
+}
+
+/// Prefix the first line with `marker` and indent the rest to match.
+fn indent_continuation(marker: &str, body: &str) -> String {
+    let pad = " ".repeat(marker.len());
+    let mut out = String::new();
+    for (i, line) in body.lines().enumerate() {
+        if i == 0 {
+            out.push_str(marker);
+            out.push_str(line);
+        } else {
+            out.push('\n');
+            if !line.is_empty() {
+                out.push_str(&pad);
+                out.push_str(line);
+            }
+        }
+    }
+    if out.is_empty() {
+        out.push_str(marker.trim_end());
+    }
+    out
+}
+
+fn render_table(aligns: &[Alignment], rows: &[Vec<Vec<Inline>>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .enumerate()
+        .map(|(r, row)| {
+            row.iter()
+                .map(|cell| {
+                    // Header cells are bold already; drop explicit bold there.
+                    let cell = if r == 0 {
+                        without_bold(cell)
+                    } else {
+                        cell.clone()
+                    };
+                    render_inlines(&cell)
+                        .trim()
+                        .replace("\\\n", "<br>")
+                        .replace('\n', " ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .collect();
+    let aligns: Vec<Alignment> = (0..columns)
+        .map(|i| aligns.get(i).copied().unwrap_or(Alignment::None))
+        .collect();
+    format::render_table(&cells, &aligns)
+}
+
+fn without_bold(inlines: &[Inline]) -> Vec<Inline> {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text, style) => Inline::Text(
+                text.clone(),
+                Style {
+                    bold: false,
+                    ..*style
+                },
+            ),
+            Inline::Link(url, children) => Inline::Link(url.clone(), without_bold(children)),
+            other => other.clone(),
+        })
+        .collect()
+}
+
+fn render_inlines(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < inlines.len() {
+        match &inlines[i] {
+            Inline::Text(_, style) => {
+                // Merge adjacent runs with the same style so markers are not
+                // repeated (`**a****b**`).
+                let mut text = String::new();
+                while let Some(Inline::Text(t, s)) = inlines.get(i) {
+                    if s != style {
+                        break;
+                    }
+                    text.push_str(t);
+                    i += 1;
+                }
+                out.push_str(&styled_text(&escape_inline(&text), *style));
+                continue;
+            }
+            Inline::Code(code) => {
+                let fence = "`".repeat(longest_run(code, '`') + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                out.push_str(&format!("{fence}{pad}{code}{pad}{fence}"));
+            }
+            Inline::Link(url, children) => {
+                let label = render_inlines(children);
+                if label.trim().is_empty() {
+                    out.push_str(&format!("<{}>", url));
+                } else {
+                    out.push_str(&format!("[{}]({})", label.trim(), link_destination(url)));
+                }
+            }
+            Inline::Image { src, alt } => {
+                out.push_str(&format!(
+                    "![{}]({})",
+                    escape_inline(alt),
+                    link_destination(src)
+                ));
+            }
+            Inline::LineBreak => out.push_str("\\\n"),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Wrap `text` in emphasis markers, keeping surrounding whitespace outside
+/// them so the markers stay valid delimiters.
+fn styled_text(text: &str, style: Style) -> String {
+    if style == Style::default() || text.trim().is_empty() {
+        return text.to_string();
+    }
+    let core = text.trim();
+    let lead = &text[..text.len() - text.trim_start().len()];
+    let trail = &text[text.trim_end().len()..];
+    let mut marker = String::new();
+    if style.strike {
+        marker.push_str("~~");
+    }
+    if style.bold {
+        marker.push_str("**");
+    }
+    if style.italic {
+        marker.push('*');
+    }
+    let closing: String = marker.chars().rev().collect();
+    format!("{}{}{}{}{}", lead, marker, core, closing, trail)
+}
+
+fn link_destination(url: &str) -> String {
+    if url.contains(' ') || url.contains('(') || url.contains(')') {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+fn escape_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape characters that would turn a paragraph into another block type.
+fn escape_block_start(text: &str) -> String {
+    let needs_escape = text.starts_with('#')
+        || text.starts_with('>')
+        || text.starts_with("- ")
+        || text.starts_with("+ ")
+        || text.starts_with("---")
+        || text.starts_with("===");
+    if needs_escape {
+        return format!("\\{}", text);
+    }
+    let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && (text[digits..].starts_with(". ") || text[digits..].starts_with(") ")) {
+        return format!("{}\\{}", &text[..digits], &text[digits..]);
+    }
+    text.to_string()
+}
+
+fn longest_run(text: &str, target: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in text.chars() {
+        if c == target {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
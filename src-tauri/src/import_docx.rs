@@ -0,0 +1,762 @@
+use pulldown_cmark::Alignment;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::error::AppError;
+use crate::import::{has_content, AssetWriter, Block, Inline, Style};
+
+/// Convert a Word document into the import document model.
+pub fn convert(source: &Path, assets: &mut AssetWriter) -> Result<Vec<Block>, AppError> {
+    let file = File::open(source)?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| AppError::Other(format!("Not a valid DOCX file: {}", e)))?;
+
+    let document = read_part(&mut archive, "word/document.xml")?
+        .ok_or_else(|| AppError::Other("DOCX file has no word/document.xml".to_string()))?;
+    let document = parse_xml(&document)?;
+    let relationships = match read_part(&mut archive, "word/_rels/document.xml.rels")? {
+        Some(xml) => parse_relationships(&parse_xml(&xml)?),
+        None => HashMap::new(),
+    };
+    let styles = match read_part(&mut archive, "word/styles.xml")? {
+        Some(xml) => parse_styles(&parse_xml(&xml)?),
+        None => HashMap::new(),
+    };
+    let numbering = match read_part(&mut archive, "word/numbering.xml")? {
+        Some(xml) => parse_numbering(&parse_xml(&xml)?),
+        None => HashMap::new(),
+    };
+
+    let mut converter = DocxConverter {
+        archive: &mut archive,
+        assets,
+        relationships,
+        styles,
+        numbering,
+    };
+    let body = document
+        .child("body")
+        .ok_or_else(|| AppError::Other("DOCX document has no body".to_string()))?;
+    let paragraphs = converter.body_items(body);
+    Ok(group_blocks(paragraphs))
+}
+
+fn read_part(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>, AppError> {
+    let Ok(mut entry) = archive.by_name(name) else {
+        return Ok(None);
+    };
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml)?;
+    Ok(Some(xml))
+}
+
+// ── Minimal XML tree ────────────────────────────────────────────────
+
+struct XmlNode {
+    /// Local element name (`p`, `r`, `t`…).
+    name: String,
+    /// Attributes keyed by their qualified name (`w:val`, `r:id`…).
+    attrs: Vec<(String, String)>,
+    children: Vec<XmlChild>,
+}
+
+enum XmlChild {
+    Element(XmlNode),
+    Text(String),
+}
+
+impl XmlNode {
+    /// Attribute by local name, ignoring the namespace prefix.
+    fn attr(&self, local: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == local || key.rsplit(':').next() == Some(local))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &XmlNode> {
+        self.children.iter().filter_map(|c| match c {
+            XmlChild::Element(e) => Some(e),
+            XmlChild::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    /// First descendant (depth-first) with the given name.
+    fn find(&self, name: &str) -> Option<&XmlNode> {
+        self.elements().find_map(|e| {
+            if e.name == name {
+                Some(e)
+            } else {
+                e.find(name)
+            }
+        })
+    }
+
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .map(|c| match c {
+                XmlChild::Text(t) => t.clone(),
+                XmlChild::Element(e) => e.text(),
+            })
+            .collect()
+    }
+
+    /// Value of a `w:val`-style toggle property (`<w:b/>`, `<w:b w:val="0"/>`).
+    fn toggle(&self, name: &str) -> bool {
+        self.child(name)
+            .map(|e| !matches!(e.attr("val"), Some("0" | "false" | "none")))
+            .unwrap_or(false)
+    }
+}
+
+fn parse_xml(xml: &str) -> Result<XmlNode, AppError> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![XmlNode {
+        name: String::new(),
+        attrs: Vec::new(),
+        children: Vec::new(),
+    }];
+    let xml_error = |e: quick_xml::Error| AppError::Other(format!("Invalid DOCX XML: {}", e));
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(start) => stack.push(element_from(&start)),
+            Event::Empty(start) => {
+                let node = element_from(&start);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlChild::Element(node));
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let node = stack.pop().expect("stack has a parent");
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlChild::Element(node));
+                }
+            }
+            Event::Text(text) => {
+                let text = text.decode().map_err(|e| xml_error(e.into()))?;
+                if let Some(node) = stack.last_mut() {
+                    node.children.push(XmlChild::Text(text.into_owned()));
+                }
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8_lossy(&data).into_owned();
+                if let Some(node) = stack.last_mut() {
+                    node.children.push(XmlChild::Text(text));
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let name = String::from_utf8_lossy(&reference).into_owned();
+                let resolved = quick_xml::escape::unescape(&format!("&{};", name))
+                    .map(|s| s.into_owned())
+                    .unwrap_or_default();
+                if let Some(node) = stack.last_mut() {
+                    node.children.push(XmlChild::Text(resolved));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // Unclosed elements of a truncated part are dropped.
+    stack
+        .swap_remove(0)
+        .children
+        .into_iter()
+        .find_map(|c| match c {
+            XmlChild::Element(e) => Some(e),
+            XmlChild::Text(_) => None,
+        })
+        .ok_or_else(|| AppError::Other("Empty DOCX XML part".to_string()))
+}
+
+fn element_from(start: &BytesStart) -> XmlNode {
+    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+    let attrs = start
+        .attributes()
+        .filter_map(|a| a.ok())
+        .map(|a| {
+            let key = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+            let value = a
+                .unescape_value()
+                .map(|v| v.into_owned())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&a.value).into_owned());
+            (key, value)
+        })
+        .collect();
+    XmlNode {
+        name,
+        attrs,
+        children: Vec::new(),
+    }
+}
+
+// ── Package parts ───────────────────────────────────────────────────
+
+struct Relationship {
+    target: String,
+    external: bool,
+}
+
+fn parse_relationships(root: &XmlNode) -> HashMap<String, Relationship> {
+    root.elements()
+        .filter(|e| e.name == "Relationship")
+        .filter_map(|e| {
+            let id = e.attr("Id")?.to_string();
+            let target = e.attr("Target")?.to_string();
+            let external = e.attr("TargetMode") == Some("External");
+            Some((id, Relationship { target, external }))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StyleKind {
+    Heading(u8),
+    Quote,
+    Code,
+    Bold,
+    Italic,
+}
+
+fn parse_styles(root: &XmlNode) -> HashMap<String, StyleKind> {
+    let mut kinds = HashMap::new();
+    for style in root.elements().filter(|e| e.name == "style") {
+        let Some(id) = style.attr("styleId") else {
+            continue;
+        };
+        let name = style
+            .child("name")
+            .and_then(|n| n.attr("val"))
+            .unwrap_or(id)
+            .to_lowercase();
+        let outline = style
+            .child("pPr")
+            .and_then(|p| p.child("outlineLvl"))
+            .and_then(|o| o.attr("val"))
+            .and_then(|v| v.parse::<u8>().ok());
+
+        let kind = if name == "title" {
+            Some(StyleKind::Heading(1))
+        } else if let Some(level) = name
+            .strip_prefix("heading ")
+            .and_then(|l| l.trim().parse::<u8>().ok())
+        {
+            Some(StyleKind::Heading(level.clamp(1, 6)))
+        } else if let Some(level) = outline.filter(|l| *l < 6) {
+            Some(StyleKind::Heading(level + 1))
+        } else if name.contains("quote") {
+            Some(StyleKind::Quote)
+        } else if ["code", "source", "verbatim", "preformatted"]
+            .iter()
+            .any(|k| name.contains(k))
+        {
+            Some(StyleKind::Code)
+        } else if name == "strong" {
+            Some(StyleKind::Bold)
+        } else if name == "emphasis" {
+            Some(StyleKind::Italic)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            kinds.insert(id.to_string(), kind);
+        }
+    }
+    kinds
+}
+
+/// `(numId, level)` → `(ordered, start)`.
+fn parse_numbering(root: &XmlNode) -> HashMap<(String, u8), (bool, u64)> {
+    let mut abstracts: HashMap<String, HashMap<u8, (bool, u64)>> = HashMap::new();
+    for abs in root.elements().filter(|e| e.name == "abstractNum") {
+        let Some(id) = abs.attr("abstractNumId") else {
+            continue;
+        };
+        let levels = abs
+            .elements()
+            .filter(|e| e.name == "lvl")
+            .filter_map(|lvl| {
+                let level = lvl.attr("ilvl")?.parse().ok()?;
+                let format = lvl
+                    .child("numFmt")
+                    .and_then(|f| f.attr("val"))
+                    .unwrap_or("bullet");
+                let start = lvl
+                    .child("start")
+                    .and_then(|s| s.attr("val"))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                Some((level, (!matches!(format, "bullet" | "none"), start)))
+            })
+            .collect();
+        abstracts.insert(id.to_string(), levels);
+    }
+
+    let mut numbering = HashMap::new();
+    for num in root.elements().filter(|e| e.name == "num") {
+        let (Some(num_id), Some(abstract_id)) = (
+            num.attr("numId"),
+            num.child("abstractNumId").and_then(|a| a.attr("val")),
+        ) else {
+            continue;
+        };
+        let Some(levels) = abstracts.get(abstract_id) else {
+            continue;
+        };
+        for (level, (ordered, mut start)) in levels {
+            let start_override = num
+                .elements()
+                .filter(|o| o.name == "lvlOverride")
+                .find(|o| o.attr("ilvl").and_then(|v| v.parse::<u8>().ok()) == Some(*level))
+                .and_then(|o| o.child("startOverride"))
+                .and_then(|s| s.attr("val"))
+                .and_then(|v| v.parse().ok());
+            if let Some(value) = start_override {
+                start = value;
+            }
+            numbering.insert((num_id.to_string(), *level), (*ordered, start));
+        }
+    }
+    numbering
+}
+
+// ── Body conversion ─────────────────────────────────────────────────
+
+/// A body-level item before consecutive list, quote and code paragraphs are
+/// grouped into blocks.
+enum BodyItem {
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    ListItem {
+        level: u8,
+        ordered: bool,
+        start: u64,
+        inlines: Vec<Inline>,
+    },
+    Quote(Vec<Inline>),
+    CodeLine(String),
+    Table(Block),
+}
+
+struct DocxConverter<'a> {
+    archive: &'a mut ZipArchive<File>,
+    assets: &'a mut AssetWriter,
+    relationships: HashMap<String, Relationship>,
+    styles: HashMap<String, StyleKind>,
+    numbering: HashMap<(String, u8), (bool, u64)>,
+}
+
+impl DocxConverter<'_> {
+    fn body_items(&mut self, container: &XmlNode) -> Vec<BodyItem> {
+        let mut items = Vec::new();
+        for element in container.elements() {
+            match element.name.as_str() {
+                "p" => {
+                    if let Some(item) = self.paragraph(element) {
+                        items.push(item);
+                    }
+                }
+                "tbl" => {
+                    if let Some(table) = self.table(element) {
+                        items.push(BodyItem::Table(table));
+                    }
+                }
+                // Content controls (e.g. a table of contents) wrap regular content.
+                "sdt" => {
+                    if let Some(content) = element.child("sdtContent") {
+                        items.extend(self.body_items(content));
+                    }
+                }
+                _ => {}
+            }
+        }
+        items
+    }
+
+    fn paragraph(&mut self, p: &XmlNode) -> Option<BodyItem> {
+        let ppr = p.child("pPr");
+        let style = ppr
+            .and_then(|ppr| ppr.child("pStyle"))
+            .and_then(|s| s.attr("val"))
+            .and_then(|id| self.styles.get(id).copied());
+
+        if style == Some(StyleKind::Code) {
+            return Some(BodyItem::CodeLine(self.plain_text(p)));
+        }
+
+        let mut inlines = Vec::new();
+        self.inlines(p, Style::default(), &mut inlines);
+        if !has_content(&inlines) {
+            return None;
+        }
+
+        let outline_level = ppr
+            .and_then(|ppr| ppr.child("outlineLvl"))
+            .and_then(|o| o.attr("val"))
+            .and_then(|v| v.parse::<u8>().ok())
+            .filter(|l| *l < 6);
+        if let Some(StyleKind::Heading(level)) = style {
+            return Some(BodyItem::Heading(level, inlines));
+        }
+        if let Some(level) = outline_level {
+            return Some(BodyItem::Heading(level + 1, inlines));
+        }
+
+        let num_pr = ppr.and_then(|ppr| ppr.child("numPr"));
+        if let Some(num_pr) = num_pr {
+            let num_id = num_pr.child("numId").and_then(|n| n.attr("val"));
+            let level = num_pr
+                .child("ilvl")
+                .and_then(|l| l.attr("val"))
+                .and_then(|v| v.parse::<u8>().ok())
+                .unwrap_or(0);
+            // numId 0 explicitly removes numbering.
+            if let Some(num_id) = num_id.filter(|id| *id != "0") {
+                let (ordered, start) = self
+                    .numbering
+                    .get(&(num_id.to_string(), level))
+                    .copied()
+                    .unwrap_or((false, 1));
+                return Some(BodyItem::ListItem {
+                    level,
+                    ordered,
+                    start,
+                    inlines,
+                });
+            }
+        }
+
+        if style == Some(StyleKind::Quote) {
+            return Some(BodyItem::Quote(inlines));
+        }
+        Some(BodyItem::Paragraph(inlines))
+    }
+
+    fn plain_text(&self, p: &XmlNode) -> String {
+        let mut text = String::new();
+        collect_run_text(p, &mut text);
+        text
+    }
+
+    /// Collect inline content of a paragraph (or hyperlink, field, insertion…).
+    fn inlines(&mut self, container: &XmlNode, style: Style, out: &mut Vec<Inline>) {
+        for element in container.elements() {
+            match element.name.as_str() {
+                "r" => self.run(element, style, out),
+                "hyperlink" => {
+                    let mut children = Vec::new();
+                    self.inlines(element, style, &mut children);
+                    let target = element
+                        .attr("id")
+                        .and_then(|id| self.relationships.get(id))
+                        .filter(|rel| rel.external)
+                        .map(|rel| rel.target.clone());
+                    match target {
+                        Some(url) => out.push(Inline::Link(url, children)),
+                        // Internal bookmarks do not survive the conversion.
+                        None => out.extend(children),
+                    }
+                }
+                "ins" | "smartTag" | "fldSimple" | "customXml" => self.inlines(element, style, out),
+                "sdt" => {
+                    if let Some(content) = element.child("sdtContent") {
+                        self.inlines(content, style, out);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn run(&mut self, run: &XmlNode, base: Style, out: &mut Vec<Inline>) {
+        let rpr = run.child("rPr");
+        let char_style = rpr
+            .and_then(|rpr| rpr.child("rStyle"))
+            .and_then(|s| s.attr("val"))
+            .and_then(|id| self.styles.get(id).copied());
+        let monospace = rpr
+            .and_then(|rpr| rpr.child("rFonts"))
+            .and_then(|f| f.attr("ascii"))
+            .map(|font| {
+                let font = font.to_lowercase();
+                ["courier", "consolas", "mono", "menlo"]
+                    .iter()
+                    .any(|m| font.contains(m))
+            })
+            .unwrap_or(false);
+        let is_code = monospace || char_style == Some(StyleKind::Code);
+        let style = Style {
+            bold: base.bold
+                || char_style == Some(StyleKind::Bold)
+                || rpr.map(|r| r.toggle("b")).unwrap_or(false),
+            italic: base.italic
+                || char_style == Some(StyleKind::Italic)
+                || rpr.map(|r| r.toggle("i")).unwrap_or(false),
+            strike: base.strike
+                || rpr
+                    .map(|r| r.toggle("strike") || r.toggle("dstrike"))
+                    .unwrap_or(false),
+        };
+
+        let mut text = String::new();
+        let flush = |text: &mut String, out: &mut Vec<Inline>| {
+            if text.is_empty() {
+                return;
+            }
+            let content = std::mem::take(text);
+            if is_code {
+                match out.last_mut() {
+                    Some(Inline::Code(previous)) => previous.push_str(&content),
+                    _ => out.push(Inline::Code(content)),
+                }
+            } else {
+                out.push(Inline::Text(content, style));
+            }
+        };
+
+        for element in run.elements() {
+            match element.name.as_str() {
+                "t" => text.push_str(&element.text()),
+                "tab" | "ptab" => text.push(' '),
+                "noBreakHyphen" | "softHyphen" => text.push('-'),
+                // Page and column breaks have no markdown equivalent.
+                "br" | "cr" if element.attr("type").is_none_or(|t| t == "textWrapping") => {
+                    flush(&mut text, out);
+                    out.push(Inline::LineBreak);
+                }
+                "drawing" | "pict" | "object" => {
+                    flush(&mut text, out);
+                    if let Some(image) = self.image(element) {
+                        out.push(image);
+                    }
+                }
+                _ => {}
+            }
+        }
+        flush(&mut text, out);
+    }
+
+    /// Extract an embedded picture (DrawingML or legacy VML) into the assets folder.
+    fn image(&mut self, drawing: &XmlNode) -> Option<Inline> {
+        let rel_id = drawing
+            .find("blip")
+            .and_then(|b| b.attr("embed").or_else(|| b.attr("link")))
+            .or_else(|| drawing.find("imagedata").and_then(|i| i.attr("id")))?;
+        let alt = drawing
+            .find("docPr")
+            .and_then(|d| d.attr("descr").or_else(|| d.attr("title")))
+            .unwrap_or("")
+            .to_string();
+        let rel = self.relationships.get(rel_id)?;
+        if rel.external {
+            return Some(Inline::Image {
+                src: rel.target.clone(),
+                alt,
+            });
+        }
+
+        let part = match rel.target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("word/{}", rel.target),
+        };
+        let extension = Path::new(&part)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "png".to_string());
+        let mut data = Vec::new();
+        self.archive
+            .by_name(&part)
+            .ok()?
+            .read_to_end(&mut data)
+            .ok()?;
+        let src = self.assets.save(&extension, data).ok()?;
+        Some(Inline::Image { src, alt })
+    }
+
+    fn table(&mut self, tbl: &XmlNode) -> Option<Block> {
+        let mut aligns = Vec::new();
+        let mut rows = Vec::new();
+        for (i, tr) in tbl.elements().filter(|e| e.name == "tr").enumerate() {
+            let mut cells = Vec::new();
+            for tc in tr.elements().filter(|e| e.name == "tc") {
+                let mut inlines = Vec::new();
+                for p in tc.elements().filter(|e| e.name == "p" || e.name == "tbl") {
+                    let mut paragraph = Vec::new();
+                    if p.name == "p" {
+                        self.inlines(p, Style::default(), &mut paragraph);
+                    } else {
+                        // Nested tables are flattened into text.
+                        let mut text = String::new();
+                        collect_run_text(p, &mut text);
+                        paragraph.push(Inline::Text(text, Style::default()));
+                    }
+                    if has_content(&paragraph) {
+                        if has_content(&inlines) {
+                            inlines.push(Inline::LineBreak);
+                        }
+                        inlines.extend(paragraph);
+                    }
+                }
+                if i == 0 {
+                    let jc = tc
+                        .child("p")
+                        .and_then(|p| p.child("pPr"))
+                        .and_then(|ppr| ppr.child("jc"))
+                        .and_then(|jc| jc.attr("val"));
+                    aligns.push(match jc {
+                        Some("center") => Alignment::Center,
+                        Some("right" | "end") => Alignment::Right,
+                        _ => Alignment::None,
+                    });
+                }
+                cells.push(inlines);
+            }
+            if !cells.is_empty() {
+                rows.push(cells);
+            }
+        }
+        if rows.is_empty() {
+            None
+        } else {
+            Some(Block::Table { aligns, rows })
+        }
+    }
+}
+
+fn collect_run_text(node: &XmlNode, out: &mut String) {
+    for element in node.elements() {
+        match element.name.as_str() {
+            "t" => out.push_str(&element.text()),
+            "tab" => out.push('\t'),
+            "br" | "cr" => out.push('\n'),
+            "p" if !out.is_empty() => {
+                out.push(' ');
+                collect_run_text(element, out);
+            }
+            "del" | "instrText" => {}
+            _ => collect_run_text(element, out),
+        }
+    }
+}
+
+/// Group consecutive list, quote and code paragraphs into blocks.
+fn group_blocks(items: Vec<BodyItem>) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut items = items.into_iter().peekable();
+
+    while let Some(item) = items.next() {
+        match item {
+            BodyItem::Heading(level, inlines) => blocks.push(Block::Heading(level, inlines)),
+            BodyItem::Paragraph(inlines) => blocks.push(Block::Paragraph(inlines)),
+            BodyItem::Table(table) => blocks.push(table),
+            BodyItem::CodeLine(line) => {
+                let mut lines = vec![line];
+                while let Some(BodyItem::CodeLine(_)) = items.peek() {
+                    if let Some(BodyItem::CodeLine(next)) = items.next() {
+                        lines.push(next);
+                    }
+                }
+                blocks.push(Block::Code {
+                    lang: String::new(),
+                    text: lines.join("\n"),
+                });
+            }
+            BodyItem::Quote(inlines) => {
+                let mut quoted = vec![Block::Paragraph(inlines)];
+                while let Some(BodyItem::Quote(_)) = items.peek() {
+                    if let Some(BodyItem::Quote(next)) = items.next() {
+                        quoted.push(Block::Paragraph(next));
+                    }
+                }
+                blocks.push(Block::Quote(quoted));
+            }
+            BodyItem::ListItem {
+                level,
+                ordered,
+                start,
+                inlines,
+            } => {
+                let mut entries = vec![ListEntry {
+                    level,
+                    ordered,
+                    start,
+                    inlines,
+                }];
+                while let Some(BodyItem::ListItem { .. }) = items.peek() {
+                    if let Some(BodyItem::ListItem {
+                        level,
+                        ordered,
+                        start,
+                        inlines,
+                    }) = items.next()
+                    {
+                        entries.push(ListEntry {
+                            level,
+                            ordered,
+                            start,
+                            inlines,
+                        });
+                    }
+                }
+                let mut index = 0;
+                let base = entries.iter().map(|e| e.level).min().unwrap_or(0);
+                blocks.extend(build_lists(&entries, &mut index, base));
+            }
+        }
+    }
+    blocks
+}
+
+struct ListEntry {
+    level: u8,
+    ordered: bool,
+    start: u64,
+    inlines: Vec<Inline>,
+}
+
+/// Build nested lists from flat, level-tagged list paragraphs.
+fn build_lists(entries: &[ListEntry], index: &mut usize, level: u8) -> Vec<Block> {
+    let mut lists: Vec<Block> = Vec::new();
+    while let Some(entry) = entries.get(*index) {
+        if entry.level < level {
+            break;
+        }
+        if entry.level > level {
+            let nested = build_lists(entries, index, entry.level);
+            match lists.last_mut() {
+                Some(Block::List { items, .. }) if !items.is_empty() => {
+                    items.last_mut().expect("checked non-empty").extend(nested)
+                }
+                _ => lists.extend(nested),
+            }
+            continue;
+        }
+
+        let continues =
+            matches!(lists.last(), Some(Block::List { ordered, .. }) if *ordered == entry.ordered);
+        if !continues {
+            lists.push(Block::List {
+                ordered: entry.ordered,
+                start: entry.start,
+                items: Vec::new(),
+            });
+        }
+        if let Some(Block::List { items, .. }) = lists.last_mut() {
+            items.push(vec![Block::Paragraph(entry.inlines.clone())]);
+        }
+        *index += 1;
+    }
+    lists
+}
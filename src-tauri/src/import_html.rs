@@ -0,0 +1,453 @@
+use base64::Engine;
+use pulldown_cmark::Alignment;
+use scraper::{ElementRef, Html};
+use std::fs;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::import::{has_content, AssetWriter, Block, Inline, Style};
+use crate::markdown::{is_external_url, percent_decode};
+
+/// Elements whose content is never imported.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "iframe", "object", "svg", "canvas",
+    "button", "select", "textarea", "input",
+];
+
+/// Elements that only group other blocks.
+const CONTAINERS: &[&str] = &[
+    "html", "body", "div", "section", "article", "main", "header", "footer", "aside", "nav",
+    "figure", "center", "details", "summary", "fieldset", "address", "dl", "form",
+];
+
+const BLOCKS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "blockquote",
+    "pre",
+    "table",
+    "hr",
+    "dt",
+    "dd",
+    "figcaption",
+];
+
+/// Convert an HTML file into the import document model.
+pub fn convert(source: &Path, assets: &mut AssetWriter) -> Result<Vec<Block>, AppError> {
+    let html = fs::read_to_string(source)?;
+    let document = Html::parse_document(&html);
+    let base_dir = source.parent().unwrap_or(Path::new("."));
+
+    let mut converter = HtmlConverter { base_dir, assets };
+    let mut blocks = converter.blocks(document.root_element());
+
+    // Pages without a heading get their <title> as one.
+    let has_heading = blocks.iter().any(|b| matches!(b, Block::Heading(..)));
+    if !has_heading {
+        let title = document
+            .root_element()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .find(|e| e.value().name() == "title")
+            .map(|e| collapse_whitespace(&e.text().collect::<String>()));
+        if let Some(title) = title.filter(|t| !t.trim().is_empty()) {
+            blocks.insert(
+                0,
+                Block::Heading(
+                    1,
+                    vec![Inline::Text(title.trim().to_string(), Style::default())],
+                ),
+            );
+        }
+    }
+    Ok(blocks)
+}
+
+struct HtmlConverter<'a> {
+    base_dir: &'a Path,
+    assets: &'a mut AssetWriter,
+}
+
+impl HtmlConverter<'_> {
+    /// Convert the children of a block container. Loose inline content between
+    /// blocks becomes paragraphs.
+    fn blocks(&mut self, element: ElementRef) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut pending: Vec<Inline> = Vec::new();
+
+        for node in element.children() {
+            let Some(child) = ElementRef::wrap(node) else {
+                if let Some(text) = node.value().as_text() {
+                    pending.push(Inline::Text(collapse_whitespace(text), Style::default()));
+                }
+                continue;
+            };
+            let name = child.value().name();
+            if SKIPPED.contains(&name) {
+                continue;
+            }
+            let is_block = BLOCKS.contains(&name)
+                || CONTAINERS.contains(&name)
+                || (!is_inline_tag(name) && has_block_descendant(child));
+            if !is_block {
+                self.inline(child, Style::default(), &mut pending);
+                continue;
+            }
+
+            flush_paragraph(&mut pending, &mut blocks);
+            match name {
+                "p" | "dd" | "figcaption" => {
+                    let mut inlines = Vec::new();
+                    self.inline_children(child, Style::default(), &mut inlines);
+                    flush_paragraph(&mut inlines, &mut blocks);
+                }
+                "dt" => {
+                    let mut inlines = Vec::new();
+                    let bold = Style {
+                        bold: true,
+                        ..Style::default()
+                    };
+                    self.inline_children(child, bold, &mut inlines);
+                    flush_paragraph(&mut inlines, &mut blocks);
+                }
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    let level = name[1..].parse().unwrap_or(1);
+                    let mut inlines = Vec::new();
+                    self.inline_children(child, Style::default(), &mut inlines);
+                    if has_content(&inlines) {
+                        blocks.push(Block::Heading(level, inlines));
+                    }
+                }
+                "ul" | "ol" => blocks.push(self.list(child, name == "ol")),
+                "li" => blocks.extend(self.blocks(child)),
+                "blockquote" => {
+                    let inner = self.blocks(child);
+                    if !inner.is_empty() {
+                        blocks.push(Block::Quote(inner));
+                    }
+                }
+                "pre" => blocks.push(code_block(child)),
+                "table" => {
+                    if let Some(table) = self.table(child) {
+                        blocks.push(table);
+                    }
+                }
+                "hr" => blocks.push(Block::Rule),
+                _ => blocks.extend(self.blocks(child)),
+            }
+        }
+
+        flush_paragraph(&mut pending, &mut blocks);
+        blocks
+    }
+
+    fn list(&mut self, element: ElementRef, ordered: bool) -> Block {
+        let start = element
+            .value()
+            .attr("start")
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(1);
+        let items = element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "li")
+            .map(|li| self.blocks(li))
+            .collect();
+        Block::List {
+            ordered,
+            start,
+            items,
+        }
+    }
+
+    fn table(&mut self, element: ElementRef) -> Option<Block> {
+        let rows: Vec<ElementRef> = element
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "tr")
+            // Skip rows of nested tables.
+            .filter(|tr| {
+                tr.ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|a| a.value().name() == "table")
+                    .map(|t| t.id() == element.id())
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        let mut aligns = Vec::new();
+        let mut out_rows = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let mut cells = Vec::new();
+            for cell in row
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|c| matches!(c.value().name(), "td" | "th"))
+            {
+                if i == 0 {
+                    aligns.push(cell_alignment(cell));
+                }
+                let mut inlines = Vec::new();
+                self.cell_inlines(cell, &mut inlines);
+                cells.push(inlines);
+            }
+            if !cells.is_empty() {
+                out_rows.push(cells);
+            }
+        }
+
+        if out_rows.is_empty() {
+            None
+        } else {
+            Some(Block::Table {
+                aligns,
+                rows: out_rows,
+            })
+        }
+    }
+
+    /// Flatten cell content into one line; block boundaries become `<br>`.
+    fn cell_inlines(&mut self, element: ElementRef, out: &mut Vec<Inline>) {
+        for node in element.children() {
+            let Some(child) = ElementRef::wrap(node) else {
+                if let Some(text) = node.value().as_text() {
+                    out.push(Inline::Text(collapse_whitespace(text), Style::default()));
+                }
+                continue;
+            };
+            let name = child.value().name();
+            if SKIPPED.contains(&name) {
+                continue;
+            }
+            if BLOCKS.contains(&name) || CONTAINERS.contains(&name) {
+                if has_content(out) {
+                    out.push(Inline::LineBreak);
+                }
+                self.cell_inlines(child, out);
+            } else {
+                self.inline(child, Style::default(), out);
+            }
+        }
+    }
+
+    fn inline_children(&mut self, element: ElementRef, style: Style, out: &mut Vec<Inline>) {
+        for node in element.children() {
+            if let Some(child) = ElementRef::wrap(node) {
+                self.inline(child, style, out);
+            } else if let Some(text) = node.value().as_text() {
+                out.push(Inline::Text(collapse_whitespace(text), style));
+            }
+        }
+    }
+
+    fn inline(&mut self, element: ElementRef, style: Style, out: &mut Vec<Inline>) {
+        let name = element.value().name();
+        match name {
+            _ if SKIPPED.contains(&name) => {}
+            "br" => out.push(Inline::LineBreak),
+            "strong" | "b" => self.inline_children(
+                element,
+                Style {
+                    bold: true,
+                    ..style
+                },
+                out,
+            ),
+            "em" | "i" | "cite" | "var" => self.inline_children(
+                element,
+                Style {
+                    italic: true,
+                    ..style
+                },
+                out,
+            ),
+            "del" | "s" | "strike" => self.inline_children(
+                element,
+                Style {
+                    strike: true,
+                    ..style
+                },
+                out,
+            ),
+            "code" | "kbd" | "samp" | "tt" => {
+                let text = collapse_whitespace(&element.text().collect::<String>());
+                if !text.is_empty() {
+                    out.push(Inline::Code(text));
+                }
+            }
+            "img" => {
+                if let Some(src) = element.value().attr("src") {
+                    let alt = element.value().attr("alt").unwrap_or("").to_string();
+                    let src = self.import_image(src);
+                    out.push(Inline::Image { src, alt });
+                }
+            }
+            "a" => {
+                let href = element.value().attr("href").unwrap_or("").trim();
+                let mut children = Vec::new();
+                self.inline_children(element, style, &mut children);
+                if href.is_empty() || href.starts_with("javascript:") {
+                    out.extend(children);
+                } else {
+                    out.push(Inline::Link(href.to_string(), children));
+                }
+            }
+            _ => self.inline_children(element, style, out),
+        }
+    }
+
+    /// Copy local and `data:` images into the assets folder. Remote images
+    /// keep their URL; images that cannot be read keep their original source.
+    fn import_image(&mut self, src: &str) -> String {
+        if let Some(data_uri) = src.strip_prefix("data:") {
+            let Some((meta, payload)) = data_uri.split_once(',') else {
+                return src.to_string();
+            };
+            if !meta.ends_with(";base64") {
+                return src.to_string();
+            }
+            let mime = meta.trim_end_matches(";base64");
+            let extension = match mime {
+                "image/jpeg" => "jpg",
+                "image/gif" => "gif",
+                "image/svg+xml" => "svg",
+                "image/webp" => "webp",
+                _ => "png",
+            };
+            return base64::engine::general_purpose::STANDARD
+                .decode(payload.trim())
+                .ok()
+                .and_then(|data| self.assets.save(extension, data).ok())
+                .unwrap_or_else(|| src.to_string());
+        }
+        if is_external_url(src) || src.starts_with("//") {
+            return src.to_string();
+        }
+
+        let relative = percent_decode(src.split(['?', '#']).next().unwrap_or(src));
+        let path = self.base_dir.join(relative);
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "png".to_string());
+        fs::read(&path)
+            .ok()
+            .and_then(|data| self.assets.save(&extension, data).ok())
+            .unwrap_or_else(|| src.to_string())
+    }
+}
+
+fn flush_paragraph(pending: &mut Vec<Inline>, blocks: &mut Vec<Block>) {
+    if has_content(pending) {
+        blocks.push(Block::Paragraph(std::mem::take(pending)));
+    } else {
+        pending.clear();
+    }
+}
+
+fn code_block(element: ElementRef) -> Block {
+    let language_of = |e: ElementRef| {
+        e.value().attr("class").and_then(|class| {
+            class.split_whitespace().find_map(|c| {
+                c.strip_prefix("language-")
+                    .or_else(|| c.strip_prefix("lang-"))
+                    .map(str::to_string)
+            })
+        })
+    };
+    let lang = language_of(element)
+        .or_else(|| {
+            element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .find(|c| c.value().name() == "code")
+                .and_then(language_of)
+        })
+        .unwrap_or_default();
+    Block::Code {
+        lang,
+        text: element.text().collect(),
+    }
+}
+
+fn cell_alignment(cell: ElementRef) -> Alignment {
+    let value = cell.value();
+    let from_style = value.attr("style").and_then(|style| {
+        style.split(';').find_map(|decl| {
+            let (prop, val) = decl.split_once(':')?;
+            (prop.trim().eq_ignore_ascii_case("text-align")).then(|| val.trim().to_lowercase())
+        })
+    });
+    match from_style
+        .or_else(|| value.attr("align").map(str::to_lowercase))
+        .as_deref()
+    {
+        Some("left") => Alignment::Left,
+        Some("center") => Alignment::Center,
+        Some("right") => Alignment::Right,
+        _ => Alignment::None,
+    }
+}
+
+fn is_inline_tag(name: &str) -> bool {
+    matches!(
+        name,
+        "a" | "abbr"
+            | "b"
+            | "br"
+            | "cite"
+            | "code"
+            | "del"
+            | "em"
+            | "font"
+            | "i"
+            | "img"
+            | "kbd"
+            | "label"
+            | "mark"
+            | "s"
+            | "samp"
+            | "small"
+            | "span"
+            | "strike"
+            | "strong"
+            | "sub"
+            | "sup"
+            | "time"
+            | "tt"
+            | "u"
+            | "var"
+    )
+}
+
+fn has_block_descendant(element: ElementRef) -> bool {
+    element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .any(|e| BLOCKS.contains(&e.value().name()) || CONTAINERS.contains(&e.value().name()))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out
+}
@@ -1,13 +1,43 @@
 mod ai;
+mod blocks;
+mod citations;
+mod cli;
 mod commands;
+mod csv_table;
+mod docx;
+mod epub;
 mod error;
+mod format;
+mod frontmatter;
 mod git;
+mod highlight;
 mod images;
+mod import;
+mod import_docx;
+mod import_html;
+mod links;
+mod lint;
+mod markdown;
+mod pdf;
+mod pdf_layout;
+mod prose;
+mod quick_open;
+mod replace;
+mod saved_search;
 mod search;
+mod search_index;
+mod search_match;
+mod search_query;
+mod semantic;
+mod slides;
+mod spell;
+mod transclude;
 mod utils;
 mod watcher;
 mod wiki;
 
+pub use cli::run_cli;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -23,9 +53,32 @@ pub fn run() {
             commands::save_image,
             commands::write_binary_file,
             search::search_files,
+            search::start_search,
+            search::cancel_search,
+            replace::preview_replace,
+            replace::apply_replace,
+            replace::undo_replace,
+            replace::list_replace_journal,
+            quick_open::quick_open,
+            semantic::semantic_search,
+            semantic::update_semantic_index,
+            saved_search::list_saved_searches,
+            saved_search::save_search,
+            saved_search::delete_saved_search,
+            search_index::rebuild_search_index,
             git::git_status,
             git::git_diff,
             git::git_commit,
+            git::git_diff_staged,
+            git::git_stage,
+            git::git_unstage,
+            git::git_stage_hunk,
+            git::git_unstage_hunk,
+            git::git_discard,
+            git::git_file_log,
+            git::git_file_at,
+            git::git_diff_revisions,
+            git::git_restore_revision,
             watcher::start_watching,
             watcher::stop_watching,
             ai::ai_request,
@@ -33,6 +86,30 @@ pub fn run() {
             images::get_image_base64,
             images::delete_image,
             wiki::scan_wiki_links,
+            epub::export_epub,
+            pdf::export_pdf,
+            slides::export_slides,
+            docx::export_docx,
+            import::import_document,
+            lint::lint_document,
+            lint::fix_lint_issues,
+            lint::lint_folder,
+            format::format_document,
+            format::format_folder,
+            csv_table::csv_to_table,
+            csv_table::export_table_csv,
+            links::check_links,
+            transclude::resolve_embeds,
+            blocks::list_blocks,
+            blocks::create_block_id,
+            blocks::resolve_wiki_link,
+            spell::spell_check,
+            spell::spell_suggest,
+            spell::add_to_dictionary,
+            spell::list_dictionaries,
+            prose::analyze_prose,
+            citations::render_citations,
+            citations::list_citation_keys,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
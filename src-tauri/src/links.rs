@@ -0,0 +1,471 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::blocks::block_markers;
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{
+    has_url_scheme, is_external_url, parser_options, percent_decode, slugify, split_fragment,
+    LineIndex,
+};
+use crate::utils::{collect_md_files, validate_directory};
+use crate::wiki::{split_wiki_target, NoteIndex};
+
+static WIKI_LINK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(!?)\[\[([^\]\n]+)\]\]").expect("invalid wiki link regex"));
+
+#[derive(Deserialize)]
+pub struct LinkCheckOptions {
+    pub folder: String,
+    /// Also request `http(s)` URLs.
+    #[serde(default)]
+    pub check_external: bool,
+    /// Per-request timeout for external URLs, in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    /// Maximum number of external requests in flight.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Link,
+    Image,
+    Wiki,
+    External,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BrokenLink {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LinkKind,
+    /// Destination as written in the note.
+    pub target: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileLinkReport {
+    pub file_path: String,
+    pub file_name: String,
+    pub broken: Vec<BrokenLink>,
+}
+
+#[derive(Serialize)]
+pub struct LinkCheckReport {
+    /// Files with at least one broken link, sorted by path.
+    pub files: Vec<FileLinkReport>,
+    /// Number of links checked.
+    pub checked: usize,
+    pub broken: usize,
+}
+
+/// An external URL found while scanning, checked after the local pass.
+struct PendingUrl {
+    file: usize,
+    line: usize,
+    column: usize,
+    url: String,
+}
+
+/// Check relative links, heading anchors, images and wiki links in every
+/// note under `folder`, and optionally external URLs.
+///
+/// Anchors are matched against heading slugs generated the same way as in
+/// the preview. Wiki links resolve by file name anywhere in the workspace,
+/// like clicking them in the preview does.
+#[tauri::command]
+pub async fn check_links(options: LinkCheckOptions) -> Result<LinkCheckReport, AppError> {
+    let root = validate_directory(&options.folder)?;
+    let (mut reports, pending, mut checked) =
+        tokio::task::spawn_blocking(move || scan_workspace(&root))
+            .await
+            .map_err(|e| AppError::Other(format!("Link check failed: {}", e)))?;
+
+    if options.check_external && !pending.is_empty() {
+        let results = check_urls(&pending, &options).await?;
+        checked += pending.len();
+        for (item, reason) in pending.iter().zip(results) {
+            if let Some(reason) = reason {
+                reports[item.file].broken.push(BrokenLink {
+                    line: item.line,
+                    column: item.column,
+                    kind: LinkKind::External,
+                    target: item.url.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    let mut files: Vec<FileLinkReport> = reports
+        .into_iter()
+        .filter(|r| !r.broken.is_empty())
+        .collect();
+    for file in &mut files {
+        file.broken.sort_by_key(|b| (b.line, b.column));
+    }
+    let broken = files.iter().map(|f| f.broken.len()).sum();
+    Ok(LinkCheckReport {
+        files,
+        checked,
+        broken,
+    })
+}
+
+/// Heading slugs and block IDs per note, parsed on first use.
+struct AnchorCache {
+    notes: HashMap<PathBuf, Option<NoteAnchors>>,
+}
+
+struct NoteAnchors {
+    headings: HashSet<String>,
+    blocks: HashSet<String>,
+}
+
+impl AnchorCache {
+    fn get(&mut self, file: &Path) -> Option<&NoteAnchors> {
+        self.notes
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                let content = fs::read_to_string(file).ok()?;
+                let (_, body) = split_frontmatter(&content);
+                Some(NoteAnchors {
+                    headings: heading_slugs(body),
+                    blocks: block_markers(body).into_iter().map(|m| m.id).collect(),
+                })
+            })
+            .as_ref()
+    }
+
+    fn has_heading(&mut self, file: &Path, slug: &str) -> bool {
+        self.get(file).is_some_and(|a| a.headings.contains(slug))
+    }
+
+    fn has_block(&mut self, file: &Path, id: &str) -> bool {
+        self.get(file).is_some_and(|a| a.blocks.contains(id))
+    }
+}
+
+fn heading_slugs(body: &str) -> HashSet<String> {
+    let mut slugs = HashSet::new();
+    let mut heading: Option<String> = None;
+    for event in Parser::new_ext(body, parser_options()) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(text) = heading.take() {
+                    slugs.insert(slugify(&text));
+                }
+            }
+            Event::Text(t) | Event::Code(t) => {
+                if let Some(text) = heading.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            _ => {}
+        }
+    }
+    slugs
+}
+
+fn scan_workspace(root: &Path) -> (Vec<FileLinkReport>, Vec<PendingUrl>, usize) {
+    let mut files = collect_md_files(root);
+    files.sort();
+    let index = NoteIndex::build(root);
+    let mut anchors = AnchorCache {
+        notes: HashMap::new(),
+    };
+
+    let mut reports = Vec::with_capacity(files.len());
+    let mut pending = Vec::new();
+    let mut checked = 0;
+
+    for (file_index, path) in files.iter().enumerate() {
+        let mut report = FileLinkReport {
+            file_path: path.to_string_lossy().into_owned(),
+            file_name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            broken: Vec::new(),
+        };
+        if let Ok(content) = fs::read_to_string(path) {
+            let mut checker = FileChecker {
+                root,
+                path,
+                content: &content,
+                lines: LineIndex::new(&content),
+                index: &index,
+                anchors: &mut anchors,
+                broken: &mut report.broken,
+                checked: 0,
+            };
+            for (line, column, url) in checker.run() {
+                pending.push(PendingUrl {
+                    file: file_index,
+                    line,
+                    column,
+                    url,
+                });
+            }
+            checked += checker.checked;
+        }
+        reports.push(report);
+    }
+
+    (reports, pending, checked)
+}
+
+struct FileChecker<'a> {
+    root: &'a Path,
+    path: &'a Path,
+    content: &'a str,
+    lines: LineIndex<'a>,
+    index: &'a NoteIndex,
+    anchors: &'a mut AnchorCache,
+    broken: &'a mut Vec<BrokenLink>,
+    checked: usize,
+}
+
+impl FileChecker<'_> {
+    /// Check local links and return external URLs as `(line, column, url)`.
+    fn run(&mut self) -> Vec<(usize, usize, String)> {
+        let (_, body) = split_frontmatter(self.content);
+        let offset = self.content.len() - body.len();
+        let mut external = Vec::new();
+        let mut code: Vec<Range<usize>> = Vec::new();
+
+        for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+            let at = range.start + offset;
+            match event {
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    ..
+                }) => {
+                    if link_type == LinkType::Email {
+                        continue;
+                    }
+                    if let Some(url) = self.check_destination(LinkKind::Link, &dest_url, at) {
+                        external.push(url);
+                    }
+                }
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    if let Some(url) = self.check_destination(LinkKind::Image, &dest_url, at) {
+                        external.push(url);
+                    }
+                }
+                Event::Start(Tag::CodeBlock(_))
+                | Event::Code(_)
+                | Event::Html(_)
+                | Event::InlineHtml(_) => code.push(range),
+                _ => {}
+            }
+        }
+
+        for cap in WIKI_LINK_RE.captures_iter(body) {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            if code.iter().any(|r| r.contains(&whole.start())) {
+                continue;
+            }
+            let inner = &cap[2];
+            let target = inner.split('|').next().unwrap_or(inner).trim();
+            self.check_wiki(target, whole.start() + offset);
+        }
+
+        external
+            .into_iter()
+            .map(|(at, url)| {
+                let (line, column) = self.lines.position(at);
+                (line, column, url)
+            })
+            .collect()
+    }
+
+    fn report(&mut self, kind: LinkKind, target: &str, at: usize, reason: String) {
+        let (line, column) = self.lines.position(at);
+        self.broken.push(BrokenLink {
+            line,
+            column,
+            kind,
+            target: target.to_string(),
+            reason,
+        });
+    }
+
+    /// Check a markdown link or image destination. External `http(s)` URLs
+    /// are returned for the network pass.
+    fn check_destination(
+        &mut self,
+        kind: LinkKind,
+        dest: &str,
+        at: usize,
+    ) -> Option<(usize, String)> {
+        let dest = dest.trim();
+        if dest.is_empty() {
+            return None;
+        }
+        if is_external_url(dest) {
+            let lower = dest.to_ascii_lowercase();
+            if lower.starts_with("http://") || lower.starts_with("https://") {
+                return Some((at, dest.to_string()));
+            }
+            return None;
+        }
+        if has_url_scheme(dest) {
+            // Other URL schemes (tel:, obsidian:, ...) are not checked.
+            return None;
+        }
+
+        self.checked += 1;
+        let (path_part, fragment) = split_fragment(dest);
+        let target = if path_part.is_empty() {
+            self.path.to_path_buf()
+        } else {
+            let decoded = percent_decode(path_part);
+            match decoded.strip_prefix('/') {
+                Some(absolute) => self.root.join(absolute),
+                None => self.path.parent().unwrap_or(self.root).join(decoded),
+            }
+        };
+
+        if !target.exists() {
+            let reason = if kind == LinkKind::Image {
+                "Image not found".to_string()
+            } else {
+                "File not found".to_string()
+            };
+            self.report(kind, dest, at, reason);
+            return None;
+        }
+        if let Some(fragment) = fragment.filter(|f| !f.is_empty()) {
+            let is_note = target
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+            if is_note && !self.anchors.has_heading(&target, &percent_decode(fragment)) {
+                self.report(kind, dest, at, format!("No heading for #{}", fragment));
+            }
+        }
+        None
+    }
+
+    fn check_wiki(&mut self, target: &str, at: usize) {
+        self.checked += 1;
+        let (name, fragment) = split_wiki_target(target);
+        let file = if name.is_empty() {
+            Some(self.path.to_path_buf())
+        } else {
+            self.index.resolve(name).cloned()
+        };
+        let Some(file) = file else {
+            self.report(
+                LinkKind::Wiki,
+                target,
+                at,
+                "No note with this name".to_string(),
+            );
+            return;
+        };
+        let Some(fragment) = fragment.filter(|f| !f.is_empty()) else {
+            return;
+        };
+        match fragment.strip_prefix('^') {
+            Some(id) if !self.anchors.has_block(&file, id) => {
+                self.report(LinkKind::Wiki, target, at, format!("No block ^{}", id));
+            }
+            None if !self.anchors.has_heading(&file, &slugify(fragment)) => {
+                self.report(
+                    LinkKind::Wiki,
+                    target,
+                    at,
+                    format!("No heading \"{}\"", fragment),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Request each distinct URL once, at most `concurrency` at a time. Returns
+/// the failure reason for every pending URL, or `None` when it is reachable.
+async fn check_urls(
+    pending: &[PendingUrl],
+    options: &LinkCheckOptions,
+) -> Result<Vec<Option<String>>, AppError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(options.timeout_secs.max(1)))
+        .user_agent(concat!(
+            "MarkView/",
+            env!("CARGO_PKG_VERSION"),
+            " link checker"
+        ))
+        .build()
+        .map_err(|e| AppError::Network(format!("Failed to create HTTP client: {}", e)))?;
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+
+    let unique: HashSet<String> = pending
+        .iter()
+        .map(|p| split_fragment(&p.url).0.to_string())
+        .collect();
+    let mut tasks = JoinSet::new();
+    for url in unique {
+        let client = client.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = check_url(&client, &url).await;
+            (url, result)
+        });
+    }
+
+    let mut results: HashMap<String, Option<String>> = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((url, result)) = joined {
+            results.insert(url, result);
+        }
+    }
+
+    Ok(pending
+        .iter()
+        .map(|p| results.get(split_fragment(&p.url).0).cloned().flatten())
+        .collect())
+}
+
+async fn check_url(client: &reqwest::Client, url: &str) -> Option<String> {
+    // Some servers reject HEAD; retry those with GET.
+    let response = match client.head(url).send().await {
+        Ok(r) if r.status().as_u16() == 405 || r.status().as_u16() == 403 => {
+            client.get(url).send().await
+        }
+        other => other,
+    };
+    match response {
+        Ok(r) if r.status().is_success() || r.status().is_redirection() => None,
+        Ok(r) => Some(format!("HTTP {}", r.status())),
+        Err(e) if e.is_timeout() => Some("Timed out".to_string()),
+        Err(e) if e.is_connect() => Some("Could not connect".to_string()),
+        Err(e) => Some(e.to_string()),
+    }
+}
@@ -0,0 +1,655 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{parser_options, utf16_len, LineIndex};
+use crate::utils::{collect_md_files, validate_directory};
+
+/// Workspace config file, compatible with markdownlint.
+const CONFIG_FILE: &str = ".markdownlint.json";
+
+static BARE_URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://[^\s<>\[\]()"'`]+"#).expect("invalid bare URL regex"));
+static REFERENCE_DEFINITION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^ {0,3}\[[^\]]+\]:\s").expect("invalid reference definition regex"));
+
+/// Supported rules: markdownlint id and alias.
+const RULES: &[(&str, &str)] = &[
+    ("MD001", "heading-increment"),
+    ("MD004", "ul-style"),
+    ("MD009", "no-trailing-spaces"),
+    ("MD013", "line-length"),
+    ("MD024", "no-duplicate-heading"),
+    ("MD034", "no-bare-urls"),
+    ("MD045", "no-alt-text"),
+];
+
+/// An edit that resolves a diagnostic, in markdownlint's `fixInfo` shape:
+/// delete `delete_count` UTF-16 units at `line`/`column`, then insert
+/// `insert_text`.
+#[derive(Serialize, Clone, Debug)]
+pub struct LintFix {
+    pub line: usize,
+    pub column: usize,
+    pub delete_count: usize,
+    pub insert_text: String,
+    #[serde(skip)]
+    byte_range: Range<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LintDiagnostic {
+    /// Rule id, e.g. `MD009`.
+    pub rule: String,
+    /// Rule alias, e.g. `no-trailing-spaces`.
+    pub alias: String,
+    pub message: String,
+    /// 1-based line and UTF-16 column where the problem starts.
+    pub line: usize,
+    pub column: usize,
+    /// Exclusive end of the flagged range.
+    pub end_line: usize,
+    pub end_column: usize,
+    pub fix: Option<LintFix>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FileLintResult {
+    pub file_path: String,
+    pub file_name: String,
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+/// Lint configuration in `.markdownlint.json` format: `"default"` toggles all
+/// rules, and each rule (by id or alias) is either a boolean or an object of
+/// parameters.
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    default_enabled: bool,
+    rules: Map<String, Value>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            default_enabled: true,
+            rules: Map::new(),
+        }
+    }
+}
+
+impl LintConfig {
+    /// Load the config from the workspace root, falling back to defaults when
+    /// the file is missing or invalid.
+    pub fn load(folder: Option<&Path>) -> Self {
+        let Some(folder) = folder else {
+            return Self::default();
+        };
+        let parsed = fs::read_to_string(folder.join(CONFIG_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok());
+        let Some(mut rules) = parsed else {
+            return Self::default();
+        };
+        let default_enabled = rules
+            .remove("default")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        Self {
+            default_enabled,
+            rules,
+        }
+    }
+
+    fn setting(&self, id: &str) -> Option<&Value> {
+        let alias = RULES.iter().find(|(rule, _)| *rule == id).map(|(_, a)| *a);
+        self.rules.iter().find_map(|(key, value)| {
+            (key.eq_ignore_ascii_case(id) || Some(key.to_lowercase().as_str()) == alias)
+                .then_some(value)
+        })
+    }
+
+    fn enabled(&self, id: &str) -> bool {
+        match self.setting(id) {
+            Some(Value::Bool(enabled)) => *enabled,
+            Some(Value::Object(_)) => true,
+            _ => self.default_enabled,
+        }
+    }
+
+    fn param(&self, id: &str, key: &str) -> Option<&Value> {
+        self.setting(id)?.as_object()?.get(key)
+    }
+
+    fn param_usize(&self, id: &str, key: &str) -> Option<usize> {
+        self.param(id, key)?.as_u64().map(|v| v as usize)
+    }
+
+    fn param_bool(&self, id: &str, key: &str, default: bool) -> bool {
+        self.param(id, key)
+            .and_then(Value::as_bool)
+            .unwrap_or(default)
+    }
+
+    fn param_str(&self, id: &str, key: &str) -> Option<&str> {
+        self.param(id, key)?.as_str()
+    }
+}
+
+/// Lint markdown `content` with the config of the workspace at `folder`.
+#[tauri::command]
+pub fn lint_document(
+    content: String,
+    folder: Option<String>,
+) -> Result<Vec<LintDiagnostic>, AppError> {
+    let config = LintConfig::load(folder.as_deref().map(Path::new));
+    Ok(lint_markdown(&content, &config))
+}
+
+/// Apply every available fix to `content` and return the result.
+#[tauri::command]
+pub fn fix_lint_issues(content: String, folder: Option<String>) -> Result<String, AppError> {
+    let config = LintConfig::load(folder.as_deref().map(Path::new));
+    let diagnostics = lint_markdown(&content, &config);
+    Ok(apply_fixes(&content, &diagnostics))
+}
+
+/// Lint every markdown file under `folder`. Only files with diagnostics are
+/// returned, sorted by path.
+#[tauri::command]
+pub fn lint_folder(folder: String) -> Result<Vec<FileLintResult>, AppError> {
+    let root = validate_directory(&folder)?;
+    let config = LintConfig::load(Some(&root));
+
+    let mut files = collect_md_files(&root);
+    files.sort();
+
+    let mut results = Vec::new();
+    for path in files {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let diagnostics = lint_markdown(&content, &config);
+        if diagnostics.is_empty() {
+            continue;
+        }
+        results.push(FileLintResult {
+            file_path: path.to_string_lossy().into_owned(),
+            file_name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            diagnostics,
+        });
+    }
+    Ok(results)
+}
+
+/// Apply non-overlapping fixes from `diagnostics`, last to first.
+pub fn apply_fixes(content: &str, diagnostics: &[LintDiagnostic]) -> String {
+    let mut fixes: Vec<&LintFix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    fixes.sort_by_key(|f| std::cmp::Reverse(f.byte_range.start));
+
+    let mut out = content.to_string();
+    let mut limit = usize::MAX;
+    for fix in fixes {
+        if fix.byte_range.end > limit {
+            continue;
+        }
+        out.replace_range(fix.byte_range.clone(), &fix.insert_text);
+        limit = fix.byte_range.start;
+    }
+    out
+}
+
+/// Structure of a document collected in one parser pass.
+#[derive(Default)]
+struct Outline {
+    /// `(level, plain text, byte range)` of each heading.
+    headings: Vec<(u8, String, Range<usize>)>,
+    /// Byte offset of each unordered list item's marker.
+    bullet_markers: Vec<usize>,
+    /// `(alt text, byte range)` of each image.
+    images: Vec<(String, Range<usize>)>,
+    code_blocks: Vec<Range<usize>>,
+    tables: Vec<Range<usize>>,
+    /// Ranges where URLs are not bare: links, code, inline and block HTML.
+    url_exempt: Vec<Range<usize>>,
+}
+
+fn outline(body: &str, offset: usize) -> Outline {
+    let mut outline = Outline::default();
+    let mut heading: Option<(u8, String, Range<usize>)> = None;
+    let mut image: Option<(String, Range<usize>)> = None;
+    let mut list_is_ordered: Vec<bool> = Vec::new();
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        let range = range.start + offset..range.end + offset;
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                heading = Some((level as u8, String::new(), range));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(h) = heading.take() {
+                    outline.headings.push(h);
+                }
+            }
+            Event::Start(Tag::List(start)) => list_is_ordered.push(start.is_some()),
+            Event::End(TagEnd::List(_)) => {
+                list_is_ordered.pop();
+            }
+            Event::Start(Tag::Item) if list_is_ordered.last() == Some(&false) => {
+                outline.bullet_markers.push(range.start);
+            }
+            Event::Start(Tag::Image { .. }) => {
+                image = Some((String::new(), range.clone()));
+                outline.url_exempt.push(range);
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some(img) = image.take() {
+                    outline.images.push(img);
+                }
+            }
+            Event::Start(Tag::Link { .. }) => outline.url_exempt.push(range),
+            Event::Start(Tag::CodeBlock(_)) => {
+                outline.code_blocks.push(range.clone());
+                outline.url_exempt.push(range);
+            }
+            Event::Start(Tag::HtmlBlock) | Event::Html(_) | Event::InlineHtml(_) => {
+                outline.url_exempt.push(range)
+            }
+            Event::Code(_) | Event::InlineMath(_) | Event::DisplayMath(_) => {
+                outline.url_exempt.push(range)
+            }
+            Event::Start(Tag::Table(_)) => outline.tables.push(range),
+            Event::Text(text) => {
+                if let Some((_, title, _)) = heading.as_mut() {
+                    title.push_str(&text);
+                }
+                if let Some((alt, _)) = image.as_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    outline
+}
+
+/// Run every enabled rule over `content`.
+pub fn lint_markdown(content: &str, config: &LintConfig) -> Vec<LintDiagnostic> {
+    let (_, body) = split_frontmatter(content);
+    let body_offset = content.len() - body.len();
+    let index = LineIndex::new(content);
+    let outline = outline(body, body_offset);
+    let mut ctx = Context {
+        content,
+        index: &index,
+        diagnostics: Vec::new(),
+    };
+
+    if config.enabled("MD001") {
+        heading_increment(&mut ctx, &outline);
+    }
+    if config.enabled("MD004") {
+        ul_style(&mut ctx, &outline, config);
+    }
+    if config.enabled("MD009") {
+        trailing_spaces(&mut ctx, &outline, config, body_offset);
+    }
+    if config.enabled("MD013") {
+        line_length(&mut ctx, &outline, config, body_offset);
+    }
+    if config.enabled("MD024") {
+        duplicate_headings(&mut ctx, &outline, config);
+    }
+    if config.enabled("MD034") {
+        bare_urls(&mut ctx, &outline, body_offset);
+    }
+    if config.enabled("MD045") {
+        missing_alt_text(&mut ctx, &outline);
+    }
+
+    ctx.diagnostics
+        .sort_by(|a, b| (a.line, a.column, &a.rule).cmp(&(b.line, b.column, &b.rule)));
+    ctx.diagnostics
+}
+
+struct Context<'a> {
+    content: &'a str,
+    index: &'a LineIndex<'a>,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl Context<'_> {
+    fn report(
+        &mut self,
+        rule: &str,
+        message: String,
+        range: Range<usize>,
+        fix: Option<(Range<usize>, String)>,
+    ) {
+        let alias = RULES
+            .iter()
+            .find(|(id, _)| *id == rule)
+            .map(|(_, alias)| alias.to_string())
+            .unwrap_or_default();
+        let (line, column) = self.index.position(range.start);
+        let (end_line, end_column) = self.index.position(range.end);
+        let fix = fix.map(|(byte_range, insert_text)| {
+            let (line, column) = self.index.position(byte_range.start);
+            LintFix {
+                line,
+                column,
+                delete_count: utf16_len(&self.content[byte_range.clone()]),
+                insert_text,
+                byte_range,
+            }
+        });
+        self.diagnostics.push(LintDiagnostic {
+            rule: rule.to_string(),
+            alias,
+            message,
+            line,
+            column,
+            end_line,
+            end_column,
+            fix,
+        });
+    }
+
+    /// Byte range of the line containing `offset`, without its line ending.
+    fn line_range(&self, offset: usize) -> Range<usize> {
+        let start = self.content[..offset]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = self.content[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(self.content.len());
+        let end = if self.content[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        start..end
+    }
+
+    /// Body lines as `(byte range without line ending)`.
+    fn body_lines(&self, body_offset: usize) -> Vec<Range<usize>> {
+        let mut lines = Vec::new();
+        let mut start = body_offset;
+        for line in self.content[body_offset..].split_inclusive('\n') {
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            lines.push(start..start + trimmed.len());
+            start += line.len();
+        }
+        lines
+    }
+}
+
+fn contains(ranges: &[Range<usize>], offset: usize) -> bool {
+    ranges.iter().any(|r| r.contains(&offset))
+}
+
+fn overlaps(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+    ranges
+        .iter()
+        .any(|r| r.start < range.end && range.start < r.end)
+}
+
+/// MD001: heading levels should only increment by one level at a time.
+fn heading_increment(ctx: &mut Context, outline: &Outline) {
+    let mut previous: Option<u8> = None;
+    for (level, _, range) in &outline.headings {
+        if let Some(prev) = previous {
+            if *level > prev + 1 {
+                let line = ctx.line_range(range.start);
+                ctx.report(
+                    "MD001",
+                    format!(
+                        "Heading levels should only increment by one level at a time [Expected: h{}; Actual: h{}]",
+                        prev + 1,
+                        level
+                    ),
+                    line,
+                    None,
+                );
+            }
+        }
+        previous = Some(*level);
+    }
+}
+
+/// MD004: unordered list style (`consistent`, `dash`, `asterisk`, `plus`).
+fn ul_style(ctx: &mut Context, outline: &Outline, config: &LintConfig) {
+    let configured = match config.param_str("MD004", "style") {
+        Some("dash") => Some('-'),
+        Some("asterisk") => Some('*'),
+        Some("plus") => Some('+'),
+        _ => None,
+    };
+    let mut expected = configured;
+    for &marker_offset in &outline.bullet_markers {
+        let Some(marker) = ctx.content[marker_offset..].chars().next() else {
+            continue;
+        };
+        if !matches!(marker, '-' | '*' | '+') {
+            continue;
+        }
+        let want = *expected.get_or_insert(marker);
+        if marker != want {
+            ctx.report(
+                "MD004",
+                format!(
+                    "Unordered list style [Expected: {}; Actual: {}]",
+                    marker_name(want),
+                    marker_name(marker)
+                ),
+                marker_offset..marker_offset + 1,
+                Some((marker_offset..marker_offset + 1, want.to_string())),
+            );
+        }
+    }
+}
+
+fn marker_name(marker: char) -> &'static str {
+    match marker {
+        '*' => "asterisk",
+        '+' => "plus",
+        _ => "dash",
+    }
+}
+
+/// MD009: trailing spaces. Exactly `br_spaces` (default 2) spaces before a
+/// non-empty line are a hard line break and allowed.
+fn trailing_spaces(ctx: &mut Context, outline: &Outline, config: &LintConfig, body_offset: usize) {
+    let br_spaces = config.param_usize("MD009", "br_spaces").unwrap_or(2);
+    let strict = config.param_bool("MD009", "strict", false);
+    let lines = ctx.body_lines(body_offset);
+
+    for (i, line) in lines.iter().enumerate() {
+        if contains(&outline.code_blocks, line.start) {
+            continue;
+        }
+        let text = &ctx.content[line.clone()];
+        let trimmed = text.trim_end_matches([' ', '\t']);
+        let trailing = text.len() - trimmed.len();
+        if trailing == 0 {
+            continue;
+        }
+        let next_has_text = lines
+            .get(i + 1)
+            .map(|next| !ctx.content[next.clone()].trim().is_empty())
+            .unwrap_or(false);
+        let is_break = !strict
+            && !trimmed.is_empty()
+            && trailing == br_spaces
+            && next_has_text
+            && !text[trimmed.len()..].contains('\t');
+        if is_break {
+            continue;
+        }
+        let range = line.start + trimmed.len()..line.end;
+        ctx.report(
+            "MD009",
+            format!(
+                "Trailing spaces [Expected: {}; Actual: {}]",
+                if br_spaces >= 2 {
+                    format!("0 or {}", br_spaces)
+                } else {
+                    "0".to_string()
+                },
+                trailing
+            ),
+            range.clone(),
+            Some((range, String::new())),
+        );
+    }
+}
+
+/// MD013: line length. Lines are only flagged when there is whitespace past
+/// the limit, so long URLs on their own are allowed.
+fn line_length(ctx: &mut Context, outline: &Outline, config: &LintConfig, body_offset: usize) {
+    let limit = config.param_usize("MD013", "line_length").unwrap_or(80);
+    let heading_limit = config
+        .param_usize("MD013", "heading_line_length")
+        .unwrap_or(limit);
+    let code_limit = config
+        .param_usize("MD013", "code_block_line_length")
+        .unwrap_or(limit);
+    let check_code = config.param_bool("MD013", "code_blocks", true);
+    let check_tables = config.param_bool("MD013", "tables", true);
+    let check_headings = config.param_bool("MD013", "headings", true);
+    let strict = config.param_bool("MD013", "strict", false);
+
+    let heading_ranges: Vec<Range<usize>> =
+        outline.headings.iter().map(|(_, _, r)| r.clone()).collect();
+
+    for line in ctx.body_lines(body_offset) {
+        let text = &ctx.content[line.clone()];
+        let in_code = contains(&outline.code_blocks, line.start);
+        let in_heading = contains(&heading_ranges, line.start);
+        let max = if in_code {
+            if !check_code {
+                continue;
+            }
+            code_limit
+        } else if in_heading {
+            if !check_headings {
+                continue;
+            }
+            heading_limit
+        } else {
+            if !check_tables && contains(&outline.tables, line.start) {
+                continue;
+            }
+            if REFERENCE_DEFINITION_RE.is_match(text) {
+                continue;
+            }
+            limit
+        };
+
+        let length = text.chars().count();
+        if length <= max {
+            continue;
+        }
+        let over_start = text
+            .char_indices()
+            .nth(max)
+            .map(|(i, _)| i)
+            .unwrap_or(text.len());
+        if !strict && !text[over_start..].contains(char::is_whitespace) {
+            continue;
+        }
+        ctx.report(
+            "MD013",
+            format!("Line length [Expected: {}; Actual: {}]", max, length),
+            line.start + over_start..line.end,
+            None,
+        );
+    }
+}
+
+/// MD024: multiple headings with the same content. With `siblings_only`,
+/// only headings under the same parent are compared.
+fn duplicate_headings(ctx: &mut Context, outline: &Outline, config: &LintConfig) {
+    let siblings_only = config.param_bool("MD024", "siblings_only", false);
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut parents: Vec<(u8, String)> = Vec::new();
+
+    for (level, text, range) in &outline.headings {
+        while parents.last().is_some_and(|(l, _)| l >= level) {
+            parents.pop();
+        }
+        let normalized = text.trim().to_lowercase();
+        let key = if siblings_only {
+            let path: Vec<&str> = parents.iter().map(|(_, t)| t.as_str()).collect();
+            format!("{}\u{0}{}", path.join("\u{0}"), normalized)
+        } else {
+            normalized.clone()
+        };
+        parents.push((*level, normalized));
+
+        if !seen.insert(key) {
+            let line = ctx.line_range(range.start);
+            ctx.report(
+                "MD024",
+                format!(
+                    "Multiple headings with the same content [\"{}\"]",
+                    text.trim()
+                ),
+                line,
+                None,
+            );
+        }
+    }
+}
+
+/// MD034: bare URLs should be wrapped in angle brackets.
+fn bare_urls(ctx: &mut Context, outline: &Outline, body_offset: usize) {
+    for line in ctx.body_lines(body_offset) {
+        let text = &ctx.content[line.clone()];
+        if REFERENCE_DEFINITION_RE.is_match(text) {
+            continue;
+        }
+        for m in BARE_URL_RE.find_iter(text) {
+            let url = m
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_', '~']);
+            let range = line.start + m.start()..line.start + m.start() + url.len();
+            if overlaps(&outline.url_exempt, &range) {
+                continue;
+            }
+            ctx.report(
+                "MD034",
+                format!("Bare URL used [{}]", url),
+                range.clone(),
+                Some((range, format!("<{}>", url))),
+            );
+        }
+    }
+}
+
+/// MD045: images should have alternate text.
+fn missing_alt_text(ctx: &mut Context, outline: &Outline) {
+    for (alt, range) in &outline.images {
+        if alt.trim().is_empty() {
+            ctx.report(
+                "MD045",
+                "Images should have alternate text (alt text)".to_string(),
+                range.clone(),
+                None,
+            );
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+
+static SLUG_STRIP_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[^A-Za-z0-9_\s-]").expect("invalid slug strip regex"));
+static SLUG_SPACE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s+").expect("invalid slug space regex"));
+static SLUG_DASH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"-+").expect("invalid slug dash regex"));
+
+/// Parser options shared by every backend markdown consumer, matching the
+/// extensions enabled in the preview (tables, footnotes, task lists, math).
+pub fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_MATH
+}
+
+/// Turn heading text into an anchor slug.
+///
+/// Mirrors the heading-anchors plugin in the preview so that `#fragment`
+/// links resolve to the same ids in the app and in exported documents.
+pub fn slugify(text: &str) -> String {
+    let lower = text.trim().to_lowercase();
+    let stripped = SLUG_STRIP_RE.replace_all(&lower, "");
+    let dashed = SLUG_SPACE_RE.replace_all(&stripped, "-");
+    SLUG_DASH_RE.replace_all(&dashed, "-").into_owned()
+}
+
+#[derive(Debug, Clone)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+}
+
+/// Which kind of destination a [`render_html_with`] rewrite hook is asked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Link,
+    Image,
+}
+
+pub struct RenderedHtml {
+    pub html: String,
+    pub headings: Vec<Heading>,
+}
+
+/// Render markdown to HTML, assigning slug ids to every heading and letting
+/// `rewrite` replace link and image destinations. Returning `None` keeps the
+/// original destination.
+pub fn render_html_with<F>(body: &str, mut rewrite: F) -> RenderedHtml
+where
+    F: FnMut(LinkKind, &str) -> Option<String>,
+{
+    let events: Vec<Event> = Parser::new_ext(body, parser_options()).collect();
+    let mut out: Vec<Event> = Vec::with_capacity(events.len());
+    let mut headings: Vec<Heading> = Vec::new();
+
+    let mut i = 0;
+    while i < events.len() {
+        match &events[i] {
+            Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            }) => {
+                // Collect the heading text up to the matching end tag.
+                let mut text = String::new();
+                let mut j = i + 1;
+                while j < events.len() {
+                    match &events[j] {
+                        Event::End(TagEnd::Heading(_)) => break,
+                        Event::Text(t) | Event::Code(t) => text.push_str(t),
+                        _ => {}
+                    }
+                    j += 1;
+                }
+
+                let slug = id
+                    .as_ref()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| slugify(&text));
+                headings.push(Heading {
+                    level: *level as u8,
+                    text: text.trim().to_string(),
+                    slug: slug.clone(),
+                });
+                out.push(Event::Start(Tag::Heading {
+                    level: *level,
+                    id: Some(CowStr::from(slug)),
+                    classes: classes.clone(),
+                    attrs: attrs.clone(),
+                }));
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest = rewrite(LinkKind::Link, dest_url)
+                    .map(CowStr::from)
+                    .unwrap_or_else(|| dest_url.clone());
+                out.push(Event::Start(Tag::Link {
+                    link_type: *link_type,
+                    dest_url: dest,
+                    title: title.clone(),
+                    id: id.clone(),
+                }));
+            }
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => {
+                let dest = rewrite(LinkKind::Image, dest_url)
+                    .map(CowStr::from)
+                    .unwrap_or_else(|| dest_url.clone());
+                out.push(Event::Start(Tag::Image {
+                    link_type: *link_type,
+                    dest_url: dest,
+                    title: title.clone(),
+                    id: id.clone(),
+                }));
+            }
+            other => out.push(other.clone()),
+        }
+        i += 1;
+    }
+
+    let mut html_out = String::with_capacity(body.len() * 3 / 2);
+    html::push_html(&mut html_out, out.into_iter());
+
+    RenderedHtml {
+        html: html_out,
+        headings,
+    }
+}
+
+/// Return `true` when `dest` points outside the workspace (URL, mailto, data URI).
+pub fn is_external_url(dest: &str) -> bool {
+    let lower = dest.to_ascii_lowercase();
+    lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || lower.starts_with("mailto:")
+        || lower.starts_with("data:")
+        || lower.starts_with("//")
+}
+
+/// Return `true` when `dest` starts with a URL scheme such as `tel:` or
+/// `obsidian:`. Single letters are not schemes, so Windows drive paths pass.
+pub fn has_url_scheme(dest: &str) -> bool {
+    dest.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    })
+}
+
+/// Split a link destination into its path and optional `#fragment`.
+pub fn split_fragment(dest: &str) -> (&str, Option<&str>) {
+    match dest.find('#') {
+        Some(pos) => (&dest[..pos], Some(&dest[pos + 1..])),
+        None => (dest, None),
+    }
+}
+
+/// Escape text for inclusion in HTML/XML content or attribute values.
+pub fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes in a link destination so it can be used as a file path.
+pub fn percent_decode(dest: &str) -> String {
+    let bytes = dest.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Maps byte offsets in a document to 1-based line numbers and 1-based
+/// UTF-16 columns, the coordinates the editor works in.
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { text, line_starts }
+    }
+
+    /// `(line, column)` of the byte `offset`.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let column = self.text[start..offset].encode_utf16().count() + 1;
+        (line + 1, column)
+    }
+}
+
+/// Length of `text` in UTF-16 code units.
+pub fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
@@ -0,0 +1,490 @@
+use printpdf::path::PaintMode;
+use printpdf::{
+    Actions, BorderArray, BuiltinFont, Image, ImageTransform, IndirectFontRef, Line,
+    LinkAnnotation, Mm, PdfDocument, PdfLayerReference, Point, Rect,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::citations::apply_citations;
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::pdf_layout::{
+    layout_markdown, layout_toc, pdf_safe_text, text_width, unsupported_chars, Color, DrawOp,
+    FontFace, LaidOutPage, LayoutConfig, OutlineEntry, PageGeometry, IMAGE_DPI,
+};
+use crate::transclude::expand_embeds;
+
+/// Headings deeper than this are left out of the generated table of contents.
+const TOC_MAX_LEVEL: u8 = 3;
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    /// Portrait width and height in mm.
+    fn dimensions(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::A5 => (148.0, 210.0),
+            PageSize::Letter => (215.9, 279.4),
+            PageSize::Legal => (215.9, 355.6),
+        }
+    }
+}
+
+/// Page margins in mm.
+#[derive(Deserialize, Clone, Copy)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self {
+            top: 20.0,
+            right: 20.0,
+            bottom: 20.0,
+            left: 20.0,
+        }
+    }
+}
+
+/// Header or footer template. Each slot may contain the placeholders
+/// `{page}`, `{pages}`, `{title}`, `{file}` and `{date}`.
+#[derive(Deserialize, Clone, Default)]
+pub struct HeaderFooter {
+    pub left: Option<String>,
+    pub center: Option<String>,
+    pub right: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PdfOptions {
+    /// Markdown file to typeset; relative image paths resolve against its folder.
+    pub input_path: String,
+    pub output_path: String,
+    /// Editor content to typeset instead of the file on disk (unsaved changes).
+    pub content: Option<String>,
+    #[serde(default)]
+    pub page_size: PageSize,
+    #[serde(default)]
+    pub landscape: bool,
+    #[serde(default)]
+    pub margins: Margins,
+    /// Body font size in points.
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    pub header: Option<HeaderFooter>,
+    /// Defaults to a centred `{page} / {pages}` when omitted.
+    pub footer: Option<HeaderFooter>,
+    /// Prepend a generated table of contents.
+    #[serde(default)]
+    pub toc: bool,
+    #[serde(default)]
+    pub page_break_before_h1: bool,
+    #[serde(default = "default_true")]
+    pub highlight_code: bool,
+    /// Workspace root used to find `![[embedded]]` notes by name.
+    pub folder: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PdfOutcome {
+    pub output_path: String,
+    /// Characters the built-in PDF fonts cannot show, printed as `?`.
+    pub unsupported_chars: Vec<String>,
+}
+
+fn default_font_size() -> f32 {
+    11.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Typeset a markdown file into a PDF without going through the webview.
+///
+/// Supports page size and orientation, margins, header/footer templates with
+/// page numbers, an optional table of contents, syntax-highlighted code and
+/// embedded local images. Explicit page breaks are taken from
+/// `<!-- pagebreak -->`, `\newpage` or `page-break-*` HTML. Text uses the
+/// built-in PDF fonts, which only cover Windows-1252; other characters are
+/// printed as `?` and listed in the outcome along with the written path.
+#[tauri::command]
+pub fn export_pdf(options: PdfOptions) -> Result<PdfOutcome, AppError> {
+    let input = PathBuf::from(&options.input_path);
+    let content = match options.content {
+        Some(content) => content,
+        None if input.is_file() => fs::read_to_string(&input)?,
+        None => return Err(AppError::NotFound(options.input_path)),
+    };
+    let (frontmatter, body) = split_frontmatter(&content);
+    let base_dir = input.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let root = options
+        .folder
+        .as_ref()
+        .map_or(base_dir.clone(), PathBuf::from);
+    let body = apply_citations(
+        &expand_embeds(body, &input, &root),
+        frontmatter.as_ref(),
+        &input,
+        &root,
+    );
+
+    let (mut width, mut height) = options.page_size.dimensions();
+    if options.landscape {
+        std::mem::swap(&mut width, &mut height);
+    }
+    let geometry = PageGeometry {
+        width,
+        height,
+        margin_top: options.margins.top,
+        margin_right: options.margins.right,
+        margin_bottom: options.margins.bottom,
+        margin_left: options.margins.left,
+    };
+    if geometry.content_width() < 20.0 || geometry.content_bottom() - geometry.margin_top < 20.0 {
+        return Err(AppError::Export(
+            "Margins leave no room for content".to_string(),
+        ));
+    }
+
+    let config = LayoutConfig {
+        geometry,
+        font_size: options.font_size.clamp(6.0, 32.0),
+        highlight_code: options.highlight_code,
+        page_break_before_h1: options.page_break_before_h1,
+        base_dir: &base_dir,
+    };
+    let body_layout = layout_markdown(&body, &config);
+    let mut unsupported = unsupported_chars(&body);
+
+    let mut pages: Vec<LaidOutPage> = Vec::new();
+    let mut outline: Vec<OutlineEntry> = body_layout.outline.clone();
+
+    if options.toc && !outline.is_empty() {
+        let toc_outline: Vec<&OutlineEntry> = outline
+            .iter()
+            .filter(|e| e.level <= TOC_MAX_LEVEL)
+            .collect();
+        // Lay the TOC out once to learn how many pages it takes, then again
+        // with the final page numbers (right-aligned, so the count is stable).
+        let draft: Vec<(u8, String, String)> = toc_outline
+            .iter()
+            .map(|e| (e.level, e.text.clone(), (e.page + 1).to_string()))
+            .collect();
+        let toc_pages = layout_toc(&draft, &config).len();
+        let entries: Vec<(u8, String, String)> = toc_outline
+            .iter()
+            .map(|e| {
+                (
+                    e.level,
+                    e.text.clone(),
+                    (e.page + 1 + toc_pages).to_string(),
+                )
+            })
+            .collect();
+        pages.extend(layout_toc(&entries, &config));
+        for entry in outline.iter_mut() {
+            entry.page += toc_pages;
+        }
+    }
+    pages.extend(body_layout.pages);
+
+    let title = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("title").map(str::to_string))
+        .or_else(|| {
+            body_layout
+                .outline
+                .iter()
+                .find(|e| e.level == 1)
+                .map(|e| e.text.clone())
+        })
+        .unwrap_or_else(|| {
+            input
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+    let author = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("author").map(str::to_string))
+        .unwrap_or_default();
+
+    let footer = options.footer.clone().unwrap_or(HeaderFooter {
+        left: None,
+        center: Some("{page} / {pages}".to_string()),
+        right: None,
+    });
+    let context = TemplateContext {
+        title: &title,
+        file: &input
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        date: &chrono::Local::now().format("%Y-%m-%d").to_string(),
+    };
+    unsupported.extend(decorate_pages(
+        &mut pages,
+        &geometry,
+        config.font_size,
+        options.header.as_ref(),
+        Some(&footer),
+        &context,
+    ));
+
+    write_pdf(
+        Path::new(&options.output_path),
+        &pages,
+        &body_layout.images,
+        &geometry,
+        &title,
+        &author,
+        &outline,
+    )?;
+
+    Ok(PdfOutcome {
+        output_path: options.output_path,
+        unsupported_chars: unsupported.into_iter().map(String::from).collect(),
+    })
+}
+
+struct TemplateContext<'a> {
+    title: &'a str,
+    file: &'a str,
+    date: &'a str,
+}
+
+fn expand_template(template: &str, page: usize, pages: usize, ctx: &TemplateContext) -> String {
+    template
+        .replace("{page}", &page.to_string())
+        .replace("{pages}", &pages.to_string())
+        .replace("{title}", ctx.title)
+        .replace("{file}", ctx.file)
+        .replace("{date}", ctx.date)
+}
+
+/// Add header and footer text to every page, centred in the top and bottom
+/// margins. Returns the characters of that text the fonts cannot show.
+fn decorate_pages(
+    pages: &mut [LaidOutPage],
+    geometry: &PageGeometry,
+    font_size: f32,
+    header: Option<&HeaderFooter>,
+    footer: Option<&HeaderFooter>,
+    ctx: &TemplateContext,
+) -> BTreeSet<char> {
+    let mut unsupported = BTreeSet::new();
+    let size = font_size * 0.8;
+    let total = pages.len();
+    let grey = Color(0.4, 0.4, 0.4);
+
+    for (i, page) in pages.iter_mut().enumerate() {
+        for (slots, baseline) in [
+            (header, geometry.margin_top / 2.0 + 1.0),
+            (footer, geometry.height - geometry.margin_bottom / 2.0 + 1.0),
+        ] {
+            let Some(slots) = slots else {
+                continue;
+            };
+            for (template, align) in [(&slots.left, 0), (&slots.center, 1), (&slots.right, 2)] {
+                let Some(template) = template else {
+                    continue;
+                };
+                let text = expand_template(template, i + 1, total, ctx);
+                unsupported.extend(unsupported_chars(&text));
+                let text = pdf_safe_text(&text);
+                if text.is_empty() {
+                    continue;
+                }
+                let w = text_width(&text, FontFace::Regular, size);
+                let x = match align {
+                    0 => geometry.margin_left,
+                    1 => (geometry.width - w) / 2.0,
+                    _ => geometry.width - geometry.margin_right - w,
+                };
+                page.ops.push(DrawOp::Text {
+                    x,
+                    y: baseline,
+                    text,
+                    face: FontFace::Regular,
+                    size,
+                    color: grey,
+                });
+            }
+        }
+    }
+    unsupported
+}
+
+/// Render laid-out pages into a PDF file using the built-in PDF fonts.
+pub fn write_pdf(
+    output: &Path,
+    pages: &[LaidOutPage],
+    images: &[image::DynamicImage],
+    geometry: &PageGeometry,
+    title: &str,
+    author: &str,
+    outline: &[OutlineEntry],
+) -> Result<(), AppError> {
+    let pdf_err = |e: printpdf::Error| AppError::Export(format!("Failed to write PDF: {}", e));
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(title, Mm(geometry.width), Mm(geometry.height), "Content");
+    let doc = doc.with_author(author).with_creator("MarkView");
+
+    let mut fonts: HashMap<FontFace, IndirectFontRef> = HashMap::new();
+    for (face, builtin) in [
+        (FontFace::Regular, BuiltinFont::Helvetica),
+        (FontFace::Bold, BuiltinFont::HelveticaBold),
+        (FontFace::Italic, BuiltinFont::HelveticaOblique),
+        (FontFace::BoldItalic, BuiltinFont::HelveticaBoldOblique),
+        (FontFace::Mono, BuiltinFont::Courier),
+        (FontFace::MonoBold, BuiltinFont::CourierBold),
+    ] {
+        fonts.insert(face, doc.add_builtin_font(builtin).map_err(pdf_err)?);
+    }
+
+    let mut page_indices = Vec::with_capacity(pages.len());
+    for (i, page) in pages.iter().enumerate() {
+        let (page_index, layer_index) = if i == 0 {
+            (first_page, first_layer)
+        } else {
+            doc.add_page(Mm(geometry.width), Mm(geometry.height), "Content")
+        };
+        page_indices.push(page_index);
+        let layer = doc.get_page(page_index).get_layer(layer_index);
+        for op in &page.ops {
+            render_op(&layer, op, geometry.height, &fonts, images);
+        }
+    }
+
+    // printpdf keeps a single bookmark per page, so use the first heading on each.
+    let mut bookmarked = std::collections::HashSet::new();
+    for entry in outline {
+        if let Some(page_index) = page_indices.get(entry.page) {
+            if bookmarked.insert(entry.page) {
+                doc.add_bookmark(entry.text.clone(), *page_index);
+            }
+        }
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = BufWriter::new(File::create(output)?);
+    doc.save(&mut writer).map_err(pdf_err)?;
+    Ok(())
+}
+
+fn pdf_color(color: Color) -> printpdf::Color {
+    printpdf::Color::Rgb(printpdf::Rgb::new(color.0, color.1, color.2, None))
+}
+
+/// Draw one operation, flipping from top-left layout coordinates to PDF's
+/// bottom-left origin.
+fn render_op(
+    layer: &PdfLayerReference,
+    op: &DrawOp,
+    page_height: f32,
+    fonts: &HashMap<FontFace, IndirectFontRef>,
+    images: &[image::DynamicImage],
+) {
+    match op {
+        DrawOp::Text {
+            x,
+            y,
+            text,
+            face,
+            size,
+            color,
+        } => {
+            layer.set_fill_color(pdf_color(*color));
+            layer.use_text(
+                text.clone(),
+                *size,
+                Mm(*x),
+                Mm(page_height - y),
+                &fonts[face],
+            );
+        }
+        DrawOp::FillRect { x, y, w, h, color } => {
+            layer.set_fill_color(pdf_color(*color));
+            layer.add_rect(
+                Rect::new(
+                    Mm(*x),
+                    Mm(page_height - y - h),
+                    Mm(x + w),
+                    Mm(page_height - y),
+                )
+                .with_mode(PaintMode::Fill),
+            );
+        }
+        DrawOp::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            width,
+            color,
+        } => {
+            layer.set_outline_color(pdf_color(*color));
+            layer.set_outline_thickness(*width);
+            layer.add_line(Line {
+                points: vec![
+                    (Point::new(Mm(*x1), Mm(page_height - y1)), false),
+                    (Point::new(Mm(*x2), Mm(page_height - y2)), false),
+                ],
+                is_closed: false,
+            });
+        }
+        DrawOp::Image { index, x, y, w, h } => {
+            let Some(img) = images.get(*index) else {
+                return;
+            };
+            let natural_w = img.width() as f32 / IMAGE_DPI * 25.4;
+            let natural_h = img.height() as f32 / IMAGE_DPI * 25.4;
+            Image::from_dynamic_image(img).add_to_layer(
+                layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(*x)),
+                    translate_y: Some(Mm(page_height - y - h)),
+                    scale_x: Some(w / natural_w),
+                    scale_y: Some(h / natural_h),
+                    dpi: Some(IMAGE_DPI),
+                    ..Default::default()
+                },
+            );
+        }
+        DrawOp::Link { x, y, w, h, uri } => {
+            layer.add_link_annotation(LinkAnnotation::new(
+                Rect::new(
+                    Mm(*x),
+                    Mm(page_height - y - h),
+                    Mm(x + w),
+                    Mm(page_height - y),
+                ),
+                Some(BorderArray::Solid([0.0, 0.0, 0.0])),
+                None,
+                Actions::uri(uri.clone()),
+                None,
+            ));
+        }
+    }
+}
@@ -0,0 +1,1256 @@
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Parser, Tag, TagEnd};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::highlight::{highlight_lines, TokenKind};
+use crate::markdown::{is_external_url, parser_options, percent_decode};
+
+/// Points to millimetres.
+pub const PT_TO_MM: f32 = 25.4 / 72.0;
+
+/// Horizontal indentation per list / blockquote nesting level, in mm.
+const INDENT_STEP: f32 = 6.0;
+
+/// Inner padding of code blocks and table cells, in mm.
+const CELL_PADDING: f32 = 1.5;
+
+/// Pixel density assumed for images without physical size information.
+pub const IMAGE_DPI: f32 = 96.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontFace {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+    MonoBold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color(pub f32, pub f32, pub f32);
+
+const BLACK: Color = Color(0.0, 0.0, 0.0);
+const LINK_BLUE: Color = Color(0.1, 0.3, 0.75);
+const GREY: Color = Color(0.45, 0.45, 0.45);
+const RULE_GREY: Color = Color(0.75, 0.75, 0.75);
+const CODE_BACKGROUND: Color = Color(0.95, 0.95, 0.95);
+const TABLE_HEADER_BACKGROUND: Color = Color(0.9, 0.9, 0.9);
+
+/// A single drawing instruction. Coordinates are in millimetres measured from
+/// the top-left corner of the page; `y` of text is the baseline.
+#[derive(Debug, Clone)]
+pub enum DrawOp {
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        face: FontFace,
+        size: f32,
+        color: Color,
+    },
+    FillRect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        color: Color,
+    },
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        width: f32,
+        color: Color,
+    },
+    Image {
+        index: usize,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+    Link {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        uri: String,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct LaidOutPage {
+    pub ops: Vec<DrawOp>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlineEntry {
+    pub level: u8,
+    pub text: String,
+    /// Zero-based index into the laid-out pages.
+    pub page: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub margin_top: f32,
+    pub margin_right: f32,
+    pub margin_bottom: f32,
+    pub margin_left: f32,
+}
+
+impl PageGeometry {
+    pub fn content_width(&self) -> f32 {
+        self.width - self.margin_left - self.margin_right
+    }
+
+    pub fn content_bottom(&self) -> f32 {
+        self.height - self.margin_bottom
+    }
+}
+
+pub struct LayoutConfig<'a> {
+    pub geometry: PageGeometry,
+    /// Body font size in points.
+    pub font_size: f32,
+    pub highlight_code: bool,
+    pub page_break_before_h1: bool,
+    /// Folder relative image paths are resolved against.
+    pub base_dir: &'a Path,
+}
+
+pub struct LayoutResult {
+    pub pages: Vec<LaidOutPage>,
+    pub outline: Vec<OutlineEntry>,
+    pub images: Vec<DynamicImage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SpanStyle {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    strike: bool,
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    text: String,
+    style: SpanStyle,
+}
+
+/// One placed run of text on a line, with its measured width.
+#[derive(Debug, Clone)]
+struct Run {
+    text: String,
+    style: SpanStyle,
+    width: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TextLine {
+    runs: Vec<Run>,
+    width: f32,
+}
+
+struct TableState {
+    alignments: Vec<Alignment>,
+    header: Vec<Vec<Span>>,
+    rows: Vec<Vec<Vec<Span>>>,
+    current_row: Vec<Vec<Span>>,
+}
+
+/// Typeset markdown `body` into pages of drawing operations.
+pub fn layout_markdown(body: &str, config: &LayoutConfig) -> LayoutResult {
+    let mut typesetter = Typesetter::new(config);
+    typesetter.run(body);
+    typesetter.finish()
+}
+
+/// Typeset a table of contents. `entries` are `(level, text, page_label)`.
+pub fn layout_toc(entries: &[(u8, String, String)], config: &LayoutConfig) -> Vec<LaidOutPage> {
+    let mut ts = Typesetter::new(config);
+    let size = config.font_size;
+    let title_size = size * 1.6;
+
+    ts.ensure_space(line_height(title_size));
+    let baseline = ts.y + line_height(title_size) * 0.75;
+    ts.push(DrawOp::Text {
+        x: config.geometry.margin_left,
+        y: baseline,
+        text: "Contents".to_string(),
+        face: FontFace::Bold,
+        size: title_size,
+        color: BLACK,
+    });
+    ts.y += line_height(title_size) * 1.5;
+
+    let right = config.geometry.width - config.geometry.margin_right;
+    for (level, text, page_label) in entries {
+        let face = if *level == 1 {
+            FontFace::Bold
+        } else {
+            FontFace::Regular
+        };
+        let indent = f32::from(level.saturating_sub(1)) * INDENT_STEP;
+        let number_width = text_width(page_label, FontFace::Regular, size);
+        let available = config.geometry.content_width() - indent - number_width - 4.0;
+        let label = truncate_to_width(text, face, size, available);
+
+        ts.ensure_space(line_height(size));
+        let baseline = ts.y + line_height(size) * 0.75;
+        ts.push(DrawOp::Text {
+            x: config.geometry.margin_left + indent,
+            y: baseline,
+            text: label,
+            face,
+            size,
+            color: BLACK,
+        });
+        ts.push(DrawOp::Text {
+            x: right - number_width,
+            y: baseline,
+            text: page_label.clone(),
+            face: FontFace::Regular,
+            size,
+            color: BLACK,
+        });
+        ts.y += line_height(size);
+    }
+
+    ts.finish().pages
+}
+
+struct Typesetter<'a> {
+    config: &'a LayoutConfig<'a>,
+    pages: Vec<LaidOutPage>,
+    outline: Vec<OutlineEntry>,
+    images: Vec<DynamicImage>,
+    /// Cursor position (top of the next line) in mm from the page top.
+    y: f32,
+
+    spans: Vec<Span>,
+    style: SpanStyle,
+    bold_depth: usize,
+    italic_depth: usize,
+    heading: Option<u8>,
+    code_block: Option<(String, String)>,
+    image: Option<(String, String)>,
+    list_stack: Vec<Option<u64>>,
+    pending_marker: Option<String>,
+    quote_depth: usize,
+    footnote_depth: usize,
+    seen_footnote: bool,
+    table: Option<TableState>,
+}
+
+impl<'a> Typesetter<'a> {
+    fn new(config: &'a LayoutConfig<'a>) -> Self {
+        Self {
+            config,
+            pages: vec![LaidOutPage::default()],
+            outline: Vec::new(),
+            images: Vec::new(),
+            y: config.geometry.margin_top,
+            spans: Vec::new(),
+            style: SpanStyle::default(),
+            bold_depth: 0,
+            italic_depth: 0,
+            heading: None,
+            code_block: None,
+            image: None,
+            list_stack: Vec::new(),
+            pending_marker: None,
+            quote_depth: 0,
+            footnote_depth: 0,
+            seen_footnote: false,
+            table: None,
+        }
+    }
+
+    fn finish(self) -> LayoutResult {
+        LayoutResult {
+            pages: self.pages,
+            outline: self.outline,
+            images: self.images,
+        }
+    }
+
+    fn geometry(&self) -> &PageGeometry {
+        &self.config.geometry
+    }
+
+    fn push(&mut self, op: DrawOp) {
+        self.pages
+            .last_mut()
+            .expect("typesetter always has a page")
+            .ops
+            .push(op);
+    }
+
+    fn page_is_empty(&self) -> bool {
+        self.pages.last().map(|p| p.ops.is_empty()).unwrap_or(true)
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(LaidOutPage::default());
+        self.y = self.geometry().margin_top;
+    }
+
+    /// Start a new page unless `height` mm still fit below the cursor.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y + height > self.geometry().content_bottom() && !self.page_is_empty() {
+            self.new_page();
+        }
+    }
+
+    fn indent(&self) -> f32 {
+        (self.quote_depth + self.list_stack.len() + self.footnote_depth) as f32 * INDENT_STEP
+    }
+
+    fn body_size(&self) -> f32 {
+        if self.footnote_depth > 0 {
+            self.config.font_size * 0.85
+        } else {
+            self.config.font_size
+        }
+    }
+
+    fn run(&mut self, body: &str) {
+        for event in Parser::new_ext(body, parser_options()) {
+            self.handle(event);
+        }
+        self.flush_paragraph();
+    }
+
+    fn handle(&mut self, event: Event) {
+        if let Some((_, buffer)) = self.code_block.as_mut() {
+            match event {
+                Event::Text(text) => buffer.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let (lang, code) = self.code_block.take().expect("checked above");
+                    self.code_block(&lang, &code);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some((_, alt)) = self.image.as_mut() {
+            match event {
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                Event::End(TagEnd::Image) => {
+                    let (dest, alt) = self.image.take().expect("checked above");
+                    self.flush_paragraph();
+                    self.image_block(&dest, &alt);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.push_text(&text),
+            Event::Code(text) => {
+                let mut style = self.style.clone();
+                style.code = true;
+                self.spans.push(Span {
+                    text: text.to_string(),
+                    style,
+                });
+            }
+            Event::InlineMath(text) => {
+                let mut style = self.style.clone();
+                style.code = true;
+                self.spans.push(Span {
+                    text: format!("${}$", text),
+                    style,
+                });
+            }
+            Event::DisplayMath(text) => {
+                self.flush_paragraph();
+                self.code_block("", &text);
+            }
+            Event::SoftBreak => self.push_text(" "),
+            Event::HardBreak => self.push_text("\n"),
+            Event::Rule => {
+                self.flush_paragraph();
+                self.rule();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let lower = html.to_lowercase();
+                if lower.contains("pagebreak") || lower.contains("page-break") {
+                    self.flush_paragraph();
+                    self.new_page();
+                } else if lower.starts_with("<br") {
+                    self.push_text("\n");
+                }
+            }
+            Event::FootnoteReference(label) => {
+                let style = self.style.clone();
+                self.spans.push(Span {
+                    text: format!("[{}]", label),
+                    style,
+                });
+            }
+            Event::TaskListMarker(checked) => {
+                self.pending_marker = Some(if checked { "[x]" } else { "[ ]" }.to_string());
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {}
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                let level = level as u8;
+                if level == 1 && self.config.page_break_before_h1 && !self.page_is_empty() {
+                    self.new_page();
+                }
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_paragraph();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code_block = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                self.list_stack.push(start);
+            }
+            Tag::Item => {
+                self.flush_paragraph();
+                let marker = match self.list_stack.last_mut() {
+                    Some(Some(n)) => {
+                        let marker = format!("{}.", n);
+                        *n += 1;
+                        marker
+                    }
+                    _ => "\u{2022}".to_string(),
+                };
+                self.pending_marker = Some(marker);
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.flush_paragraph();
+                if !self.seen_footnote {
+                    self.seen_footnote = true;
+                    self.rule();
+                }
+                self.footnote_depth += 1;
+                self.pending_marker = Some(format!("[{}]", label));
+            }
+            Tag::Table(alignments) => {
+                self.flush_paragraph();
+                self.table = Some(TableState {
+                    alignments,
+                    header: Vec::new(),
+                    rows: Vec::new(),
+                    current_row: Vec::new(),
+                });
+            }
+            Tag::TableHead | Tag::TableRow | Tag::TableCell => self.spans.clear(),
+            Tag::Emphasis => {
+                self.italic_depth += 1;
+                self.style.italic = true;
+            }
+            Tag::Strong => {
+                self.bold_depth += 1;
+                self.style.bold = true;
+            }
+            Tag::Strikethrough => self.style.strike = true,
+            Tag::Link { dest_url, .. } if is_external_url(&dest_url) => {
+                self.style.link = Some(dest_url.to_string());
+            }
+            Tag::Image { dest_url, .. } => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush_paragraph(),
+            TagEnd::Heading(_) => {
+                let level = self.heading.take().unwrap_or(1);
+                let spans = std::mem::take(&mut self.spans);
+                self.heading_block(level, spans);
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.y += line_height(self.body_size()) * 0.3;
+            }
+            TagEnd::List(_) => {
+                self.flush_paragraph();
+                self.list_stack.pop();
+                if self.list_stack.is_empty() {
+                    self.y += line_height(self.body_size()) * 0.3;
+                }
+            }
+            TagEnd::Item => self.flush_paragraph(),
+            TagEnd::FootnoteDefinition => {
+                self.flush_paragraph();
+                self.footnote_depth = self.footnote_depth.saturating_sub(1);
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.header = std::mem::take(&mut table.current_row);
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    let row = std::mem::take(&mut table.current_row);
+                    table.rows.push(row);
+                }
+            }
+            TagEnd::TableCell => {
+                let spans = std::mem::take(&mut self.spans);
+                if let Some(table) = self.table.as_mut() {
+                    table.current_row.push(spans);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.table_block(table);
+                }
+            }
+            TagEnd::Emphasis => {
+                self.italic_depth = self.italic_depth.saturating_sub(1);
+                self.style.italic = self.italic_depth > 0;
+            }
+            TagEnd::Strong => {
+                self.bold_depth = self.bold_depth.saturating_sub(1);
+                self.style.bold = self.bold_depth > 0;
+            }
+            TagEnd::Strikethrough => self.style.strike = false,
+            TagEnd::Link => self.style.link = None,
+            _ => {}
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let style = self.style.clone();
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+
+    /// Lay out the buffered inline spans as a paragraph at the current indent.
+    fn flush_paragraph(&mut self) {
+        if self.table.is_some() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        let text: String = spans.iter().map(|s| s.text.as_str()).collect();
+        let trimmed = text.trim();
+
+        if trimmed == "\\pagebreak" || trimmed == "\\newpage" {
+            self.new_page();
+            return;
+        }
+        if trimmed.is_empty() {
+            if let Some(marker) = self.pending_marker.take() {
+                // Empty list item: still show its marker.
+                self.paragraph(
+                    vec![Span {
+                        text: " ".to_string(),
+                        style: SpanStyle::default(),
+                    }],
+                    Some(marker),
+                );
+            }
+            return;
+        }
+
+        let marker = self.pending_marker.take();
+        self.paragraph(spans, marker);
+    }
+
+    fn paragraph(&mut self, spans: Vec<Span>, marker: Option<String>) {
+        let size = self.body_size();
+        let x = self.geometry().margin_left + self.indent();
+        let width = self.geometry().content_width() - self.indent();
+        let lines = break_lines(&spans, width, size);
+        let lh = line_height(size);
+
+        for (i, line) in lines.iter().enumerate() {
+            self.ensure_space(lh);
+            let baseline = self.y + lh * 0.75;
+            if i == 0 {
+                if let Some(marker) = &marker {
+                    let marker_width = text_width(marker, FontFace::Regular, size);
+                    self.push(DrawOp::Text {
+                        x: x - marker_width - 1.5,
+                        y: baseline,
+                        text: marker.clone(),
+                        face: FontFace::Regular,
+                        size,
+                        color: BLACK,
+                    });
+                }
+            }
+            self.quote_bars(lh);
+            self.draw_line(line, x, baseline, size, BLACK);
+            self.y += lh;
+        }
+
+        if self.list_stack.is_empty() || self.quote_depth > 0 {
+            self.y += lh * 0.5;
+        }
+    }
+
+    /// Draw blockquote bars next to the line that starts at the cursor.
+    fn quote_bars(&mut self, height: f32) {
+        for depth in 0..self.quote_depth {
+            let x = self.geometry().margin_left + depth as f32 * INDENT_STEP + 1.5;
+            self.push(DrawOp::Line {
+                x1: x,
+                y1: self.y,
+                x2: x,
+                y2: self.y + height,
+                width: 1.5,
+                color: RULE_GREY,
+            });
+        }
+    }
+
+    fn draw_line(&mut self, line: &TextLine, x: f32, baseline: f32, size: f32, color: Color) {
+        let mut cursor = x;
+        for run in &line.runs {
+            let face = face_for(&run.style);
+            let run_size = if run.style.code { size * 0.9 } else { size };
+            let run_color = if run.style.link.is_some() {
+                LINK_BLUE
+            } else {
+                color
+            };
+
+            if run.style.code {
+                self.push(DrawOp::FillRect {
+                    x: cursor - 0.3,
+                    y: baseline - run_size * PT_TO_MM * 0.8,
+                    w: run.width + 0.6,
+                    h: run_size * PT_TO_MM * 1.05,
+                    color: CODE_BACKGROUND,
+                });
+            }
+            self.push(DrawOp::Text {
+                x: cursor,
+                y: baseline,
+                text: run.text.clone(),
+                face,
+                size: run_size,
+                color: run_color,
+            });
+            if run.style.strike {
+                let mid = baseline - run_size * PT_TO_MM * 0.3;
+                self.push(DrawOp::Line {
+                    x1: cursor,
+                    y1: mid,
+                    x2: cursor + run.width,
+                    y2: mid,
+                    width: 0.5,
+                    color: run_color,
+                });
+            }
+            if let Some(uri) = &run.style.link {
+                self.push(DrawOp::Line {
+                    x1: cursor,
+                    y1: baseline + 0.5,
+                    x2: cursor + run.width,
+                    y2: baseline + 0.5,
+                    width: 0.4,
+                    color: LINK_BLUE,
+                });
+                self.push(DrawOp::Link {
+                    x: cursor,
+                    y: baseline - size * PT_TO_MM * 0.8,
+                    w: run.width,
+                    h: size * PT_TO_MM * 1.1,
+                    uri: uri.clone(),
+                });
+            }
+            cursor += run.width;
+        }
+    }
+
+    fn heading_block(&mut self, level: u8, spans: Vec<Span>) {
+        let scale = match level {
+            1 => 2.0,
+            2 => 1.6,
+            3 => 1.3,
+            4 => 1.15,
+            _ => 1.0,
+        };
+        let size = self.config.font_size * scale;
+        let lh = line_height(size);
+        let spans: Vec<Span> = spans
+            .into_iter()
+            .map(|mut s| {
+                s.style.bold = true;
+                s
+            })
+            .collect();
+        let text: String = spans.iter().map(|s| s.text.as_str()).collect();
+
+        if !self.page_is_empty() {
+            self.y += lh * 0.4;
+        }
+        let x = self.geometry().margin_left + self.indent();
+        let width = self.geometry().content_width() - self.indent();
+        let lines = break_lines(&spans, width, size);
+
+        // Keep the heading together with at least one line of the next block.
+        self.ensure_space(lh * lines.len() as f32 + line_height(self.config.font_size) * 2.0);
+        self.outline.push(OutlineEntry {
+            level,
+            text: text.trim().to_string(),
+            page: self.pages.len() - 1,
+        });
+
+        for line in &lines {
+            let baseline = self.y + lh * 0.75;
+            self.draw_line(line, x, baseline, size, BLACK);
+            self.y += lh;
+        }
+        if level <= 2 {
+            let y = self.y + 0.5;
+            self.push(DrawOp::Line {
+                x1: x,
+                y1: y,
+                x2: self.geometry().width - self.geometry().margin_right,
+                y2: y,
+                width: if level == 1 { 0.8 } else { 0.4 },
+                color: RULE_GREY,
+            });
+            self.y += 1.5;
+        }
+        self.y += lh * 0.25;
+    }
+
+    fn code_block(&mut self, lang: &str, code: &str) {
+        let size = self.config.font_size * 0.85;
+        let lh = line_height(size);
+        let x = self.geometry().margin_left + self.indent();
+        let width = self.geometry().content_width() - self.indent();
+        let char_width = text_width("M", FontFace::Mono, size);
+        let max_chars = (((width - CELL_PADDING * 2.0) / char_width).floor() as usize).max(1);
+
+        let highlighted = if self.config.highlight_code && !lang.is_empty() {
+            highlight_lines(code, lang)
+        } else {
+            code.lines()
+                .map(|l| vec![(TokenKind::Plain, l.to_string())])
+                .collect()
+        };
+
+        self.y += lh * 0.2;
+        for tokens in highlighted {
+            // Wrap long lines at the character limit, keeping token colours.
+            let mut visual_lines: Vec<Vec<(TokenKind, String)>> = vec![Vec::new()];
+            let mut count = 0;
+            for (kind, text) in tokens {
+                for ch in text.chars() {
+                    if count == max_chars {
+                        visual_lines.push(Vec::new());
+                        count = 0;
+                    }
+                    let line = visual_lines.last_mut().expect("at least one line");
+                    match line.last_mut() {
+                        Some((last_kind, last_text)) if *last_kind == kind => last_text.push(ch),
+                        _ => line.push((kind, ch.to_string())),
+                    }
+                    count += 1;
+                }
+            }
+
+            for visual in visual_lines {
+                self.ensure_space(lh);
+                self.push(DrawOp::FillRect {
+                    x,
+                    y: self.y,
+                    w: width,
+                    h: lh,
+                    color: CODE_BACKGROUND,
+                });
+                self.quote_bars(lh);
+                let baseline = self.y + lh * 0.75;
+                let mut cursor = x + CELL_PADDING;
+                for (kind, text) in visual {
+                    let (face, color) = token_style(kind);
+                    let w = text_width(&text, face, size);
+                    self.push(DrawOp::Text {
+                        x: cursor,
+                        y: baseline,
+                        text,
+                        face,
+                        size,
+                        color,
+                    });
+                    cursor += w;
+                }
+                self.y += lh;
+            }
+        }
+        self.y += lh * 0.6;
+    }
+
+    fn rule(&mut self) {
+        let lh = line_height(self.config.font_size);
+        self.ensure_space(lh);
+        let y = self.y + lh / 2.0;
+        self.push(DrawOp::Line {
+            x1: self.geometry().margin_left,
+            y1: y,
+            x2: self.geometry().width - self.geometry().margin_right,
+            y2: y,
+            width: 0.5,
+            color: RULE_GREY,
+        });
+        self.y += lh;
+    }
+
+    fn image_block(&mut self, dest: &str, alt: &str) {
+        let size = self.body_size();
+        let loaded = if is_external_url(dest) {
+            None
+        } else {
+            let path = self.config.base_dir.join(percent_decode(dest));
+            image::open(&path).ok().map(flatten_alpha)
+        };
+
+        let Some(img) = loaded else {
+            // Unsupported or remote image: show its alt text instead.
+            let label = if alt.is_empty() { dest } else { alt };
+            self.paragraph(
+                vec![Span {
+                    text: format!("[image: {}]", label),
+                    style: SpanStyle {
+                        italic: true,
+                        ..SpanStyle::default()
+                    },
+                }],
+                None,
+            );
+            return;
+        };
+
+        let (px_w, px_h) = img.dimensions();
+        let max_w = self.geometry().content_width() - self.indent();
+        let max_h = self.geometry().content_bottom() - self.geometry().margin_top;
+        let mut w = px_w as f32 / IMAGE_DPI * 25.4;
+        let mut h = px_h as f32 / IMAGE_DPI * 25.4;
+        let scale = (max_w / w).min(max_h / h).min(1.0);
+        w *= scale;
+        h *= scale;
+
+        self.ensure_space(h);
+        let index = self.images.len();
+        self.images.push(img);
+        let x = self.geometry().margin_left + self.indent();
+        self.push(DrawOp::Image {
+            index,
+            x,
+            y: self.y,
+            w,
+            h,
+        });
+        self.y += h + line_height(size) * 0.5;
+    }
+
+    fn table_block(&mut self, table: TableState) {
+        let size = self.body_size() * 0.95;
+        let lh = line_height(size);
+        let columns = table
+            .rows
+            .iter()
+            .map(|r| r.len())
+            .chain(std::iter::once(table.header.len()))
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        let x0 = self.geometry().margin_left + self.indent();
+        let total_width = self.geometry().content_width() - self.indent();
+
+        // Column widths proportional to natural content width.
+        let mut natural = vec![10.0_f32; columns];
+        for row in std::iter::once(&table.header).chain(table.rows.iter()) {
+            for (i, cell) in row.iter().enumerate() {
+                let w: f32 = cell
+                    .iter()
+                    .map(|s| text_width(&s.text, face_for(&s.style), size))
+                    .sum::<f32>()
+                    + CELL_PADDING * 2.0;
+                natural[i] = natural[i].max(w);
+            }
+        }
+        let natural_total: f32 = natural.iter().sum();
+        let widths: Vec<f32> = if natural_total <= total_width {
+            natural
+        } else {
+            natural
+                .iter()
+                .map(|w| w / natural_total * total_width)
+                .collect()
+        };
+        let table_width: f32 = widths.iter().sum();
+
+        let draw_row = |ts: &mut Self, cells: &[Vec<Span>], header: bool| {
+            let laid: Vec<Vec<TextLine>> = (0..columns)
+                .map(|i| {
+                    let mut spans = cells.get(i).cloned().unwrap_or_default();
+                    if header {
+                        for s in spans.iter_mut() {
+                            s.style.bold = true;
+                        }
+                    }
+                    break_lines(&spans, widths[i] - CELL_PADDING * 2.0, size)
+                })
+                .collect();
+            let row_lines = laid.iter().map(|l| l.len()).max().unwrap_or(1).max(1);
+            let row_height = row_lines as f32 * lh + CELL_PADDING;
+
+            ts.ensure_space(row_height);
+            let top = ts.y;
+            if header {
+                ts.push(DrawOp::FillRect {
+                    x: x0,
+                    y: top,
+                    w: table_width,
+                    h: row_height,
+                    color: TABLE_HEADER_BACKGROUND,
+                });
+            }
+
+            let mut cx = x0;
+            for (i, lines) in laid.iter().enumerate() {
+                let cell_inner = widths[i] - CELL_PADDING * 2.0;
+                for (j, line) in lines.iter().enumerate() {
+                    let offset = match table.alignments.get(i) {
+                        Some(Alignment::Center) => (cell_inner - line.width) / 2.0,
+                        Some(Alignment::Right) => cell_inner - line.width,
+                        _ => 0.0,
+                    };
+                    let baseline = top + CELL_PADDING / 2.0 + j as f32 * lh + lh * 0.75;
+                    ts.draw_line(
+                        line,
+                        cx + CELL_PADDING + offset.max(0.0),
+                        baseline,
+                        size,
+                        BLACK,
+                    );
+                }
+                cx += widths[i];
+            }
+
+            // Cell borders.
+            let bottom = top + row_height;
+            let mut bx = x0;
+            for w in std::iter::once(0.0).chain(widths.iter().copied()) {
+                bx += w;
+                ts.push(DrawOp::Line {
+                    x1: bx,
+                    y1: top,
+                    x2: bx,
+                    y2: bottom,
+                    width: 0.3,
+                    color: GREY,
+                });
+            }
+            for y in [top, bottom] {
+                ts.push(DrawOp::Line {
+                    x1: x0,
+                    y1: y,
+                    x2: x0 + table_width,
+                    y2: y,
+                    width: 0.3,
+                    color: GREY,
+                });
+            }
+            ts.y = bottom;
+        };
+
+        if !table.header.is_empty() {
+            draw_row(self, &table.header, true);
+        }
+        for row in &table.rows {
+            draw_row(self, row, false);
+        }
+        self.y += lh * 0.6;
+    }
+}
+
+/// Composite images with transparency onto white, since the PDF writer
+/// embeds plain RGB data.
+fn flatten_alpha(img: DynamicImage) -> DynamicImage {
+    if !img.color().has_alpha() {
+        return img;
+    }
+    let rgba = img.to_rgba8();
+    let (w, h) = rgba.dimensions();
+    let mut out = RgbImage::new(w, h);
+    for (x, y, px) in rgba.enumerate_pixels() {
+        let a = px[3] as f32 / 255.0;
+        let blend = |c: u8| (c as f32 * a + 255.0 * (1.0 - a)).round() as u8;
+        out.put_pixel(x, y, Rgb([blend(px[0]), blend(px[1]), blend(px[2])]));
+    }
+    DynamicImage::ImageRgb8(out)
+}
+
+fn token_style(kind: TokenKind) -> (FontFace, Color) {
+    match kind {
+        TokenKind::Plain => (FontFace::Mono, Color(0.15, 0.15, 0.15)),
+        TokenKind::Keyword => (FontFace::MonoBold, Color(0.55, 0.1, 0.55)),
+        TokenKind::String => (FontFace::Mono, Color(0.1, 0.5, 0.1)),
+        TokenKind::Comment => (FontFace::Mono, Color(0.5, 0.5, 0.5)),
+        TokenKind::Number => (FontFace::Mono, Color(0.1, 0.35, 0.7)),
+    }
+}
+
+fn face_for(style: &SpanStyle) -> FontFace {
+    match (style.code, style.bold, style.italic) {
+        (true, true, _) => FontFace::MonoBold,
+        (true, false, _) => FontFace::Mono,
+        (false, true, true) => FontFace::BoldItalic,
+        (false, true, false) => FontFace::Bold,
+        (false, false, true) => FontFace::Italic,
+        (false, false, false) => FontFace::Regular,
+    }
+}
+
+pub fn line_height(size: f32) -> f32 {
+    size * PT_TO_MM * 1.4
+}
+
+/// Greedy line breaking of styled spans into lines no wider than `width` mm.
+/// A `"\n"` inside a span forces a break.
+fn break_lines(spans: &[Span], width: f32, size: f32) -> Vec<TextLine> {
+    let mut lines: Vec<TextLine> = vec![TextLine::default()];
+
+    for span in spans {
+        let face = face_for(&span.style);
+        let run_size = if span.style.code { size * 0.9 } else { size };
+        let text = pdf_safe_text(&span.text);
+
+        for (seg_idx, segment) in text.split('\n').enumerate() {
+            if seg_idx > 0 {
+                lines.push(TextLine::default());
+            }
+            for word in split_keeping_spaces(segment) {
+                let mut w = text_width(word, face, run_size);
+                let line = lines.last_mut().expect("at least one line");
+                let is_space = word.trim().is_empty();
+
+                if line.width + w > width && !line.runs.is_empty() {
+                    if is_space {
+                        // Never start a line with whitespace.
+                        continue;
+                    }
+                    trim_trailing_space(line, face, run_size);
+                    lines.push(TextLine::default());
+                } else if is_space && line.runs.is_empty() {
+                    continue;
+                }
+
+                // Hard-split words that are wider than the whole line.
+                let mut remaining = word.to_string();
+                while w > width && remaining.chars().count() > 1 {
+                    let piece = truncate_to_width(&remaining, face, run_size, width);
+                    let piece = if piece.is_empty() {
+                        remaining.chars().next().expect("non-empty").to_string()
+                    } else {
+                        piece
+                    };
+                    let piece_width = text_width(&piece, face, run_size);
+                    append_run(
+                        lines.last_mut().expect("at least one line"),
+                        &piece,
+                        &span.style,
+                        piece_width,
+                    );
+                    remaining = remaining[piece.len()..].to_string();
+                    lines.push(TextLine::default());
+                    w = text_width(&remaining, face, run_size);
+                }
+                append_run(
+                    lines.last_mut().expect("at least one line"),
+                    &remaining,
+                    &span.style,
+                    w,
+                );
+            }
+        }
+    }
+
+    for line in lines.iter_mut() {
+        if let Some(last) = line.runs.last() {
+            let face = face_for(&last.style);
+            let size = if last.style.code { size * 0.9 } else { size };
+            trim_trailing_space(line, face, size);
+        }
+    }
+    if lines.len() > 1 && lines.last().map(|l| l.runs.is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+    lines
+}
+
+fn append_run(line: &mut TextLine, text: &str, style: &SpanStyle, width: f32) {
+    if text.is_empty() {
+        return;
+    }
+    line.width += width;
+    match line.runs.last_mut() {
+        Some(run) if &run.style == style => {
+            run.text.push_str(text);
+            run.width += width;
+        }
+        _ => line.runs.push(Run {
+            text: text.to_string(),
+            style: style.clone(),
+            width,
+        }),
+    }
+}
+
+fn trim_trailing_space(line: &mut TextLine, face: FontFace, size: f32) {
+    if let Some(run) = line.runs.last_mut() {
+        let trimmed_len = run.text.trim_end().len();
+        if trimmed_len < run.text.len() {
+            let removed = text_width(&run.text[trimmed_len..], face, size);
+            run.text.truncate(trimmed_len);
+            run.width -= removed;
+            line.width -= removed;
+        }
+    }
+}
+
+/// Split text into alternating words and whitespace runs.
+fn split_keeping_spaces(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_space: Option<bool> = None;
+    for (i, ch) in text.char_indices() {
+        let space = ch == ' ' || ch == '\t';
+        if let Some(prev) = in_space {
+            if prev != space {
+                parts.push(&text[start..i]);
+                start = i;
+            }
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        parts.push(&text[start..]);
+    }
+    parts
+}
+
+fn truncate_to_width(text: &str, face: FontFace, size: f32, width: f32) -> String {
+    let mut out = String::new();
+    let mut w = 0.0;
+    for ch in text.chars() {
+        let cw = char_width(ch, face) / 1000.0 * size * PT_TO_MM;
+        if w + cw > width {
+            break;
+        }
+        w += cw;
+        out.push(ch);
+    }
+    out
+}
+
+/// Replace characters the built-in PDF fonts cannot encode (anything outside
+/// Windows-1252) with `?`, so measurement matches what is rendered.
+pub fn pdf_safe_text(text: &str) -> String {
+    text.chars()
+        .map(|c| if is_win_ansi(c) { c } else { '?' })
+        .collect()
+}
+
+/// The distinct characters of `text` that `pdf_safe_text` replaces.
+pub fn unsupported_chars(text: &str) -> BTreeSet<char> {
+    text.chars()
+        .filter(|&c| !c.is_control() && !is_win_ansi(c))
+        .collect()
+}
+
+fn is_win_ansi(c: char) -> bool {
+    matches!(c, '\n' | ' '..='~' | '\u{A0}'..='\u{FF}') || "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ".contains(c)
+}
+
+/// Width of `text` in mm for one of the built-in fonts.
+pub fn text_width(text: &str, face: FontFace, size: f32) -> f32 {
+    text.chars().map(|c| char_width(c, face)).sum::<f32>() / 1000.0 * size * PT_TO_MM
+}
+
+/// Glyph advance in 1/1000 em for the standard Helvetica / Courier metrics.
+fn char_width(c: char, face: FontFace) -> f32 {
+    match face {
+        FontFace::Mono | FontFace::MonoBold => 600.0,
+        FontFace::Bold | FontFace::BoldItalic => helvetica_width(c, &HELVETICA_BOLD_WIDTHS),
+        FontFace::Regular | FontFace::Italic => helvetica_width(c, &HELVETICA_WIDTHS),
+    }
+}
+
+fn helvetica_width(c: char, table: &[u16; 95]) -> f32 {
+    let code = c as u32;
+    if (32..=126).contains(&code) {
+        table[(code - 32) as usize] as f32
+    } else {
+        match c {
+            '\t' => 1112.0,
+            '•' => 350.0,
+            '–' => 556.0,
+            '—' => 1000.0,
+            '‘' | '’' => 222.0,
+            '“' | '”' => 333.0,
+            '…' => 1000.0,
+            _ => 556.0,
+        }
+    }
+}
+
+/// Helvetica advance widths for ASCII 32..=126 (from the Adobe AFM files).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths for ASCII 32..=126.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
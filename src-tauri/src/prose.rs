@@ -0,0 +1,734 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{parser_options, LineIndex};
+
+/// Workspace style guide for the prose checks.
+const CONFIG_FILE: &str = ".prose.json";
+const DEFAULT_MAX_SENTENCE_WORDS: usize = 30;
+const DEFAULT_MAX_GRADE: f64 = 12.0;
+/// Paragraphs shorter than this get scores but are never flagged.
+const MIN_GRADED_WORDS: usize = 20;
+
+const WEASEL_WORDS: &[&str] = &[
+    "many",
+    "various",
+    "very",
+    "fairly",
+    "several",
+    "extremely",
+    "exceedingly",
+    "quite",
+    "remarkably",
+    "few",
+    "surprisingly",
+    "mostly",
+    "largely",
+    "huge",
+    "tiny",
+    "is a number",
+    "are a number",
+    "excellent",
+    "interestingly",
+    "significantly",
+    "substantially",
+    "clearly",
+    "vast",
+    "relatively",
+    "completely",
+    "literally",
+    "basically",
+    "arguably",
+    "virtually",
+    "somewhat",
+    "really",
+];
+
+const CLICHES: &[&str] = &[
+    "a chip off the old block",
+    "a clean slate",
+    "a dark and stormy night",
+    "a far cry",
+    "a level playing field",
+    "a perfect storm",
+    "add insult to injury",
+    "at the end of the day",
+    "avoid like the plague",
+    "back to square one",
+    "back to the drawing board",
+    "ballpark figure",
+    "best of both worlds",
+    "bite the bullet",
+    "by the same token",
+    "cut to the chase",
+    "easier said than done",
+    "every cloud has a silver lining",
+    "few and far between",
+    "for all intents and purposes",
+    "game changer",
+    "hit the ground running",
+    "in the nick of time",
+    "it goes without saying",
+    "last but not least",
+    "low-hanging fruit",
+    "move the needle",
+    "needle in a haystack",
+    "at this point in time",
+    "outside the box",
+    "paradigm shift",
+    "pushing the envelope",
+    "think outside the box",
+    "tip of the iceberg",
+    "touch base",
+    "when all is said and done",
+    "win-win",
+    "not rocket science",
+    "the elephant in the room",
+    "only time will tell",
+    "the calm before the storm",
+    "par for the course",
+    "read between the lines",
+    "the bottom line",
+];
+
+/// Irregular past participles, separated by whitespace.
+const IRREGULAR_PARTICIPLES: &str = "\
+    awoken been born beat become begun bent bet bid bound bitten bled blown broken bred brought \
+    broadcast built burnt burst bought cast caught chosen clung come cost crept cut dealt dug \
+    done drawn dreamt drunk driven eaten fallen fed felt fought found fled flung flown \
+    forbidden forecast forgotten forgiven frozen given gone ground grown hung heard hidden hit \
+    held hurt kept knelt known laid led left lent let lain lit lost made meant met paid put \
+    quit read ridden rung risen run said seen sought sold sent set shaken shed shot shown \
+    shrunk shut sung sunk sat slept slid slung spoken spent spilt spun spread sprung stood \
+    stolen stuck stung stunk struck sworn swept swollen swum swung taken taught torn told \
+    thought thrown thrust trodden understood upset woken worn woven wept won wound written";
+
+/// Abbreviations whose trailing period does not end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "e.g", "i.e", "etc", "vs", "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "fig", "no",
+    "cf", "approx", "al",
+];
+
+static WORD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[\p{L}\p{N}]+(?:[-'’][\p{L}\p{N}]+)*").expect("invalid word regex"));
+static SENTENCE_END_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"[.!?]+["')\]’”]*(?:\s+|$)"#).expect("invalid sentence regex"));
+static PASSIVE_RE: Lazy<Regex> = Lazy::new(|| {
+    let participles = IRREGULAR_PARTICIPLES
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("|");
+    Regex::new(&format!(
+        r"(?i)\b(?:am|are|were|being|is|been|was|be)\b(?:\s+\w+ly)?\s+(?:\w+ed|{})\b",
+        participles
+    ))
+    .expect("invalid passive voice regex")
+});
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProseDiagnostic {
+    /// Rule name, e.g. `passive-voice`, or the name of a custom rule.
+    pub rule: String,
+    pub message: String,
+    /// 1-based line and UTF-16 column where the flagged text starts.
+    pub line: usize,
+    pub column: usize,
+    /// Exclusive end of the flagged text.
+    pub end_line: usize,
+    pub end_column: usize,
+    /// Replacement for the flagged text, when the rule has one.
+    pub suggestion: Option<String>,
+}
+
+/// Readability scores. Grade levels are US school grades.
+#[derive(Serialize, Clone, Debug)]
+pub struct Readability {
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub gunning_fog: f64,
+    pub coleman_liau: f64,
+    pub automated_readability: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ParagraphStats {
+    pub line: usize,
+    pub end_line: usize,
+    pub words: usize,
+    pub sentences: usize,
+    pub readability: Readability,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProseReport {
+    pub diagnostics: Vec<ProseDiagnostic>,
+    pub paragraphs: Vec<ParagraphStats>,
+    pub words: usize,
+    pub sentences: usize,
+    /// Scores over all paragraphs, `None` for a note without prose.
+    pub readability: Option<Readability>,
+}
+
+/// Prose rules in `.prose.json`: `"default"` toggles all built-in rules and
+/// each rule is either a boolean or an object of parameters. Custom
+/// `substitutions` and `existence` rules encode a house style guide:
+///
+/// ```json
+/// {
+///   "weasel-words": { "ignore": ["very"] },
+///   "sentence-length": { "max": 25 },
+///   "readability": { "max_grade": 10 },
+///   "substitutions": { "utilize": "use", "in order to": "to" },
+///   "existence": [{ "name": "no-minimizers", "tokens": ["simply", "just"],
+///                   "message": "Don't tell readers it's easy" }]
+/// }
+/// ```
+#[derive(Debug, Clone)]
+struct ProseConfig {
+    default_enabled: bool,
+    rules: Map<String, Value>,
+}
+
+impl ProseConfig {
+    fn load(folder: Option<&Path>) -> Self {
+        let mut rules = folder
+            .and_then(|f| fs::read_to_string(f.join(CONFIG_FILE)).ok())
+            .and_then(|text| serde_json::from_str::<Map<String, Value>>(&text).ok())
+            .unwrap_or_default();
+        let default_enabled = rules
+            .remove("default")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        Self {
+            default_enabled,
+            rules,
+        }
+    }
+
+    fn enabled(&self, rule: &str) -> bool {
+        match self.rules.get(rule) {
+            Some(Value::Bool(enabled)) => *enabled,
+            Some(Value::Object(_)) => true,
+            _ => self.default_enabled,
+        }
+    }
+
+    fn param(&self, rule: &str, key: &str) -> Option<&Value> {
+        self.rules.get(rule)?.as_object()?.get(key)
+    }
+
+    fn param_list(&self, rule: &str, key: &str) -> Vec<String> {
+        string_list(self.param(rule, key))
+    }
+
+    /// Built-in word list adjusted by the rule's `extra` and `ignore` keys.
+    fn word_list(&self, rule: &str, builtin: &[&str]) -> Vec<String> {
+        let ignore: Vec<String> = self
+            .param_list(rule, "ignore")
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect();
+        builtin
+            .iter()
+            .map(|w| w.to_string())
+            .chain(self.param_list(rule, "extra"))
+            .filter(|w| !ignore.contains(&w.to_lowercase()))
+            .collect()
+    }
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A phrase rule compiled into one regex.
+struct PhraseRule {
+    rule: String,
+    regex: Regex,
+    message: String,
+    /// Replacements by lowercase phrase, for substitution rules.
+    replacements: Vec<(String, String)>,
+}
+
+impl PhraseRule {
+    fn new(rule: &str, phrases: &[String], message: &str, ignore_case: bool) -> Option<Self> {
+        let mut alternatives: Vec<String> = phrases
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                p.split_whitespace()
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(r"\s+")
+            })
+            .collect();
+        if alternatives.is_empty() {
+            return None;
+        }
+        // Longest first so that "is a number" wins over shorter overlaps.
+        alternatives.sort_by_key(|a| std::cmp::Reverse(a.len()));
+        let flags = if ignore_case { "(?i)" } else { "" };
+        let pattern = format!(r"{}\b(?:{})\b", flags, alternatives.join("|"));
+        Some(Self {
+            rule: rule.to_string(),
+            regex: Regex::new(&pattern).ok()?,
+            message: message.to_string(),
+            replacements: Vec::new(),
+        })
+    }
+}
+
+/// Check the prose of a markdown note against the workspace style guide and
+/// score the readability of each paragraph.
+#[tauri::command]
+pub fn analyze_prose(content: String, folder: Option<String>) -> Result<ProseReport, AppError> {
+    let config = ProseConfig::load(folder.as_deref().map(Path::new));
+    Ok(analyze_markdown(&content, &config))
+}
+
+fn analyze_markdown(content: &str, config: &ProseConfig) -> ProseReport {
+    let (_, body) = split_frontmatter(content);
+    let offset = content.len() - body.len();
+    let index = LineIndex::new(content);
+    let rules = phrase_rules(config);
+    let max_sentence_words = config
+        .param("sentence-length", "max")
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(DEFAULT_MAX_SENTENCE_WORDS);
+    let max_grade = config
+        .param("readability", "max_grade")
+        .and_then(Value::as_f64)
+        .unwrap_or(DEFAULT_MAX_GRADE);
+
+    let mut diagnostics = Vec::new();
+    let mut paragraphs = Vec::new();
+    let mut totals = Counts::default();
+    for unit in prose_units(body) {
+        let mut flag =
+            |rule: &str, message: String, range: Range<usize>, suggestion: Option<String>| {
+                let start = index.position(offset + unit.source(range.start));
+                let end = index.position(offset + unit.source_end(range.end));
+                diagnostics.push(ProseDiagnostic {
+                    rule: rule.to_string(),
+                    message,
+                    line: start.0,
+                    column: start.1,
+                    end_line: end.0,
+                    end_column: end.1,
+                    suggestion,
+                });
+            };
+        let text = unit.text.as_str();
+
+        for rule in &rules {
+            for m in rule.regex.find_iter(text) {
+                let phrase = m.as_str();
+                let lower = phrase
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .to_lowercase();
+                let suggestion = rule
+                    .replacements
+                    .iter()
+                    .find(|(from, _)| *from == lower)
+                    .map(|(_, to)| to.clone());
+                let message = match &suggestion {
+                    Some(to) => format!("Use \"{}\" instead of \"{}\"", to, phrase),
+                    None => rule.message.replace("{}", phrase),
+                };
+                flag(&rule.rule, message, m.range(), suggestion);
+            }
+        }
+
+        if config.enabled("passive-voice") {
+            for m in PASSIVE_RE.find_iter(text) {
+                flag(
+                    "passive-voice",
+                    format!("\"{}\" may be passive voice", m.as_str()),
+                    m.range(),
+                    None,
+                );
+            }
+        }
+
+        if config.enabled("repeated-words") {
+            let words: Vec<_> = WORD_RE.find_iter(text).collect();
+            for pair in words.windows(2) {
+                let gap = &text[pair[0].end()..pair[1].start()];
+                if !gap.is_empty()
+                    && gap.chars().all(char::is_whitespace)
+                    && pair[0].as_str().to_lowercase() == pair[1].as_str().to_lowercase()
+                    && pair[0].as_str().chars().any(char::is_alphabetic)
+                {
+                    flag(
+                        "repeated-words",
+                        format!("\"{}\" is repeated", pair[1].as_str()),
+                        pair[0].start()..pair[1].end(),
+                        Some(pair[0].as_str().to_string()),
+                    );
+                }
+            }
+        }
+
+        if !unit.paragraph {
+            continue;
+        }
+        let sentences = split_sentences(text);
+        if config.enabled("sentence-length") {
+            for sentence in &sentences {
+                let count = WORD_RE.find_iter(&text[sentence.clone()]).count();
+                if count > max_sentence_words {
+                    flag(
+                        "sentence-length",
+                        format!("Sentence has {} words (max {})", count, max_sentence_words),
+                        sentence.clone(),
+                        None,
+                    );
+                }
+            }
+        }
+
+        let counts = Counts::of(text, sentences.len());
+        if counts.words == 0 {
+            continue;
+        }
+        let readability = counts.readability();
+        if config.enabled("readability")
+            && counts.words >= MIN_GRADED_WORDS
+            && readability.flesch_kincaid_grade > max_grade
+        {
+            flag(
+                "readability",
+                format!(
+                    "Paragraph reads at grade {} (max {})",
+                    readability.flesch_kincaid_grade, max_grade
+                ),
+                0..text.len(),
+                None,
+            );
+        }
+        let start = index.position(offset + unit.source(0)).0;
+        let end = index.position(offset + unit.source_end(text.len())).0;
+        paragraphs.push(ParagraphStats {
+            line: start,
+            end_line: end,
+            words: counts.words,
+            sentences: counts.sentences,
+            readability,
+        });
+        totals.add(&counts);
+    }
+
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    ProseReport {
+        diagnostics,
+        paragraphs,
+        words: totals.words,
+        sentences: totals.sentences,
+        readability: (totals.words > 0).then(|| totals.readability()),
+    }
+}
+
+fn phrase_rules(config: &ProseConfig) -> Vec<PhraseRule> {
+    let mut rules = Vec::new();
+    if config.enabled("weasel-words") {
+        let words = config.word_list("weasel-words", WEASEL_WORDS);
+        rules.extend(PhraseRule::new(
+            "weasel-words",
+            &words,
+            "\"{}\" is a weasel word",
+            true,
+        ));
+    }
+    if config.enabled("cliches") {
+        let phrases = config.word_list("cliches", CLICHES);
+        rules.extend(PhraseRule::new(
+            "cliches",
+            &phrases,
+            "\"{}\" is a cliché",
+            true,
+        ));
+    }
+    if let Some(Value::Object(map)) = config.rules.get("substitutions") {
+        let pairs: Vec<(String, String)> = map
+            .iter()
+            .filter_map(|(from, to)| Some((from.to_lowercase(), to.as_str()?.to_string())))
+            .collect();
+        let phrases: Vec<String> = pairs.iter().map(|(from, _)| from.clone()).collect();
+        if let Some(mut rule) = PhraseRule::new("substitutions", &phrases, "", true) {
+            rule.replacements = pairs;
+            rules.push(rule);
+        }
+    }
+    let existence = match config.rules.get("existence") {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(item @ Value::Object(_)) => vec![item],
+        _ => Vec::new(),
+    };
+    for item in existence {
+        let name = item
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("existence");
+        let message = item
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Avoid \"{}\"");
+        let ignore_case = item
+            .get("ignore_case")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let tokens = string_list(item.get("tokens"));
+        rules.extend(PhraseRule::new(name, &tokens, message, ignore_case));
+    }
+    rules
+}
+
+/// A run of prose from one paragraph, heading, list item or table cell, with
+/// inline markup removed.
+struct ProseUnit {
+    text: String,
+    /// `(text offset, source offset, length, exact)`; inexact segments (such
+    /// as escapes and entities) map every offset to their source start.
+    segments: Vec<(usize, usize, usize, bool)>,
+    /// Paragraphs get sentence and readability checks; headings and table
+    /// cells only get phrase checks.
+    paragraph: bool,
+}
+
+impl ProseUnit {
+    fn segment(&self, offset: usize) -> &(usize, usize, usize, bool) {
+        let i = self
+            .segments
+            .partition_point(|(start, ..)| *start <= offset)
+            .saturating_sub(1);
+        &self.segments[i]
+    }
+
+    fn source(&self, offset: usize) -> usize {
+        let &(start, source, len, exact) = self.segment(offset);
+        if exact {
+            source + (offset - start).min(len)
+        } else {
+            source
+        }
+    }
+
+    /// Source offset of an exclusive end, kept inside the preceding segment.
+    fn source_end(&self, offset: usize) -> usize {
+        let last = self.text[..offset]
+            .chars()
+            .next_back()
+            .map_or(0, char::len_utf8);
+        self.source(offset - last) + last
+    }
+}
+
+fn prose_units(body: &str) -> Vec<ProseUnit> {
+    let mut units = Vec::new();
+    let mut current: Option<ProseUnit> = None;
+    let mut skip_depth = 0usize;
+
+    let flush = |current: &mut Option<ProseUnit>, units: &mut Vec<ProseUnit>| {
+        if let Some(unit) = current.take() {
+            if unit.text.chars().any(char::is_alphanumeric) {
+                units.push(unit);
+            }
+        }
+    };
+    let start = |paragraph: bool| ProseUnit {
+        text: String::new(),
+        segments: Vec::new(),
+        paragraph,
+    };
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::Image { .. }) => skip_depth += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::HtmlBlock | TagEnd::Image) => {
+                skip_depth = skip_depth.saturating_sub(1)
+            }
+            Event::Start(Tag::Paragraph | Tag::Item) => {
+                flush(&mut current, &mut units);
+                current = Some(start(true));
+            }
+            Event::Start(Tag::Heading { .. } | Tag::TableCell) => {
+                flush(&mut current, &mut units);
+                current = Some(start(false));
+            }
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Item | TagEnd::Heading(_) | TagEnd::TableCell,
+            ) => flush(&mut current, &mut units),
+            Event::Text(text) if skip_depth == 0 => {
+                let unit = current.get_or_insert_with(|| start(true));
+                let exact = text.len() == range.len();
+                unit.segments
+                    .push((unit.text.len(), range.start, text.len(), exact));
+                unit.text.push_str(&text);
+            }
+            Event::SoftBreak | Event::HardBreak | Event::Code(_) | Event::InlineMath(_)
+                if skip_depth == 0 =>
+            {
+                // Keep words on either side apart without adding a word.
+                if let Some(unit) = current.as_mut() {
+                    unit.segments.push((unit.text.len(), range.start, 1, false));
+                    unit.text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    flush(&mut current, &mut units);
+    units
+}
+
+/// Sentence ranges in `text`, trimmed of surrounding whitespace.
+fn split_sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for m in SENTENCE_END_RE.find_iter(text) {
+        let before = &text[start..m.start()];
+        let last_word = before
+            .rsplit(|c: char| c.is_whitespace() || c == '(')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let is_initial = last_word.chars().count() == 1
+            && last_word.chars().all(char::is_alphabetic)
+            && m.as_str().starts_with('.');
+        if m.end() < text.len() && (ABBREVIATIONS.contains(&last_word.as_str()) || is_initial) {
+            continue;
+        }
+        let end = m.start() + m.as_str().trim_end().len();
+        push_trimmed(text, start..end, &mut sentences);
+        start = m.end();
+    }
+    push_trimmed(text, start..text.len(), &mut sentences);
+    sentences
+}
+
+fn push_trimmed(text: &str, range: Range<usize>, sentences: &mut Vec<Range<usize>>) {
+    let slice = &text[range.clone()];
+    let leading = slice.len() - slice.trim_start().len();
+    let trimmed = slice.trim();
+    if trimmed.chars().any(char::is_alphanumeric) {
+        let start = range.start + leading;
+        sentences.push(start..start + trimmed.len());
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    words: usize,
+    sentences: usize,
+    syllables: usize,
+    /// Words of three or more syllables.
+    complex_words: usize,
+    letters: usize,
+}
+
+impl Counts {
+    fn of(text: &str, sentences: usize) -> Self {
+        let mut counts = Self {
+            sentences: sentences.max(1),
+            ..Self::default()
+        };
+        for word in WORD_RE.find_iter(text).map(|m| m.as_str()) {
+            let syllables = count_syllables(word);
+            counts.words += 1;
+            counts.syllables += syllables;
+            counts.complex_words += usize::from(syllables >= 3);
+            counts.letters += word.chars().filter(|c| c.is_alphanumeric()).count();
+        }
+        counts
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.words += other.words;
+        self.sentences += other.sentences;
+        self.syllables += other.syllables;
+        self.complex_words += other.complex_words;
+        self.letters += other.letters;
+    }
+
+    fn readability(&self) -> Readability {
+        let words = self.words.max(1) as f64;
+        let sentences = self.sentences.max(1) as f64;
+        let words_per_sentence = words / sentences;
+        let syllables_per_word = self.syllables as f64 / words;
+        let letters_per_word = self.letters as f64 / words;
+        let round = |v: f64| (v * 10.0).round() / 10.0;
+        Readability {
+            flesch_reading_ease: round(
+                206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word,
+            ),
+            flesch_kincaid_grade: round(
+                0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59,
+            ),
+            gunning_fog: round(
+                0.4 * (words_per_sentence + 100.0 * self.complex_words as f64 / words),
+            ),
+            coleman_liau: round(
+                0.0588 * letters_per_word * 100.0 - 0.296 * sentences / words * 100.0 - 15.8,
+            ),
+            automated_readability: round(
+                4.71 * letters_per_word + 0.5 * words_per_sentence - 21.43,
+            ),
+        }
+    }
+}
+
+/// Estimate English syllables from vowel groups.
+fn count_syllables(word: &str) -> usize {
+    let word: String = word
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect();
+    if word.is_empty() {
+        return 0;
+    }
+    if word.chars().count() <= 3 {
+        return 1;
+    }
+    let mut trimmed = word.as_str();
+    for suffix in ["es", "ed"] {
+        if let Some(stem) = trimmed.strip_suffix(suffix) {
+            trimmed = stem;
+            break;
+        }
+    }
+    if !trimmed.ends_with("le") {
+        trimmed = trimmed.strip_suffix('e').unwrap_or(trimmed);
+    }
+    let mut groups = 0;
+    let mut in_vowels = false;
+    for c in trimmed.chars() {
+        let vowel = "aeiouyàáâäèéêëìíîïòóôöùúûü".contains(c);
+        if vowel && !in_vowels {
+            groups += 1;
+        }
+        in_vowels = vowel;
+    }
+    groups.max(1)
+}
@@ -0,0 +1,473 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::utils::{collect_workspace_files, validate_directory};
+use crate::watcher::FileChangeEvent;
+
+/// Only the start of each note is read for its title and aliases.
+const HEAD_BYTES: u64 = 16 * 1024;
+const DEFAULT_LIMIT: usize = 50;
+/// Score added for matches in each field, so the file name wins over a
+/// title, alias or directory match of the same quality.
+const NAME_BONUS: i64 = 30;
+const TITLE_BONUS: i64 = 15;
+const ALIAS_BONUS: i64 = 15;
+/// Extra score for recently modified files, halving roughly every 3 weeks.
+const RECENCY_BONUS: f64 = 20.0;
+const RECENCY_DAYS: f64 = 30.0;
+/// Extra score for the most recently opened file, less for older ones.
+const OPENED_BONUS: i64 = 40;
+
+/// One in-memory index per open workspace, keyed by absolute path.
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, IndexState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+/// Signalled whenever a background build finishes.
+static BUILT: Condvar = Condvar::new();
+
+enum IndexState {
+    /// Building in the background. Watcher changes that arrive meanwhile
+    /// are replayed once it is ready.
+    Building {
+        pending: HashSet<PathBuf>,
+    },
+    Ready(HashMap<String, Entry>),
+    /// The build ended without an index; the next query starts another.
+    Failed,
+}
+
+/// Marks the build of `root` as failed if it ends without an index, for
+/// example by panicking, and wakes the queries waiting for it.
+struct BuildGuard(PathBuf);
+
+impl Drop for BuildGuard {
+    fn drop(&mut self) {
+        let mut all = indexes();
+        if let Some(state @ IndexState::Building { .. }) = all.get_mut(&self.0) {
+            *state = IndexState::Failed;
+        }
+        drop(all);
+        BUILT.notify_all();
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QuickOpenOptions {
+    pub folder: String,
+    pub query: String,
+    /// Maximum number of matches, 50 by default.
+    pub limit: Option<usize>,
+    /// Recently opened files, most recent first; they rank higher.
+    #[serde(default)]
+    pub recent: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchField {
+    Name,
+    Path,
+    Title,
+    Alias,
+}
+
+#[derive(Serialize)]
+pub struct QuickOpenMatch {
+    pub path: String,
+    pub relative_path: String,
+    pub name: String,
+    pub title: Option<String>,
+    /// The field the query matched best.
+    pub field: MatchField,
+    /// The matched text: the name, relative path, title or alias.
+    pub text: String,
+    /// UTF-16 offsets of the matched characters in `text`.
+    pub positions: Vec<usize>,
+    pub score: i64,
+}
+
+/// A searchable string, with a lowercased char per original char.
+struct Field {
+    text: String,
+    chars: Vec<char>,
+}
+
+impl Field {
+    fn new(text: String) -> Self {
+        let chars = text
+            .chars()
+            .map(|c| c.to_lowercase().next().unwrap_or(c))
+            .collect();
+        Field { text, chars }
+    }
+}
+
+struct Entry {
+    name: Field,
+    relative: Field,
+    title: Option<Field>,
+    aliases: Vec<Field>,
+    modified: u64,
+}
+
+impl Entry {
+    fn load(path: &Path, root: &Path) -> Entry {
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+
+        let head = read_head(path);
+        let (frontmatter, body) = split_frontmatter(&head);
+        let title = frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get("title"))
+            .map(|t| t.trim().to_string())
+            .or_else(|| {
+                body.lines()
+                    .find_map(|line| line.strip_prefix("# "))
+                    .map(|t| t.trim().to_string())
+            })
+            .filter(|t| !t.is_empty() && *t != name);
+        let aliases = frontmatter
+            .map(|fm| {
+                let mut aliases = fm.get_list("aliases");
+                aliases.extend(fm.get_list("alias"));
+                aliases
+            })
+            .unwrap_or_default();
+
+        Entry {
+            name: Field::new(name),
+            relative: Field::new(relative),
+            title: title.map(Field::new),
+            aliases: aliases
+                .into_iter()
+                .filter(|a| !a.trim().is_empty())
+                .map(Field::new)
+                .collect(),
+            modified,
+        }
+    }
+}
+
+fn indexes() -> MutexGuard<'static, HashMap<PathBuf, IndexState>> {
+    INDEXES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_head(path: &Path) -> String {
+    let mut bytes = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(HEAD_BYTES).read_to_end(&mut bytes);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn build(root: &Path) -> HashMap<String, Entry> {
+    collect_workspace_files(root)
+        .into_iter()
+        .map(|path| {
+            let entry = Entry::load(&path, root);
+            (path.to_string_lossy().into_owned(), entry)
+        })
+        .collect()
+}
+
+/// Build the index for `root` in the background unless it already exists
+/// or is being built.
+pub fn open(root: &Path) {
+    let mut all = indexes();
+    if matches!(
+        all.get(root),
+        Some(IndexState::Building { .. } | IndexState::Ready(_))
+    ) {
+        return;
+    }
+    all.insert(
+        root.to_path_buf(),
+        IndexState::Building {
+            pending: HashSet::new(),
+        },
+    );
+    drop(all);
+
+    let root = root.to_path_buf();
+    thread::spawn(move || {
+        let _guard = BuildGuard(root.clone());
+        let mut entries = build(&root);
+
+        let mut all = indexes();
+        if let Some(IndexState::Building { pending }) = all.remove(&root) {
+            for path in pending {
+                let key = path.to_string_lossy().into_owned();
+                if path.is_file() {
+                    entries.insert(key, Entry::load(&path, &root));
+                } else {
+                    entries.remove(&key);
+                }
+            }
+            all.insert(root, IndexState::Ready(entries));
+        }
+    });
+}
+
+/// Apply watcher events for the workspace at `folder`.
+pub fn apply_changes(folder: &str, changes: &[FileChangeEvent]) {
+    let root = Path::new(folder);
+    let mut all = indexes();
+    let entries = match all.get_mut(root) {
+        Some(IndexState::Building { pending }) => {
+            pending.extend(changes.iter().map(|c| PathBuf::from(&c.path)));
+            return;
+        }
+        Some(IndexState::Ready(entries)) => entries,
+        Some(IndexState::Failed) | None => return,
+    };
+    for change in changes {
+        if change.change_type == "deleted" {
+            entries.remove(&change.path);
+        } else {
+            let entry = Entry::load(Path::new(&change.path), root);
+            entries.insert(change.path.clone(), entry);
+        }
+    }
+}
+
+/// Fuzzy-finds files in `folder` by name, relative path, title (frontmatter
+/// `title` or first `#` heading) and frontmatter `aliases`. The query's
+/// characters must appear in order; runs, word starts and file-name matches
+/// score higher, as do recently modified and recently opened files. An
+/// empty query lists files by recency.
+///
+/// Served from an in-memory index that the file watcher keeps current; the
+/// first query for a workspace that is not being watched builds it, and
+/// queries made while it is being built wait for it, off the main thread.
+#[tauri::command]
+pub async fn quick_open(options: QuickOpenOptions) -> Result<Vec<QuickOpenMatch>, AppError> {
+    tokio::task::spawn_blocking(move || find(options))
+        .await
+        .map_err(|e| AppError::Other(format!("Quick open failed: {}", e)))?
+}
+
+fn find(options: QuickOpenOptions) -> Result<Vec<QuickOpenMatch>, AppError> {
+    let root = validate_directory(&options.folder)?;
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT);
+    let pattern: Vec<char> = options
+        .query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect();
+    let opened: HashMap<&str, usize> = options
+        .recent
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.as_str(), i))
+        .collect();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    open(&root);
+    let mut all = indexes();
+    let entries = loop {
+        match all.get(&root) {
+            Some(IndexState::Ready(entries)) => break entries,
+            Some(IndexState::Building { .. }) => {
+                all = BUILT.wait(all).unwrap_or_else(|e| e.into_inner());
+            }
+            Some(IndexState::Failed) => {
+                return Err(AppError::Other(
+                    "Indexing the workspace failed; try again".to_string(),
+                ));
+            }
+            // Dropped while waiting; build it again
+            None => {
+                drop(all);
+                open(&root);
+                all = indexes();
+            }
+        }
+    };
+
+    let mut matches: Vec<Scored> = entries
+        .iter()
+        .filter_map(|(path, entry)| {
+            let (score, field, text, positions) = best_match(&pattern, entry)?;
+            let age_days = now.saturating_sub(entry.modified) as f64 / 86_400.0;
+            let recency = (RECENCY_BONUS * (-age_days / RECENCY_DAYS).exp()) as i64;
+            let opened = opened
+                .get(path.as_str())
+                .map_or(0, |&i| (OPENED_BONUS - 2 * i as i64).max(0));
+            Some(Scored {
+                score: score + recency + opened,
+                path,
+                entry,
+                field,
+                text,
+                positions,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| {
+                a.entry
+                    .relative
+                    .text
+                    .len()
+                    .cmp(&b.entry.relative.text.len())
+            })
+            .then_with(|| a.path.cmp(b.path))
+    });
+    matches.truncate(limit);
+
+    Ok(matches
+        .into_iter()
+        .map(|m| QuickOpenMatch {
+            path: m.path.clone(),
+            relative_path: m.entry.relative.text.clone(),
+            name: m.entry.name.text.clone(),
+            title: m.entry.title.as_ref().map(|t| t.text.clone()),
+            field: m.field,
+            positions: utf16_positions(&m.text.text, &m.positions),
+            text: m.text.text.clone(),
+            score: m.score,
+        })
+        .collect())
+}
+
+/// A matching entry while results are ranked.
+struct Scored<'a> {
+    score: i64,
+    path: &'a String,
+    entry: &'a Entry,
+    field: MatchField,
+    text: &'a Field,
+    positions: Vec<usize>,
+}
+
+/// The entry's best-scoring field for `pattern`, with matched char indices.
+fn best_match<'a>(
+    pattern: &[char],
+    entry: &'a Entry,
+) -> Option<(i64, MatchField, &'a Field, Vec<usize>)> {
+    if pattern.is_empty() {
+        return Some((0, MatchField::Name, &entry.name, Vec::new()));
+    }
+    let mut best: Option<(i64, MatchField, &Field, Vec<usize>)> = None;
+    let mut consider = |field: MatchField, text: &'a Field, bonus: i64| {
+        if let Some((score, positions)) = fuzzy(pattern, &text.chars) {
+            let score = score + bonus;
+            if best.as_ref().is_none_or(|b| score > b.0) {
+                best = Some((score, field, text, positions));
+            }
+        }
+    };
+
+    let name = &entry.name.chars;
+    let mut name_bonus = NAME_BONUS;
+    if name.as_slice() == pattern {
+        name_bonus += 50;
+    } else if name.starts_with(pattern) {
+        name_bonus += 25;
+    }
+    consider(MatchField::Name, &entry.name, name_bonus);
+    if let Some(title) = &entry.title {
+        consider(MatchField::Title, title, TITLE_BONUS);
+    }
+    for alias in &entry.aliases {
+        consider(MatchField::Alias, alias, ALIAS_BONUS);
+    }
+    consider(MatchField::Path, &entry.relative, 0);
+    best
+}
+
+/// Score `pattern` as a subsequence of `text`, in the spirit of fzf: the
+/// shortest window holding the match is found, then every matched char
+/// scores, with bonuses for runs and word starts and a penalty per gap.
+fn fuzzy(pattern: &[char], text: &[char]) -> Option<(i64, Vec<usize>)> {
+    if pattern.len() > text.len() {
+        return None;
+    }
+    // Leftmost end of a full match
+    let mut p = 0;
+    let mut end = 0;
+    for (i, &c) in text.iter().enumerate() {
+        if c == pattern[p] {
+            p += 1;
+            if p == pattern.len() {
+                end = i;
+                break;
+            }
+        }
+    }
+    if p < pattern.len() {
+        return None;
+    }
+    // Walk back to the latest start that still matches
+    let mut p = pattern.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if text[i] == pattern[p - 1] {
+            p -= 1;
+            if p == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut p = 0;
+    for (i, &c) in text.iter().enumerate().take(end + 1).skip(start) {
+        if p < pattern.len() && c == pattern[p] {
+            score += 16;
+            if positions.last().is_some_and(|&last| last + 1 == i) {
+                score += 8;
+            }
+            if i == 0 || matches!(text[i - 1], '/' | '\\' | '-' | '_' | ' ' | '.') {
+                score += 10;
+            }
+            positions.push(i);
+            p += 1;
+        }
+    }
+    score -= (end + 1 - start - pattern.len()) as i64;
+    Some((score, positions))
+}
+
+/// Convert char indices in `text` to UTF-16 offsets.
+fn utf16_positions(text: &str, positions: &[usize]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(positions.len());
+    let mut units = 0;
+    let mut next = positions.iter().peekable();
+    for (i, c) in text.chars().enumerate() {
+        if next.peek() == Some(&&i) {
+            offsets.push(units);
+            next.next();
+        }
+        units += c.len_utf16();
+    }
+    offsets
+}
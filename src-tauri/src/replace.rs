@@ -0,0 +1,519 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::markdown::utf16_len;
+use crate::search::{Replacement, Search};
+use crate::search_match::MatchOptions;
+use crate::utils::{app_data_subdir, fnv1a, prune_oldest, timestamped_name, validate_directory};
+
+/// Folder under the app data dir holding one undo journal per applied replace.
+const JOURNAL_DIR: &str = "replace-journal";
+/// Only the most recent journals are kept.
+const MAX_JOURNALS: usize = 20;
+
+#[derive(Deserialize)]
+pub struct ReplaceOptions {
+    pub folder: String,
+    /// Text to replace, matched as one literal or regex pattern; the search
+    /// operators and field terms do not apply.
+    pub query: String,
+    /// Replacement text. In regex mode `$1`, `$name` and `${name}` insert
+    /// capture groups and `$$` a literal `$`.
+    pub replacement: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub ignore_diacritics: bool,
+}
+
+/// Planned changes to one file.
+#[derive(Serialize)]
+pub struct FilePreview {
+    pub file_path: String,
+    pub file_name: String,
+    /// Identifies the content the preview was made from; applying fails if
+    /// the file has changed since.
+    pub content_hash: String,
+    pub changes: Vec<ReplaceChange>,
+}
+
+#[derive(Serialize)]
+pub struct ReplaceChange {
+    /// Position of the match within its file, used to select it.
+    pub id: usize,
+    pub line_number: usize,
+    pub line_content: String,
+    /// Match range within `line_content`, in UTF-16 code units.
+    pub match_start: usize,
+    pub match_end: usize,
+    pub replacement: String,
+}
+
+/// The matches of one previewed file to replace.
+#[derive(Deserialize)]
+pub struct FileSelection {
+    pub file_path: String,
+    pub content_hash: String,
+    pub ids: Vec<usize>,
+}
+
+#[derive(Serialize)]
+pub struct ReplaceOutcome {
+    /// Pass to `undo_replace` to revert the whole operation.
+    pub journal_id: String,
+    pub files_changed: usize,
+    pub replacements: usize,
+}
+
+/// A past replace that can still be undone.
+#[derive(Serialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub created_at: i64,
+    pub query: String,
+    pub replacement: String,
+    pub files: usize,
+    pub replacements: usize,
+}
+
+/// What `undo_replace` needs to put every file back.
+#[derive(Serialize, Deserialize)]
+struct Journal {
+    id: String,
+    created_at: i64,
+    folder: String,
+    query: String,
+    replacement: String,
+    replacements: usize,
+    files: Vec<JournalFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JournalFile {
+    path: String,
+    original: String,
+    /// Hash of the content written by the replace, to detect later edits.
+    replaced_hash: String,
+}
+
+/// Lists every match `options.query` would replace, grouped per file in
+/// path order, without changing anything.
+#[tauri::command]
+pub fn preview_replace(
+    app: AppHandle,
+    options: ReplaceOptions,
+) -> Result<Vec<FilePreview>, AppError> {
+    if options.query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let root = validate_directory(&options.folder)?;
+    let search = build_search(&options)?;
+
+    let mut files = search.candidates(&app, &root);
+    files.sort();
+
+    let mut previews = Vec::new();
+    for path in files {
+        let Some((content, replacements)) = search.replacements(&path, &root, &options.replacement)
+        else {
+            continue;
+        };
+        if replacements.is_empty() {
+            continue;
+        }
+        let lines: Vec<&str> = content.lines().collect();
+        let changes = replacements
+            .into_iter()
+            .enumerate()
+            .map(|(id, r)| {
+                let line = lines.get(r.line).copied().unwrap_or_default();
+                ReplaceChange {
+                    id,
+                    line_number: r.line + 1,
+                    line_content: line.to_string(),
+                    match_start: utf16_len(&line[..r.line_range.start]),
+                    match_end: utf16_len(&line[..r.line_range.end]),
+                    replacement: r.text,
+                }
+            })
+            .collect();
+        previews.push(FilePreview {
+            file_path: path.to_string_lossy().into_owned(),
+            file_name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            content_hash: content_hash(&content),
+            changes,
+        });
+    }
+    Ok(previews)
+}
+
+/// Replaces the `selected` matches of a preview. Either every file is
+/// written or none is, and the original contents go to an undo journal.
+/// Fails without changes if any selected file was edited after the preview.
+#[tauri::command]
+pub fn apply_replace(
+    app: AppHandle,
+    options: ReplaceOptions,
+    selected: Vec<FileSelection>,
+) -> Result<ReplaceOutcome, AppError> {
+    let root = validate_directory(&options.folder)?;
+    let search = build_search(&options)?;
+
+    let mut writes: Vec<(PathBuf, String)> = Vec::new();
+    let mut journal_files = Vec::new();
+    let mut count = 0;
+    for selection in &selected {
+        if selection.ids.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(&selection.file_path);
+        if !path.starts_with(&root) || path.components().any(|c| c == Component::ParentDir) {
+            return Err(AppError::PathTraversal(selection.file_path.clone()));
+        }
+        let (content, replacements) = search
+            .replacements(&path, &root, &options.replacement)
+            .filter(|(content, _)| content_hash(content) == selection.content_hash)
+            .ok_or_else(|| {
+                AppError::Other(format!(
+                    "{} changed since the preview; preview the replace again",
+                    selection.file_path
+                ))
+            })?;
+
+        let (updated, replaced) = splice_selected(&content, &replacements, &selection.ids);
+        count += replaced;
+
+        journal_files.push(JournalFile {
+            path: selection.file_path.clone(),
+            replaced_hash: content_hash(&updated),
+            original: content,
+        });
+        writes.push((path, updated));
+    }
+    if writes.is_empty() {
+        return Err(AppError::Other("No matches selected".to_string()));
+    }
+
+    let journal = Journal {
+        id: timestamped_name(&journal_dir(&app)?, ".json"),
+        created_at: now(),
+        folder: options.folder,
+        query: options.query,
+        replacement: options.replacement,
+        replacements: count,
+        files: journal_files,
+    };
+    let journal_path = journal_path(&app, &journal.id)?;
+    write_journal(&journal_path, &journal)?;
+    if let Err(e) = write_all(&writes) {
+        let _ = fs::remove_file(&journal_path);
+        return Err(e);
+    }
+    if let Ok(dir) = journal_dir(&app) {
+        prune_oldest(&dir, MAX_JOURNALS);
+    }
+
+    Ok(ReplaceOutcome {
+        journal_id: journal.id,
+        files_changed: writes.len(),
+        replacements: count,
+    })
+}
+
+/// Reverts a replace by restoring the original content of every file it
+/// changed, all or nothing. Refuses if any of them was edited since.
+/// Returns the number of files restored.
+#[tauri::command]
+pub fn undo_replace(app: AppHandle, journal_id: String) -> Result<usize, AppError> {
+    let path = journal_path(&app, &journal_id)?;
+    let journal: Journal = fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| AppError::NotFound(format!("replace journal {}", journal_id)))?;
+
+    let mut writes = Vec::new();
+    let mut edited = Vec::new();
+    for file in journal.files {
+        let current = fs::read_to_string(&file.path).unwrap_or_default();
+        if current == file.original {
+            continue;
+        }
+        if content_hash(&current) != file.replaced_hash {
+            edited.push(file.path);
+            continue;
+        }
+        writes.push((PathBuf::from(file.path), file.original));
+    }
+    if !edited.is_empty() {
+        return Err(AppError::Other(format!(
+            "Cannot undo: changed since the replace: {}",
+            edited.join(", ")
+        )));
+    }
+
+    write_all(&writes)?;
+    let _ = fs::remove_file(&path);
+    Ok(writes.len())
+}
+
+/// Replaces in `folder` that can still be undone, newest first.
+#[tauri::command]
+pub fn list_replace_journal(app: AppHandle, folder: String) -> Result<Vec<JournalEntry>, AppError> {
+    let mut entries: Vec<JournalEntry> = read_journals(&app)
+        .into_iter()
+        .filter(|journal| journal.folder == folder)
+        .map(|journal| JournalEntry {
+            files: journal.files.len(),
+            id: journal.id,
+            created_at: journal.created_at,
+            query: journal.query,
+            replacement: journal.replacement,
+            replacements: journal.replacements,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(entries)
+}
+
+fn build_search(options: &ReplaceOptions) -> Result<Search, AppError> {
+    let match_options = MatchOptions {
+        case_sensitive: options.case_sensitive,
+        whole_word: options.whole_word,
+        ignore_diacritics: options.ignore_diacritics,
+    };
+    Search::pattern(options.query.clone(), match_options, options.regex)
+}
+
+/// `content` with the replacements whose position is in `ids` applied, and
+/// how many that was.
+fn splice_selected(content: &str, replacements: &[Replacement], ids: &[usize]) -> (String, usize) {
+    let ids: HashSet<usize> = ids.iter().copied().collect();
+    let mut updated = String::with_capacity(content.len());
+    let mut copied = 0;
+    let mut count = 0;
+    for (id, r) in replacements.iter().enumerate() {
+        if !ids.contains(&id) {
+            continue;
+        }
+        updated.push_str(&content[copied..r.range.start]);
+        updated.push_str(&r.text);
+        copied = r.range.end;
+        count += 1;
+    }
+    updated.push_str(&content[copied..]);
+    (updated, count)
+}
+
+fn content_hash(content: &str) -> String {
+    format!("{:016x}", fnv1a(content.as_bytes()))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Write every file, all or nothing. New contents are first written beside
+/// their files and only renamed into place once all of them are on disk; if
+/// a rename fails, the files already replaced get their old content back.
+fn write_all(files: &[(PathBuf, String)]) -> Result<(), AppError> {
+    let temps: Vec<PathBuf> = files.iter().map(|(path, _)| temp_path(path)).collect();
+    let remove_temps = || {
+        for temp in &temps {
+            let _ = fs::remove_file(temp);
+        }
+    };
+
+    for ((_, content), temp) in files.iter().zip(&temps) {
+        if let Err(e) = fs::write(temp, content) {
+            remove_temps();
+            return Err(e.into());
+        }
+    }
+
+    let mut originals: Vec<(&Path, Option<Vec<u8>>)> = Vec::new();
+    for ((path, _), temp) in files.iter().zip(&temps) {
+        let original = fs::read(path).ok();
+        if let Err(e) = fs::rename(temp, path) {
+            for (done, content) in originals {
+                if let Some(content) = content {
+                    let _ = fs::write(done, content);
+                }
+            }
+            remove_temps();
+            return Err(e.into());
+        }
+        originals.push((path, original));
+    }
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.replace.tmp", name))
+}
+
+fn journal_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app_data_subdir(app, JOURNAL_DIR)
+}
+
+fn journal_path(app: &AppHandle, id: &str) -> Result<PathBuf, AppError> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::PathTraversal(id.to_string()));
+    }
+    Ok(journal_dir(app)?.join(format!("{}.json", id)))
+}
+
+fn write_journal(path: &Path, journal: &Journal) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_vec(journal).map_err(|e| AppError::Other(e.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn read_journals(app: &AppHandle) -> Vec<Journal> {
+    let Ok(dir) = journal_dir(app) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| serde_json::from_slice(&fs::read(path).ok()?).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh scratch folder for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("markview-replace-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn replacement(content: &str, needle: &str, nth: usize, text: &str) -> Replacement {
+        let start = content.match_indices(needle).nth(nth).unwrap().0;
+        Replacement {
+            line: 0,
+            line_range: 0..0,
+            range: start..start + needle.len(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn splice_applies_only_selected_matches() {
+        let content = "cat and cat and cat";
+        let replacements: Vec<Replacement> = (0..3)
+            .map(|i| replacement(content, "cat", i, "dog"))
+            .collect();
+        let (updated, count) = splice_selected(content, &replacements, &[0, 2]);
+        assert_eq!(updated, "dog and cat and dog");
+        assert_eq!(count, 2);
+        assert_eq!(
+            splice_selected(content, &replacements, &[]),
+            (content.to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn write_all_replaces_every_file() {
+        let dir = scratch("all");
+        let a = dir.join("a.md");
+        let b = dir.join("b.md");
+        fs::write(&a, "old a").unwrap();
+        fs::write(&b, "old b").unwrap();
+
+        write_all(&[
+            (a.clone(), "new a".to_string()),
+            (b.clone(), "new b".to_string()),
+        ])
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "new b");
+        assert_eq!(names(&dir), ["a.md", "b.md"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_all_changes_nothing_if_a_temp_write_fails() {
+        let dir = scratch("temp");
+        let a = dir.join("a.md");
+        fs::write(&a, "old a").unwrap();
+        let missing = dir.join("missing").join("b.md");
+
+        assert!(write_all(&[
+            (a.clone(), "new a".to_string()),
+            (missing, "new b".to_string())
+        ])
+        .is_err());
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old a");
+        assert_eq!(names(&dir), ["a.md"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_all_restores_replaced_files_if_a_rename_fails() {
+        let dir = scratch("rename");
+        let a = dir.join("a.md");
+        fs::write(&a, "old a").unwrap();
+        // Renaming a file over a non-empty folder fails on every platform.
+        let blocked = dir.join("b.md");
+        fs::create_dir(&blocked).unwrap();
+        fs::write(blocked.join("keep.md"), "").unwrap();
+
+        assert!(write_all(&[
+            (a.clone(), "new a".to_string()),
+            (blocked, "new b".to_string())
+        ])
+        .is_err());
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old a");
+        assert_eq!(names(&dir), ["a.md", "b.md"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn content_hash_tracks_content() {
+        assert_eq!(content_hash("note"), content_hash("note"));
+        assert_ne!(content_hash("note"), content_hash("note "));
+        assert_eq!(content_hash("").len(), 16);
+    }
+}
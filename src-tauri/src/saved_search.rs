@@ -0,0 +1,375 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, Once};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::FileEntry;
+use crate::error::AppError;
+use crate::search::Search;
+use crate::search_match::MatchOptions;
+use crate::utils::{validate_directory, write_atomic};
+use crate::watcher::FileChangeEvent;
+
+/// Workspace config file holding the saved searches.
+const CONFIG_FILE: &str = ".markview-searches.json";
+/// Path prefix of the virtual smart folder entries in the file tree.
+const SMART_FOLDER_PREFIX: &str = "smart-folder:";
+
+/// How often folders with a `modified:` filter are searched again, so files
+/// drop out of them as time passes.
+const TIME_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Live smart folder contents per workspace, kept current by the watcher.
+/// Searching the workspace happens on background threads, never under the lock.
+static SMART_FOLDERS: Lazy<Mutex<HashMap<PathBuf, Workspace>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static TIME_REFRESH: Once = Once::new();
+
+/// A named search, stored in the workspace so it can be shared and rerun.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedSearch {
+    /// Stable identifier; derived from the name when saved without one.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub ignore_diacritics: bool,
+    /// Show the matching files as a virtual folder in the file tree.
+    #[serde(default)]
+    pub smart_folder: bool,
+}
+
+impl SavedSearch {
+    fn compile(&self) -> Result<Search, AppError> {
+        let options = MatchOptions {
+            case_sensitive: self.case_sensitive,
+            whole_word: self.whole_word,
+            ignore_diacritics: self.ignore_diacritics,
+        };
+        Search::new(self.query.clone(), options, self.regex)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SearchConfig {
+    #[serde(default)]
+    searches: Vec<SavedSearch>,
+}
+
+impl SearchConfig {
+    /// Read the config of the workspace at `root`; a missing file is empty.
+    fn load(root: &Path) -> Result<Self, AppError> {
+        match fs::read_to_string(root.join(CONFIG_FILE)) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| AppError::Other(format!("Invalid {}: {}", CONFIG_FILE, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, root: &Path) -> Result<(), AppError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| AppError::Other(e.to_string()))?;
+        write_atomic(&root.join(CONFIG_FILE), (json + "\n").as_bytes())?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Workspace {
+    /// `None` until the first search of the workspace finishes.
+    folders: Option<Vec<SmartFolder>>,
+    /// Bumped when the saved searches change, so the results of a search
+    /// started before are not stored.
+    generation: u64,
+    scanning: bool,
+}
+
+struct SmartFolder {
+    search: SavedSearch,
+    compiled: Search,
+    files: BTreeSet<PathBuf>,
+}
+
+impl SmartFolder {
+    fn load(app: &AppHandle, root: &Path, search: SavedSearch) -> Option<Self> {
+        let compiled = search.compile().ok()?;
+        let files = matching_files(app, root, &compiled);
+        Some(SmartFolder {
+            search,
+            compiled,
+            files,
+        })
+    }
+}
+
+fn smart_folders() -> MutexGuard<'static, HashMap<PathBuf, Workspace>> {
+    SMART_FOLDERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Saved searches of the workspace at `folder`, in their stored order.
+#[tauri::command]
+pub fn list_saved_searches(folder: String) -> Result<Vec<SavedSearch>, AppError> {
+    let root = validate_directory(&folder)?;
+    Ok(SearchConfig::load(&root)?.searches)
+}
+
+/// Add `search` to the workspace, or replace the saved search with its id.
+/// The query must be valid; an empty id is derived from the name.
+#[tauri::command]
+pub fn save_search(
+    app: AppHandle,
+    folder: String,
+    search: SavedSearch,
+) -> Result<SavedSearch, AppError> {
+    let root = validate_directory(&folder)?;
+    let mut search = search;
+    search.name = search.name.trim().to_string();
+    if search.name.is_empty() {
+        return Err(AppError::Other("A saved search needs a name".to_string()));
+    }
+    if search.query.trim().is_empty() {
+        return Err(AppError::Other("A saved search needs a query".to_string()));
+    }
+    search.compile()?;
+
+    let mut config = SearchConfig::load(&root)?;
+    if search.id.is_empty() {
+        search.id = unique_id(&search.name, &config.searches);
+    }
+    match config.searches.iter_mut().find(|s| s.id == search.id) {
+        Some(existing) => *existing = search.clone(),
+        None => config.searches.push(search.clone()),
+    }
+    config.save(&root)?;
+    rescan(&app, &root);
+    Ok(search)
+}
+
+/// Remove the saved search `id` from the workspace.
+#[tauri::command]
+pub fn delete_saved_search(app: AppHandle, folder: String, id: String) -> Result<(), AppError> {
+    let root = validate_directory(&folder)?;
+    let mut config = SearchConfig::load(&root)?;
+    let before = config.searches.len();
+    config.searches.retain(|s| s.id != id);
+    if config.searches.len() == before {
+        return Err(AppError::NotFound(format!("saved search {}", id)));
+    }
+    config.save(&root)?;
+    rescan(&app, &root);
+    Ok(())
+}
+
+/// A slug of `name` that no other saved search uses.
+fn unique_id(name: &str, searches: &[SavedSearch]) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() {
+        "search".to_string()
+    } else {
+        slug
+    };
+    let mut id = base.clone();
+    let mut n = 2;
+    while searches.iter().any(|s| s.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// Tree entries for the smart folders of the workspace at `root`: one
+/// virtual directory per saved search marked as a smart folder, holding the
+/// files that currently match it. Only cached results are returned; the
+/// first listing starts a background search and `smart-folders-changed` is
+/// emitted once it is done. Watcher events keep the results current, and
+/// folders with a `modified:` filter are searched again every minute.
+pub fn smart_folder_entries(app: &AppHandle, root: &Path) -> Vec<FileEntry> {
+    let mut all = smart_folders();
+    let workspace = all.entry(root.to_path_buf()).or_default();
+    let Some(folders) = &workspace.folders else {
+        if !workspace.scanning {
+            workspace.scanning = true;
+            let generation = workspace.generation;
+            drop(all);
+            spawn_scan(app.clone(), root.to_path_buf(), generation);
+        }
+        return Vec::new();
+    };
+
+    folders
+        .iter()
+        .map(|folder| {
+            let mut children: Vec<FileEntry> = folder
+                .files
+                .iter()
+                .filter(|path| path.is_file())
+                .map(|path| FileEntry {
+                    name: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    path: path.to_string_lossy().into_owned(),
+                    is_directory: false,
+                    children: None,
+                    smart_folder: None,
+                })
+                .collect();
+            children.sort_by_key(|entry| entry.name.to_lowercase());
+            FileEntry {
+                name: folder.search.name.clone(),
+                path: format!("{}{}", SMART_FOLDER_PREFIX, folder.search.id),
+                is_directory: true,
+                children: Some(children),
+                smart_folder: Some(folder.search.id.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Search the workspace again after its saved searches changed. The
+/// current entries stay listed until the new results are in.
+fn rescan(app: &AppHandle, root: &Path) {
+    let mut all = smart_folders();
+    let Some(workspace) = all.get_mut(root) else {
+        return;
+    };
+    workspace.generation += 1;
+    if !workspace.scanning {
+        workspace.scanning = true;
+        let generation = workspace.generation;
+        drop(all);
+        spawn_scan(app.clone(), root.to_path_buf(), generation);
+    }
+}
+
+/// Search every smart folder of `root` in the background and store the
+/// results, starting over if the saved searches change meanwhile.
+fn spawn_scan(app: AppHandle, root: PathBuf, mut generation: u64) {
+    thread::spawn(move || {
+        loop {
+            // A broken config only hides the smart folders; the search panel
+            // reports the error
+            let searches = SearchConfig::load(&root).unwrap_or_default().searches;
+            let folders: Vec<SmartFolder> = searches
+                .into_iter()
+                .filter(|s| s.smart_folder)
+                .filter_map(|search| SmartFolder::load(&app, &root, search))
+                .collect();
+
+            let mut all = smart_folders();
+            let workspace = all.entry(root.clone()).or_default();
+            if workspace.generation == generation {
+                workspace.folders = Some(folders);
+                workspace.scanning = false;
+                break;
+            }
+            generation = workspace.generation;
+        }
+        let _ = app.emit("smart-folders-changed", root.to_string_lossy());
+        start_time_refresh(&app);
+    });
+}
+
+/// Start the thread that searches time-relative folders again, once.
+fn start_time_refresh(app: &AppHandle) {
+    TIME_REFRESH.call_once(|| {
+        let app = app.clone();
+        thread::spawn(move || loop {
+            thread::sleep(TIME_REFRESH_INTERVAL);
+            refresh_time_relative(&app);
+        });
+    });
+}
+
+/// Search the folders with a `modified:` filter again, outside the lock.
+/// Recompiling resolves relative dates such as `7d` against now.
+fn refresh_time_relative(app: &AppHandle) {
+    let stale: Vec<(PathBuf, u64, Vec<SavedSearch>)> = smart_folders()
+        .iter()
+        .filter_map(|(root, workspace)| {
+            let searches: Vec<SavedSearch> = workspace
+                .folders
+                .as_ref()?
+                .iter()
+                .filter(|folder| folder.compiled.depends_on_time())
+                .map(|folder| folder.search.clone())
+                .collect();
+            (!searches.is_empty()).then(|| (root.clone(), workspace.generation, searches))
+        })
+        .collect();
+
+    for (root, generation, searches) in stale {
+        let refreshed: Vec<SmartFolder> = searches
+            .into_iter()
+            .filter_map(|search| SmartFolder::load(app, &root, search))
+            .collect();
+
+        let mut all = smart_folders();
+        let Some(folders) = all
+            .get_mut(&root)
+            .filter(|workspace| workspace.generation == generation)
+            .and_then(|workspace| workspace.folders.as_mut())
+        else {
+            continue;
+        };
+        let mut changed = false;
+        for new in refreshed {
+            if let Some(folder) = folders.iter_mut().find(|f| f.search.id == new.search.id) {
+                changed |= folder.files != new.files;
+                *folder = new;
+            }
+        }
+        drop(all);
+        if changed {
+            let _ = app.emit("smart-folders-changed", root.to_string_lossy());
+        }
+    }
+}
+
+fn matching_files(app: &AppHandle, root: &Path, search: &Search) -> BTreeSet<PathBuf> {
+    search
+        .candidates(app, root)
+        .into_iter()
+        .filter(|path| search.matches(path, root))
+        .collect()
+}
+
+/// Re-test the files in watcher events against the smart folders of the
+/// workspace at `folder`. Returns whether any folder's contents changed.
+pub fn apply_changes(folder: &str, changes: &[FileChangeEvent]) -> bool {
+    let root = Path::new(folder);
+    let mut all = smart_folders();
+    let Some(folders) = all.get_mut(root).and_then(|w| w.folders.as_mut()) else {
+        return false;
+    };
+    let mut changed = false;
+    for folder in folders.iter_mut() {
+        for change in changes {
+            let path = PathBuf::from(&change.path);
+            changed |= if change.change_type != "deleted" && folder.compiled.matches(&path, root) {
+                folder.files.insert(path)
+            } else {
+                folder.files.remove(&path)
+            };
+        }
+    }
+    changed
+}
@@ -1,8 +1,33 @@
-use serde::Serialize;
+use once_cell::sync::Lazy;
+use once_cell::unsync::OnceCell;
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
 
 use crate::error::AppError;
-use crate::utils::{collect_md_files, validate_directory};
+use crate::frontmatter::{split_frontmatter, Frontmatter};
+use crate::markdown::{parser_options, utf16_len};
+use crate::search_index;
+use crate::search_match::{MatchOptions, Matcher};
+use crate::search_query::{parse_query, Filter, Query, SearchQuery};
+use crate::utils::{collect_workspace_files, validate_directory};
+
+/// Inline `#tags`; purely numeric ones (issue numbers) are dropped later.
+static TAG_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|\s)#([\w/-]+)").expect("invalid tag regex"));
+
+/// Cancellation flags of the streamed searches still running, by id.
+static RUNNING: Lazy<Mutex<HashMap<u64, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 #[derive(Serialize, Clone)]
 pub struct SearchResult {
@@ -10,81 +35,669 @@ pub struct SearchResult {
     pub file_name: String,
     pub line_number: usize,
     pub line_content: String,
+    /// Match range within `line_content`, in UTF-16 code units.
     pub match_start: usize,
     pub match_end: usize,
 }
 
+#[derive(Deserialize)]
+pub struct SearchOptions {
+    /// Chosen by the caller and echoed in every event of this search.
+    pub search_id: u64,
+    pub folder: String,
+    pub query: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub ignore_diacritics: bool,
+    /// Lines shown before and after each match, at most 10.
+    #[serde(default)]
+    pub context_lines: usize,
+}
+
+/// The matches of one file in a streamed search.
+#[derive(Serialize, Clone)]
+pub struct FileMatches {
+    pub file_path: String,
+    pub file_name: String,
+    /// Every match in the file, including any beyond the result cap.
+    pub hit_count: usize,
+    pub matches: Vec<LineMatch>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LineMatch {
+    pub line_number: usize,
+    pub line_content: String,
+    /// Match range within `line_content`, in UTF-16 code units.
+    pub match_start: usize,
+    pub match_end: usize,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+/// Payload of the `search-results` event.
+#[derive(Serialize, Clone)]
+pub struct SearchBatch {
+    pub search_id: u64,
+    pub files: Vec<FileMatches>,
+}
+
+/// Payload of the `search-done` event.
+#[derive(Serialize, Clone)]
+pub struct SearchSummary {
+    pub search_id: u64,
+    pub total_hits: usize,
+    pub total_files: usize,
+    /// Whether matches were left out because of the result cap.
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
 /// Maximum number of results returned to avoid overwhelming the UI.
 const MAX_RESULTS: usize = 500;
+const MAX_CONTEXT_LINES: usize = 10;
+/// Streamed results are sent at most this often.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
 
-/// Recursively searches `.md` files under `folder` for lines containing `query`.
+/// Searches `.md` and `.csv`/`.tsv` files under `folder` with the query
+/// syntax of [`parse_query`]: words, phrases, `AND`/`OR`/`NOT` and field
+/// filters such as `tag:`, `path:` or `modified:`. With `regex` the whole
+/// query is a single regular expression instead.
 ///
-/// Returns a list of [`SearchResult`] entries sorted by file path then line number,
-/// capped at 500 results. Unreadable files are silently skipped.
+/// Case-insensitive matching uses Unicode case folding (`straße` finds
+/// `STRASSE`); `ignore_diacritics` also lets `cafe` find `café`, and
+/// `whole_word` rejects matches inside a longer word.
+///
+/// Candidate files come from the workspace's persistent index, most relevant
+/// (BM25) first; until the index is built every file is scanned in path
+/// order. Results within a file are in line order, capped at 500 in total.
+/// Files that match only through filters are reported once, on their first
+/// heading. Unreadable files are silently skipped.
 #[tauri::command]
 pub fn search_files(
+    app: AppHandle,
     folder: String,
     query: String,
     case_sensitive: bool,
+    regex: Option<bool>,
+    whole_word: Option<bool>,
+    ignore_diacritics: Option<bool>,
 ) -> Result<Vec<SearchResult>, AppError> {
-    if query.is_empty() {
+    if query.trim().is_empty() {
         return Ok(Vec::new());
     }
 
     let root = validate_directory(&folder)?;
-
-    let search_query = if case_sensitive {
-        query.clone()
-    } else {
-        query.to_lowercase()
+    let options = MatchOptions {
+        case_sensitive,
+        whole_word: whole_word.unwrap_or(false),
+        ignore_diacritics: ignore_diacritics.unwrap_or(false),
     };
+    let search = Search::new(query, options, regex.unwrap_or(false))?;
 
     let mut results: Vec<SearchResult> = Vec::new();
 
-    let mut md_files = collect_md_files(&root);
-    md_files.sort();
-
-    'outer: for file_path in md_files {
-        let content = match fs::read_to_string(&file_path) {
-            Ok(c) => c,
-            Err(_) => continue,
+    for file_path in search.candidates(&app, &root) {
+        let note = Note::new(&file_path, &root);
+        let Some(hits) = note.hits(&search) else {
+            continue;
         };
 
         let file_path_str = file_path.to_string_lossy().into_owned();
-        let file_name = file_path
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_default();
+        let file_name = file_name(&file_path);
+        let lines: Vec<&str> = note.content().unwrap_or_default().lines().collect();
 
-        for (line_idx, line) in content.lines().enumerate() {
-            let haystack = if case_sensitive {
-                line.to_string()
+        for hit in hits {
+            let line = lines.get(hit.line).copied().unwrap_or_default();
+            let (match_start, match_end) = hit.utf16_range(line);
+            results.push(SearchResult {
+                file_path: file_path_str.clone(),
+                file_name: file_name.clone(),
+                line_number: hit.line + 1,
+                line_content: line.to_string(),
+                match_start,
+                match_end,
+            });
+            if results.len() >= MAX_RESULTS {
+                return Ok(results);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Runs a search like [`search_files`] in the background. Matches arrive in
+/// `search-results` events, grouped per file with `context_lines` lines
+/// around each; a final `search-done` event carries the total hit count and
+/// whether the 500-match cap was reached. Invalid queries fail right away.
+#[tauri::command]
+pub fn start_search(app: AppHandle, options: SearchOptions) -> Result<(), AppError> {
+    let search_id = options.search_id;
+    let mut summary = SearchSummary {
+        search_id,
+        total_hits: 0,
+        total_files: 0,
+        truncated: false,
+        cancelled: false,
+    };
+    if options.query.trim().is_empty() {
+        let _ = app.emit("search-done", summary);
+        return Ok(());
+    }
+
+    let root = validate_directory(&options.folder)?;
+    let match_options = MatchOptions {
+        case_sensitive: options.case_sensitive,
+        whole_word: options.whole_word,
+        ignore_diacritics: options.ignore_diacritics,
+    };
+    let search = Search::new(options.query, match_options, options.regex)?;
+    let context = options.context_lines.min(MAX_CONTEXT_LINES);
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = running().insert(search_id, cancelled.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    thread::spawn(move || {
+        let mut batch: Vec<FileMatches> = Vec::new();
+        let mut sent_at = Instant::now();
+        let mut remaining = MAX_RESULTS;
+
+        for file_path in search.candidates(&app, &root) {
+            if cancelled.load(Ordering::Relaxed) {
+                summary.cancelled = true;
+                batch.clear();
+                break;
+            }
+            let note = Note::new(&file_path, &root);
+            let Some(hits) = note.hits(&search) else {
+                continue;
+            };
+            summary.total_files += 1;
+            summary.total_hits += hits.len();
+            // Keep counting past the cap so the total is exact
+            if hits.len() > remaining {
+                summary.truncated = true;
+            }
+            if remaining == 0 {
+                continue;
+            }
+
+            let lines: Vec<&str> = note.content().unwrap_or_default().lines().collect();
+            let matches = hits
+                .iter()
+                .take(remaining)
+                .map(|hit| line_match(&lines, hit, context))
+                .collect::<Vec<_>>();
+            remaining -= matches.len();
+            batch.push(FileMatches {
+                file_path: file_path.to_string_lossy().into_owned(),
+                file_name: file_name(&file_path),
+                hit_count: hits.len(),
+                matches,
+            });
+
+            if sent_at.elapsed() >= BATCH_INTERVAL {
+                emit_batch(&app, search_id, &mut batch);
+                sent_at = Instant::now();
+            }
+        }
+
+        emit_batch(&app, search_id, &mut batch);
+        {
+            let mut running = running();
+            if running
+                .get(&search_id)
+                .is_some_and(|flag| Arc::ptr_eq(flag, &cancelled))
+            {
+                running.remove(&search_id);
+            }
+        }
+        let _ = app.emit("search-done", summary);
+    });
+
+    Ok(())
+}
+
+/// Stops a search started with [`start_search`]. Results already sent stay
+/// valid; no further batches follow.
+#[tauri::command]
+pub fn cancel_search(search_id: u64) {
+    if let Some(flag) = running().remove(&search_id) {
+        flag.store(true, Ordering::Relaxed);
+    }
+}
+
+fn running() -> MutexGuard<'static, HashMap<u64, Arc<AtomicBool>>> {
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn emit_batch(app: &AppHandle, search_id: u64, batch: &mut Vec<FileMatches>) {
+    if batch.is_empty() {
+        return;
+    }
+    let files = std::mem::take(batch);
+    let _ = app.emit("search-results", SearchBatch { search_id, files });
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn line_match(lines: &[&str], hit: &Hit, context: usize) -> LineMatch {
+    let line = lines.get(hit.line).copied().unwrap_or_default();
+    let (match_start, match_end) = hit.utf16_range(line);
+    let before = hit.line.saturating_sub(context).min(lines.len())..hit.line.min(lines.len());
+    let after = (hit.line + 1).min(lines.len())..(hit.line + 1 + context).min(lines.len());
+    LineMatch {
+        line_number: hit.line + 1,
+        line_content: line.to_string(),
+        match_start,
+        match_end,
+        context_before: lines[before].iter().map(|l| l.to_string()).collect(),
+        context_after: lines[after].iter().map(|l| l.to_string()).collect(),
+    }
+}
+
+/// A parsed query and how its text is matched.
+pub struct Search {
+    query: SearchQuery,
+    /// The compiled pattern in regex mode, where the query is one `Text`.
+    regex: Option<Matcher>,
+    options: MatchOptions,
+}
+
+/// One match to be replaced, located by line and by byte offsets.
+pub struct Replacement {
+    /// Zero-based line of the match.
+    pub line: usize,
+    /// Byte range within the line.
+    pub line_range: Range<usize>,
+    /// Byte range within the whole file.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl Search {
+    pub fn new(query: String, options: MatchOptions, regex: bool) -> Result<Self, AppError> {
+        if regex {
+            return Self::pattern(query, options, true);
+        }
+        Ok(Search {
+            query: parse_query(&query)?,
+            regex: None,
+            options,
+        })
+    }
+
+    /// The whole query as a single literal or regex pattern, bypassing the
+    /// query syntax, so operators and field terms are matched as text.
+    pub fn pattern(query: String, options: MatchOptions, regex: bool) -> Result<Self, AppError> {
+        if query.is_empty() {
+            return Err(AppError::Query("The query is empty".to_string()));
+        }
+        Ok(Search {
+            regex: if regex {
+                Some(Matcher::regex(&query, options)?)
             } else {
-                line.to_lowercase()
+                None
+            },
+            query: SearchQuery {
+                expr: Query::Text(query),
+                in_code: false,
+            },
+            options,
+        })
+    }
+
+    /// Files to test, most relevant first when the index can rank them.
+    pub fn candidates(&self, app: &AppHandle, root: &Path) -> Vec<PathBuf> {
+        // A regex can match text the index has no tokens for
+        let terms = match self.regex {
+            Some(_) => Vec::new(),
+            None => self.query.expr.required_terms(),
+        };
+        search_index::ranked_files(app, root, &terms).unwrap_or_else(|| {
+            let mut files = collect_workspace_files(root);
+            files.sort();
+            files
+        })
+    }
+
+    /// Whether results can change with the clock alone, without any file
+    /// changing. Such a search must be rebuilt to move its date bounds.
+    pub fn depends_on_time(&self) -> bool {
+        self.query.expr.depends_on_time()
+    }
+
+    /// Whether the file at `path` matches the query.
+    pub fn matches(&self, path: &Path, root: &Path) -> bool {
+        Note::new(path, root).hits(self).is_some()
+    }
+
+    /// The file's content and the matches `template` would replace, in
+    /// order and without overlaps. Files that do not match give `None`.
+    pub fn replacements(
+        &self,
+        path: &Path,
+        root: &Path,
+        template: &str,
+    ) -> Option<(String, Vec<Replacement>)> {
+        let note = Note::new(path, root);
+        let hits = note.hits(self)?;
+        let content = note.content()?;
+        let starts = note.line_starts();
+
+        let mut replacements = Vec::new();
+        let mut covered = 0;
+        for hit in hits {
+            let line_start = starts[hit.line];
+            if hit.start == hit.end || line_start + hit.start < covered {
+                continue;
+            }
+            let line = content[line_start..].lines().next().unwrap_or_default();
+            let text = match &self.regex {
+                Some(regex) => regex.replacement(line, hit.start..hit.end, template),
+                None => template.to_string(),
             };
+            covered = line_start + hit.end;
+            replacements.push(Replacement {
+                line: hit.line,
+                line_range: hit.start..hit.end,
+                range: line_start + hit.start..covered,
+                text,
+            });
+        }
+        Some((content.to_string(), replacements))
+    }
+}
+
+/// A match: zero-based line and byte range within that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Hit {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Hit {
+    /// The match range in UTF-16 code units, as the editor counts them.
+    fn utf16_range(&self, line: &str) -> (usize, usize) {
+        let start = self.start.min(line.len());
+        let end = self.end.min(line.len());
+        (utf16_len(&line[..start]), utf16_len(&line[..end]))
+    }
+}
+
+/// A file being tested against a query. Everything but the path is loaded
+/// on first use, so path and date filters never read the file.
+struct Note<'a> {
+    path: &'a Path,
+    root: &'a Path,
+    content: OnceCell<Option<String>>,
+    frontmatter: OnceCell<Option<Frontmatter>>,
+    line_starts: OnceCell<Vec<usize>>,
+    code: OnceCell<Vec<Range<usize>>>,
+    headings: OnceCell<Vec<usize>>,
+    tags: OnceCell<Vec<String>>,
+}
+
+impl<'a> Note<'a> {
+    fn new(path: &'a Path, root: &'a Path) -> Self {
+        Note {
+            path,
+            root,
+            content: OnceCell::new(),
+            frontmatter: OnceCell::new(),
+            line_starts: OnceCell::new(),
+            code: OnceCell::new(),
+            headings: OnceCell::new(),
+            tags: OnceCell::new(),
+        }
+    }
+
+    fn content(&self) -> Option<&str> {
+        self.content
+            .get_or_init(|| fs::read_to_string(self.path).ok())
+            .as_deref()
+    }
+
+    fn frontmatter(&self) -> Option<&Frontmatter> {
+        self.frontmatter
+            .get_or_init(|| self.content().and_then(|c| split_frontmatter(c).0))
+            .as_ref()
+    }
+
+    /// Byte offset where each line starts.
+    fn line_starts(&self) -> &[usize] {
+        self.line_starts.get_or_init(|| {
+            let content = self.content().unwrap_or_default();
+            std::iter::once(0)
+                .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+                .collect()
+        })
+    }
+
+    /// Offset of the markdown body, after any frontmatter.
+    fn body_start(&self) -> usize {
+        let content = self.content().unwrap_or_default();
+        content.len() - split_frontmatter(content).1.len()
+    }
+
+    /// Byte ranges of code spans and blocks.
+    fn code(&self) -> &[Range<usize>] {
+        self.code.get_or_init(|| {
+            let offset = self.body_start();
+            let body = &self.content().unwrap_or_default()[offset..];
+            Parser::new_ext(body, parser_options())
+                .into_offset_iter()
+                .filter_map(|(event, range)| match event {
+                    Event::Start(Tag::CodeBlock(_)) | Event::Code(_) => {
+                        Some(range.start + offset..range.end + offset)
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Zero-based lines on which headings start.
+    fn headings(&self) -> &[usize] {
+        self.headings.get_or_init(|| {
+            let starts = self.line_starts();
+            let offset = self.body_start();
+            let body = &self.content().unwrap_or_default()[offset..];
+            Parser::new_ext(body, parser_options())
+                .into_offset_iter()
+                .filter_map(|(event, range)| match event {
+                    Event::Start(Tag::Heading { .. }) => {
+                        Some(starts.partition_point(|&s| s <= range.start + offset) - 1)
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Frontmatter `tags` plus inline `#tags` outside code, lowercased.
+    fn tags(&self) -> &[String] {
+        self.tags.get_or_init(|| {
+            let mut tags: Vec<String> = Vec::new();
+            if let Some(fm) = self.frontmatter() {
+                for value in fm.get_list("tags").into_iter().chain(fm.get_list("tag")) {
+                    tags.extend(
+                        value
+                            .split(|c: char| c == ',' || c.is_whitespace())
+                            .map(|t| t.trim_start_matches('#').to_lowercase())
+                            .filter(|t| !t.is_empty()),
+                    );
+                }
+            }
+            let content = self.content().unwrap_or_default();
+            let body_start = self.body_start();
+            let code = self.code();
+            for cap in TAG_RE.captures_iter(&content[body_start..]) {
+                let tag = cap.get(1).expect("tag group always exists");
+                let at = body_start + tag.start();
+                if code.iter().any(|r| r.contains(&at)) {
+                    continue;
+                }
+                if tag.as_str().chars().all(|c| c.is_ascii_digit()) {
+                    continue;
+                }
+                tags.push(tag.as_str().to_lowercase());
+            }
+            tags
+        })
+    }
+
+    fn modified(&self) -> Option<i64> {
+        let modified = fs::metadata(self.path).ok()?.modified().ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+    }
 
-            let mut search_start = 0;
-            while let Some(pos) = haystack[search_start..].find(&search_query) {
-                let match_start = search_start + pos;
-                let match_end = match_start + query.len();
-
-                results.push(SearchResult {
-                    file_path: file_path_str.clone(),
-                    file_name: file_name.clone(),
-                    line_number: line_idx + 1,
-                    line_content: line.to_string(),
-                    match_start,
-                    match_end,
-                });
-
-                if results.len() >= MAX_RESULTS {
-                    break 'outer;
+    /// Sorted matches of the whole query, or `None` when the note does not
+    /// match or cannot be read. Notes that match only through filters get a
+    /// single empty match on their first heading.
+    fn hits(&self, search: &Search) -> Option<Vec<Hit>> {
+        let mut hits = self.eval(&search.query.expr, search)?;
+        self.content()?;
+        if hits.is_empty() {
+            let line = self.headings().first().copied().unwrap_or(0);
+            hits.push(Hit {
+                line,
+                start: 0,
+                end: 0,
+            });
+        }
+        hits.sort();
+        hits.dedup();
+        Some(hits)
+    }
+
+    /// Whether the note satisfies `expr`, and if so the line matches that
+    /// should be highlighted (empty for conditions on the file as a whole).
+    fn eval(&self, expr: &Query, search: &Search) -> Option<Vec<Hit>> {
+        match expr {
+            Query::Text(text) => {
+                let literal;
+                let matcher = match &search.regex {
+                    Some(regex) => regex,
+                    None => {
+                        literal = Matcher::literal(text, search.options);
+                        &literal
+                    }
+                };
+                let hits: Vec<Hit> = self
+                    .find(matcher, None)
+                    .into_iter()
+                    .filter(|hit| !search.query.in_code || self.in_code(hit))
+                    .collect();
+                (!hits.is_empty()).then_some(hits)
+            }
+            Query::Heading(text) => {
+                let options = MatchOptions {
+                    case_sensitive: false,
+                    ..search.options
+                };
+                let matcher = Matcher::literal(text, options);
+                let hits = self.find(&matcher, Some(self.headings()));
+                (!hits.is_empty()).then_some(hits)
+            }
+            Query::Filter(filter) => self.matches_filter(filter).then(Vec::new),
+            Query::And(items) => {
+                let mut hits = Vec::new();
+                for item in items {
+                    hits.extend(self.eval(item, search)?);
+                }
+                Some(hits)
+            }
+            Query::Or(items) => {
+                let mut matched = false;
+                let mut hits = Vec::new();
+                for item in items {
+                    if let Some(found) = self.eval(item, search) {
+                        matched = true;
+                        hits.extend(found);
+                    }
                 }
+                matched.then_some(hits)
+            }
+            Query::Not(inner) => match self.eval(inner, search) {
+                Some(_) => None,
+                None => Some(Vec::new()),
+            },
+        }
+    }
+
+    /// Matches on every line, or only on `only_lines`.
+    fn find(&self, matcher: &Matcher, only_lines: Option<&[usize]>) -> Vec<Hit> {
+        let Some(content) = self.content() else {
+            return Vec::new();
+        };
 
-                search_start = match_start + 1;
+        let mut hits = Vec::new();
+        for (line_idx, line) in content.lines().enumerate() {
+            if only_lines.is_some_and(|lines| !lines.contains(&line_idx)) {
+                continue;
             }
+            hits.extend(matcher.find_iter(line).into_iter().map(|range| Hit {
+                line: line_idx,
+                start: range.start,
+                end: range.end,
+            }));
         }
+        hits
     }
 
-    Ok(results)
+    fn in_code(&self, hit: &Hit) -> bool {
+        let Some(&line_start) = self.line_starts().get(hit.line) else {
+            return false;
+        };
+        let at = line_start + hit.start;
+        self.code().iter().any(|r| r.contains(&at))
+    }
+
+    fn matches_filter(&self, filter: &Filter) -> bool {
+        match filter {
+            Filter::Path(pattern) => {
+                let relative = self.path.strip_prefix(self.root).unwrap_or(self.path);
+                pattern.matches(&relative.to_string_lossy().replace('\\', "/"))
+            }
+            Filter::File(pattern) => self
+                .path
+                .file_name()
+                .is_some_and(|name| pattern.matches(&name.to_string_lossy())),
+            Filter::Tag(tag) => self.tags().iter().any(|t| {
+                t == tag
+                    || t.strip_prefix(tag.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }),
+            Filter::Prop { key, value } => {
+                let Some(fm) = self.frontmatter() else {
+                    return false;
+                };
+                let items = fm.get_list(key);
+                match value {
+                    Some(value) => {
+                        let value = value.to_lowercase();
+                        items.iter().any(|item| item.to_lowercase() == value)
+                    }
+                    None => !items.is_empty(),
+                }
+            }
+            Filter::Modified { from, to } => self.modified().is_some_and(|time| {
+                from.is_none_or(|from| time >= from) && to.is_none_or(|to| time < to)
+            }),
+        }
+    }
 }
@@ -0,0 +1,453 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::search_match::fold;
+use crate::utils::{
+    collect_workspace_files, file_stamp, index_store_path, validate_directory, write_atomic,
+};
+use crate::watcher::FileChangeEvent;
+
+/// Bumped whenever the file format or tokenizer changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 2;
+/// Folder under the app data dir holding one index file per workspace.
+const INDEX_DIR: &str = "search-index";
+/// BM25 term-frequency saturation and document-length normalisation.
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// Incremental updates are written back at most this often; the rest is
+/// flushed when the watcher stops.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, IndexState>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+enum IndexState {
+    /// Loading or building in the background. Watcher changes that arrive
+    /// meanwhile are replayed once it is ready.
+    Building {
+        pending: HashSet<PathBuf>,
+    },
+    Ready(Box<WorkspaceIndex>),
+}
+
+struct WorkspaceIndex {
+    data: IndexData,
+    /// Where the index is persisted; `None` keeps it in memory only.
+    store: Option<PathBuf>,
+    dirty: bool,
+    saved_at: Instant,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct IndexData {
+    version: u32,
+    root: String,
+    /// Indexed files by id; removed files leave a free slot.
+    docs: Vec<Option<Document>>,
+    /// Term → `(document id, term frequency)` for every document containing it.
+    postings: HashMap<String, Vec<(u32, u32)>>,
+    total_length: u64,
+    #[serde(skip)]
+    ids: HashMap<String, u32>,
+    #[serde(skip)]
+    free: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    path: String,
+    modified: u64,
+    size: u64,
+    /// Token count, for BM25 length normalisation.
+    length: u32,
+    /// Distinct terms, so the document can be removed from the postings.
+    terms: Vec<String>,
+}
+
+/// A query token and whether the query text continues past it on either
+/// side, which decides how it may match an indexed term.
+struct QueryToken {
+    text: String,
+    open_start: bool,
+    open_end: bool,
+}
+
+impl QueryToken {
+    fn matches(&self, term: &str) -> bool {
+        match (self.open_start, self.open_end) {
+            (false, false) => term == self.text,
+            (false, true) => term.starts_with(&self.text),
+            (true, false) => term.ends_with(&self.text),
+            (true, true) => term.contains(&self.text),
+        }
+    }
+}
+
+fn indexes() -> MutexGuard<'static, HashMap<PathBuf, IndexState>> {
+    INDEXES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Runs of letters and digits, case folded and without diacritics so the
+/// index finds candidates for every search mode.
+fn tokenize(text: &str) -> Vec<String> {
+    fold(text, true, true)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn query_tokens(query: &str) -> Vec<QueryToken> {
+    let lower = fold(query, true, true);
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in lower
+        .char_indices()
+        .chain(std::iter::once((lower.len(), ' ')))
+    {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                tokens.push(QueryToken {
+                    text: lower[from..i].to_string(),
+                    open_start: from == 0,
+                    open_end: i == lower.len(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Modification time in seconds and size, used to spot changed files.
+impl IndexData {
+    fn new(root: String) -> Self {
+        IndexData {
+            version: INDEX_VERSION,
+            root,
+            ..Default::default()
+        }
+    }
+
+    fn rebuild_ids(&mut self) {
+        self.ids.clear();
+        self.free.clear();
+        for (id, doc) in self.docs.iter().enumerate() {
+            match doc {
+                Some(doc) => {
+                    self.ids.insert(doc.path.clone(), id as u32);
+                }
+                None => self.free.push(id as u32),
+            }
+        }
+    }
+
+    fn live_docs(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn add(&mut self, path: &Path) {
+        let Some((modified, size)) = file_stamp(path) else {
+            return;
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        let mut length = 0u32;
+        for token in tokenize(&content) {
+            *counts.entry(token).or_insert(0) += 1;
+            length += 1;
+        }
+
+        let id = match self.free.pop() {
+            Some(free) => free,
+            None => {
+                self.docs.push(None);
+                (self.docs.len() - 1) as u32
+            }
+        };
+        let mut terms = Vec::with_capacity(counts.len());
+        for (term, tf) in counts {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .push((id, tf));
+            terms.push(term);
+        }
+        let key = path.to_string_lossy().into_owned();
+        self.ids.insert(key.clone(), id);
+        self.total_length += u64::from(length);
+        self.docs[id as usize] = Some(Document {
+            path: key,
+            modified,
+            size,
+            length,
+            terms,
+        });
+    }
+
+    fn remove(&mut self, path: &str) {
+        let Some(id) = self.ids.remove(path) else {
+            return;
+        };
+        let Some(doc) = self.docs[id as usize].take() else {
+            return;
+        };
+        self.free.push(id);
+        self.total_length -= u64::from(doc.length);
+        for term in doc.terms {
+            if let Some(list) = self.postings.get_mut(&term) {
+                list.retain(|&(doc_id, _)| doc_id != id);
+                if list.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, path: &Path) {
+        self.remove(&path.to_string_lossy());
+        if path.is_file() {
+            self.add(path);
+        }
+    }
+
+    /// Bring the index in line with the files on disk, re-reading only
+    /// files whose size or modification time changed.
+    fn reconcile(&mut self, root: &Path) {
+        let mut seen: HashSet<String> = HashSet::new();
+        for path in collect_workspace_files(root) {
+            let key = path.to_string_lossy().into_owned();
+            let unchanged = self
+                .ids
+                .get(&key)
+                .and_then(|&id| self.docs[id as usize].as_ref())
+                .is_some_and(|doc| file_stamp(&path) == Some((doc.modified, doc.size)));
+            if !unchanged {
+                self.update(&path);
+            }
+            seen.insert(key);
+        }
+        let gone: Vec<String> = self
+            .ids
+            .keys()
+            .filter(|k| !seen.contains(*k))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.remove(&path);
+        }
+    }
+
+    /// Score every document containing all query tokens with BM25.
+    fn rank(&self, tokens: &[QueryToken]) -> Vec<(f64, &str)> {
+        let total = self.live_docs();
+        if total == 0 {
+            return Vec::new();
+        }
+        let average_length = (self.total_length as f64 / total as f64).max(1.0);
+        let mut scores: Option<HashMap<u32, f64>> = None;
+
+        for token in tokens {
+            let mut frequencies: HashMap<u32, u32> = HashMap::new();
+            let mut add = |list: &Vec<(u32, u32)>| {
+                for &(id, tf) in list {
+                    *frequencies.entry(id).or_insert(0) += tf;
+                }
+            };
+            if !token.open_start && !token.open_end {
+                if let Some(list) = self.postings.get(&token.text) {
+                    add(list);
+                }
+            } else {
+                for (term, list) in &self.postings {
+                    if token.matches(term) {
+                        add(list);
+                    }
+                }
+            }
+
+            let df = frequencies.len() as f64;
+            let idf = (1.0 + (total as f64 - df + 0.5) / (df + 0.5)).ln();
+            let token_scores = frequencies.into_iter().filter_map(|(id, tf)| {
+                let doc = self.docs[id as usize].as_ref()?;
+                let tf = f64::from(tf);
+                let norm = 1.0 - BM25_B + BM25_B * f64::from(doc.length) / average_length;
+                Some((id, idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * norm)))
+            });
+            scores = Some(match scores {
+                None => token_scores.collect(),
+                Some(mut previous) => {
+                    let current: HashMap<u32, f64> = token_scores.collect();
+                    previous.retain(|id, _| current.contains_key(id));
+                    for (id, score) in previous.iter_mut() {
+                        *score += current[id];
+                    }
+                    previous
+                }
+            });
+        }
+
+        let mut ranked: Vec<(f64, &str)> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.docs[id as usize]
+                    .as_ref()
+                    .map(|doc| (score, doc.path.as_str()))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        ranked
+    }
+}
+
+impl WorkspaceIndex {
+    fn save(&mut self) {
+        self.dirty = false;
+        self.saved_at = Instant::now();
+        let Some(store) = &self.store else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_vec(&self.data) {
+            let _ = write_atomic(store, &json);
+        }
+    }
+}
+
+fn load(store: &Path, root: &str) -> Option<IndexData> {
+    let bytes = fs::read(store).ok()?;
+    let mut data: IndexData = serde_json::from_slice(&bytes).ok()?;
+    if data.version != INDEX_VERSION || data.root != root {
+        return None;
+    }
+    data.rebuild_ids();
+    Some(data)
+}
+
+/// Start loading the index for `root` in the background unless it is
+/// already loaded or on its way.
+pub fn open(app: &AppHandle, root: &Path) {
+    let mut all = indexes();
+    if all.contains_key(root) {
+        return;
+    }
+    all.insert(
+        root.to_path_buf(),
+        IndexState::Building {
+            pending: HashSet::new(),
+        },
+    );
+    drop(all);
+
+    let root = root.to_path_buf();
+    let store = index_store_path(app, INDEX_DIR, &root);
+    thread::spawn(move || {
+        let key = root.to_string_lossy().into_owned();
+        let mut data = store
+            .as_deref()
+            .and_then(|s| load(s, &key))
+            .unwrap_or_else(|| IndexData::new(key));
+        data.reconcile(&root);
+        let mut index = WorkspaceIndex {
+            data,
+            store,
+            dirty: false,
+            saved_at: Instant::now(),
+        };
+        index.save();
+
+        let mut all = indexes();
+        let Some(IndexState::Building { pending }) = all.remove(&root) else {
+            // Rebuilt or dropped while we were working; keep the newer state.
+            return;
+        };
+        for path in &pending {
+            index.data.update(path);
+        }
+        index.dirty = !pending.is_empty();
+        all.insert(root, IndexState::Ready(Box::new(index)));
+    });
+}
+
+/// Files under `root` that may contain every one of `terms`, best BM25
+/// match first.
+///
+/// Returns `None` while the index is still being built, or when the terms
+/// have no letters or digits to look up; callers then scan every file.
+pub fn ranked_files(app: &AppHandle, root: &Path, terms: &[String]) -> Option<Vec<PathBuf>> {
+    open(app, root);
+    let tokens: Vec<QueryToken> = terms.iter().flat_map(|t| query_tokens(t)).collect();
+    if tokens.is_empty() {
+        return None;
+    }
+    let all = indexes();
+    let Some(IndexState::Ready(index)) = all.get(root) else {
+        return None;
+    };
+    Some(
+        index
+            .data
+            .rank(&tokens)
+            .into_iter()
+            .map(|(_, path)| PathBuf::from(path))
+            .collect(),
+    )
+}
+
+/// Apply watcher events for the workspace at `folder`.
+pub fn apply_changes(folder: &str, changes: &[FileChangeEvent]) {
+    let mut all = indexes();
+    match all.get_mut(Path::new(folder)) {
+        Some(IndexState::Building { pending }) => {
+            pending.extend(changes.iter().map(|c| PathBuf::from(&c.path)));
+        }
+        Some(IndexState::Ready(index)) => {
+            for change in changes {
+                if change.change_type == "deleted" {
+                    index.data.remove(&change.path);
+                } else {
+                    index.data.update(Path::new(&change.path));
+                }
+            }
+            index.dirty = true;
+            if index.saved_at.elapsed() >= SAVE_INTERVAL {
+                index.save();
+            }
+        }
+        None => {}
+    }
+}
+
+/// Write pending incremental updates for `folder` to disk.
+pub fn flush(folder: &str) {
+    if let Some(IndexState::Ready(index)) = indexes().get_mut(Path::new(folder)) {
+        if index.dirty {
+            index.save();
+        }
+    }
+}
+
+/// Discard the search index of `folder` and build it again from scratch.
+#[tauri::command]
+pub fn rebuild_search_index(app: AppHandle, folder: String) -> Result<(), AppError> {
+    let root = validate_directory(&folder)?;
+    indexes().remove(&root);
+    if let Some(store) = index_store_path(&app, INDEX_DIR, &root) {
+        let _ = fs::remove_file(store);
+    }
+    open(&app, &root);
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+use crate::error::AppError;
+
+/// How search text is compared against file contents.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Treat `e`, `é` and `ë` as the same letter.
+    pub ignore_diacritics: bool,
+}
+
+/// Compiled search text, matched one line at a time.
+pub enum Matcher {
+    Literal {
+        needle: String,
+        options: MatchOptions,
+    },
+    Regex {
+        regex: Regex,
+        options: MatchOptions,
+    },
+}
+
+impl Matcher {
+    pub fn literal(text: &str, options: MatchOptions) -> Self {
+        Matcher::Literal {
+            needle: fold(text, !options.case_sensitive, options.ignore_diacritics),
+            options,
+        }
+    }
+
+    /// Compile `pattern` with the `regex` crate's syntax. Case-insensitive
+    /// patterns use Unicode simple case folding.
+    pub fn regex(pattern: &str, options: MatchOptions) -> Result<Self, AppError> {
+        let pattern = if options.ignore_diacritics {
+            fold(pattern, false, true)
+        } else {
+            pattern.to_string()
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| AppError::Query(e.to_string()))?;
+        Ok(Matcher::Regex { regex, options })
+    }
+
+    /// Non-overlapping, non-empty matches in `line`, as byte ranges of the
+    /// original (unfolded) line.
+    pub fn find_iter(&self, line: &str) -> Vec<Range<usize>> {
+        let (options, folded) = match self {
+            Matcher::Literal { options, .. } => (
+                options,
+                Folded::new(line, !options.case_sensitive, options.ignore_diacritics),
+            ),
+            Matcher::Regex { options, .. } => {
+                (options, Folded::new(line, false, options.ignore_diacritics))
+            }
+        };
+        let text = folded.text.as_str();
+
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while from <= text.len() {
+            let found = match self {
+                Matcher::Literal { needle, .. } if needle.is_empty() => None,
+                Matcher::Literal { needle, .. } => text[from..]
+                    .find(needle.as_str())
+                    .map(|pos| from + pos..from + pos + needle.len()),
+                Matcher::Regex { regex, .. } => regex.find_at(text, from).map(|m| m.range()),
+            };
+            let Some(range) = found else {
+                break;
+            };
+            if range.is_empty() {
+                from = next_char(text, range.start);
+                continue;
+            }
+            let original = folded.original(range.clone());
+            if options.whole_word && !is_whole_word(line, &original) {
+                from = next_char(text, range.start);
+                continue;
+            }
+            from = range.end;
+            matches.push(original);
+        }
+        matches
+    }
+
+    /// Text to put in place of the match at `range` of `line`: `template`
+    /// as is for literal search; for regex search with `$1`, `$name` and
+    /// `${name}` replaced by capture groups and `$$` by `$`.
+    pub fn replacement(&self, line: &str, range: Range<usize>, template: &str) -> String {
+        let Matcher::Regex { regex, options } = self else {
+            return template.to_string();
+        };
+        let folded = Folded::new(line, false, options.ignore_diacritics);
+        let at = folded.starts.partition_point(|&s| s < range.start);
+        let Some(captures) = regex
+            .captures_at(&folded.text, at)
+            .filter(|c| c.get(0).is_some_and(|m| m.start() == at))
+        else {
+            return template.to_string();
+        };
+        let group = |m: regex::Match| match m.is_empty() {
+            true => "",
+            false => &line[folded.original(m.range())],
+        };
+        expand(template, |name| match name.parse::<usize>() {
+            Ok(index) => captures.get(index).map_or("", group),
+            Err(_) => captures.name(name).map_or("", group),
+        })
+    }
+}
+
+/// Expand `$group` references in a replacement template, following the
+/// `regex` crate's syntax.
+fn expand<'a>(template: &str, group: impl Fn(&str) -> &'a str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let (name, after) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(close) => (&braced[..close], &braced[close + 1..]),
+                None => ("", rest),
+            },
+            None => {
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..len], &rest[len..])
+            }
+        };
+        if name.is_empty() {
+            out.push('$');
+        } else {
+            out.push_str(group(name));
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Fold `text` for comparison: Unicode case folding when `case` is set,
+/// and combining marks removed when `diacritics` is set.
+pub fn fold(text: &str, case: bool, diacritics: bool) -> String {
+    Folded::new(text, case, diacritics).text
+}
+
+/// Folded text together with the original byte range of every folded byte.
+struct Folded {
+    text: String,
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+}
+
+impl Folded {
+    fn new(text: &str, case: bool, diacritics: bool) -> Self {
+        let mut folded = Folded {
+            text: String::with_capacity(text.len()),
+            starts: Vec::with_capacity(text.len()),
+            ends: Vec::with_capacity(text.len()),
+        };
+        if !case && !diacritics {
+            folded.text.push_str(text);
+            for (i, c) in text.char_indices() {
+                for _ in 0..c.len_utf8() {
+                    folded.starts.push(i);
+                    folded.ends.push(i + c.len_utf8());
+                }
+            }
+            return folded;
+        }
+
+        let mut previous = 0;
+        for (i, c) in text.char_indices() {
+            let end = i + c.len_utf8();
+            let before = folded.text.len();
+            if diacritics {
+                decompose_canonical(c, |d| {
+                    if !is_combining_mark(d) {
+                        push_char(&mut folded.text, d, case);
+                    }
+                });
+            } else {
+                push_char(&mut folded.text, c, case);
+            }
+            let added = folded.text.len() - before;
+            if added == 0 {
+                // A dropped mark belongs to the letter before it
+                for e in &mut folded.ends[previous..] {
+                    *e = end;
+                }
+                continue;
+            }
+            folded.starts.extend(std::iter::repeat_n(i, added));
+            folded.ends.extend(std::iter::repeat_n(end, added));
+            previous = before;
+        }
+        folded
+    }
+
+    /// Map a byte range of the folded text back onto the original.
+    fn original(&self, range: Range<usize>) -> Range<usize> {
+        self.starts[range.start]..self.ends[range.end - 1]
+    }
+}
+
+/// Push `c`, case folded when `case` is set. Covers the full foldings that
+/// matter for prose (`ß` → `ss`, final sigma, ligatures) on top of
+/// lowercasing; `İ` folds to a plain `i` so Turkish text matches either way.
+fn push_char(out: &mut String, c: char, case: bool) {
+    if !case {
+        out.push(c);
+        return;
+    }
+    match c {
+        'ß' | 'ẞ' => out.push_str("ss"),
+        'ς' => out.push('σ'),
+        'ſ' => out.push('s'),
+        'İ' => out.push('i'),
+        'ﬀ' => out.push_str("ff"),
+        'ﬁ' => out.push_str("fi"),
+        'ﬂ' => out.push_str("fl"),
+        'ﬃ' => out.push_str("ffi"),
+        'ﬄ' => out.push_str("ffl"),
+        'ﬅ' | 'ﬆ' => out.push_str("st"),
+        _ => out.extend(c.to_lowercase()),
+    }
+}
+
+fn next_char(text: &str, at: usize) -> usize {
+    at + text[at..].chars().next().map_or(1, char::len_utf8)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || is_combining_mark(c)
+}
+
+/// Whether `range` of `line` is not directly preceded or followed by a
+/// word character.
+fn is_whole_word(line: &str, range: &Range<usize>) -> bool {
+    let before = line[..range.start].chars().next_back();
+    let after = line[range.end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
@@ -0,0 +1,559 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::error::AppError;
+
+/// Field names recognised before a colon. Other `word:` prefixes, as in
+/// `std::fs` or `TODO:fix`, are searched as plain text.
+const FIELDS: &[&str] = &["path", "file", "tag", "heading", "in", "prop", "modified"];
+
+/// A parsed search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub expr: Query,
+    /// `in:code`: text terms only match inside code spans and blocks.
+    pub in_code: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// A word or quoted phrase, matched as a substring of a line.
+    Text(String),
+    /// `heading:` text contained in one of the note's headings.
+    Heading(String),
+    Filter(Filter),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+/// Conditions on the file rather than on its lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Path relative to the workspace root (`/`-separated).
+    Path(Pattern),
+    File(Pattern),
+    /// Lowercase tag without `#`; also matches nested `tag/child` tags.
+    Tag(String),
+    /// Frontmatter key, optionally with the value one of its items must equal.
+    Prop {
+        key: String,
+        value: Option<String>,
+    },
+    /// Modification time within `[from, to)`, in Unix seconds.
+    Modified {
+        from: Option<i64>,
+        to: Option<i64>,
+    },
+}
+
+/// Case-insensitive substring, or a glob when it contains `*` or `?`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Substring(String),
+    Glob(String),
+}
+
+impl Pattern {
+    fn new(text: &str) -> Self {
+        let lower = text.to_lowercase();
+        if lower.contains(['*', '?']) {
+            Pattern::Glob(lower)
+        } else {
+            Pattern::Substring(lower)
+        }
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        match self {
+            Pattern::Substring(s) => text.contains(s.as_str()),
+            Pattern::Glob(g) => glob_match(g, &text),
+        }
+    }
+}
+
+/// `*` matches any run of characters (including `/`), `?` a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+impl Query {
+    /// Text every matching file must contain, for narrowing candidates
+    /// through the index. Branches of `OR` and `NOT` contribute nothing.
+    pub fn required_terms(&self) -> Vec<String> {
+        match self {
+            Query::Text(text) | Query::Heading(text) => vec![text.clone()],
+            Query::And(items) => items.iter().flat_map(Query::required_terms).collect(),
+            Query::Filter(_) | Query::Or(_) | Query::Not(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the query has a `modified:` filter, whose bounds are resolved
+    /// against the current date when parsed (`today`, `7d`, ...).
+    pub fn depends_on_time(&self) -> bool {
+        match self {
+            Query::Filter(Filter::Modified { .. }) => true,
+            Query::Text(_) | Query::Heading(_) | Query::Filter(_) => false,
+            Query::And(items) | Query::Or(items) => items.iter().any(Query::depends_on_time),
+            Query::Not(inner) => inner.depends_on_time(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Leaf(Query),
+    InCode,
+}
+
+fn query_error(message: String) -> AppError {
+    AppError::Query(message)
+}
+
+/// Parse the search box syntax:
+///
+/// - words are combined with `AND` unless separated by `OR`; `NOT` or a
+///   leading `-` negates, and parentheses group;
+/// - `"quoted phrases"` match literally, including `AND`/`OR`/`NOT`;
+/// - `path:`, `file:` (substring or `*`/`?` glob), `tag:`, `heading:`,
+///   `prop:key` / `prop:key=value` and `in:code`;
+/// - `modified:` with `2024-05-01`, `2024-05`, `2024`, `today`,
+///   `yesterday`, `7d`, `2w`, a comparison (`>2024-05-01`, `<=2024-05`) or a
+///   range (`2024-01-01..2024-03-31`, either end optional).
+pub fn parse_query(input: &str) -> Result<SearchQuery, AppError> {
+    let tokens = tokenize(input)?;
+    let in_code = tokens.iter().any(|(t, _)| *t == Token::InCode);
+    let tokens: Vec<(Token, usize)> = tokens
+        .into_iter()
+        .filter(|(t, _)| *t != Token::InCode)
+        .collect();
+
+    if tokens.is_empty() {
+        return Err(query_error(if in_code {
+            "in:code needs a search term to look for".to_string()
+        } else {
+            "The query is empty".to_string()
+        }));
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if let Some((token, column)) = parser.tokens.get(parser.pos) {
+        return Err(query_error(match token {
+            Token::Close => format!("Unmatched ')' at column {}", column),
+            _ => format!("Unexpected input at column {}", column),
+        }));
+    }
+    Ok(SearchQuery { expr, in_code })
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, AppError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    let column = |byte: usize| input[..byte].chars().count() + 1;
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let col = column(start);
+        match c {
+            '(' => {
+                chars.next();
+                tokens.push((Token::Open, col));
+            }
+            ')' => {
+                chars.next();
+                tokens.push((Token::Close, col));
+            }
+            '"' => {
+                chars.next();
+                let text = read_quoted(&mut chars, col)?;
+                if text.is_empty() {
+                    return Err(query_error(format!("Empty phrase at column {}", col)));
+                }
+                tokens.push((Token::Leaf(Query::Text(text)), col));
+            }
+            '-' if chars
+                .clone()
+                .nth(1)
+                .is_some_and(|(_, next)| !next.is_whitespace() && next != ')') =>
+            {
+                chars.next();
+                tokens.push((Token::Not, col));
+            }
+            _ => {
+                let word = read_word(&mut chars, col)?;
+                let token = match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => field_token(&word, col)?,
+                };
+                tokens.push((token, col));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Read up to the closing quote; `\"` and `\\` are escapes.
+fn read_quoted(chars: &mut Peekable<CharIndices>, column: usize) -> Result<String, AppError> {
+    let mut text = String::new();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => return Ok(text),
+            '\\' => match chars.next() {
+                Some((_, escaped)) => text.push(escaped),
+                None => break,
+            },
+            _ => text.push(c),
+        }
+    }
+    Err(query_error(format!(
+        "Unclosed quote starting at column {}",
+        column
+    )))
+}
+
+/// Read a bare word up to whitespace or a parenthesis. Quoted parts are
+/// kept together with their quotes removed, as in `path:"daily notes"`.
+fn read_word(chars: &mut Peekable<CharIndices>, column: usize) -> Result<String, AppError> {
+    let mut word = String::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            break;
+        }
+        chars.next();
+        if c == '"' {
+            word.push_str(&read_quoted(chars, column)?);
+        } else {
+            word.push(c);
+        }
+    }
+    Ok(word)
+}
+
+fn field_token(word: &str, column: usize) -> Result<Token, AppError> {
+    let Some((name, value)) = word.split_once(':') else {
+        return Ok(Token::Leaf(Query::Text(word.to_string())));
+    };
+    let name = name.to_ascii_lowercase();
+    let is_field = FIELDS.contains(&name.as_str()) && !value.is_empty() && !value.starts_with("//");
+    if !is_field {
+        return Ok(Token::Leaf(Query::Text(word.to_string())));
+    }
+
+    let leaf = |query| Ok(Token::Leaf(query));
+    match name.as_str() {
+        "path" => leaf(Query::Filter(Filter::Path(Pattern::new(
+            &value.replace('\\', "/"),
+        )))),
+        "file" => leaf(Query::Filter(Filter::File(Pattern::new(value)))),
+        "tag" => {
+            let tag = value.trim_start_matches('#').to_lowercase();
+            if tag.is_empty() {
+                return Err(query_error(format!(
+                    "tag: at column {} needs a tag name",
+                    column
+                )));
+            }
+            leaf(Query::Filter(Filter::Tag(tag)))
+        }
+        "heading" => leaf(Query::Heading(value.to_string())),
+        "in" => match value.to_ascii_lowercase().as_str() {
+            "code" => Ok(Token::InCode),
+            other => Err(query_error(format!(
+                "Unknown scope 'in:{}' at column {}; only in:code is supported",
+                other, column
+            ))),
+        },
+        "prop" => {
+            let (key, value) = match value.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+                None => (value.trim(), None),
+            };
+            if key.is_empty() {
+                return Err(query_error(format!(
+                    "prop: at column {} needs a property name, as in prop:status=draft",
+                    column
+                )));
+            }
+            leaf(Query::Filter(Filter::Prop {
+                key: key.to_string(),
+                value: value.filter(|v| !v.is_empty()),
+            }))
+        }
+        "modified" => {
+            let (from, to) = parse_date_range(value).ok_or_else(|| {
+                query_error(format!(
+                    "Invalid date '{}' at column {}; use e.g. 2024-05-01, >2024-05, \
+                     2024-01-01..2024-03-31 or 7d",
+                    value, column
+                ))
+            })?;
+            leaf(Query::Filter(Filter::Modified { from, to }))
+        }
+        _ => leaf(Query::Text(word.to_string())),
+    }
+}
+
+/// Start of a local calendar day as a Unix timestamp.
+fn day_start(date: NaiveDate) -> Option<i64> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    Some(Local.from_local_datetime(&midnight).earliest()?.timestamp())
+}
+
+/// The days covered by `2024-05-01`, `2024-05`, `2024`, `today`,
+/// `yesterday`, `7d` or `2w`, as `[first, last_exclusive)`.
+fn parse_period(text: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let today = Local::now().date_naive();
+    let text = text.trim().to_ascii_lowercase();
+    match text.as_str() {
+        "today" => return Some((today, today + Duration::days(1))),
+        "yesterday" => return Some((today - Duration::days(1), today)),
+        _ => {}
+    }
+    if let Some(count) = text.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+        return Some((today - Duration::days(count - 1), today + Duration::days(1)));
+    }
+    if let Some(count) = text.strip_suffix('w').and_then(|n| n.parse::<i64>().ok()) {
+        return Some((
+            today - Duration::days(count * 7 - 1),
+            today + Duration::days(1),
+        ));
+    }
+
+    let parts: Vec<&str> = text.split('-').collect();
+    let numbers: Option<Vec<u32>> = parts.iter().map(|p| p.parse().ok()).collect();
+    let numbers = numbers?;
+    match numbers.as_slice() {
+        [year] if parts[0].len() == 4 => {
+            let first = NaiveDate::from_ymd_opt(*year as i32, 1, 1)?;
+            Some((first, first.with_year(first.year() + 1)?))
+        }
+        [year, month] => {
+            let first = NaiveDate::from_ymd_opt(*year as i32, *month, 1)?;
+            let next = if *month == 12 {
+                NaiveDate::from_ymd_opt(*year as i32 + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(*year as i32, month + 1, 1)?
+            };
+            Some((first, next))
+        }
+        [year, month, day] => {
+            let date = NaiveDate::from_ymd_opt(*year as i32, *month, *day)?;
+            Some((date, date + Duration::days(1)))
+        }
+        _ => None,
+    }
+}
+
+type DateBounds = (Option<i64>, Option<i64>);
+
+fn parse_date_range(value: &str) -> Option<DateBounds> {
+    if let Some((from, to)) = value.split_once("..") {
+        let from = match from.trim() {
+            "" => None,
+            text => Some(day_start(parse_period(text)?.0)?),
+        };
+        let to = match to.trim() {
+            "" => None,
+            text => Some(day_start(parse_period(text)?.1)?),
+        };
+        if from.is_none() && to.is_none() {
+            return None;
+        }
+        return Some((from, to));
+    }
+
+    let (op, rest) = [">=", "<=", ">", "<"]
+        .iter()
+        .find_map(|op| value.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("", value));
+    let (first, after) = parse_period(rest)?;
+    let (first, after) = (day_start(first)?, day_start(after)?);
+    Some(match op {
+        ">" => (Some(after), None),
+        ">=" => (Some(first), None),
+        "<" => (None, Some(first)),
+        "<=" => (None, Some(after)),
+        _ => (Some(first), Some(after)),
+    })
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    /// Column of the current token, or just past the end of the query.
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, c)| *c)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, AppError> {
+        let mut items = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            let column = self.column();
+            self.pos += 1;
+            if !self.starts_term() {
+                return Err(query_error(format!(
+                    "OR at column {} needs a term on both sides",
+                    column
+                )));
+            }
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Query::Or(items)
+        })
+    }
+
+    fn starts_term(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Open) | Some(Token::Not) | Some(Token::Leaf(_))
+        )
+    }
+
+    fn parse_and(&mut self) -> Result<Query, AppError> {
+        if !self.starts_term() {
+            let column = self.column();
+            return Err(query_error(match self.peek() {
+                Some(Token::Or) => format!("OR at column {} needs a term on both sides", column),
+                Some(Token::And) => format!("AND at column {} needs a term on both sides", column),
+                Some(Token::Close) => format!("Unmatched ')' at column {}", column),
+                _ => format!("Expected a search term at column {}", column),
+            }));
+        }
+        let mut items = vec![self.parse_unary()?];
+        loop {
+            if self.peek() == Some(&Token::And) {
+                let column = self.column();
+                self.pos += 1;
+                if !self.starts_term() {
+                    return Err(query_error(format!(
+                        "AND at column {} needs a term on both sides",
+                        column
+                    )));
+                }
+            } else if !self.starts_term() {
+                break;
+            }
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Query::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, AppError> {
+        if self.peek() == Some(&Token::Not) {
+            let column = self.column();
+            self.pos += 1;
+            if !self.starts_term() {
+                return Err(query_error(format!(
+                    "NOT at column {} needs a term after it",
+                    column
+                )));
+            }
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, AppError> {
+        let column = self.column();
+        match self.tokens.get(self.pos).map(|(t, _)| t.clone()) {
+            Some(Token::Leaf(query)) => {
+                self.pos += 1;
+                Ok(query)
+            }
+            Some(Token::Open) => {
+                self.pos += 1;
+                if self.peek() == Some(&Token::Close) {
+                    return Err(query_error(format!(
+                        "Empty parentheses at column {}",
+                        column
+                    )));
+                }
+                let inner = self.parse_or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(query_error(format!(
+                        "Missing ')' for the '(' at column {}",
+                        column
+                    )));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            _ => Err(query_error(format!(
+                "Expected a search term at column {}",
+                column
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_prefixes_are_text() {
+        for word in ["std::fs", "TODO:fix", "http://example.com", "note:"] {
+            let query = parse_query(word).unwrap();
+            assert_eq!(query.expr, Query::Text(word.to_string()), "{}", word);
+        }
+    }
+
+    #[test]
+    fn known_filters_still_validate() {
+        assert!(matches!(
+            parse_query("Tag:#Draft").unwrap().expr,
+            Query::Filter(Filter::Tag(tag)) if tag == "draft"
+        ));
+        assert!(parse_query("modified:someday").is_err());
+        assert!(parse_query("in:prose").is_err());
+    }
+}
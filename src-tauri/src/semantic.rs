@@ -0,0 +1,547 @@
+use base64::Engine as _;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::utils::{
+    collect_md_files, file_stamp, fnv1a, index_store_path, validate_directory, write_atomic,
+};
+
+/// Bumped whenever the file format or chunking changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+/// Folder under the app data dir holding one vector index per workspace.
+const INDEX_DIR: &str = "semantic-index";
+/// Passages are cut at paragraph boundaries once they reach this many bytes.
+const CHUNK_BYTES: usize = 1200;
+/// Longer passages are truncated before they are embedded.
+const MAX_INPUT_BYTES: usize = 8000;
+/// Passages sent per `/embeddings` request.
+const BATCH_SIZE: usize = 32;
+const DEFAULT_LIMIT: usize = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Loaded indexes by workspace root. The async mutex is held while an index
+/// is brought up to date, so concurrent searches never embed twice.
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, VectorIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// An OpenAI-compatible embeddings endpoint, as set in the AI settings.
+#[derive(Deserialize, Clone)]
+pub struct EmbeddingConfig {
+    /// API base such as `https://api.openai.com/v1` or Ollama's
+    /// `http://localhost:11434/v1`; `/embeddings` is appended.
+    pub base_url: String,
+    pub model: String,
+    #[serde(default)]
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct SemanticSearchOptions {
+    pub folder: String,
+    pub query: String,
+    pub config: EmbeddingConfig,
+    /// Maximum number of passages, 20 by default.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct SemanticHit {
+    pub file_path: String,
+    pub file_name: String,
+    /// One-based lines the passage spans.
+    pub line_start: usize,
+    pub line_end: usize,
+    /// The heading the passage sits under, if any.
+    pub heading: Option<String>,
+    pub text: String,
+    /// Cosine similarity to the query.
+    pub score: f32,
+}
+
+#[derive(Serialize)]
+pub struct SemanticIndexStats {
+    pub files: usize,
+    pub passages: usize,
+    /// Passages embedded by this update; the rest were reused.
+    pub embedded: usize,
+}
+
+/// Payload of the `semantic-index-progress` event.
+#[derive(Serialize, Clone)]
+struct IndexProgress {
+    folder: String,
+    done: usize,
+    total: usize,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VectorIndex {
+    version: u32,
+    root: String,
+    /// Endpoint and model the vectors came from; vectors of different
+    /// models cannot be compared, so a change rebuilds the index.
+    endpoint: String,
+    model: String,
+    files: HashMap<String, IndexedFile>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct IndexedFile {
+    modified: u64,
+    size: u64,
+    passages: Vec<Passage>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Passage {
+    line_start: usize,
+    line_end: usize,
+    heading: Option<String>,
+    text: String,
+    /// Hash of the embedded input, so unchanged passages of an edited note
+    /// keep their vectors.
+    hash: u64,
+    /// Unit length, so a dot product is the cosine similarity.
+    #[serde(serialize_with = "encode_vector", deserialize_with = "decode_vector")]
+    vector: Vec<f32>,
+}
+
+/// Vectors are stored as base64 little-endian `f32`s, several times smaller
+/// than JSON numbers.
+fn encode_vector<S: Serializer>(vector: &[f32], serializer: S) -> Result<S::Ok, S::Error> {
+    let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+    serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+fn decode_vector<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(serde::de::Error::custom)?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Embeds `query` and returns the most similar passages of the notes in
+/// `folder`. New and changed notes are chunked and embedded first, so the
+/// first search in a workspace indexes all of it; `semantic-index-progress`
+/// events report how far that has got.
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    options: SemanticSearchOptions,
+) -> Result<Vec<SemanticHit>, AppError> {
+    let root = validate_directory(&options.folder)?;
+    if options.query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let client = http_client()?;
+
+    let mut indexes = INDEXES.lock().await;
+    update_index(&app, &client, &options.config, &root, &mut indexes).await?;
+    let query = embed(
+        &client,
+        &options.config,
+        &[options.query.trim().to_string()],
+    )
+    .await?
+    .pop()
+    .ok_or_else(|| AppError::Network("Embeddings endpoint returned no vector".to_string()))?;
+
+    let index = &indexes[&root];
+    let mut hits: Vec<(f32, &String, &Passage)> = index
+        .files
+        .iter()
+        .flat_map(|(path, file)| file.passages.iter().map(move |p| (path, p)))
+        .filter(|(_, p)| p.vector.len() == query.len())
+        .map(|(path, p)| (dot(&query, &p.vector), path, p))
+        .collect();
+    hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+    hits.truncate(options.limit.unwrap_or(DEFAULT_LIMIT));
+
+    Ok(hits
+        .into_iter()
+        .map(|(score, path, passage)| SemanticHit {
+            file_path: path.clone(),
+            file_name: Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            line_start: passage.line_start,
+            line_end: passage.line_end,
+            heading: passage.heading.clone(),
+            text: passage.text.clone(),
+            score,
+        })
+        .collect())
+}
+
+/// Brings the vector index of `folder` up to date without searching.
+#[tauri::command]
+pub async fn update_semantic_index(
+    app: AppHandle,
+    folder: String,
+    config: EmbeddingConfig,
+) -> Result<SemanticIndexStats, AppError> {
+    let root = validate_directory(&folder)?;
+    let client = http_client()?;
+    let mut indexes = INDEXES.lock().await;
+    let embedded = update_index(&app, &client, &config, &root, &mut indexes).await?;
+    let index = &indexes[&root];
+    Ok(SemanticIndexStats {
+        files: index.files.len(),
+        passages: index.files.values().map(|f| f.passages.len()).sum(),
+        embedded,
+    })
+}
+
+fn http_client() -> Result<reqwest::Client, AppError> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| AppError::Network(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Re-chunk notes whose size or modification time changed, drop deleted
+/// ones and embed every passage without a vector. Progress is saved even
+/// if the endpoint fails part way. Returns the number of passages embedded.
+async fn update_index(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    config: &EmbeddingConfig,
+    root: &Path,
+    indexes: &mut HashMap<PathBuf, VectorIndex>,
+) -> Result<usize, AppError> {
+    let store = index_store_path(app, INDEX_DIR, root);
+    let root_str = root.to_string_lossy().into_owned();
+    let index = indexes.entry(root.to_path_buf()).or_insert_with(|| {
+        store
+            .as_deref()
+            .and_then(|s| load(s, &root_str))
+            .unwrap_or_default()
+    });
+    if index.version != INDEX_VERSION
+        || index.endpoint != config.base_url
+        || index.model != config.model
+    {
+        *index = VectorIndex {
+            version: INDEX_VERSION,
+            root: root_str,
+            endpoint: config.base_url.clone(),
+            model: config.model.clone(),
+            files: HashMap::new(),
+        };
+    }
+
+    let known: HashMap<String, (u64, u64)> = index
+        .files
+        .iter()
+        .map(|(path, file)| (path.clone(), (file.modified, file.size)))
+        .collect();
+    let scan_root = root.to_path_buf();
+    let Scan { present, changed } = tokio::task::spawn_blocking(move || scan(&scan_root, &known))
+        .await
+        .map_err(|e| AppError::Other(format!("Indexing failed: {}", e)))?;
+
+    let before = index.files.len();
+    index.files.retain(|path, _| present.contains_key(path));
+    let mut dirty = index.files.len() != before;
+
+    // Passages of changed notes reuse the vectors of identical old ones
+    let mut pending: Vec<(String, usize)> = Vec::new();
+    for (path, mut file) in changed {
+        let old: HashMap<u64, &Vec<f32>> = index
+            .files
+            .get(&path)
+            .map(|f| f.passages.iter().map(|p| (p.hash, &p.vector)).collect())
+            .unwrap_or_default();
+        for (i, passage) in file.passages.iter_mut().enumerate() {
+            match old.get(&passage.hash) {
+                Some(vector) if !vector.is_empty() => passage.vector = (*vector).clone(),
+                _ => pending.push((path.clone(), i)),
+            }
+        }
+        index.files.insert(path, file);
+        dirty = true;
+    }
+
+    let total = pending.len();
+    let mut result = Ok(total);
+    for (done, batch) in pending.chunks(BATCH_SIZE).enumerate() {
+        let _ = app.emit(
+            "semantic-index-progress",
+            IndexProgress {
+                folder: root.to_string_lossy().into_owned(),
+                done: done * BATCH_SIZE,
+                total,
+            },
+        );
+        let inputs: Vec<String> = batch
+            .iter()
+            .map(|(path, i)| embedding_input(path, &index.files[path].passages[*i]))
+            .collect();
+        match embed(client, config, &inputs).await {
+            Ok(vectors) => {
+                for ((path, i), vector) in batch.iter().zip(vectors) {
+                    if let Some(passage) = index
+                        .files
+                        .get_mut(path)
+                        .and_then(|f| f.passages.get_mut(*i))
+                    {
+                        passage.vector = vector;
+                    }
+                }
+            }
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    if result.is_err() {
+        // Keep what was embedded; the rest is retried next time
+        for file in index.files.values_mut() {
+            if file.passages.iter().any(|p| p.vector.is_empty()) {
+                file.modified = 0;
+            }
+        }
+    }
+    if total > 0 {
+        let _ = app.emit(
+            "semantic-index-progress",
+            IndexProgress {
+                folder: root.to_string_lossy().into_owned(),
+                done: total,
+                total,
+            },
+        );
+    }
+
+    if dirty {
+        if let Some(store) = &store {
+            save(store, index);
+        }
+    }
+    result
+}
+
+/// Notes found by [`scan`]: every note's stamp, and fresh passages for the
+/// notes whose stamp changed.
+struct Scan {
+    present: HashMap<String, (u64, u64)>,
+    changed: Vec<(String, IndexedFile)>,
+}
+
+/// Size and modification time of every note, chunking those that differ
+/// from `known`.
+fn scan(root: &Path, known: &HashMap<String, (u64, u64)>) -> Scan {
+    let mut present = HashMap::new();
+    let mut changed = Vec::new();
+    for path in collect_md_files(root) {
+        let Some(stamp) = file_stamp(&path) else {
+            continue;
+        };
+        let key = path.to_string_lossy().into_owned();
+        if known.get(&key) != Some(&stamp) {
+            if let Ok(content) = fs::read_to_string(&path) {
+                changed.push((
+                    key.clone(),
+                    IndexedFile {
+                        modified: stamp.0,
+                        size: stamp.1,
+                        passages: chunk_note(&content),
+                    },
+                ));
+            }
+        }
+        present.insert(key, stamp);
+    }
+    Scan { present, changed }
+}
+
+/// Split a note into passages of whole paragraphs, starting a new one at
+/// every heading. Frontmatter is skipped; fenced code stays in one piece.
+fn chunk_note(content: &str) -> Vec<Passage> {
+    let body = split_frontmatter(content).1;
+    let first_line = content[..content.len() - body.len()].lines().count();
+
+    let mut passages = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut text = String::new();
+    let mut start = 0;
+    let mut end = 0;
+    let mut in_fence = false;
+
+    let mut flush = |text: &mut String, heading: &Option<String>, start: usize, end: usize| {
+        let trimmed = text.trim();
+        if !trimmed.is_empty() {
+            passages.push(Passage {
+                line_start: start + 1,
+                line_end: end + 1,
+                heading: heading.clone(),
+                text: trimmed.to_string(),
+                hash: 0,
+                vector: Vec::new(),
+            });
+        }
+        text.clear();
+    };
+
+    for (i, line) in body.lines().enumerate() {
+        let line_no = first_line + i;
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let is_heading = !in_fence && trimmed.starts_with('#') && {
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            level <= 6 && trimmed[level..].starts_with(' ')
+        };
+        let paragraph_end = !in_fence && trimmed.is_empty();
+
+        if is_heading {
+            flush(&mut text, &heading, start, end);
+            heading = Some(trimmed.trim_start_matches('#').trim().to_string());
+        }
+        if text.is_empty() {
+            start = line_no;
+        }
+        if !trimmed.is_empty() || in_fence {
+            text.push_str(line);
+            text.push('\n');
+            end = line_no;
+        }
+        if paragraph_end && text.len() >= CHUNK_BYTES {
+            flush(&mut text, &heading, start, end);
+        }
+    }
+    flush(&mut text, &heading, start, end);
+
+    for passage in &mut passages {
+        passage.hash = fnv1a(passage_input(passage).as_bytes());
+    }
+    passages
+}
+
+/// The passage with its heading, which often carries the topic.
+fn passage_input(passage: &Passage) -> String {
+    let mut input = match &passage.heading {
+        Some(heading) if !passage.text.starts_with('#') => {
+            format!("{}\n\n{}", heading, passage.text)
+        }
+        _ => passage.text.clone(),
+    };
+    if input.len() > MAX_INPUT_BYTES {
+        let mut cut = MAX_INPUT_BYTES;
+        while !input.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        input.truncate(cut);
+    }
+    input
+}
+
+/// Text sent to the endpoint: the note's name, then the passage.
+fn embedding_input(path: &str, passage: &Passage) -> String {
+    let name = Path::new(path)
+        .file_stem()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{}\n\n{}", name, passage_input(passage))
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+/// Unit-length vectors for `inputs`, in order.
+async fn embed(
+    client: &reqwest::Client,
+    config: &EmbeddingConfig,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>, AppError> {
+    let url = format!("{}/embeddings", config.base_url.trim_end_matches('/'));
+    let mut request = client.post(&url).json(&serde_json::json!({
+        "model": config.model,
+        "input": inputs,
+    }));
+    if !config.api_key.is_empty() {
+        request = request.bearer_auth(&config.api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Embeddings request failed: {}", e)))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?;
+    if !status.is_success() {
+        return Err(AppError::Network(format!(
+            "Embeddings endpoint returned {}: {}",
+            status.as_u16(),
+            body.chars().take(300).collect::<String>()
+        )));
+    }
+
+    let mut parsed: EmbeddingResponse = serde_json::from_str(&body)
+        .map_err(|e| AppError::Network(format!("Unexpected embeddings response: {}", e)))?;
+    if parsed.data.len() != inputs.len() {
+        return Err(AppError::Network(format!(
+            "Embeddings endpoint returned {} vectors for {} inputs",
+            parsed.data.len(),
+            inputs.len()
+        )));
+    }
+    parsed.data.sort_by_key(|d| d.index);
+    Ok(parsed
+        .data
+        .into_iter()
+        .map(|d| normalize(d.embedding))
+        .collect())
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in &mut vector {
+            *v /= norm;
+        }
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn load(store: &Path, root: &str) -> Option<VectorIndex> {
+    let bytes = fs::read(store).ok()?;
+    let index: VectorIndex = serde_json::from_slice(&bytes).ok()?;
+    (index.version == INDEX_VERSION && index.root == root).then_some(index)
+}
+
+fn save(store: &Path, index: &VectorIndex) {
+    if let Ok(json) = serde_json::to_vec(index) {
+        let _ = write_atomic(store, &json);
+    }
+}
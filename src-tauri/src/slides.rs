@@ -0,0 +1,729 @@
+use base64::Engine as _;
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::citations::apply_citations;
+use crate::epub::{media_type_for, resolve_local};
+use crate::error::AppError;
+use crate::frontmatter::{split_frontmatter, Frontmatter};
+use crate::markdown::{
+    escape_xml, is_external_url, parser_options, percent_decode, render_html_with, split_fragment,
+    LinkKind,
+};
+use crate::pdf::write_pdf;
+use crate::pdf_layout::{
+    layout_markdown, text_width, Color, DrawOp, FontFace, LaidOutPage, LayoutConfig, LayoutResult,
+    OutlineEntry, PageGeometry,
+};
+use crate::transclude::{code_ranges, expand_embeds};
+use crate::wiki::wiki_links_to_markdown;
+
+/// Speaker notes start at a `Note:` or `Notes:` line and run to the end of the slide.
+static NOTES_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^Notes?:[ \t]*").expect("invalid notes regex"));
+
+/// Body font size of PDF slides; it shrinks in steps until a slide fits one page.
+const PDF_FONT_SIZE: f32 = 20.0;
+const PDF_MIN_FONT_SIZE: f32 = 10.0;
+const PDF_FONT_STEP: f32 = 2.0;
+const PDF_NUMBER_SIZE: f32 = 9.0;
+const PDF_NUMBER_COLOR: Color = Color(0.45, 0.45, 0.45);
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideFormat {
+    #[default]
+    Html,
+    Pdf,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl SlideTheme {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "dark" => Some(SlideTheme::Dark),
+            "light" => Some(SlideTheme::Light),
+            _ => None,
+        }
+    }
+
+    fn class_name(self) -> &'static str {
+        match self {
+            SlideTheme::Dark => "dark",
+            SlideTheme::Light => "light",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AspectRatio {
+    Wide,
+    Standard,
+}
+
+impl AspectRatio {
+    fn parse(value: &str) -> Self {
+        match value.trim() {
+            "4:3" | "4/3" => AspectRatio::Standard,
+            _ => AspectRatio::Wide,
+        }
+    }
+
+    /// Slide canvas in CSS pixels for the HTML deck.
+    fn pixels(self) -> (u32, u32) {
+        match self {
+            AspectRatio::Wide => (1280, 720),
+            AspectRatio::Standard => (960, 720),
+        }
+    }
+
+    /// Page width and height in mm for the PDF.
+    fn millimetres(self) -> (f32, f32) {
+        match self {
+            AspectRatio::Wide => (254.0, 142.875),
+            AspectRatio::Standard => (254.0, 190.5),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SlideOptions {
+    /// Note holding the deck; relative image paths resolve against its folder.
+    pub input_path: String,
+    pub output_path: String,
+    /// Editor content to export instead of the file on disk (unsaved changes).
+    pub content: Option<String>,
+    #[serde(default)]
+    pub format: SlideFormat,
+    /// Colour scheme of the HTML deck when its frontmatter sets no `theme`.
+    #[serde(default)]
+    pub theme: SlideTheme,
+    /// Workspace root used to find `![[embedded]]` notes by name.
+    pub folder: Option<String>,
+}
+
+/// Deck-wide settings from the note's frontmatter.
+struct Deck {
+    title: String,
+    author: String,
+    lang: String,
+    theme: SlideTheme,
+    aspect: AspectRatio,
+    paginate: bool,
+}
+
+impl Deck {
+    fn new(
+        frontmatter: Option<&Frontmatter>,
+        theme: SlideTheme,
+        slides: &[Slide],
+        input: &Path,
+    ) -> Self {
+        let get = |key: &str| {
+            frontmatter
+                .and_then(|fm| fm.get(key))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        Deck {
+            title: get("title")
+                .map(str::to_string)
+                .or_else(|| slides.iter().find_map(|s| s.title.clone()))
+                .unwrap_or_else(|| {
+                    input
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                }),
+            author: get("author").unwrap_or_default().to_string(),
+            lang: get("lang").unwrap_or("en").to_string(),
+            theme: get("theme").and_then(SlideTheme::parse).unwrap_or(theme),
+            aspect: get("aspect-ratio").map_or(AspectRatio::Wide, AspectRatio::parse),
+            paginate: get("paginate") != Some("false"),
+        }
+    }
+}
+
+/// Per-slide settings from a `<!-- key: value -->` comment opening the slide.
+#[derive(Default)]
+struct Directives {
+    /// Extra CSS classes; `lead` and `center` centre the content vertically.
+    class: Option<String>,
+    /// A CSS colour, or an image path or URL used as a cover background.
+    background: Option<String>,
+    color: Option<String>,
+    paginate: Option<bool>,
+}
+
+struct Slide {
+    body: String,
+    notes: String,
+    title: Option<String>,
+    directives: Directives,
+}
+
+impl Slide {
+    fn is_centered(&self) -> bool {
+        self.directives
+            .class
+            .as_deref()
+            .is_some_and(|c| c.split_whitespace().any(|n| n == "lead" || n == "center"))
+    }
+}
+
+/// Export a `---`-separated note as a slide deck.
+///
+/// HTML output is a single self-contained file with images inlined, keyboard
+/// and click navigation, a progress bar and a speaker view (`S`) showing the
+/// notes, the next slide and a timer. PDF output puts one slide per 16:9 (or
+/// 4:3) page in the light print style, shrinking text until each slide fits.
+/// Frontmatter may set `title`, `author`, `theme`, `aspect-ratio` and
+/// `paginate`; slides may open with a comment such as
+/// `<!-- class: lead -->` and end with `Note:` speaker notes. Returns the path
+/// of the written file.
+#[tauri::command]
+pub fn export_slides(options: SlideOptions) -> Result<String, AppError> {
+    let input = PathBuf::from(&options.input_path);
+    let content = match options.content {
+        Some(content) => content,
+        None => fs::read_to_string(&input)?,
+    };
+    let base_dir = input.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let root = options
+        .folder
+        .as_ref()
+        .map_or(base_dir.clone(), PathBuf::from);
+
+    // Decks often open with a bare `---` separator; a block without keys is
+    // the title slide rather than frontmatter.
+    let (frontmatter, body) = match split_frontmatter(&content) {
+        (Some(fm), body) if !fm.is_empty() => (Some(fm), body),
+        _ => (None, content.as_str()),
+    };
+    let body = apply_citations(
+        &expand_embeds(body, &input, &root),
+        frontmatter.as_ref(),
+        &input,
+        &root,
+    );
+    let body = wiki_links_to_markdown(&body);
+
+    let slides: Vec<Slide> = split_slides(&body).into_iter().map(parse_slide).collect();
+    if slides.is_empty() {
+        return Err(AppError::Export("The note has no slides".to_string()));
+    }
+    let deck = Deck::new(frontmatter.as_ref(), options.theme, &slides, &input);
+
+    let output = Path::new(&options.output_path);
+    match options.format {
+        SlideFormat::Html => fs::write(output, render_deck(&slides, &deck, &root, &base_dir))?,
+        SlideFormat::Pdf => write_slides_pdf(&slides, &deck, &base_dir, output)?,
+    }
+    Ok(options.output_path)
+}
+
+/// Split on lines consisting of `---` outside code, like presentation mode.
+fn split_slides(body: &str) -> Vec<&str> {
+    let code = code_ranges(body);
+    let mut slides = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        if line.trim_end() == "---" && !code.iter().any(|r| r.contains(&offset)) {
+            slides.push(&body[start..offset]);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    slides.push(&body[start..]);
+    slides.retain(|s| !s.trim().is_empty());
+    slides
+}
+
+fn parse_slide(text: &str) -> Slide {
+    let (directives, rest) = text
+        .trim_start()
+        .strip_prefix("<!--")
+        .and_then(|after| {
+            let end = after.find("-->")?;
+            Some((parse_directives(&after[..end])?, &after[end + 3..]))
+        })
+        .unwrap_or_else(|| (Directives::default(), text));
+
+    let code = code_ranges(rest);
+    let notes_at = NOTES_RE
+        .find_iter(rest)
+        .find(|m| !code.iter().any(|r| r.contains(&m.start())));
+    let (body, notes) = match notes_at {
+        Some(m) => (&rest[..m.start()], &rest[m.end()..]),
+        None => (rest, ""),
+    };
+
+    Slide {
+        body: body.trim().to_string(),
+        notes: notes.trim().to_string(),
+        title: first_heading(body),
+        directives,
+    }
+}
+
+/// Parse `key: value` lines; `None` when the comment is not a directive block.
+fn parse_directives(comment: &str) -> Option<Directives> {
+    let mut directives = Directives::default();
+    let mut found = false;
+    for line in comment.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let (key, value) = line.split_once(':')?;
+        let value = value
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string();
+        match key.trim() {
+            "class" => directives.class = Some(value),
+            "background" => directives.background = Some(value),
+            "color" => directives.color = Some(value),
+            "paginate" => directives.paginate = Some(value != "false"),
+            _ => return None,
+        }
+        found = true;
+    }
+    found.then_some(directives)
+}
+
+fn first_heading(body: &str) -> Option<String> {
+    let mut text: Option<String> = None;
+    for event in Parser::new_ext(body, parser_options()) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => text = Some(String::new()),
+            Event::Text(t) | Event::Code(t) => {
+                if let Some(text) = text.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                return text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Turns local image paths into `data:` URIs so the deck is a single file.
+struct ImageInliner<'a> {
+    root: &'a Path,
+    note_dir: &'a Path,
+    cache: HashMap<PathBuf, Option<String>>,
+}
+
+impl ImageInliner<'_> {
+    fn data_uri(&mut self, dest: &str) -> Option<String> {
+        if is_external_url(dest) {
+            return None;
+        }
+        let (path_part, _) = split_fragment(dest);
+        let path = resolve_local(self.root, self.note_dir, &percent_decode(path_part))?;
+        self.cache
+            .entry(path)
+            .or_insert_with_key(|path| {
+                let media_type = media_type_for(path)?;
+                let data = fs::read(path).ok()?;
+                Some(format!(
+                    "data:{};base64,{}",
+                    media_type,
+                    base64::engine::general_purpose::STANDARD.encode(data)
+                ))
+            })
+            .clone()
+    }
+
+    fn render(&mut self, markdown: &str) -> String {
+        render_html_with(markdown, |kind, dest| match kind {
+            LinkKind::Image => self.data_uri(dest),
+            LinkKind::Link => None,
+        })
+        .html
+    }
+}
+
+fn background_css(value: &str, images: &mut ImageInliner) -> String {
+    let is_image = is_external_url(value) || media_type_for(Path::new(value)).is_some();
+    if !is_image {
+        return format!("background: {};", value);
+    }
+    let url = if is_external_url(value) {
+        Some(value.to_string())
+    } else {
+        images.data_uri(value)
+    };
+    url.map(|url| format!("background-image: url('{}');", url.replace('\'', "%27")))
+        .unwrap_or_default()
+}
+
+fn render_deck(slides: &[Slide], deck: &Deck, root: &Path, note_dir: &Path) -> String {
+    let mut images = ImageInliner {
+        root,
+        note_dir,
+        cache: HashMap::new(),
+    };
+    let mut sections = String::new();
+
+    for (i, slide) in slides.iter().enumerate() {
+        let directives = &slide.directives;
+        let mut classes = String::from("slide");
+        for name in directives.class.iter().flat_map(|c| c.split_whitespace()) {
+            if name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                classes.push(' ');
+                classes.push_str(name);
+            }
+        }
+        let mut style = String::new();
+        if let Some(background) = &directives.background {
+            style.push_str(&background_css(background, &mut images));
+        }
+        if let Some(color) = &directives.color {
+            style.push_str(&format!("color: {};", color));
+        }
+
+        sections.push_str(&format!(
+            "<section class=\"{}\" data-title=\"{}\"{}>\n<div class=\"slide-content\">\n{}</div>\n",
+            classes,
+            escape_xml(slide.title.as_deref().unwrap_or_default()),
+            if style.is_empty() {
+                String::new()
+            } else {
+                format!(" style=\"{}\"", escape_xml(&style))
+            },
+            images.render(&slide.body)
+        ));
+        if directives.paginate.unwrap_or(deck.paginate) {
+            sections.push_str(&format!("<div class=\"slide-number\">{}</div>\n", i + 1));
+        }
+        if !slide.notes.is_empty() {
+            sections.push_str(&format!(
+                "<aside class=\"notes\">\n{}</aside>\n",
+                images.render(&slide.notes)
+            ));
+        }
+        sections.push_str("</section>\n");
+    }
+
+    let (width, height) = deck.aspect.pixels();
+    // Slides go in last so their text is never mistaken for a placeholder.
+    DECK_TEMPLATE
+        .replace("{{lang}}", &escape_xml(&deck.lang))
+        .replace("{{title}}", &escape_xml(&deck.title))
+        .replace("{{author}}", &escape_xml(&deck.author))
+        .replace("{{theme}}", deck.theme.class_name())
+        .replace("{{width}}", &width.to_string())
+        .replace("{{height}}", &height.to_string())
+        .replace("{{slides}}", &sections)
+}
+
+fn write_slides_pdf(
+    slides: &[Slide],
+    deck: &Deck,
+    base_dir: &Path,
+    output: &Path,
+) -> Result<(), AppError> {
+    let (width, height) = deck.aspect.millimetres();
+    let geometry = PageGeometry {
+        width,
+        height,
+        margin_top: 14.0,
+        margin_right: 18.0,
+        margin_bottom: 16.0,
+        margin_left: 18.0,
+    };
+
+    let mut pages: Vec<LaidOutPage> = Vec::new();
+    let mut images = Vec::new();
+    let mut outline: Vec<OutlineEntry> = Vec::new();
+
+    for (i, slide) in slides.iter().enumerate() {
+        let layout = fit_slide(&slide.body, geometry, base_dir);
+        if let Some(title) = &slide.title {
+            outline.push(OutlineEntry {
+                level: 1,
+                text: title.clone(),
+                page: pages.len(),
+            });
+        }
+
+        let single_page = layout.pages.len() == 1;
+        let image_offset = images.len();
+        let mut slide_pages = layout.pages;
+        if slide_pages.is_empty() {
+            slide_pages.push(LaidOutPage::default());
+        }
+        for mut page in slide_pages {
+            for op in page.ops.iter_mut() {
+                if let DrawOp::Image { index, .. } = op {
+                    *index += image_offset;
+                }
+            }
+            if single_page && slide.is_centered() {
+                center_vertically(&mut page, &geometry);
+            }
+            if slide.directives.paginate.unwrap_or(deck.paginate) {
+                let label = (i + 1).to_string();
+                page.ops.push(DrawOp::Text {
+                    x: geometry.width
+                        - geometry.margin_right
+                        - text_width(&label, FontFace::Regular, PDF_NUMBER_SIZE),
+                    y: geometry.height - geometry.margin_bottom / 2.0,
+                    text: label,
+                    face: FontFace::Regular,
+                    size: PDF_NUMBER_SIZE,
+                    color: PDF_NUMBER_COLOR,
+                });
+            }
+            pages.push(page);
+        }
+        images.extend(layout.images);
+    }
+
+    write_pdf(
+        output,
+        &pages,
+        &images,
+        &geometry,
+        &deck.title,
+        &deck.author,
+        &outline,
+    )
+}
+
+/// Lay a slide out, shrinking the font until it fits on a single page.
+fn fit_slide(body: &str, geometry: PageGeometry, base_dir: &Path) -> LayoutResult {
+    let mut font_size = PDF_FONT_SIZE;
+    loop {
+        let config = LayoutConfig {
+            geometry,
+            font_size,
+            highlight_code: true,
+            page_break_before_h1: false,
+            base_dir,
+        };
+        let layout = layout_markdown(body, &config);
+        if layout.pages.len() <= 1 || font_size <= PDF_MIN_FONT_SIZE {
+            return layout;
+        }
+        font_size -= PDF_FONT_STEP;
+    }
+}
+
+/// Move everything on the page down so the content sits mid-slide.
+fn center_vertically(page: &mut LaidOutPage, geometry: &PageGeometry) {
+    let bottom = page
+        .ops
+        .iter()
+        .map(|op| match op {
+            DrawOp::Text { y, .. } => *y,
+            DrawOp::FillRect { y, h, .. }
+            | DrawOp::Image { y, h, .. }
+            | DrawOp::Link { y, h, .. } => y + h,
+            DrawOp::Line { y1, y2, .. } => y1.max(*y2),
+        })
+        .fold(geometry.margin_top, f32::max);
+    let shift = (geometry.content_bottom() - bottom) / 2.0;
+    if shift <= 0.0 {
+        return;
+    }
+    for op in page.ops.iter_mut() {
+        match op {
+            DrawOp::Text { y, .. }
+            | DrawOp::FillRect { y, .. }
+            | DrawOp::Image { y, .. }
+            | DrawOp::Link { y, .. } => *y += shift,
+            DrawOp::Line { y1, y2, .. } => {
+                *y1 += shift;
+                *y2 += shift;
+            }
+        }
+    }
+}
+
+const DECK_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="{{lang}}">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<meta name="author" content="{{author}}">
+<title>{{title}}</title>
+<style>
+*, *::before, *::after { box-sizing: border-box; }
+html, body { margin: 0; height: 100%; }
+body.theme-dark { --bg: #1e1e2e; --bg-code: #181825; --text: #cdd6f4; --muted: #6c7086; --accent: #89b4fa; --border: #313244; --backdrop: #11111b; }
+body.theme-light { --bg: #ffffff; --bg-code: #f5f5f5; --text: #1e1e2e; --muted: #6c7086; --accent: #1e66f5; --border: #e0e0e0; --backdrop: #dcdce4; }
+body { overflow: hidden; background: var(--backdrop); color: var(--text); font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; }
+.stage { position: fixed; inset: 0; }
+.slide { display: none; flex-direction: column; position: absolute; left: 50%; top: 50%; width: {{width}}px; height: {{height}}px; margin: calc({{height}}px / -2) 0 0 calc({{width}}px / -2); padding: 64px 80px; transform: scale(var(--scale, 1)); overflow: hidden; background: var(--bg) center / cover no-repeat; font-size: 30px; line-height: 1.45; -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+.slide.active { display: flex; }
+.slide-content { flex: 1; min-height: 0; }
+.slide.lead .slide-content, .slide.center .slide-content { display: flex; flex-direction: column; justify-content: center; }
+.slide.lead { text-align: center; }
+h1, h2, h3, h4 { margin: 0 0 0.4em; line-height: 1.2; }
+h1 { font-size: 2em; }
+h2 { font-size: 1.5em; }
+h3 { font-size: 1.2em; }
+p, ul, ol, blockquote, pre, table { margin: 0 0 0.6em; }
+li + li { margin-top: 0.2em; }
+a { color: var(--accent); }
+code { font-family: 'SF Mono', 'Fira Code', Consolas, monospace; font-size: 0.85em; background: var(--bg-code); padding: 0.1em 0.3em; border-radius: 4px; }
+pre { background: var(--bg-code); border: 1px solid var(--border); border-radius: 8px; padding: 0.8em 1em; overflow: auto; font-size: 0.7em; line-height: 1.5; }
+pre code { background: none; padding: 0; font-size: 1em; }
+blockquote { border-left: 4px solid var(--accent); padding-left: 1em; color: var(--muted); }
+img { max-width: 100%; max-height: 480px; }
+table { border-collapse: collapse; font-size: 0.8em; }
+th, td { border: 1px solid var(--border); padding: 0.3em 0.7em; }
+th { background: var(--bg-code); }
+.slide-number { position: absolute; right: 32px; bottom: 20px; font-size: 18px; color: var(--muted); }
+.notes { display: none; }
+.progress { position: fixed; left: 0; right: 0; bottom: 0; height: 4px; }
+.progress-bar { width: 0; height: 100%; background: var(--accent); transition: width 0.2s; }
+.speaker-panel { display: none; }
+body.speaker .stage { right: 40%; }
+body.speaker .speaker-panel { display: flex; flex-direction: column; gap: 20px; position: fixed; top: 0; right: 0; bottom: 0; width: 40%; padding: 24px; overflow-y: auto; background: var(--bg); border-left: 1px solid var(--border); font-size: 20px; }
+.speaker-timer { font-size: 36px; font-variant-numeric: tabular-nums; cursor: pointer; }
+.speaker-label { margin-bottom: 6px; font-size: 13px; text-transform: uppercase; letter-spacing: 0.05em; color: var(--muted); }
+.speaker-notes { line-height: 1.5; }
+@media print {
+  @page { size: {{width}}px {{height}}px; margin: 0; }
+  html, body { height: auto; overflow: visible; background: none; }
+  .stage { position: static; }
+  .slide { display: flex !important; position: relative; left: 0; top: 0; margin: 0; transform: none; break-after: page; }
+  .progress, .speaker-panel { display: none !important; }
+}
+</style>
+</head>
+<body class="theme-{{theme}}">
+<main class="stage">
+{{slides}}</main>
+<div class="progress"><div class="progress-bar"></div></div>
+<aside class="speaker-panel">
+<div class="speaker-timer" title="Click to reset">00:00</div>
+<div><div class="speaker-label">Next</div><div class="speaker-next"></div></div>
+<div><div class="speaker-label">Notes</div><div class="speaker-notes"></div></div>
+</aside>
+<script>
+(function () {
+  var slides = Array.prototype.slice.call(document.querySelectorAll('.slide'));
+  var stage = document.querySelector('.stage');
+  var bar = document.querySelector('.progress-bar');
+  var width = {{width}};
+  var height = {{height}};
+  var speaker = /[?&]speaker\b/.test(location.search);
+  var peer = speaker ? window.opener : null;
+  var current = -1;
+  var started = Date.now();
+
+  function fit() {
+    var scale = Math.min(stage.clientWidth / width, stage.clientHeight / height);
+    document.documentElement.style.setProperty('--scale', String(scale));
+  }
+
+  function fromHash() {
+    var n = parseInt(location.hash.slice(1), 10);
+    return isNaN(n) ? 0 : n - 1;
+  }
+
+  function show(n, notify) {
+    n = Math.max(0, Math.min(slides.length - 1, n));
+    if (n === current) return;
+    if (current >= 0) slides[current].classList.remove('active');
+    current = n;
+    slides[n].classList.add('active');
+    bar.style.width = ((n + 1) / slides.length) * 100 + '%';
+    if (fromHash() !== n) location.hash = '#' + (n + 1);
+    if (speaker) updateSpeaker();
+    if (notify && peer && !peer.closed) peer.postMessage({ slide: n }, '*');
+  }
+
+  function updateSpeaker() {
+    var notes = slides[current].querySelector('.notes');
+    document.querySelector('.speaker-notes').innerHTML = notes ? notes.innerHTML : '<em>No notes</em>';
+    var next = slides[current + 1];
+    document.querySelector('.speaker-next').textContent = next
+      ? next.getAttribute('data-title') || 'Slide ' + (current + 2)
+      : 'End of deck';
+  }
+
+  function tick() {
+    var seconds = Math.floor((Date.now() - started) / 1000);
+    var m = Math.floor(seconds / 60);
+    var s = seconds % 60;
+    document.querySelector('.speaker-timer').textContent = (m < 10 ? '0' : '') + m + ':' + (s < 10 ? '0' : '') + s;
+  }
+
+  function openSpeakerView() {
+    if (peer && !peer.closed) {
+      peer.focus();
+      return;
+    }
+    var url = location.href.split('#')[0].split('?')[0] + '?speaker#' + (current + 1);
+    peer = window.open(url, 'speaker-view', 'width=1200,height=700');
+  }
+
+  function toggleFullscreen() {
+    if (document.fullscreenElement) document.exitFullscreen();
+    else if (document.documentElement.requestFullscreen) document.documentElement.requestFullscreen();
+  }
+
+  document.addEventListener('keydown', function (e) {
+    if (e.ctrlKey || e.metaKey || e.altKey) return;
+    switch (e.key) {
+      case 'ArrowRight': case 'ArrowDown': case 'PageDown': case ' ': case 'n':
+        show(current + 1, true); break;
+      case 'ArrowLeft': case 'ArrowUp': case 'PageUp': case 'Backspace': case 'p':
+        show(current - 1, true); break;
+      case 'Home': show(0, true); break;
+      case 'End': show(slides.length - 1, true); break;
+      case 'f': toggleFullscreen(); break;
+      case 's': if (!speaker) openSpeakerView(); break;
+      default: return;
+    }
+    e.preventDefault();
+  });
+
+  stage.addEventListener('click', function (e) {
+    if (e.target.closest('a')) return;
+    var rect = stage.getBoundingClientRect();
+    show(e.clientX - rect.left < rect.width / 2 ? current - 1 : current + 1, true);
+  });
+  window.addEventListener('hashchange', function () { show(fromHash(), true); });
+  window.addEventListener('message', function (e) {
+    if (e.data && typeof e.data.slide === 'number') show(e.data.slide, false);
+  });
+  window.addEventListener('resize', fit);
+
+  if (speaker) {
+    document.body.classList.add('speaker');
+    document.querySelector('.speaker-timer').addEventListener('click', function () {
+      started = Date.now();
+      tick();
+    });
+    setInterval(tick, 1000);
+    tick();
+  }
+  fit();
+  show(fromHash(), false);
+})();
+</script>
+</body>
+</html>
+"#;
@@ -0,0 +1,789 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{parser_options, LineIndex};
+
+/// Workspace word list, one word per line, shared through the repository.
+const WORDS_FILE: &str = ".spelling";
+const MAX_SUGGESTIONS: usize = 8;
+const DEFAULT_TRY: &str = "esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'";
+
+static WORD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[\p{L}\p{M}\p{N}_]+(?:['’][\p{L}\p{M}]+)*").expect("invalid word regex")
+});
+/// Text that is never prose: URLs, emails, wiki links and file paths.
+static SKIP_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:https?://|www\.)\S+|[\w.+-]+@[\w-]+\.[\w.-]+|\[\[[^\]\n]*\]\]|\S+[/\\]\S+")
+        .expect("invalid skip regex")
+});
+
+/// Parsed dictionaries by `.dic` path, loaded on first use.
+static DICTIONARIES: Lazy<Mutex<HashMap<PathBuf, Arc<Dictionary>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Deserialize)]
+pub struct SpellCheckOptions {
+    pub content: String,
+    /// Workspace folder holding the shared `.spelling` word list.
+    pub folder: Option<String>,
+    /// Dictionary used when the note sets no `lang` in its frontmatter.
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Extra folder searched for `.dic`/`.aff` files before the system ones.
+    pub dictionary_dir: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SpellSuggestOptions {
+    pub word: String,
+    #[serde(default = "default_language")]
+    pub language: String,
+    pub dictionary_dir: Option<String>,
+}
+
+fn default_language() -> String {
+    "en_US".to_string()
+}
+
+#[derive(Serialize)]
+pub struct Misspelling {
+    pub word: String,
+    pub line: usize,
+    /// 1-based UTF-16 column.
+    pub column: usize,
+    /// Length in UTF-16 code units.
+    pub length: usize,
+}
+
+#[derive(Serialize)]
+pub struct SpellCheckResult {
+    /// Dictionary the note was checked with, e.g. `de_DE`.
+    pub language: String,
+    pub misspellings: Vec<Misspelling>,
+}
+
+/// Check the prose of a markdown note against a Hunspell dictionary and the
+/// workspace word list.
+///
+/// Code, math, HTML, URLs, wiki links and frontmatter are skipped, as are
+/// words with digits, all-caps acronyms and mixed-case identifiers. A `lang`
+/// or `language` frontmatter key selects the dictionary for the note.
+#[tauri::command]
+pub fn spell_check(options: SpellCheckOptions) -> Result<SpellCheckResult, AppError> {
+    let (frontmatter, body) = split_frontmatter(&options.content);
+    let offset = options.content.len() - body.len();
+    let language = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("lang").or_else(|| fm.get("language")))
+        .map(str::to_string)
+        .unwrap_or(options.language);
+    let (language, dictionary) = load_dictionary(&language, options.dictionary_dir.as_deref())?;
+    let workspace_words = options
+        .folder
+        .as_deref()
+        .map(|f| read_word_list(&Path::new(f).join(WORDS_FILE)))
+        .unwrap_or_default();
+
+    let index = LineIndex::new(&options.content);
+    let mut verdicts: HashMap<&str, bool> = HashMap::new();
+    let mut misspellings = Vec::new();
+    for range in prose_words(body) {
+        let word = &body[range.clone()];
+        let correct = *verdicts.entry(word).or_insert_with(|| {
+            workspace_words.contains(&normalize_word(word).to_lowercase())
+                || dictionary.check(&normalize_word(word))
+        });
+        if !correct {
+            let (line, column) = index.position(range.start + offset);
+            misspellings.push(Misspelling {
+                word: word.to_string(),
+                line,
+                column,
+                length: word.encode_utf16().count(),
+            });
+        }
+    }
+    Ok(SpellCheckResult {
+        language,
+        misspellings,
+    })
+}
+
+/// Suggest corrections for a misspelled word, best first.
+#[tauri::command]
+pub fn spell_suggest(options: SpellSuggestOptions) -> Result<Vec<String>, AppError> {
+    let (_, dictionary) = load_dictionary(&options.language, options.dictionary_dir.as_deref())?;
+    Ok(dictionary.suggest(&normalize_word(&options.word)))
+}
+
+/// Add a word to the workspace word list, creating it if needed.
+#[tauri::command]
+pub fn add_to_dictionary(folder: String, word: String) -> Result<(), AppError> {
+    let word = normalize_word(word.trim());
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(AppError::Other(format!("Not a single word: {}", word)));
+    }
+    let path = Path::new(&folder).join(WORDS_FILE);
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    if existing
+        .lines()
+        .any(|l| l.trim().eq_ignore_ascii_case(&word))
+    {
+        return Ok(());
+    }
+    let mut words: Vec<&str> = existing.lines().collect();
+    let lowercase = word.to_lowercase();
+    words.push(&lowercase);
+    // Keep comments at the top and the words sorted so merges stay simple.
+    let (mut comments, mut entries): (Vec<&str>, Vec<&str>) = words
+        .into_iter()
+        .filter(|l| !l.trim().is_empty())
+        .partition(|l| l.starts_with('#'));
+    entries.sort_by_key(|w| w.to_lowercase());
+    entries.dedup();
+    comments.extend(entries);
+    fs::write(&path, comments.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// Languages of the Hunspell dictionaries that can be found, e.g. `en_US`.
+#[tauri::command]
+pub fn list_dictionaries(dictionary_dir: Option<String>) -> Vec<String> {
+    let mut languages: Vec<String> = dictionary_dirs(dictionary_dir.as_deref())
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "dic") && path.with_extension("aff").is_file()
+        })
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+fn dictionary_dirs(custom: Option<&str>) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Some(custom) = custom.filter(|c| !c.is_empty()) {
+        dirs.push(PathBuf::from(custom));
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if let Some(home) = &home {
+        dirs.push(home.join(".local/share/hunspell"));
+        dirs.push(home.join("Library/Spelling"));
+    }
+    for dir in [
+        "/usr/share/hunspell",
+        "/usr/share/myspell",
+        "/usr/share/myspell/dicts",
+        "/usr/local/share/hunspell",
+        "/Library/Spelling",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+/// Find and parse the dictionary for `language`. `en-us`, `en_US` and `en`
+/// all find `en_US.dic`.
+fn load_dictionary(
+    language: &str,
+    custom_dir: Option<&str>,
+) -> Result<(String, Arc<Dictionary>), AppError> {
+    let wanted = language.trim().replace('-', "_").to_lowercase();
+    let mut found: Option<PathBuf> = None;
+    for dir in dictionary_dirs(custom_dir) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut candidates: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "dic"))
+            .filter(|p| p.with_extension("aff").is_file())
+            .collect();
+        candidates.sort();
+        let stem = |p: &PathBuf| {
+            p.file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        };
+        found = candidates
+            .iter()
+            .find(|p| stem(p) == wanted)
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|p| stem(p).starts_with(&format!("{}_", wanted)))
+            })
+            .cloned();
+        if found.is_some() {
+            break;
+        }
+    }
+    let dic = found
+        .ok_or_else(|| AppError::NotFound(format!("Hunspell dictionary for '{}'", language)))?;
+    let name = dic
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut cache = DICTIONARIES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(dictionary) = cache.get(&dic) {
+        return Ok((name, dictionary.clone()));
+    }
+    let dictionary = Arc::new(Dictionary::load(&dic.with_extension("aff"), &dic)?);
+    cache.insert(dic, dictionary.clone());
+    Ok((name, dictionary))
+}
+
+fn read_word_list(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| normalize_word(l).to_lowercase())
+        .collect()
+}
+
+fn normalize_word(word: &str) -> String {
+    word.replace('’', "'")
+}
+
+/// Byte ranges of the words in the prose of `body`.
+fn prose_words(body: &str) -> Vec<Range<usize>> {
+    // Wiki links and URLs span several text events, so match them up front.
+    let skipped: Vec<Range<usize>> = SKIP_RE.find_iter(body).map(|m| m.range()).collect();
+    let mut words = Vec::new();
+    let mut skip_depth = 0usize;
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::MetadataBlock(_)) => {
+                skip_depth += 1
+            }
+            Event::Start(Tag::Link {
+                link_type: LinkType::Autolink | LinkType::Email,
+                ..
+            }) => skip_depth += 1,
+            Event::End(TagEnd::CodeBlock) | Event::End(TagEnd::MetadataBlock(_)) => {
+                skip_depth = skip_depth.saturating_sub(1)
+            }
+            Event::End(TagEnd::Link) if skip_depth > 0 => skip_depth = skip_depth.saturating_sub(1),
+            Event::Text(_) if skip_depth == 0 => {
+                for m in WORD_RE.find_iter(&body[range.clone()]) {
+                    let word = range.start + m.start()..range.start + m.end();
+                    let in_skipped = skipped
+                        .iter()
+                        .any(|s| s.start < word.end && word.start < s.end);
+                    if !in_skipped && is_checkable(m.as_str()) {
+                        words.push(word);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Skip numbers, identifiers, acronyms and single letters.
+fn is_checkable(word: &str) -> bool {
+    if word.chars().count() < 2 || word.chars().any(|c| c.is_numeric() || c == '_') {
+        return false;
+    }
+    let mut chars = word.chars().filter(|c| c.is_alphabetic());
+    let rest_has_upper = chars.by_ref().skip(1).any(char::is_uppercase);
+    !rest_has_upper
+}
+
+type Flag = u32;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FlagMode {
+    Char,
+    Long,
+    Num,
+}
+
+impl FlagMode {
+    fn parse(self, text: &str) -> Vec<Flag> {
+        match self {
+            FlagMode::Char => text.chars().map(|c| c as Flag).collect(),
+            FlagMode::Long => text
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |acc, &c| (acc << 16) | c as Flag))
+                .collect(),
+            FlagMode::Num => text
+                .split(',')
+                .filter_map(|n| n.trim().parse().ok())
+                .collect(),
+        }
+    }
+}
+
+/// An affix condition such as `[^aeiou]y`, one element per character.
+#[derive(Default)]
+struct Condition(Vec<CharClass>);
+
+enum CharClass {
+    Any,
+    Set(Vec<char>, bool),
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Set(chars, negated) => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+impl Condition {
+    fn parse(text: &str) -> Self {
+        if text == "." {
+            return Self::default();
+        }
+        let mut classes = Vec::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => classes.push(CharClass::Any),
+                '[' => {
+                    let mut set: Vec<char> = Vec::new();
+                    let mut negated = false;
+                    for (i, c) in chars.by_ref().enumerate() {
+                        match c {
+                            '^' if i == 0 => negated = true,
+                            ']' => break,
+                            c => set.push(c),
+                        }
+                    }
+                    classes.push(CharClass::Set(set, negated));
+                }
+                c => classes.push(CharClass::Set(vec![c], false)),
+            }
+        }
+        Self(classes)
+    }
+
+    fn matches_start(&self, word: &[char]) -> bool {
+        word.len() >= self.0.len() && self.0.iter().zip(word).all(|(class, &c)| class.matches(c))
+    }
+
+    fn matches_end(&self, word: &[char]) -> bool {
+        word.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .rev()
+                .zip(word.iter().rev())
+                .all(|(class, &c)| class.matches(c))
+    }
+}
+
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Condition,
+}
+
+/// A Hunspell dictionary: the word list from the `.dic` file and the affix
+/// rules from the `.aff` file. Supports prefixes and suffixes (with cross
+/// products), conditions, simple compounding and the flags that change
+/// whether a stem counts as a word on its own.
+struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    keep_case: Option<Flag>,
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    only_in_compound: Option<Flag>,
+    compound: Option<Flag>,
+    compound_min: usize,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+}
+
+impl Dictionary {
+    fn load(aff_path: &Path, dic_path: &Path) -> Result<Self, AppError> {
+        let aff_bytes = fs::read(aff_path)?;
+        let encoding = String::from_utf8_lossy(&aff_bytes)
+            .lines()
+            .find_map(|l| l.strip_prefix("SET ").map(|e| e.trim().to_uppercase()))
+            .unwrap_or_else(|| "ISO8859-1".to_string());
+        let aff = decode(&aff_bytes, &encoding);
+        let dic = decode(&fs::read(dic_path)?, &encoding);
+        Ok(Self::parse(&aff, &dic))
+    }
+
+    fn parse(aff: &str, dic: &str) -> Self {
+        let mut dictionary = Self {
+            words: HashMap::new(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            keep_case: None,
+            need_affix: None,
+            forbidden: None,
+            no_suggest: None,
+            only_in_compound: None,
+            compound: None,
+            compound_min: 3,
+            try_chars: DEFAULT_TRY.chars().collect(),
+            replacements: Vec::new(),
+        };
+
+        let mut mode = FlagMode::Char;
+        // Headers announce how many rule lines follow for each affix flag.
+        let mut pending_rules: HashMap<(String, String), usize> = HashMap::new();
+        let mut cross_products: HashMap<(String, String), bool> = HashMap::new();
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(&directive) = fields.first() else {
+                continue;
+            };
+            let single_flag =
+                |mode: FlagMode| fields.get(1).and_then(|f| mode.parse(f).first().copied());
+            match directive {
+                "FLAG" => {
+                    mode = match fields.get(1).copied() {
+                        Some("long") => FlagMode::Long,
+                        Some("num") => FlagMode::Num,
+                        _ => FlagMode::Char,
+                    }
+                }
+                "TRY" => {
+                    if let Some(chars) = fields.get(1) {
+                        dictionary.try_chars = chars.chars().collect();
+                    }
+                }
+                "KEEPCASE" => dictionary.keep_case = single_flag(mode),
+                "NEEDAFFIX" | "PSEUDOROOT" => dictionary.need_affix = single_flag(mode),
+                "FORBIDDENWORD" => dictionary.forbidden = single_flag(mode),
+                "NOSUGGEST" => dictionary.no_suggest = single_flag(mode),
+                "ONLYINCOMPOUND" => dictionary.only_in_compound = single_flag(mode),
+                "COMPOUNDFLAG" => dictionary.compound = single_flag(mode),
+                "COMPOUNDMIN" => {
+                    if let Some(min) = fields.get(1).and_then(|n| n.parse().ok()) {
+                        dictionary.compound_min = min;
+                    }
+                }
+                "REP" if fields.len() >= 3 => dictionary
+                    .replacements
+                    .push((fields[1].replace('_', " "), fields[2].replace('_', " "))),
+                "PFX" | "SFX" if fields.len() >= 4 => {
+                    let key = (directive.to_string(), fields[1].to_string());
+                    let remaining = pending_rules.get(&key).copied().unwrap_or(0);
+                    if remaining == 0 {
+                        if let Ok(count) = fields[3].parse() {
+                            pending_rules.insert(key.clone(), count);
+                            cross_products.insert(key, fields[2] == "Y");
+                        }
+                        continue;
+                    }
+                    pending_rules.insert(key.clone(), remaining - 1);
+                    let Some(&flag) = mode.parse(fields[1]).first() else {
+                        continue;
+                    };
+                    let clean = |s: &str| {
+                        if s == "0" {
+                            String::new()
+                        } else {
+                            s.to_string()
+                        }
+                    };
+                    // Continuation flags after `/` are not supported.
+                    let add = fields[3].split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        cross_product: cross_products.get(&key).copied().unwrap_or(false),
+                        strip: clean(fields[2]),
+                        add: clean(add),
+                        condition: Condition::parse(fields.get(4).copied().unwrap_or(".")),
+                    };
+                    if directive == "PFX" {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for line in dic.lines().skip(1) {
+            let entry = line.split('\t').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            // An unescaped `/` separates the word from its flags.
+            let mut split_at = None;
+            let mut escaped = false;
+            for (i, c) in entry.char_indices() {
+                match c {
+                    '\\' => escaped = !escaped,
+                    '/' if !escaped => {
+                        split_at = Some(i);
+                        break;
+                    }
+                    _ => escaped = false,
+                }
+            }
+            let (word, flags) = match split_at {
+                Some(i) => (
+                    &entry[..i],
+                    mode.parse(entry[i + 1..].split_whitespace().next().unwrap_or_default()),
+                ),
+                None => (
+                    entry.split_whitespace().next().unwrap_or_default(),
+                    Vec::new(),
+                ),
+            };
+            dictionary
+                .words
+                .entry(word.replace("\\/", "/"))
+                .or_default()
+                .extend(flags);
+        }
+        dictionary
+    }
+
+    fn has_flag(flags: &[Flag], flag: Option<Flag>) -> bool {
+        flag.is_some_and(|f| flags.contains(&f))
+    }
+
+    /// `true` when `word` is spelled correctly, trying the lowercase and
+    /// capitalized forms of capitalized words.
+    fn check(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if Self::has_flag(flags, self.forbidden) {
+                return false;
+            }
+        }
+        if self.check_exact(word) || self.check_compound(word, 0) {
+            return true;
+        }
+        let mut chars = word.chars();
+        let first_upper = chars.next().is_some_and(char::is_uppercase);
+        if !first_upper {
+            return false;
+        }
+        let lower = word.to_lowercase();
+        let keeps_case = |w: &str| {
+            self.words
+                .get(w)
+                .is_some_and(|flags| Self::has_flag(flags, self.keep_case))
+        };
+        if !keeps_case(&lower) && (self.check_exact(&lower) || self.check_compound(&lower, 0)) {
+            return true;
+        }
+        let capitalized = capitalize(&lower);
+        capitalized != word && !keeps_case(&capitalized) && self.check_exact(&capitalized)
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if !Self::has_flag(flags, self.need_affix)
+                && !Self::has_flag(flags, self.only_in_compound)
+                && !Self::has_flag(flags, self.forbidden)
+            {
+                return true;
+            }
+        }
+        self.check_affixed(word)
+    }
+
+    fn stem_has(&self, stem: &str, flags: &[Flag]) -> bool {
+        self.words.get(stem).is_some_and(|stem_flags| {
+            !Self::has_flag(stem_flags, self.forbidden)
+                && flags.iter().all(|f| stem_flags.contains(f))
+        })
+    }
+
+    fn check_affixed(&self, word: &str) -> bool {
+        let chars: Vec<char> = word.chars().collect();
+        for suffix in &self.suffixes {
+            let Some(base) = strip_suffix_chars(&chars, &suffix.add) else {
+                continue;
+            };
+            let mut stem: Vec<char> = base.to_vec();
+            stem.extend(suffix.strip.chars());
+            if !suffix.condition.matches_end(&stem) {
+                continue;
+            }
+            let stem_str: String = stem.iter().collect();
+            if self.stem_has(&stem_str, &[suffix.flag]) {
+                return true;
+            }
+            if !suffix.cross_product {
+                continue;
+            }
+            for prefix in self.prefixes.iter().filter(|p| p.cross_product) {
+                let Some(rest) = strip_prefix_chars(&stem, &prefix.add) else {
+                    continue;
+                };
+                let mut root: Vec<char> = prefix.strip.chars().collect();
+                root.extend_from_slice(rest);
+                if prefix.condition.matches_start(&root)
+                    && self.stem_has(
+                        &root.iter().collect::<String>(),
+                        &[suffix.flag, prefix.flag],
+                    )
+                {
+                    return true;
+                }
+            }
+        }
+        for prefix in &self.prefixes {
+            let Some(rest) = strip_prefix_chars(&chars, &prefix.add) else {
+                continue;
+            };
+            let mut stem: Vec<char> = prefix.strip.chars().collect();
+            stem.extend_from_slice(rest);
+            if prefix.condition.matches_start(&stem)
+                && self.stem_has(&stem.iter().collect::<String>(), &[prefix.flag])
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Words made of up to three dictionary words carrying `COMPOUNDFLAG`.
+    fn check_compound(&self, word: &str, depth: usize) -> bool {
+        let Some(flag) = self.compound else {
+            return false;
+        };
+        if depth > 2 {
+            return false;
+        }
+        let chars: Vec<char> = word.chars().collect();
+        let min = self.compound_min.max(1);
+        if chars.len() < min * 2 {
+            return false;
+        }
+        (min..=chars.len() - min).any(|i| {
+            let head: String = chars[..i].iter().collect();
+            let tail: String = chars[i..].iter().collect();
+            self.stem_has(&head, &[flag])
+                && (self.stem_has(&tail, &[flag]) || self.check_compound(&tail, depth + 1))
+        })
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let mut candidates: Vec<String> = Vec::new();
+        for (from, to) in &self.replacements {
+            for (i, _) in word.match_indices(from.as_str()) {
+                candidates.push(format!("{}{}{}", &word[..i], to, &word[i + from.len()..]));
+            }
+        }
+        let chars: Vec<char> = word.chars().collect();
+        for i in 0..chars.len() {
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            candidates.push(deleted.iter().collect());
+            if i + 1 < chars.len() {
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                candidates.push(swapped.iter().collect());
+            }
+            for &c in &self.try_chars {
+                if c != chars[i] {
+                    let mut replaced = chars.clone();
+                    replaced[i] = c;
+                    candidates.push(replaced.iter().collect());
+                }
+            }
+        }
+        for i in 0..=chars.len() {
+            for &c in &self.try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                candidates.push(inserted.iter().collect());
+            }
+        }
+        for i in 1..chars.len() {
+            let head: String = chars[..i].iter().collect();
+            let tail: String = chars[i..].iter().collect();
+            if self.check(&head) && self.check(&tail) {
+                candidates.push(format!("{} {}", head, tail));
+            }
+        }
+
+        let capitalized = chars.first().is_some_and(|c| c.is_uppercase());
+        let mut seen = HashSet::new();
+        let mut suggestions = Vec::new();
+        for candidate in candidates {
+            let candidate = if capitalized {
+                capitalize(&candidate)
+            } else {
+                candidate
+            };
+            if candidate == word || !seen.insert(candidate.clone()) {
+                continue;
+            }
+            let suggestible = candidate.contains(' ')
+                || (self.check(&candidate)
+                    && !self
+                        .words
+                        .get(&candidate)
+                        .is_some_and(|flags| Self::has_flag(flags, self.no_suggest)));
+            if suggestible {
+                suggestions.push(candidate);
+                if suggestions.len() == MAX_SUGGESTIONS {
+                    break;
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+fn strip_suffix_chars<'a>(word: &'a [char], suffix: &str) -> Option<&'a [char]> {
+    let suffix: Vec<char> = suffix.chars().collect();
+    (word.len() > suffix.len() && word.ends_with(&suffix))
+        .then(|| &word[..word.len() - suffix.len()])
+}
+
+fn strip_prefix_chars<'a>(word: &'a [char], prefix: &str) -> Option<&'a [char]> {
+    let prefix: Vec<char> = prefix.chars().collect();
+    (word.len() > prefix.len() && word.starts_with(&prefix)).then(|| &word[prefix.len()..])
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Decode dictionary files. Besides UTF-8 only the Latin-1 family is
+/// supported, which covers the common Western European dictionaries.
+fn decode(bytes: &[u8], encoding: &str) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if encoding == "UTF-8" || encoding == "UTF8" => text.to_string(),
+        Ok(text) if text.is_ascii() => text.to_string(),
+        _ if encoding == "UTF-8" => String::from_utf8_lossy(bytes).into_owned(),
+        _ => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
@@ -0,0 +1,427 @@
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, HeadingLevel, LinkType, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+use crate::blocks::{block_markers, block_range, strip_block_markers};
+use crate::error::AppError;
+use crate::frontmatter::split_frontmatter;
+use crate::markdown::{
+    has_url_scheme, is_external_url, parser_options, percent_decode, slugify, split_fragment,
+};
+use crate::utils::validate_directory;
+use crate::wiki::{split_wiki_target, NoteIndex};
+
+/// How many levels of embeds inside embedded notes are followed.
+const MAX_EMBED_DEPTH: usize = 8;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "avif"];
+
+static EMBED_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"!\[\[([^\]\n]+)\]\]").expect("invalid embed regex"));
+/// What may precede an embed that sits on its own line: indentation and
+/// blockquote markers.
+static BLOCK_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[ \t]*(?:>[ \t]?)*$").expect("invalid block prefix regex"));
+
+#[derive(Deserialize)]
+pub struct EmbedOptions {
+    /// Markdown of the note, frontmatter included.
+    pub content: String,
+    /// Path of the note; relative embeds and links resolve against its folder.
+    pub file_path: String,
+    /// Workspace root used to find embedded notes by name. Defaults to the
+    /// note's folder.
+    pub folder: Option<String>,
+}
+
+/// Replace `![[note]]`, `![[note#Section]]` and `![[note^block-id]]` embeds
+/// with the referenced content, recursively.
+///
+/// Links in embedded notes are rewritten relative to the embedding note.
+/// Cycles and embeds nested deeper than [`MAX_EMBED_DEPTH`] are replaced by
+/// a short notice instead of being expanded.
+#[tauri::command]
+pub fn resolve_embeds(options: EmbedOptions) -> Result<String, AppError> {
+    let source = PathBuf::from(&options.file_path);
+    let root = match &options.folder {
+        Some(folder) => validate_directory(folder)?,
+        None => source.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+    let (_, body) = split_frontmatter(&options.content);
+    let frontmatter = &options.content[..options.content.len() - body.len()];
+    Ok(format!(
+        "{}{}",
+        frontmatter,
+        expand_embeds(body, &source, &root)
+    ))
+}
+
+/// Expand the embeds in `body`, the markdown of `source` without its
+/// frontmatter. Used by the exporters before rendering.
+pub fn expand_embeds(body: &str, source: &Path, root: &Path) -> String {
+    if !body.contains("![[") {
+        return body.to_string();
+    }
+    let source = source
+        .canonicalize()
+        .unwrap_or_else(|_| source.to_path_buf());
+    let mut embedder = Embedder {
+        root,
+        host_dir: source.parent().unwrap_or(root).to_path_buf(),
+        index: None,
+        stack: vec![(source.clone(), None)],
+    };
+    embedder.expand(body, &source, 0)
+}
+
+struct Embedder<'a> {
+    root: &'a Path,
+    /// Folder of the note being rendered; links in embedded notes are
+    /// rewritten relative to it.
+    host_dir: PathBuf,
+    /// Built on the first lookup by name.
+    index: Option<NoteIndex>,
+    /// Notes (and sections) currently being expanded, for cycle detection.
+    stack: Vec<(PathBuf, Option<String>)>,
+}
+
+impl Embedder<'_> {
+    fn expand(&mut self, text: &str, note: &Path, depth: usize) -> String {
+        let code = code_ranges(text);
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for cap in EMBED_RE.captures_iter(text) {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            if whole.start() < last || code.iter().any(|r| r.contains(&whole.start())) {
+                continue;
+            }
+            let target = cap[1].split('|').next().unwrap_or_default().trim();
+            let rendered = self.render(target, note, depth);
+
+            let line_start = text[..whole.start()].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = text[whole.end()..]
+                .find('\n')
+                .map_or(text.len(), |i| whole.end() + i);
+            let prefix = &text[line_start..whole.start()];
+            if BLOCK_PREFIX_RE.is_match(prefix) && text[whole.end()..line_end].trim().is_empty() {
+                // An embed on its own line becomes a block of its own, keeping
+                // any blockquote markers in front of every line.
+                out.push_str(&text[last..line_start]);
+                let blank = prefix.trim_end();
+                out.push_str(blank);
+                out.push('\n');
+                for line in rendered.lines() {
+                    out.push_str(if line.is_empty() { blank } else { prefix });
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                out.push_str(blank);
+                last = line_end;
+            } else {
+                out.push_str(&text[last..whole.start()]);
+                out.push_str(&rendered);
+                last = whole.end();
+            }
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+
+    /// Markdown replacing a single `![[target]]`.
+    fn render(&mut self, target: &str, note: &Path, depth: usize) -> String {
+        let (name, fragment) = split_wiki_target(target);
+        let fragment = fragment.map(str::to_string);
+        let note_dir = note.parent().unwrap_or(self.root).to_path_buf();
+
+        let file = if name.is_empty() {
+            Some(note.to_path_buf())
+        } else {
+            self.find(name, &note_dir)
+        };
+        let Some(file) = file else {
+            return notice("Embedded note not found", target);
+        };
+        let file = file.canonicalize().unwrap_or(file);
+
+        let is_note = file
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        if !is_note {
+            let href = relative_path(&self.host_dir, &file);
+            let label = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let is_image = file.extension().is_some_and(|ext| {
+                IMAGE_EXTENSIONS
+                    .iter()
+                    .any(|known| ext.eq_ignore_ascii_case(known))
+            });
+            return if is_image {
+                format!("![{}](<{}>)", label, href)
+            } else {
+                format!("[{}](<{}>)", label, href)
+            };
+        }
+
+        let key = (file.clone(), fragment.clone());
+        if self.stack.contains(&key) {
+            return notice("Embed cycle", target);
+        }
+        if depth >= MAX_EMBED_DEPTH {
+            return notice("Embed depth limit reached", target);
+        }
+
+        let Ok(content) = fs::read_to_string(&file) else {
+            return notice("Embedded note not found", target);
+        };
+        let (_, body) = split_frontmatter(&content);
+        let selected = match fragment.as_deref() {
+            None => Some(body.to_string()),
+            Some(f) if f.starts_with('^') => extract_block(body, &f[1..]),
+            Some(f) => extract_section(body, f),
+        };
+        let Some(selected) = selected else {
+            return notice("Embedded section not found", target);
+        };
+
+        let embedded_dir = file.parent().unwrap_or(self.root).to_path_buf();
+        let selected = rebase_links(&selected, &embedded_dir, &self.host_dir);
+        self.stack.push(key);
+        let expanded = self.expand(&selected, &file, depth + 1);
+        self.stack.pop();
+        expanded.trim_end().to_string()
+    }
+
+    /// Find an embedded file relative to the embedding note, the workspace
+    /// root, or by name anywhere in the workspace.
+    fn find(&mut self, name: &str, note_dir: &Path) -> Option<PathBuf> {
+        let decoded = percent_decode(name);
+        let mut candidates = vec![note_dir.join(&decoded), self.root.join(&decoded)];
+        if !decoded.to_lowercase().ends_with(".md") {
+            let with_extension = format!("{}.md", decoded);
+            candidates.push(note_dir.join(&with_extension));
+            candidates.push(self.root.join(&with_extension));
+        }
+        if let Some(found) = candidates.into_iter().find(|p| p.is_file()) {
+            return Some(found);
+        }
+        let root = self.root;
+        self.index
+            .get_or_insert_with(|| NoteIndex::build(root))
+            .resolve(&decoded)
+            .cloned()
+    }
+}
+
+fn notice(message: &str, target: &str) -> String {
+    format!("*{}: {}*", message, target.replace('*', "\\*"))
+}
+
+/// Byte ranges of code spans, code blocks and HTML, where embeds are literal text.
+pub fn code_ranges(text: &str) -> Vec<Range<usize>> {
+    Parser::new_ext(text, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Code(_) | Event::Html(_) => Some(range),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The section under the heading whose slug matches `heading`, up to the
+/// next heading of the same or a higher level. The heading itself is kept.
+fn extract_section(body: &str, heading: &str) -> Option<String> {
+    let wanted = slugify(heading);
+    let mut current: Option<(HeadingLevel, usize, String)> = None;
+    let mut start: Option<(HeadingLevel, usize)> = None;
+
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                if let Some((open_level, from)) = start {
+                    if level <= open_level {
+                        return Some(body[from..range.start].to_string());
+                    }
+                } else {
+                    current = Some((level, range.start, String::new()));
+                }
+            }
+            Event::Text(t) | Event::Code(t) => {
+                if let Some((_, _, text)) = current.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, from, text)) = current.take() {
+                    if slugify(&text) == wanted {
+                        start = Some((level, from));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    start.map(|(_, from)| body[from..].to_string())
+}
+
+/// The paragraph, list item or other block marked with `^id`, without
+/// block markers.
+fn extract_block(body: &str, id: &str) -> Option<String> {
+    let marker = block_markers(body).into_iter().find(|m| m.id == id)?;
+    let range = block_range(body, &marker.range)?;
+    Some(
+        strip_block_markers(&dedent(&body[range]))
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// Remove the indentation of the first line from every line.
+fn dedent(text: &str) -> String {
+    let indent = text.len() - text.trim_start_matches([' ', '\t']).len();
+    text.lines()
+        .map(|line| {
+            let strip = line.len() - line.trim_start_matches([' ', '\t']).len();
+            &line[strip.min(indent)..]
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rewrite relative inline link and image destinations in `text`, written
+/// for a note in `from_dir`, so they work from `to_dir`.
+fn rebase_links(text: &str, from_dir: &Path, to_dir: &Path) -> String {
+    if from_dir == to_dir {
+        return text.to_string();
+    }
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for (event, range) in Parser::new_ext(text, parser_options()).into_offset_iter() {
+        let (link_type, dest) = match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                ..
+            }) => (link_type, dest_url),
+            _ => continue,
+        };
+        if link_type != LinkType::Inline
+            || dest.is_empty()
+            || dest.starts_with(['#', '/'])
+            || is_external_url(&dest)
+            || has_url_scheme(&dest)
+        {
+            continue;
+        }
+
+        // The destination follows the last `](` of the link source.
+        let source = &text[range.clone()];
+        let Some(open) = source.rfind("](") else {
+            continue;
+        };
+        let after = range.start + open + 2;
+        let raw_start =
+            after + (text[after..range.end].len() - text[after..range.end].trim_start().len());
+        let raw = &text[raw_start..range.end];
+        let raw_len = if raw.starts_with('<') {
+            match raw.find('>') {
+                Some(close) => close + 1,
+                None => continue,
+            }
+        } else {
+            raw.find(char::is_whitespace)
+                .unwrap_or(raw.len().saturating_sub(1))
+        };
+
+        let (path_part, fragment) = split_fragment(&dest);
+        let target = normalize(&from_dir.join(percent_decode(path_part)));
+        let mut rebased = relative_path(to_dir, &target);
+        if let Some(fragment) = fragment {
+            rebased.push('#');
+            rebased.push_str(fragment);
+        }
+        edits.push((raw_start..raw_start + raw_len, format!("<{}>", rebased)));
+    }
+
+    // A link's edit comes before those of images nested in its text, but its
+    // destination ends later; apply from the end so offsets stay valid
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut out = text.to_string();
+    for (range, replacement) in edits {
+        out.replace_range(range, &replacement);
+    }
+    out
+}
+
+/// Resolve `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `target` relative to the folder `base`, with `/` separators.
+fn relative_path(base: &Path, target: &Path) -> String {
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); base.len() - common];
+    parts.extend(
+        target[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebase_links_rewrites_relative_destinations() {
+        let text = "See [other](other.md#part) and ![img](img.png \"Title\").";
+        assert_eq!(
+            rebase_links(text, Path::new("/vault/notes"), Path::new("/vault/sub")),
+            "See [other](<../notes/other.md#part>) and ![img](<../notes/img.png> \"Title\")."
+        );
+    }
+
+    #[test]
+    fn rebase_links_handles_images_nested_in_links() {
+        let text = "See [![badge](img.png)](other.md) here.";
+        assert_eq!(
+            rebase_links(text, Path::new("/vault/notes"), Path::new("/vault/sub")),
+            "See [![badge](<../notes/img.png>)](<../notes/other.md>) here."
+        );
+    }
+
+    #[test]
+    fn rebase_links_keeps_absolute_and_external_links() {
+        let text = "[a](/x.md) [b](https://example.com) [c](#top)";
+        assert_eq!(
+            rebase_links(text, Path::new("/vault/notes"), Path::new("/vault/sub")),
+            text
+        );
+    }
+}
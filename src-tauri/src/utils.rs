@@ -1,4 +1,8 @@
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
 use walkdir::WalkDir;
 
 use crate::error::AppError;
@@ -10,6 +14,20 @@ pub fn is_markdown_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Check whether `path` is a delimited data file (`.csv` or `.tsv`), shown
+/// read-only in the workspace.
+pub fn is_table_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("tsv"))
+        .unwrap_or(false)
+}
+
+/// Check whether `path` is a file the workspace lists, searches and indexes:
+/// markdown or CSV/TSV.
+pub fn is_workspace_file(path: &Path) -> bool {
+    is_markdown_file(path) || is_table_file(path)
+}
+
 /// Validate that `path` is an existing directory and return it as a `PathBuf`.
 pub fn validate_directory(path: &str) -> Result<PathBuf, AppError> {
     let root = PathBuf::from(path);
@@ -29,6 +47,16 @@ pub fn collect_md_files(folder: &Path) -> Vec<PathBuf> {
         .collect()
 }
 
+/// Recursively collect all markdown and CSV/TSV file paths under `folder`.
+pub fn collect_workspace_files(folder: &Path) -> Vec<PathBuf> {
+    WalkDir::new(folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_workspace_file(e.path()))
+        .map(|e| e.into_path())
+        .collect()
+}
+
 /// Sanitize a filename to prevent path-traversal attacks.
 ///
 /// Strips any directory components and rejects names that contain `..`.
@@ -47,3 +75,98 @@ pub fn sanitize_filename(name: &str) -> Result<String, AppError> {
 
     Ok(clean)
 }
+
+/// Modification time in seconds and size of the file at `path`, which
+/// together tell whether it changed since it was indexed.
+pub fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Some((modified, metadata.len()))
+}
+
+/// The subdirectory `name` of the app data directory. It is not created.
+pub fn app_data_subdir(app: &AppHandle, name: &str) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Other(e.to_string()))?;
+    Ok(dir.join(name))
+}
+
+/// File persisting an index of the workspace at `root`, in the app data
+/// subdirectory `dir` and named after a hash of the workspace path.
+pub fn index_store_path(app: &AppHandle, dir: &str, root: &Path) -> Option<PathBuf> {
+    let hash = fnv1a(root.to_string_lossy().as_bytes());
+    let dir = app_data_subdir(app, dir).ok()?;
+    Some(dir.join(format!("{:016x}.json", hash)))
+}
+
+/// A name for a new entry of `dir` (`suffix` appended) from the current
+/// time in milliseconds, zero-padded so names sort by age.
+pub fn timestamped_name(dir: &Path, suffix: &str) -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let mut name = format!("{:015}", millis);
+    let mut n = 1;
+    while dir.join(format!("{}{}", name, suffix)).exists() {
+        name = format!("{:015}-{}", millis, n);
+        n += 1;
+    }
+    name
+}
+
+/// Delete all but the newest `keep` entries of a directory whose entries
+/// are named by `timestamped_name`.
+pub fn prune_oldest(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    // Names sort by age, so the newest come last
+    paths.sort();
+    let excess = paths.len().saturating_sub(keep);
+    for path in paths.into_iter().take(excess) {
+        let _ = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+    }
+}
+
+/// Write `bytes` to `path` through a temporary file beside it and a rename,
+/// so a crash never leaves half a file. Missing parent directories are
+/// created.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.tmp", name));
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&temp, bytes))
+        .and_then(|_| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+/// 64-bit FNV-1a hash. Unlike `DefaultHasher` it is stable across builds,
+/// so it can name files and be stored.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -7,7 +7,10 @@ use std::time::Duration;
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 
-use crate::utils::is_markdown_file;
+use crate::quick_open;
+use crate::saved_search;
+use crate::search_index;
+use crate::utils::is_workspace_file;
 
 #[derive(Serialize, Clone)]
 pub struct FileChangeEvent {
@@ -33,9 +36,11 @@ fn set_running(val: bool) {
     }
 }
 
-/// Starts polling the given `folder` for `.md` file changes every 2 seconds.
+/// Starts polling the given `folder` for markdown and CSV/TSV file changes
+/// every 2 seconds, the files the search indexes cover.
 /// Any previous watcher is stopped before starting a new one.
-/// Detected changes are emitted as a `file-changes` event to the frontend.
+/// Detected changes are emitted as a `file-changes` event to the frontend,
+/// followed by `smart-folders-changed` when they alter a smart folder.
 #[tauri::command]
 pub fn start_watching(app: AppHandle, folder: String) -> Result<(), String> {
     // Stop any existing watcher
@@ -47,6 +52,10 @@ pub fn start_watching(app: AppHandle, folder: String) -> Result<(), String> {
     // Mark the new watcher as running
     set_running(true);
 
+    // Load the search indexes while the workspace is open
+    search_index::open(&app, std::path::Path::new(&folder));
+    quick_open::open(std::path::Path::new(&folder));
+
     thread::spawn(move || {
         let mut file_hashes: HashMap<String, u64> = HashMap::new();
 
@@ -55,6 +64,7 @@ pub fn start_watching(app: AppHandle, folder: String) -> Result<(), String> {
 
         loop {
             if !is_running() {
+                search_index::flush(&folder);
                 break;
             }
 
@@ -95,7 +105,13 @@ pub fn start_watching(app: AppHandle, folder: String) -> Result<(), String> {
             }
 
             if !changes.is_empty() {
+                search_index::apply_changes(&folder, &changes);
+                quick_open::apply_changes(&folder, &changes);
+                let smart_folders_changed = saved_search::apply_changes(&folder, &changes);
                 let _ = app.emit("file-changes", changes);
+                if smart_folders_changed {
+                    let _ = app.emit("smart-folders-changed", &folder);
+                }
             }
 
             file_hashes = current_files;
@@ -112,14 +128,14 @@ pub fn stop_watching() -> Result<(), String> {
     Ok(())
 }
 
-/// Walks `folder` recursively and records every `.md` file together with a
+/// Walks `folder` recursively and records every workspace file together with a
 /// simple hash derived from its size and last-modified timestamp.
 fn scan_files(folder: &str, files: &mut HashMap<String, u64>) {
     for entry in walkdir::WalkDir::new(folder)
         .into_iter()
         .filter_map(|e| e.ok())
     {
-        if entry.file_type().is_file() && is_markdown_file(entry.path()) {
+        if entry.file_type().is_file() && is_workspace_file(entry.path()) {
             let path = entry.path().to_string_lossy().into_owned();
             if let Ok(metadata) = fs::metadata(entry.path()) {
                 let hash = metadata.len()
@@ -1,9 +1,13 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 use crate::error::AppError;
+use crate::markdown::{slugify, split_fragment, LineIndex};
 use crate::utils::{collect_md_files, validate_directory};
 
 static WIKI_LINK_RE: Lazy<Regex> =
@@ -12,8 +16,17 @@ static WIKI_LINK_RE: Lazy<Regex> =
 #[derive(Serialize, Clone)]
 pub struct WikiLink {
     pub source_file: String,
+    /// Linked note, without any `#Heading` or `#^block` part.
     pub target: String,
     pub alias: Option<String>,
+    /// `true` for `![[...]]` embeds.
+    pub embed: bool,
+    /// Heading the link points at, for `[[note#Heading]]`.
+    pub heading: Option<String>,
+    /// Block ID the link points at, for `[[note#^block-id]]`.
+    pub block: Option<String>,
+    /// 1-based line of the link in the source file.
+    pub line: usize,
 }
 
 #[derive(Serialize, Clone)]
@@ -37,21 +50,32 @@ pub fn scan_wiki_links(folder: String) -> Result<WikiScanResult, AppError> {
         files.push(path_str.clone());
 
         if let Ok(content) = fs::read_to_string(md_path) {
+            let index = LineIndex::new(&content);
             for cap in WIKI_LINK_RE.captures_iter(&content) {
-                let inner = cap[1].to_string();
-                let (target, alias) = if let Some(pos) = inner.find('|') {
-                    (
-                        inner[..pos].trim().to_string(),
-                        Some(inner[pos + 1..].trim().to_string()),
-                    )
-                } else {
-                    (inner.trim().to_string(), None)
+                let whole = cap.get(0).expect("capture group 0 always exists");
+                let embed = content[..whole.start()].ends_with('!');
+                let inner = &cap[1];
+                let (target, alias) = match inner.find('|') {
+                    Some(pos) => (&inner[..pos], Some(inner[pos + 1..].trim().to_string())),
+                    None => (inner, None),
+                };
+                let (name, fragment) = split_wiki_target(target.trim());
+                let (heading, block) = match fragment {
+                    Some(f) => match f.strip_prefix('^') {
+                        Some(id) => (None, Some(id.to_string())),
+                        None => (Some(f.to_string()), None),
+                    },
+                    None => (None, None),
                 };
 
                 links.push(WikiLink {
                     source_file: path_str.clone(),
-                    target,
+                    target: name.to_string(),
                     alias,
+                    embed,
+                    heading,
+                    block,
+                    line: index.position(whole.start()).0,
                 });
             }
         }
@@ -59,3 +83,95 @@ pub fn scan_wiki_links(folder: String) -> Result<WikiScanResult, AppError> {
 
     Ok(WikiScanResult { links, files })
 }
+
+/// Split a wiki link target into the note name and the part after `#`.
+/// Block references keep their `^`: both `note#^id` and the embed shorthand
+/// `note^id` give `("note", Some("^id"))`.
+pub fn split_wiki_target(target: &str) -> (&str, Option<&str>) {
+    match split_fragment(target) {
+        (name, Some(fragment)) => (name.trim(), Some(fragment.trim())),
+        (name, None) => match name.find('^') {
+            Some(pos) => (name[..pos].trim(), Some(&name[pos..])),
+            None => (name.trim(), None),
+        },
+    }
+}
+
+/// Every file in the workspace by lowercase name, with and without extension,
+/// and by lowercase path relative to the root without extension.
+pub struct NoteIndex {
+    by_name: HashMap<String, PathBuf>,
+}
+
+impl NoteIndex {
+    pub fn build(root: &Path) -> Self {
+        let mut by_name = HashMap::new();
+        let files = WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file());
+        for entry in files {
+            let path = entry.into_path();
+            let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/")
+                .to_lowercase();
+            let mut keys = vec![name.clone(), relative.clone()];
+            if let Some(stem) = name.strip_suffix(".md") {
+                keys.push(stem.to_string());
+                keys.push(relative.trim_end_matches(".md").to_string());
+            }
+            for key in keys {
+                by_name.entry(key).or_insert_with(|| path.clone());
+            }
+        }
+        Self { by_name }
+    }
+
+    /// Resolve a wiki link target the way the preview does: by exact name,
+    /// then with spaces turned into dashes.
+    pub fn resolve(&self, target: &str) -> Option<&PathBuf> {
+        let lower = target.trim().to_lowercase().replace('\\', "/");
+        self.by_name.get(&lower).or_else(|| {
+            self.by_name
+                .get(&lower.split_whitespace().collect::<Vec<_>>().join("-"))
+        })
+    }
+}
+
+/// Rewrite `[[target]]` / `[[target|alias]]` as regular markdown links to
+/// `target.md` so backend renderers can resolve them like any relative link.
+/// Embeds (`![[...]]`) are left untouched.
+pub fn wiki_links_to_markdown(content: &str) -> String {
+    WIKI_LINK_RE
+        .replace_all(content, |cap: &regex::Captures| {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            if content[..whole.start()].ends_with('!') {
+                return whole.as_str().to_string();
+            }
+
+            let inner = &cap[1];
+            let (target, alias) = match inner.find('|') {
+                Some(pos) => (inner[..pos].trim(), inner[pos + 1..].trim()),
+                None => (inner.trim(), inner.trim()),
+            };
+            let (path, fragment) = split_fragment(target);
+            let file = if path.is_empty() || path.to_lowercase().ends_with(".md") {
+                path.to_string()
+            } else {
+                format!("{}.md", path)
+            };
+            let dest = match fragment {
+                Some(frag) => format!("{}#{}", file, slugify(frag)),
+                None => file,
+            };
+            format!("[{}](<{}>)", alias, dest)
+        })
+        .into_owned()
+}
@@ -58,6 +58,39 @@ export function saveAiConfig(config: AiConfig): void {
   localStorage.setItem(STORAGE_KEY, JSON.stringify(config));
 }
 
+// --- Embeddings config (semantic search) ---
+
+/** An OpenAI-compatible `/embeddings` endpoint, e.g. Ollama's `/v1`. */
+export interface EmbeddingConfig {
+  baseUrl: string;
+  apiKey: string;
+  model: string;
+}
+
+const DEFAULT_EMBEDDING_CONFIG: EmbeddingConfig = {
+  baseUrl: 'http://localhost:11434/v1',
+  apiKey: '',
+  model: 'nomic-embed-text',
+};
+
+const EMBEDDING_STORAGE_KEY = 'markview-embedding-config';
+
+export function loadEmbeddingConfig(): EmbeddingConfig {
+  try {
+    const stored = localStorage.getItem(EMBEDDING_STORAGE_KEY);
+    if (stored) {
+      return { ...DEFAULT_EMBEDDING_CONFIG, ...JSON.parse(stored) };
+    }
+  } catch {
+    // ignore parse errors
+  }
+  return { ...DEFAULT_EMBEDDING_CONFIG };
+}
+
+export function saveEmbeddingConfig(config: EmbeddingConfig): void {
+  localStorage.setItem(EMBEDDING_STORAGE_KEY, JSON.stringify(config));
+}
+
 // --- Proxy through Tauri backend ---
 
 async function proxyRequest(
@@ -1,5 +1,6 @@
 import { autocompletion, type CompletionContext, type CompletionResult } from '@codemirror/autocomplete';
 import type { EditorView } from '@codemirror/view';
+import { citationCompletions, type CitationContext } from '$lib/citations';
 
 interface SnippetItem {
   label: string;
@@ -176,9 +177,9 @@ function mermaidCompletions(context: CompletionContext): CompletionResult | null
   };
 }
 
-export function markdownAutocompletion() {
+export function markdownAutocompletion(getCitationContext: () => CitationContext) {
   return autocompletion({
-    override: [markdownCompletions, mermaidCompletions],
+    override: [markdownCompletions, mermaidCompletions, citationCompletions(getCitationContext)],
     defaultKeymap: true,
   });
 }
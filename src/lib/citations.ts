@@ -0,0 +1,84 @@
+import type { CompletionContext, CompletionResult } from '@codemirror/autocomplete';
+import { invoke } from '@tauri-apps/api/core';
+
+export interface CitationKey {
+  key: string;
+  title: string;
+  authors: string;
+  year: string;
+}
+
+interface CitationResult {
+  content: string;
+  style: 'apa' | 'chicago' | 'ieee';
+  missing: string[];
+}
+
+/** Where the editor's note lives, for locating its bibliography. */
+export interface CitationContext {
+  filePath: string | null;
+  folder: string | null;
+}
+
+const KEYS_TTL = 5000;
+
+/**
+ * Format `[@key]` citations and append the bibliography, for the preview and
+ * HTML export. Notes without citations are returned unchanged.
+ */
+export async function resolveCitations(
+  content: string,
+  filePath: string | null,
+  folder: string | null
+): Promise<string> {
+  if (!content.includes('@')) return content;
+  try {
+    const result = await invoke<CitationResult>('render_citations', {
+      options: { content, file_path: filePath, folder },
+    });
+    return result.content;
+  } catch {
+    return content;
+  }
+}
+
+let cachedKeys: { signature: string; time: number; keys: CitationKey[] } | null = null;
+
+async function citationKeys(content: string, context: CitationContext): Promise<CitationKey[]> {
+  // The bibliography can be named in frontmatter, so it is part of the key
+  const bibliography = /^bibliography:.*$/m.exec(content)?.[0] ?? '';
+  const signature = `${context.folder}|${context.filePath}|${bibliography}`;
+  if (cachedKeys && cachedKeys.signature === signature && Date.now() - cachedKeys.time < KEYS_TTL) {
+    return cachedKeys.keys;
+  }
+  try {
+    const keys = await invoke<CitationKey[]>('list_citation_keys', {
+      options: { content, file_path: context.filePath, folder: context.folder },
+    });
+    cachedKeys = { signature, time: Date.now(), keys };
+    return keys;
+  } catch {
+    return [];
+  }
+}
+
+/** Completion source for citation keys after `@` in `[@` or `; @`. */
+export function citationCompletions(getContext: () => CitationContext) {
+  return async (context: CompletionContext): Promise<CompletionResult | null> => {
+    const match = context.matchBefore(/(?:^|[\s[;-])@[\w:.#$%&+?<>~/-]*/);
+    if (!match) return null;
+    const from = match.from + match.text.indexOf('@') + 1;
+    const keys = await citationKeys(context.state.doc.toString(), getContext());
+    if (keys.length === 0) return null;
+    return {
+      from,
+      options: keys.map((k) => ({
+        label: k.key,
+        detail: [k.authors, k.year].filter(Boolean).join(' '),
+        info: k.title,
+        type: 'text' as const,
+      })),
+      validFor: /^[\w:.#$%&+?<>~/-]*$/,
+    };
+  };
+}
@@ -319,6 +319,58 @@ export function printDocument(): void {
   window.print();
 }
 
+/**
+ * Typesets markdown into a PDF in the backend (`export_pdf`), with page
+ * numbers in the footer. Opens a save dialog for the destination and
+ * resolves to the characters the PDF fonts could not show, or `null` when
+ * the dialog was cancelled.
+ */
+export async function exportToPdf(
+  markdownContent: string,
+  fileName: string,
+  sourcePath: string | null,
+  folder: string | null = null
+): Promise<string[] | null> {
+  const defaultPath = fileName.replace(/\.md$/i, '.pdf');
+
+  const path = await save({
+    defaultPath,
+    filters: [{ name: 'PDF', extensions: ['pdf'] }],
+  });
+
+  if (!path) return null;
+  const outcome = await invoke<{ output_path: string; unsupported_chars: string[] }>(
+    'export_pdf',
+    {
+      options: {
+        input_path: sourcePath ?? fileName,
+        output_path: path,
+        content: markdownContent,
+        folder,
+      },
+    }
+  );
+  return outcome.unsupported_chars;
+}
+
+/**
+ * Builds an EPUB book from every note in `folder` in the backend
+ * (`export_epub`), ordered by `SUMMARY.md` or by path. Opens a save dialog
+ * for the destination.
+ */
+export async function exportToEpub(folder: string): Promise<void> {
+  const name = folder.split(/[\\/]/).filter(Boolean).pop() ?? 'book';
+
+  const path = await save({
+    defaultPath: `${name}.epub`,
+    filters: [{ name: 'EPUB', extensions: ['epub'] }],
+  });
+
+  if (path) {
+    await invoke('export_epub', { options: { folder, output_path: path } });
+  }
+}
+
 /**
  * Escapes HTML special characters to prevent injection in the title.
  */
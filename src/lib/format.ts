@@ -0,0 +1,27 @@
+import { invoke } from '@tauri-apps/api/core';
+import { settingsManager } from '$lib/settings.svelte';
+
+export type BulletStyle = 'dash' | 'asterisk' | 'plus';
+export type EmphasisStyle = 'asterisk' | 'underscore';
+export type ProseWrap = 'preserve' | 'always' | 'never';
+
+/** Formatter options in the shape expected by the backend. */
+function formatOptions() {
+  const s = settingsManager.settings;
+  return {
+    bullet: s.formatBullet,
+    emphasis: s.formatEmphasis,
+    prose_wrap: s.formatProseWrap,
+    print_width: s.formatPrintWidth,
+  };
+}
+
+/** Format markdown with the user's formatter settings. */
+export function formatMarkdown(content: string): Promise<string> {
+  return invoke<string>('format_document', { content, options: formatOptions() });
+}
+
+/** Format every markdown file in `folder`; returns the changed paths. */
+export function formatFolder(folder: string): Promise<string[]> {
+  return invoke<string[]>('format_folder', { folder, options: formatOptions() });
+}
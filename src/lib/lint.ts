@@ -0,0 +1,162 @@
+import { ViewPlugin, Decoration, EditorView, keymap } from '@codemirror/view';
+import type { ViewUpdate, DecorationSet } from '@codemirror/view';
+import { StateEffect, StateField } from '@codemirror/state';
+import type { Extension, Text } from '@codemirror/state';
+import { invoke } from '@tauri-apps/api/core';
+
+export interface LintFix {
+  line: number;
+  column: number;
+  delete_count: number;
+  insert_text: string;
+}
+
+export interface LintDiagnostic {
+  rule: string;
+  alias: string;
+  message: string;
+  line: number;
+  column: number;
+  end_line: number;
+  end_column: number;
+  fix: LintFix | null;
+}
+
+export interface FileLintResult {
+  file_path: string;
+  file_name: string;
+  diagnostics: LintDiagnostic[];
+}
+
+const LINT_DELAY = 500;
+
+/** Convert a 1-based line and UTF-16 column into a document offset. */
+function toOffset(doc: Text, line: number, column: number): number {
+  if (line > doc.lines) return doc.length;
+  const info = doc.line(line);
+  return Math.min(info.from + column - 1, info.to);
+}
+
+interface PlacedDiagnostic {
+  from: number;
+  to: number;
+  /** Start of the fix's deleted range, mapped through later edits. */
+  fixFrom: number | null;
+  diagnostic: LintDiagnostic;
+}
+
+const setDiagnostics = StateEffect.define<PlacedDiagnostic[]>();
+
+const diagnosticsField = StateField.define<{ items: PlacedDiagnostic[]; decorations: DecorationSet }>({
+  create() {
+    return { items: [], decorations: Decoration.none };
+  },
+  update(value, tr) {
+    for (const effect of tr.effects) {
+      if (effect.is(setDiagnostics)) {
+        const items = effect.value;
+        const decorations = Decoration.set(
+          items.map(({ from, to, diagnostic }) =>
+            Decoration.mark({
+              class: 'cm-lint-mark',
+              attributes: { title: `${diagnostic.rule}/${diagnostic.alias}: ${diagnostic.message}` },
+            }).range(from, Math.max(to, from + 1))
+          ),
+          true
+        );
+        return { items, decorations };
+      }
+    }
+    if (!tr.docChanged) return value;
+    return {
+      items: value.items.map((item) => ({
+        ...item,
+        from: tr.changes.mapPos(item.from),
+        to: tr.changes.mapPos(item.to),
+        fixFrom: item.fixFrom === null ? null : tr.changes.mapPos(item.fixFrom),
+      })),
+      decorations: value.decorations.map(tr.changes),
+    };
+  },
+  provide: (field) => EditorView.decorations.from(field, (value) => value.decorations),
+});
+
+function createLintPlugin(folder: string | null) {
+  return ViewPlugin.fromClass(
+    class {
+      timer: ReturnType<typeof setTimeout> | null = null;
+      version = 0;
+
+      constructor(readonly view: EditorView) {
+        this.schedule();
+      }
+
+      update(update: ViewUpdate) {
+        if (update.docChanged) this.schedule();
+      }
+
+      schedule() {
+        if (this.timer) clearTimeout(this.timer);
+        this.timer = setTimeout(() => this.run(), LINT_DELAY);
+      }
+
+      async run() {
+        const version = ++this.version;
+        const doc = this.view.state.doc;
+        try {
+          const diagnostics = await invoke<LintDiagnostic[]>('lint_document', {
+            content: doc.toString(),
+            folder,
+          });
+          // Drop results for an outdated document
+          if (version !== this.version || this.view.state.doc !== doc) return;
+          const items = diagnostics
+            .map((diagnostic) => ({
+              from: toOffset(doc, diagnostic.line, diagnostic.column),
+              to: toOffset(doc, diagnostic.end_line, diagnostic.end_column),
+              fixFrom: diagnostic.fix ? toOffset(doc, diagnostic.fix.line, diagnostic.fix.column) : null,
+              diagnostic,
+            }))
+            .filter((item) => item.from < doc.length);
+          this.view.dispatch({ effects: setDiagnostics.of(items) });
+        } catch {
+          // Linting is best-effort
+        }
+      }
+
+      destroy() {
+        if (this.timer) clearTimeout(this.timer);
+        this.version++;
+      }
+    }
+  );
+}
+
+/** Apply the fix of the diagnostic under the cursor. */
+function applyFixAtCursor(view: EditorView): boolean {
+  const pos = view.state.selection.main.head;
+  const { items } = view.state.field(diagnosticsField);
+  const target = items.find((item) => item.fixFrom !== null && item.from <= pos && pos <= item.to);
+  if (!target || !target.diagnostic.fix || target.fixFrom === null) return false;
+
+  const fix = target.diagnostic.fix;
+  const from = target.fixFrom;
+  view.dispatch({
+    changes: { from, to: from + fix.delete_count, insert: fix.insert_text },
+  });
+  return true;
+}
+
+/**
+ * Creates a CodeMirror extension that underlines markdownlint diagnostics
+ * reported by the backend. Rules are read from `.markdownlint.json` in
+ * `folder`. `Mod-.` applies the quick fix under the cursor.
+ */
+export function createLintExtension(enabled: boolean, folder: string | null): Extension[] {
+  if (!enabled) return [];
+  return [
+    diagnosticsField,
+    createLintPlugin(folder),
+    keymap.of([{ key: 'Mod-.', run: applyFixAtCursor }]),
+  ];
+}
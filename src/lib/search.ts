@@ -0,0 +1,245 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { EmbeddingConfig } from './ai-provider';
+
+export interface SearchOptions {
+  folder: string;
+  query: string;
+  caseSensitive: boolean;
+  regex: boolean;
+  wholeWord: boolean;
+  ignoreDiacritics: boolean;
+  contextLines: number;
+}
+
+export interface LineMatch {
+  line_number: number;
+  line_content: string;
+  /** UTF-16 offsets into `line_content`. */
+  match_start: number;
+  match_end: number;
+  context_before: string[];
+  context_after: string[];
+}
+
+export interface FileMatches {
+  file_path: string;
+  file_name: string;
+  hit_count: number;
+  matches: LineMatch[];
+}
+
+export interface SearchSummary {
+  search_id: number;
+  total_hits: number;
+  total_files: number;
+  truncated: boolean;
+  cancelled: boolean;
+}
+
+interface SearchBatch {
+  search_id: number;
+  files: FileMatches[];
+}
+
+let nextSearchId = 1;
+
+/**
+ * Start a background search in the Rust backend. Batches of per-file
+ * matches are passed to `onResults` as they are found and the totals to
+ * `onDone` once finished. Invalid queries reject; the returned function
+ * cancels the search and ignores anything it still sends.
+ */
+export async function startSearch(
+  options: SearchOptions,
+  onResults: (files: FileMatches[]) => void,
+  onDone: (summary: SearchSummary) => void
+): Promise<() => void> {
+  const searchId = nextSearchId++;
+  let active = true;
+
+  const unlistenResults = await listen<SearchBatch>('search-results', (event) => {
+    if (active && event.payload.search_id === searchId) onResults(event.payload.files);
+  });
+  const unlistenDone = await listen<SearchSummary>('search-done', (event) => {
+    if (event.payload.search_id !== searchId) return;
+    stop();
+    onDone(event.payload);
+  });
+
+  function stop() {
+    active = false;
+    unlistenResults();
+    unlistenDone();
+  }
+
+  try {
+    await invoke('start_search', {
+      options: {
+        search_id: searchId,
+        folder: options.folder,
+        query: options.query,
+        case_sensitive: options.caseSensitive,
+        regex: options.regex,
+        whole_word: options.wholeWord,
+        ignore_diacritics: options.ignoreDiacritics,
+        context_lines: options.contextLines,
+      },
+    });
+  } catch (err) {
+    stop();
+    throw err;
+  }
+
+  return () => {
+    if (!active) return;
+    stop();
+    invoke('cancel_search', { searchId }).catch(() => {});
+  };
+}
+
+export interface ReplaceOptions {
+  folder: string;
+  query: string;
+  replacement: string;
+  caseSensitive: boolean;
+  regex: boolean;
+  wholeWord: boolean;
+  ignoreDiacritics: boolean;
+}
+
+export interface ReplaceChange {
+  id: number;
+  line_number: number;
+  line_content: string;
+  match_start: number;
+  match_end: number;
+  replacement: string;
+}
+
+export interface FilePreview {
+  file_path: string;
+  file_name: string;
+  content_hash: string;
+  changes: ReplaceChange[];
+}
+
+export interface ReplaceOutcome {
+  journal_id: string;
+  files_changed: number;
+  replacements: number;
+}
+
+function replaceArgs(options: ReplaceOptions) {
+  return {
+    folder: options.folder,
+    query: options.query,
+    replacement: options.replacement,
+    case_sensitive: options.caseSensitive,
+    regex: options.regex,
+    whole_word: options.wholeWord,
+    ignore_diacritics: options.ignoreDiacritics,
+  };
+}
+
+/** Every match a vault-wide replace would change, grouped per file. */
+export function previewReplace(options: ReplaceOptions): Promise<FilePreview[]> {
+  return invoke<FilePreview[]>('preview_replace', { options: replaceArgs(options) });
+}
+
+/**
+ * Replace the matches of `preview` whose ids are in `selected` (keyed by
+ * file path). All files are written or none; the result names the undo
+ * journal.
+ */
+export function applyReplace(
+  options: ReplaceOptions,
+  preview: FilePreview[],
+  selected: Map<string, Set<number>>
+): Promise<ReplaceOutcome> {
+  const selection = preview
+    .map((file) => ({
+      file_path: file.file_path,
+      content_hash: file.content_hash,
+      ids: [...(selected.get(file.file_path) ?? [])],
+    }))
+    .filter((file) => file.ids.length > 0);
+  return invoke<ReplaceOutcome>('apply_replace', { options: replaceArgs(options), selected: selection });
+}
+
+/** Revert an applied replace; resolves to the number of files restored. */
+export function undoReplace(journalId: string): Promise<number> {
+  return invoke<number>('undo_replace', { journalId });
+}
+
+export interface SemanticHit {
+  file_path: string;
+  file_name: string;
+  /** One-based, inclusive line range of the passage. */
+  line_start: number;
+  line_end: number;
+  heading: string | null;
+  text: string;
+  /** Cosine similarity to the query. */
+  score: number;
+}
+
+interface IndexProgress {
+  folder: string;
+  done: number;
+  total: number;
+}
+
+/**
+ * Find the passages in `folder` closest in meaning to `query`. The vector
+ * index is brought up to date first, so only new or changed notes are sent
+ * to the embeddings endpoint; `onProgress` follows that step.
+ */
+export async function semanticSearch(
+  folder: string,
+  query: string,
+  config: EmbeddingConfig,
+  onProgress: (done: number, total: number) => void
+): Promise<SemanticHit[]> {
+  const unlisten = await listen<IndexProgress>('semantic-index-progress', (event) => {
+    if (event.payload.folder === folder) onProgress(event.payload.done, event.payload.total);
+  });
+  try {
+    return await invoke<SemanticHit[]>('semantic_search', {
+      options: {
+        folder,
+        query,
+        config: { base_url: config.baseUrl, model: config.model, api_key: config.apiKey },
+      },
+    });
+  } finally {
+    unlisten();
+  }
+}
+
+/** A named search stored in the workspace's `.markview-searches.json`. */
+export interface SavedSearch {
+  /** Empty when saving a new search; the backend derives one from the name. */
+  id: string;
+  name: string;
+  query: string;
+  case_sensitive: boolean;
+  regex: boolean;
+  whole_word: boolean;
+  ignore_diacritics: boolean;
+  /** Show the matching files as a virtual folder in the file tree. */
+  smart_folder: boolean;
+}
+
+export function listSavedSearches(folder: string): Promise<SavedSearch[]> {
+  return invoke<SavedSearch[]>('list_saved_searches', { folder });
+}
+
+/** Add a saved search, or update the one with the same id. */
+export function saveSearch(folder: string, search: SavedSearch): Promise<SavedSearch> {
+  return invoke<SavedSearch>('save_search', { folder, search });
+}
+
+export function deleteSavedSearch(folder: string, id: string): Promise<void> {
+  return invoke('delete_saved_search', { folder, id });
+}
@@ -1,3 +1,5 @@
+import type { BulletStyle, EmphasisStyle, ProseWrap } from '$lib/format';
+
 export interface AppSettings {
   fontSize: number;
   tabSize: number;
@@ -6,6 +8,15 @@ export interface AppSettings {
   autoSaveDelay: number;
   minimapEnabled: boolean;
   inlineImages: boolean;
+  lintEnabled: boolean;
+  spellLanguage: string;
+  spellDictionaryDir: string;
+  formatOnSave: boolean;
+  formatBullet: BulletStyle;
+  formatEmphasis: EmphasisStyle;
+  formatProseWrap: ProseWrap;
+  formatPrintWidth: number;
+  docxReferenceDoc: string;
 }
 
 const STORAGE_KEY = 'markview-settings';
@@ -18,6 +29,15 @@ const defaults: AppSettings = {
   autoSaveDelay: 5000,
   minimapEnabled: false,
   inlineImages: false,
+  lintEnabled: false,
+  spellLanguage: 'en_US',
+  spellDictionaryDir: '',
+  formatOnSave: false,
+  formatBullet: 'dash',
+  formatEmphasis: 'asterisk',
+  formatProseWrap: 'preserve',
+  formatPrintWidth: 80,
+  docxReferenceDoc: '',
 };
 
 class SettingsManager {
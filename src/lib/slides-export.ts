@@ -0,0 +1,39 @@
+import { save } from '@tauri-apps/plugin-dialog';
+import { invoke } from '@tauri-apps/api/core';
+
+export type SlideFormat = 'html' | 'pdf';
+
+// ── Export slide decks (public API) ──────────────────────────────────
+// The backend (`export_slides`) splits the note on `---`, inlines images
+// and writes either a self-contained HTML deck or a PDF with one slide
+// per page, so decks can be shared outside the app.
+export async function exportSlides(
+  markdownContent: string,
+  fileName: string,
+  sourcePath: string | null,
+  folder: string | null,
+  format: SlideFormat,
+  theme: 'dark' | 'light'
+): Promise<void> {
+  const stem = fileName.replace(/\.md$/i, '');
+  const path = await save({
+    defaultPath: format === 'html' ? `${stem}.slides.html` : `${stem}.slides.pdf`,
+    filters:
+      format === 'html'
+        ? [{ name: 'HTML Slides', extensions: ['html'] }]
+        : [{ name: 'PDF Slides', extensions: ['pdf'] }],
+  });
+
+  if (path) {
+    await invoke('export_slides', {
+      options: {
+        input_path: sourcePath ?? fileName,
+        output_path: path,
+        content: markdownContent,
+        format,
+        theme,
+        folder,
+      },
+    });
+  }
+}
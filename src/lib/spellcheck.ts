@@ -1,37 +1,217 @@
-import type { Extension } from '@codemirror/state';
-import { EditorView } from '@codemirror/view';
+import { ViewPlugin, Decoration, EditorView, hoverTooltip } from '@codemirror/view';
+import type { ViewUpdate, DecorationSet, Tooltip } from '@codemirror/view';
+import { StateEffect, StateField } from '@codemirror/state';
+import type { Extension, Text } from '@codemirror/state';
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Misspelling {
+  word: string;
+  line: number;
+  column: number;
+  length: number;
+}
+
+interface SpellCheckResult {
+  language: string;
+  misspellings: Misspelling[];
+}
+
+export interface SpellCheckConfig {
+  /** Workspace folder holding the shared `.spelling` word list. */
+  folder: string | null;
+  /** Dictionary used when a note sets no `lang` in its frontmatter. */
+  language: string;
+  /** Extra folder searched for Hunspell dictionaries. */
+  dictionaryDir: string;
+}
+
+const SPELL_DELAY = 700;
+
+interface PlacedMisspelling {
+  from: number;
+  to: number;
+  word: string;
+}
+
+const setMisspellings = StateEffect.define<PlacedMisspelling[]>();
+/** Re-run the check, e.g. after a word was added to the dictionary. */
+const recheck = StateEffect.define<null>();
+
+const misspellingsField = StateField.define<{ items: PlacedMisspelling[]; decorations: DecorationSet }>({
+  create() {
+    return { items: [], decorations: Decoration.none };
+  },
+  update(value, tr) {
+    for (const effect of tr.effects) {
+      if (effect.is(setMisspellings)) {
+        const items = effect.value;
+        const decorations = Decoration.set(
+          items.map(({ from, to }) => Decoration.mark({ class: 'cm-spell-error' }).range(from, to)),
+          true
+        );
+        return { items, decorations };
+      }
+    }
+    if (!tr.docChanged) return value;
+    return {
+      items: value.items.map((item) => ({
+        ...item,
+        from: tr.changes.mapPos(item.from),
+        to: tr.changes.mapPos(item.to),
+      })),
+      decorations: value.decorations.map(tr.changes),
+    };
+  },
+  provide: (field) => EditorView.decorations.from(field, (value) => value.decorations),
+});
+
+function toOffset(doc: Text, line: number, column: number): number {
+  if (line > doc.lines) return doc.length;
+  const info = doc.line(line);
+  return Math.min(info.from + column - 1, info.to);
+}
+
+function createSpellPlugin(config: SpellCheckConfig) {
+  return ViewPlugin.fromClass(
+    class {
+      timer: ReturnType<typeof setTimeout> | null = null;
+      version = 0;
+
+      constructor(readonly view: EditorView) {
+        this.schedule();
+      }
+
+      update(update: ViewUpdate) {
+        const forced = update.transactions.some((tr) => tr.effects.some((e) => e.is(recheck)));
+        if (update.docChanged || forced) this.schedule();
+      }
+
+      schedule() {
+        if (this.timer) clearTimeout(this.timer);
+        this.timer = setTimeout(() => this.run(), SPELL_DELAY);
+      }
+
+      async run() {
+        const version = ++this.version;
+        const doc = this.view.state.doc;
+        try {
+          const result = await invoke<SpellCheckResult>('spell_check', {
+            options: {
+              content: doc.toString(),
+              folder: config.folder,
+              language: config.language,
+              dictionary_dir: config.dictionaryDir || null,
+            },
+          });
+          // Drop results for an outdated document
+          if (version !== this.version || this.view.state.doc !== doc) return;
+          const items = result.misspellings.map((m) => {
+            const from = toOffset(doc, m.line, m.column);
+            return { from, to: Math.min(from + m.length, doc.length), word: m.word };
+          });
+          this.view.dispatch({ effects: setMisspellings.of(items) });
+        } catch {
+          // No dictionary for the language: leave the text unmarked
+          if (version === this.version) this.view.dispatch({ effects: setMisspellings.of([]) });
+        }
+      }
+
+      destroy() {
+        if (this.timer) clearTimeout(this.timer);
+        this.version++;
+      }
+    }
+  );
+}
+
+function createSuggestionTooltip(config: SpellCheckConfig) {
+  return hoverTooltip(async (view, pos): Promise<Tooltip | null> => {
+    const { items } = view.state.field(misspellingsField);
+    const item = items.find((i) => i.from <= pos && pos <= i.to);
+    if (!item) return null;
+
+    let suggestions: string[] = [];
+    try {
+      suggestions = await invoke<string[]>('spell_suggest', {
+        options: {
+          word: item.word,
+          language: config.language,
+          dictionary_dir: config.dictionaryDir || null,
+        },
+      });
+    } catch {
+      // Show the tooltip without suggestions
+    }
+
+    return {
+      pos: item.from,
+      end: item.to,
+      above: true,
+      create(view) {
+        const dom = document.createElement('div');
+        dom.className = 'cm-spell-tooltip';
+
+        const replace = (text: string) => {
+          // The word may have moved since the tooltip opened
+          const current = view.state
+            .field(misspellingsField)
+            .items.find((i) => i.word === item.word && i.from <= pos && pos <= i.to);
+          if (!current) return;
+          view.dispatch({ changes: { from: current.from, to: current.to, insert: text } });
+          view.focus();
+        };
+
+        if (suggestions.length === 0) {
+          const empty = document.createElement('div');
+          empty.className = 'cm-spell-empty';
+          empty.textContent = 'No suggestions';
+          dom.appendChild(empty);
+        }
+        for (const suggestion of suggestions) {
+          const button = document.createElement('button');
+          button.className = 'cm-spell-suggestion';
+          button.textContent = suggestion;
+          button.onclick = () => replace(suggestion);
+          dom.appendChild(button);
+        }
+
+        if (config.folder) {
+          const add = document.createElement('button');
+          add.className = 'cm-spell-add';
+          add.textContent = 'Add to dictionary';
+          add.onclick = async () => {
+            try {
+              await invoke('add_to_dictionary', { folder: config.folder, word: item.word });
+              view.dispatch({ effects: recheck.of(null) });
+            } catch {
+              // Keep the word marked
+            }
+          };
+          dom.appendChild(add);
+        }
+        return { dom };
+      },
+    };
+  });
+}
 
 /**
- * Creates a CodeMirror extension that enables or disables the browser's
- * built-in spellcheck on the editor's content DOM.
- *
- * When enabled, this sets the `spellcheck="true"` attribute on the
- * `contenteditable` element that CodeMirror uses for input. The browser
- * then applies its native spell-checking UI (red underlines, suggestions
- * via right-click context menu, etc.).
+ * Creates a CodeMirror extension that underlines misspelled words using the
+ * backend's Hunspell checker. Code, URLs and frontmatter are ignored; a `lang`
+ * frontmatter key picks the dictionary per note. Hovering a word shows
+ * suggestions and an action that adds it to the workspace `.spelling` file.
  *
  * Designed to be used with a `Compartment` for dynamic toggling:
  *
  * ```ts
  * const spellCheckCompartment = new Compartment();
- * // In extensions array:
- * spellCheckCompartment.of(spellCheckExtension(false))
- * // To toggle:
+ * spellCheckCompartment.of(spellCheckExtension(false, config))
  * view.dispatch({
- *   effects: spellCheckCompartment.reconfigure(spellCheckExtension(true))
+ *   effects: spellCheckCompartment.reconfigure(spellCheckExtension(true, config))
  * });
  * ```
- *
- * @param enabled - Whether spell checking should be active
- * @returns A CodeMirror extension (or empty array when disabled)
  */
-export function spellCheckExtension(enabled: boolean): Extension {
-  if (!enabled) {
-    return [];
-  }
-
-  return EditorView.contentAttributes.of({
-    spellcheck: 'true',
-    autocorrect: 'on',
-  });
+export function spellCheckExtension(enabled: boolean, config: SpellCheckConfig): Extension[] {
+  if (!enabled) return [];
+  return [misspellingsField, createSpellPlugin(config), createSuggestionTooltip(config)];
 }
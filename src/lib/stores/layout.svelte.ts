@@ -1,4 +1,4 @@
-export type SidebarMode = 'files' | 'search' | 'git' | 'toc' | 'ai' | 'backlinks';
+export type SidebarMode = 'files' | 'search' | 'git' | 'toc' | 'ai' | 'backlinks' | 'lint' | 'links' | 'prose';
 
 const STORAGE_KEY = 'markview-layout';
 
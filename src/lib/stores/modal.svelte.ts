@@ -2,9 +2,11 @@ export type ModalId =
   | 'settings'
   | 'recentFiles'
   | 'diffView'
+  | 'fileHistory'
   | 'emojiPicker'
   | 'snippetMenu'
   | 'commandPalette'
+  | 'quickOpen'
   | 'tableEditor'
   | 'presentation'
   | 'customCss'
@@ -1,9 +1,16 @@
 import { invoke } from '@tauri-apps/api/core';
-import { open } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import type { FileEntry } from '$lib/types';
 import { toastManager } from '$lib/toast.svelte';
 import { startWatching, stopWatching, type FileChangeEvent } from '$lib/watcher';
 import { recentFiles } from '$lib/recent-files.svelte';
+import { settingsManager } from '$lib/settings.svelte';
+import { formatMarkdown, formatFolder } from '$lib/format';
+
+/** CSV/TSV files open read-only and preview as a table. */
+export function isTableFile(path: string | null): boolean {
+  return !!path && /\.(csv|tsv)$/i.test(path);
+}
 
 export interface OpenFile {
   path: string;
@@ -19,6 +26,7 @@ class WorkspaceStore {
   content = $state('');
   openFiles = $state<OpenFile[]>([]);
   selectedText = $state('');
+  cursorLine = $state(1);
 
   /** Insert command sent from toolbar/command palette to the editor. */
   insertCommand = $state<{ type: string; timestamp: number } | null>(null);
@@ -51,7 +59,7 @@ class WorkspaceStore {
         this.fileTree = [];
       }
 
-      startWatching(selected, (changes) => this.handleFileChanges(changes)).catch(() => {});
+      startWatching(selected, (changes) => this.handleFileChanges(changes), () => this.refreshTree()).catch(() => {});
       recentFiles.add(selected, 'folder');
     }
   }
@@ -115,6 +123,14 @@ class WorkspaceStore {
 
   async save() {
     if (!this.currentFile) return;
+    if (settingsManager.settings.formatOnSave && !isTableFile(this.currentFile)) {
+      try {
+        const formatted = await formatMarkdown(this.content);
+        if (formatted !== this.content) this.updateContent(formatted);
+      } catch {
+        // Save the unformatted content rather than nothing
+      }
+    }
     try {
       await invoke('write_file', { path: this.currentFile, content: this.content });
       const idx = this.openFiles.findIndex(f => f.path === this.currentFile);
@@ -167,6 +183,134 @@ class WorkspaceStore {
     }
   }
 
+  async importDocument() {
+    if (!this.currentFolder) return;
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: 'Documents', extensions: ['docx', 'html', 'htm'] }],
+    });
+    if (!selected || typeof selected !== 'string') return;
+    try {
+      const path = await invoke<string>('import_document', {
+        options: { source_path: selected, folder: this.currentFolder },
+      });
+      await this.refreshTree();
+      await this.selectFile(path);
+      toastManager.success('Document imported');
+    } catch (err) {
+      toastManager.error('Failed to import document: ' + err);
+    }
+  }
+
+  async formatDocument() {
+    if (!this.currentFile || isTableFile(this.currentFile)) return;
+    try {
+      const formatted = await formatMarkdown(this.content);
+      if (formatted !== this.content) this.updateContent(formatted);
+    } catch (err) {
+      toastManager.error('Failed to format document: ' + err);
+    }
+  }
+
+  async formatFolder() {
+    if (!this.currentFolder) return;
+    try {
+      const changed = await formatFolder(this.currentFolder);
+      // Reload open files that were rewritten on disk without local edits
+      for (const file of this.openFiles) {
+        if (!changed.includes(file.path) || file.content !== file.originalContent) continue;
+        const content = await invoke<string>('read_file', { path: file.path });
+        file.content = content;
+        file.originalContent = content;
+        if (file.path === this.currentFile) this.content = content;
+      }
+      toastManager.success(`Formatted ${changed.length} file${changed.length === 1 ? '' : 's'}`);
+    } catch (err) {
+      toastManager.error('Failed to format folder: ' + err);
+    }
+  }
+
+  async fixLintIssues() {
+    if (!this.currentFile) return;
+    try {
+      const fixed = await invoke<string>('fix_lint_issues', {
+        content: this.content,
+        folder: this.currentFolder,
+      });
+      if (fixed === this.content) {
+        toastManager.info('No fixable lint issues');
+        return;
+      }
+      this.updateContent(fixed);
+      toastManager.success('Lint issues fixed');
+    } catch (err) {
+      toastManager.error('Failed to fix lint issues: ' + err);
+    }
+  }
+
+  async insertCsvTable() {
+    if (!this.currentFile) return;
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: 'CSV/TSV', extensions: ['csv', 'tsv'] }],
+    });
+    if (!selected || typeof selected !== 'string') return;
+    try {
+      const table = await invoke<string>('csv_to_table', { options: { path: selected } });
+      this.sendInsert('__raw:\n' + table + '\n');
+    } catch (err) {
+      toastManager.error('Failed to import CSV: ' + err);
+    }
+  }
+
+  async pasteCsvTable() {
+    if (!this.currentFile) return;
+    try {
+      const text = await navigator.clipboard.readText();
+      const table = await invoke<string>('csv_to_table', { options: { text } });
+      this.sendInsert('__raw:\n' + table + '\n');
+    } catch (err) {
+      toastManager.error('Failed to paste table: ' + err);
+    }
+  }
+
+  async exportTableCsv() {
+    if (!this.currentFile) return;
+    const baseName = (this.currentFile.split(/[\\/]/).pop() ?? 'table').replace(/\.md$/i, '');
+    const outputPath = await save({
+      defaultPath: `${baseName}.csv`,
+      filters: [
+        { name: 'CSV', extensions: ['csv'] },
+        { name: 'TSV', extensions: ['tsv'] },
+      ],
+    });
+    if (!outputPath) return;
+    try {
+      const rows = await invoke<number>('export_table_csv', {
+        options: { content: this.content, line: this.cursorLine, output_path: outputPath },
+      });
+      toastManager.success(`Exported ${rows} rows`);
+    } catch (err) {
+      toastManager.error('Failed to export table: ' + err);
+    }
+  }
+
+  /** Add a `^block-id` to the block under the cursor and copy a link to it. */
+  async copyBlockLink() {
+    if (!this.currentFile) return;
+    try {
+      const result = await invoke<{ id: string; content: string; created: boolean }>('create_block_id', {
+        options: { content: this.content, line: this.cursorLine },
+      });
+      if (result.created) this.updateContent(result.content);
+      const name = (this.currentFile.split(/[\\/]/).pop() ?? '').replace(/\.md$/i, '');
+      await navigator.clipboard.writeText(`[[${name}#^${result.id}]]`);
+      toastManager.success(`Copied link to ^${result.id}`);
+    } catch (err) {
+      toastManager.error('Failed to link block: ' + err);
+    }
+  }
+
   async handleImagePaste(file: File) {
     if (!this.currentFolder || !this.currentFile) return;
     try {
@@ -204,16 +348,15 @@ class WorkspaceStore {
 
     try {
       this.fileTree = await invoke<FileEntry[]>('read_directory', { path });
-      startWatching(path, (changes) => this.handleFileChanges(changes)).catch(() => {});
+      startWatching(path, (changes) => this.handleFileChanges(changes), () => this.refreshTree()).catch(() => {});
     } catch {
       toastManager.error('Failed to read directory');
       this.fileTree = [];
     }
   }
 
-  // --- Internal helpers ---
-
-  private async refreshTree() {
+  /** Reload the file tree, including smart folders. */
+  async refreshTree() {
     if (this.currentFolder) {
       try {
         this.fileTree = await invoke<FileEntry[]>('read_directory', { path: this.currentFolder });
@@ -221,6 +364,8 @@ class WorkspaceStore {
     }
   }
 
+  // --- Internal helpers ---
+
   private async handleFileChanges(changes: FileChangeEvent[]) {
     let needsTreeRefresh = false;
 
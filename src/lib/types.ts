@@ -3,4 +3,6 @@ export interface FileEntry {
   path: string;
   is_directory: boolean;
   children?: FileEntry[];
+  /** Id of the saved search behind a virtual smart folder. */
+  smart_folder?: string;
 }
@@ -9,17 +9,26 @@ export interface FileChangeEvent {
 export type FileChangeCallback = (changes: FileChangeEvent[]) => void;
 
 let unlisten: UnlistenFn | null = null;
+let unlistenSmartFolders: UnlistenFn | null = null;
 
 /**
  * Starts watching the given folder for `.md` file changes.
  * The Rust backend polls every 2 seconds and emits `file-changes` events
- * whenever files are created, modified, or deleted.
+ * whenever files are created, modified, or deleted, and
+ * `smart-folders-changed` when that alters which files a smart folder holds.
  */
-export async function startWatching(folder: string, callback: FileChangeCallback): Promise<void> {
+export async function startWatching(
+  folder: string,
+  callback: FileChangeCallback,
+  onSmartFoldersChanged?: () => void
+): Promise<void> {
   // Listen for file-changes events from Rust
   unlisten = await listen<FileChangeEvent[]>('file-changes', (event) => {
     callback(event.payload);
   });
+  unlistenSmartFolders = await listen<string>('smart-folders-changed', () => {
+    onSmartFoldersChanged?.();
+  });
 
   // Start the Rust watcher
   await invoke('start_watching', { folder });
@@ -34,4 +43,8 @@ export async function stopWatching(): Promise<void> {
     unlisten();
     unlisten = null;
   }
+  if (unlistenSmartFolders) {
+    unlistenSmartFolders();
+    unlistenSmartFolders = null;
+  }
 }
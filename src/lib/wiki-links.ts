@@ -1,3 +1,5 @@
+import { invoke } from '@tauri-apps/api/core';
+
 export interface WikiLinkInfo {
   target: string;
   alias?: string;
@@ -49,3 +51,23 @@ export function resolveWikiLink(target: string, knownFiles: string[]): string |
 
   return null;
 }
+
+/**
+ * Expand `![[note]]`, `![[note#Section]]` and `![[note^block]]` embeds in
+ * the backend. Returns `content` unchanged when it has no embeds or the
+ * note is unsaved.
+ */
+export async function resolveEmbeds(
+  content: string,
+  filePath: string | null,
+  folder: string | null
+): Promise<string> {
+  if (!filePath || !content.includes('![[')) return content;
+  try {
+    return await invoke<string>('resolve_embeds', {
+      options: { content, file_path: filePath, folder },
+    });
+  } catch {
+    return content;
+  }
+}